@@ -3,10 +3,10 @@ Root package for the alea_preprocess module.
 """
 
 # import submodules
-from .alea_preprocess import algos, io, parsers, tasks
+from .alea_preprocess import algos, errors, io, parsers, tasks
 
 # export modules
-__all__ = ["algos", "io", "parsers", "tasks"]
+__all__ = ["algos", "errors", "io", "parsers", "tasks"]
 
 # metadata fields
 __version__ = "0.1.13"
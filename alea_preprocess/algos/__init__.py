@@ -3,7 +3,7 @@ Top-level algorithm package
 """
 
 # imports
-from . import hashing, ngrams, similarity, splitting, tokenizers, unicode
+from . import hashing, ngrams, quality, similarity, splitting, tokenizers, unicode
 
 # exports
-__all__ = ["hashing", "ngrams", "similarity", "splitting", "tokenizers", "unicode"]
+__all__ = ["hashing", "ngrams", "quality", "similarity", "splitting", "tokenizers", "unicode"]
@@ -45,3 +45,30 @@ def hash_gz_file(path: str) -> str:
         A string representation of the hash.
     """
     ...
+
+def hash_bytes_batch(buffers: list[bytes]) -> list[str]:
+    """
+    Hash several buffers in parallel using blake3, without holding the GIL.
+
+    Args:
+        buffers: The buffers to hash.
+
+    Returns:
+        The hash of each buffer, in input order.
+    """
+    ...
+
+def hash_file_batch(paths: list[str]) -> list[str]:
+    """
+    Hash several files in parallel using blake3, without holding the GIL.
+
+    Args:
+        paths: The paths to the files to hash.
+
+    Returns:
+        The hash of each file, in input order.
+
+    Raises:
+        OSError: If any file cannot be read.
+    """
+    ...
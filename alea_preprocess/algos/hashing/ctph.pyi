@@ -58,3 +58,40 @@ def compare(hash1: str, hash2: str) -> float:
         A float representing the similarity of the hashes.
     """
     ...
+
+def hash_bytes_batch(
+    buffers: list[bytes], window_size: int, digest_size: int, precision: int
+) -> list[str]:
+    """
+    Hash several buffers in parallel using ctph, without holding the GIL.
+
+    Args:
+        buffers: The buffers to hash.
+        window_size: The size of the sliding window.
+        digest_size: The size of the hash digest.
+        precision: The precision of the rolling hash: 8, 16, 32 or 64.
+
+    Returns:
+        The hash of each buffer, in input order.
+    """
+    ...
+
+def hash_file_batch(
+    paths: list[str], window_size: int, digest_size: int, precision: int
+) -> list[str]:
+    """
+    Hash several files in parallel using ctph, without holding the GIL.
+
+    Args:
+        paths: The paths to the files to hash.
+        window_size: The size of the sliding window.
+        digest_size: The size of the hash digest.
+        precision: The precision of the rolling hash: 8, 16, 32 or 64.
+
+    Returns:
+        The hash of each file, in input order.
+
+    Raises:
+        OSError: If any file cannot be read.
+    """
+    ...
@@ -1,3 +1,5 @@
+import numpy
+
 def hash_tokens(tokens: list[int], window_size: int) -> str:
     """
     Hash tokens using rolling hash.
@@ -10,3 +12,18 @@ def hash_tokens(tokens: list[int], window_size: int) -> str:
         A string representation of the hash.
     """
     ...
+
+def hash_token_windows_numpy(tokens: list[int], window_size: int) -> numpy.ndarray:
+    """
+    Get the rolling hash after each token.
+
+    Requires numpy.
+
+    Args:
+        tokens: The list of tokens to hash.
+        window_size: The size of the rolling window.
+
+    Returns:
+        A uint64 array with one entry per token; entry i hashes the window ending at token i.
+    """
+    ...
@@ -4,6 +4,8 @@ Bytes n-gram extraction.
 
 from typing import Dict
 
+import numpy
+
 def transform(input_data: bytes, n: int) -> list[list[bytes]]:
     """
     Transform a bytes object into a list of n-grams.
@@ -17,6 +19,21 @@ def transform(input_data: bytes, n: int) -> list[list[bytes]]:
     """
     ...
 
+def transform_numpy(input_data: bytes, n: int) -> numpy.ndarray:
+    """
+    Transform a bytes object into a 2-D uint8 NumPy array of n-grams.
+
+    Requires numpy.
+
+    Args:
+        input_data: The input data.
+        n: The n-gram length, from 1 to 9.
+
+    Returns:
+        An array of shape (len(input_data) - n + 1, n), one n-gram per row.
+    """
+    ...
+
 def extract(input_data: bytes, n: int) -> Dict[bytes, int]:
     """
     Extract byte n-grams from a bytes object.
@@ -0,0 +1,9 @@
+"""
+Document quality signals and filters
+"""
+
+# import submodules
+from . import heuristics, noise
+
+# export submodules
+__all__ = ["heuristics", "noise"]
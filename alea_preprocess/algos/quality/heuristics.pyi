@@ -0,0 +1,108 @@
+"""
+Heuristic document quality signals and filters, in the style of the Gopher and C4 rules.
+"""
+
+# imports
+from typing import List, Tuple
+
+class QualitySignals:
+    """
+    Document quality signals.
+
+    Words are the word segments with at least one letter or number. Duplicate fractions count the
+    lines or paragraphs that repeat an earlier one; their char variants weigh them by length.
+    top_ngram_char_fractions is the fraction of word characters covered by the most frequent
+    2-, 3- and 4-gram, and duplicate_ngram_char_fractions the fraction covered by 5- to 10-grams
+    that repeat an earlier one. alphabetic_ratio is the fraction of word segments, including
+    punctuation and symbols, with at least one letter.
+    """
+
+    word_count: int
+    mean_word_length: float
+    symbol_to_word_ratio: float
+    ellipsis_line_fraction: float
+    bullet_line_fraction: float
+    duplicate_line_fraction: float
+    duplicate_line_char_fraction: float
+    duplicate_paragraph_fraction: float
+    duplicate_paragraph_char_fraction: float
+    top_ngram_char_fractions: List[float]
+    duplicate_ngram_char_fractions: List[float]
+    stop_word_count: int
+    alphabetic_ratio: float
+
+def get_quality_signals(text: str) -> QualitySignals:
+    """
+    Get the quality signals of a document.
+
+    Args:
+        text: The document text.
+
+    Returns:
+        The quality signals.
+    """
+    ...
+
+def get_quality_signals_batch(texts: List[str]) -> List[QualitySignals]:
+    """
+    Get the quality signals of several documents in parallel.
+
+    Args:
+        texts: The document texts.
+
+    Returns:
+        The quality signals of each document.
+    """
+    ...
+
+def check_quality(
+    text: str,
+    min_words: int = 50,
+    max_words: int = 100_000,
+    min_mean_word_length: float = 3.0,
+    max_mean_word_length: float = 10.0,
+    max_symbol_to_word_ratio: float = 0.1,
+    max_ellipsis_line_fraction: float = 0.3,
+    max_bullet_line_fraction: float = 0.9,
+    max_duplicate_line_fraction: float = 0.3,
+    max_duplicate_line_char_fraction: float = 0.2,
+    max_duplicate_paragraph_fraction: float = 0.3,
+    max_duplicate_paragraph_char_fraction: float = 0.2,
+    max_top_ngram_char_fractions: List[float] = [0.2, 0.18, 0.16],
+    max_duplicate_ngram_char_fractions: List[float] = [0.15, 0.14, 0.13, 0.12, 0.11, 0.1],
+    min_stop_words: int = 2,
+    min_alphabetic_ratio: float = 0.8,
+) -> Tuple[QualitySignals, List[str]]:
+    """
+    Get the quality signals of a document and the names of the checks it fails.
+
+    The defaults are the Gopher thresholds. Failed checks are named after the signal, e.g.,
+    "min_words", "mean_word_length", "duplicate_line_fraction" or "top_2gram_char_fraction".
+
+    Args:
+        text: The document text.
+        min_words: The minimum number of words.
+        max_words: The maximum number of words.
+        min_mean_word_length: The minimum mean word length in characters.
+        max_mean_word_length: The maximum mean word length in characters.
+        max_symbol_to_word_ratio: The maximum ratio of "#" and ellipsis symbols to words.
+        max_ellipsis_line_fraction: The maximum fraction of lines ending in an ellipsis.
+        max_bullet_line_fraction: The maximum fraction of lines starting with a bullet.
+        max_duplicate_line_fraction: The maximum fraction of duplicate lines.
+        max_duplicate_line_char_fraction: The maximum fraction of characters in duplicate lines.
+        max_duplicate_paragraph_fraction: The maximum fraction of duplicate paragraphs.
+        max_duplicate_paragraph_char_fraction: The maximum fraction of characters in duplicate
+            paragraphs.
+        max_top_ngram_char_fractions: The maximum top n-gram character fractions for n=2, 3, 4.
+        max_duplicate_ngram_char_fractions: The maximum duplicate n-gram character fractions for
+            n=5 to 10.
+        min_stop_words: The minimum number of stop words.
+        min_alphabetic_ratio: The minimum fraction of word segments with a letter.
+
+    Returns:
+        The quality signals and the failed checks; the document passes if there are none.
+
+    Raises:
+        ValueError: If the n-gram threshold lists have the wrong length.
+    """
+    ...
@@ -0,0 +1,95 @@
+"""
+Unicode category signals for OCR garbage, binary data decoded as text and encoding noise.
+"""
+
+# imports
+from typing import List
+
+class NoiseSignals:
+    """
+    Noise signals of a line or document.
+
+    Character ratios are over non-whitespace characters; unusual_transition_ratio is over adjacent
+    character pairs, e.g., letters next to other symbols or lowercase followed by uppercase.
+    letter_ratio is the fraction of letters among letters, symbols and punctuation, or 1.0 if
+    there are none.
+    """
+
+    char_count: int
+    unusual_transition_ratio: float
+    other_symbol_ratio: float
+    private_use_ratio: float
+    unassigned_ratio: float
+    control_ratio: float
+    letter_ratio: float
+
+class NoiseReport:
+    """
+    Document noise report.
+
+    noisy_char_fraction is the fraction of characters in judged lines that are in noisy lines.
+    """
+
+    document: NoiseSignals
+    line_count: int
+    noisy_line_count: int
+    noisy_char_fraction: float
+    is_unusable: bool
+
+def get_noise_signals(text: str) -> NoiseSignals:
+    """
+    Get the noise signals of a text as a whole.
+
+    Args:
+        text: The text.
+
+    Returns:
+        The noise signals, summed over its lines.
+    """
+    ...
+
+def get_line_noise_signals(text: str) -> List[NoiseSignals]:
+    """
+    Get the noise signals of each non-empty line of a text.
+
+    Args:
+        text: The text.
+
+    Returns:
+        The noise signals of each non-empty line, in order.
+    """
+    ...
+
+def get_noise_report(
+    text: str,
+    max_unusual_transition_ratio: float = 0.1,
+    max_other_symbol_ratio: float = 0.05,
+    max_private_use_ratio: float = 0.01,
+    max_unassigned_ratio: float = 0.01,
+    max_control_ratio: float = 0.01,
+    min_letter_ratio: float = 0.5,
+    min_line_chars: int = 8,
+    max_noisy_char_fraction: float = 0.3,
+) -> NoiseReport:
+    """
+    Get the document and line noise signals of a text and whether it is unusable.
+
+    Lines with fewer than min_line_chars non-whitespace characters are never noisy. The text is
+    unusable if its document signals are noisy or if more than max_noisy_char_fraction of the
+    characters in judged lines are in noisy lines.
+
+    Args:
+        text: The text, e.g., the text layer of an OCR'd PDF.
+        max_unusual_transition_ratio: The maximum fraction of unusual category transitions.
+        max_other_symbol_ratio: The maximum fraction of other symbols (So).
+        max_private_use_ratio: The maximum fraction of private-use characters (Co).
+        max_unassigned_ratio: The maximum fraction of unassigned characters (Cn).
+        max_control_ratio: The maximum fraction of non-whitespace control characters.
+        min_letter_ratio: The minimum fraction of letters among letters, symbols and punctuation.
+        min_line_chars: The minimum number of non-whitespace characters for a line to be judged.
+        max_noisy_char_fraction: The maximum fraction of judged characters in noisy lines.
+
+    Returns:
+        The noise report.
+    """
+    ...
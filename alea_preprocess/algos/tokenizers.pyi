@@ -2,18 +2,368 @@
 This module provides functions to encode and decode text buffers using different tokenizers.
 """
 
-from typing import List
+from typing import Dict, List, Optional, Tuple
+
+import numpy
+
+class TokenEncoding:
+    """
+    An encoded text with its attention mask, special token mask and character offsets.
+
+    Attributes:
+        ids: The token IDs.
+        tokens: The token strings.
+        attention_mask: 1 for real tokens and 0 for padding.
+        special_tokens_mask: 1 for special and padding tokens and 0 otherwise.
+        offsets: The (start, end) character offsets of each token in the original text.
+        overflowing: The windows that did not fit in max_length when truncating.
+    """
+
+    ids: List[int]
+    tokens: List[str]
+    attention_mask: List[int]
+    special_tokens_mask: List[int]
+    offsets: List[Tuple[int, int]]
+    overflowing: List["TokenEncoding"]
+
+    def __len__(self) -> int: ...
+
+def load_tokenizer_from_file(name: str, path: str) -> None:
+    """
+    Load a tokenizer from a local tokenizer.json file and register it under a name.
+
+    Args:
+        name: The name to register the tokenizer under.
+        path: The path to the tokenizer.json file.
+
+    Raises:
+        ValueError: If the file cannot be read or parsed.
+    """
+    pass
+
+def load_tokenizer_from_str(name: str, json: str) -> None:
+    """
+    Load a tokenizer from a tokenizer.json string and register it under a name.
+
+    Args:
+        name: The name to register the tokenizer under.
+        json: The serialized tokenizer.
+
+    Raises:
+        ValueError: If the JSON cannot be parsed.
+    """
+    pass
+
+def is_tokenizer_loaded(name: str) -> bool:
+    """
+    Check if a tokenizer is registered under the given name.
+
+    Args:
+        name: The tokenizer name.
+
+    Returns:
+        True if the tokenizer is loaded.
+    """
+    pass
+
+class TokenStats:
+    """
+    Token statistics over a set of documents.
+
+    Attributes:
+        num_documents: The number of documents.
+        total_tokens: The total number of tokens.
+        total_bytes: The total number of UTF-8 bytes.
+        min_length: The shortest document length in tokens.
+        max_length: The longest document length in tokens.
+        mean_length: The mean document length in tokens.
+        length_histogram: A map from power-of-two bucket lower bounds (0, 1, 2, 4, ...) to the
+            number of documents whose length falls in [bound, 2 * bound).
+        unique_tokens: The number of distinct token IDs seen.
+        vocab_size: The tokenizer vocabulary size, including added tokens.
+        vocab_coverage: The fraction of the vocabulary seen.
+        unk_tokens: The number of unknown tokens.
+        unk_rate: The fraction of tokens that are unknown.
+        bytes_per_token: The mean number of bytes per token.
+    """
+
+    num_documents: int
+    total_tokens: int
+    total_bytes: int
+    min_length: int
+    max_length: int
+    mean_length: float
+    length_histogram: Dict[int, int]
+    unique_tokens: int
+    vocab_size: int
+    vocab_coverage: float
+    unk_tokens: int
+    unk_rate: float
+    bytes_per_token: float
+
+class TokenizerComparison:
+    """
+    Fertility metrics for one tokenizer over a set of documents.
+
+    The document_* attributes hold one value per document, in input order.
+
+    Attributes:
+        tokenizer: The tokenizer name.
+        vocab_size: The tokenizer vocabulary size, including added tokens.
+        num_documents: The number of documents.
+        total_words: The number of words, from unicode.segmentations.segment_words.
+        total_chars: The number of characters.
+        total_tokens: The number of tokens.
+        tokens_per_word: The corpus-level fertility.
+        chars_per_token: The corpus-level characters per token.
+        domain_term_split_rate: The fraction of domain terms split into more than one token.
+        split_domain_terms: The domain terms split into more than one token.
+        document_tokens: The number of tokens per document.
+        document_tokens_per_word: The fertility per document.
+        document_chars_per_token: The characters per token per document.
+    """
+
+    tokenizer: str
+    vocab_size: int
+    num_documents: int
+    total_words: int
+    total_chars: int
+    total_tokens: int
+    tokens_per_word: float
+    chars_per_token: float
+    domain_term_split_rate: float
+    split_domain_terms: List[str]
+    document_tokens: List[int]
+    document_tokens_per_word: List[float]
+    document_chars_per_token: List[float]
+
+def compare_tokenizers(
+    tokenizers: List[str], texts: List[str], domain_terms: List[str] = []
+) -> Dict[str, TokenizerComparison]:
+    """
+    Compare the fertility of several tokenizers on the same documents.
+
+    Args:
+        tokenizers: The tokenizers to compare.
+        texts: The documents.
+        domain_terms: Terms that should ideally be single tokens, e.g., "plaintiff".
+
+    Returns:
+        A map from tokenizer name to its metrics.
+    """
+    pass
+
+def compare_tokenizers_from_file(
+    tokenizers: List[str], path: str, field: Optional[str] = None, domain_terms: List[str] = []
+) -> Dict[str, TokenizerComparison]:
+    """
+    Compare the fertility of several tokenizers on the documents of a text, JSONL or gzipped file.
+
+    Args:
+        tokenizers: The tokenizers to compare.
+        path: The path to the file.
+        field: The JSON field holding the text, e.g., "text" for JSONL files.
+        domain_terms: Terms that should ideally be single tokens.
+
+    Returns:
+        A map from tokenizer name to its metrics.
+    """
+    pass
+
+def get_vocab_overlap(tokenizer_a: str, tokenizer_b: str) -> Tuple[int, float, float, float]:
+    """
+    Get the vocabulary overlap between two tokenizers, comparing tokens as strings.
+
+    Args:
+        tokenizer_a: The first tokenizer.
+        tokenizer_b: The second tokenizer.
+
+    Returns:
+        The number of shared tokens, the Jaccard similarity, and the fraction of each
+        vocabulary that is shared.
+    """
+    pass
+
+def count_tokens(tokenizer: str, text: str) -> int:
+    """
+    Count the tokens in a text buffer without special tokens.
+
+    Args:
+        tokenizer: The tokenizer to use.
+        text: The text buffer.
+
+    Returns:
+        The number of tokens.
+    """
+    pass
+
+def count_tokens_list(tokenizer: str, text_list: List[str]) -> List[int]:
+    """
+    Count the tokens in each text buffer of a list, encoding in parallel.
+
+    Args:
+        tokenizer: The tokenizer to use.
+        text_list: The text buffers.
+
+    Returns:
+        The number of tokens in each text buffer.
+    """
+    pass
+
+def get_token_stats(tokenizer: str, text_list: List[str]) -> TokenStats:
+    """
+    Get token statistics for a list of documents.
+
+    Args:
+        tokenizer: The tokenizer to use.
+        text_list: The documents.
+
+    Returns:
+        The token statistics.
+    """
+    pass
+
+def get_token_stats_from_file(tokenizer: str, path: str, field: Optional[str] = None) -> TokenStats:
+    """
+    Get token statistics for the lines of a text, JSONL or gzipped file.
+
+    Files ending in .gz are decompressed while reading. Each line is a document, unless field is
+    set, in which case each line is parsed as JSON and the document is that string field.
+
+    Args:
+        tokenizer: The tokenizer to use.
+        path: The path to the file.
+        field: The JSON field holding the text, e.g., "text" for JSONL files.
+
+    Returns:
+        The token statistics.
+
+    Raises:
+        ValueError: If the file cannot be read, a line is not valid JSON, or the field is not a string.
+    """
+    pass
+
+def train_tokenizer(
+    paths: List[str],
+    output_path: str,
+    model_type: str = "bpe",
+    vocab_size: int = 32000,
+    min_frequency: int = 2,
+    special_tokens: List[str] = [],
+    unk_token: Optional[str] = None,
+    pre_tokenization: str = "byte_level",
+    normalization: Optional[str] = "nfc",
+    lowercase: bool = False,
+    clean_text: bool = True,
+    field: Optional[str] = None,
+) -> None:
+    """
+    Train a BPE or Unigram tokenizer on text, JSONL or gzipped files and save it as tokenizer.json.
+
+    The saved file can be passed by path to encode_str or load_tokenizer_from_file; a tokenizer
+    already cached under output_path is dropped, so retraining takes effect.
+
+    Args:
+        paths: The corpus files; files ending in .gz are decompressed while reading.
+        output_path: The path to save the tokenizer.json file to.
+        model_type: "bpe" or "unigram".
+        vocab_size: The target vocabulary size, including special tokens.
+        min_frequency: The minimum pair frequency for BPE merges.
+        special_tokens: Special tokens added at the start of the vocabulary.
+        unk_token: The unknown token, added to special_tokens if missing.
+        pre_tokenization: "byte_level", "whitespace", "bert" or "metaspace"; Unigram models
+            with "whitespace" or "bert" mark word starts with a metaspace.
+        normalization: "nfc", "nfd", "nfkc", "nfkd" or None, saved in the tokenizer.
+        lowercase: Whether to lowercase, saved in the tokenizer.
+        clean_text: Whether to clean whitespace and control characters as normalize_whitespace
+            does, saved in the tokenizer.
+        field: The JSON field holding the text, e.g., "text" for JSONL files.
+
+    Raises:
+        ValueError: If an option is invalid, or if reading, training or saving fails.
+    """
+    pass
+
+def encode(
+    tokenizer: str,
+    text: str,
+    add_special_tokens: bool = False,
+    max_length: Optional[int] = None,
+    stride: int = 0,
+    pad_to_length: Optional[int] = None,
+    pad_to_multiple_of: Optional[int] = None,
+    pad_id: Optional[int] = None,
+) -> TokenEncoding:
+    """
+    Encode a text buffer with special tokens, truncation and padding.
+
+    Args:
+        tokenizer: The tokenizer to use, as a registered name, a tokenizer.json path or a
+            Hugging Face hub identifier.
+        text: The text buffer to encode.
+        add_special_tokens: Whether to add the tokenizer's special tokens, e.g., [CLS] and [SEP].
+        max_length: If set, truncate to this many tokens and return the rest as overflowing windows.
+        stride: The number of tokens of overlap between overflowing windows.
+        pad_to_length: If set, pad to this many tokens.
+        pad_to_multiple_of: If set, pad the length up to a multiple of this value.
+        pad_id: The padding token ID; defaults to the tokenizer's [PAD] or <pad> token, or 0.
+
+    Returns:
+        The encoding.
+
+    Raises:
+        ValueError: If the tokenizer cannot be loaded or the text cannot be encoded.
+    """
+    pass
+
+def encode_list(
+    tokenizer: str,
+    texts: List[str],
+    add_special_tokens: bool = False,
+    max_length: Optional[int] = None,
+    stride: int = 0,
+    pad_to_length: Optional[int] = None,
+    pad_to_multiple_of: Optional[int] = None,
+    pad_id: Optional[int] = None,
+) -> List[TokenEncoding]:
+    """
+    Encode a list of text buffers in parallel with special tokens, truncation and padding.
+
+    With pad_to_multiple_of and no pad_to_length, all encodings are padded to the longest
+    encoding in the list, rounded up to the multiple.
+
+    Args:
+        tokenizer: The tokenizer to use.
+        texts: The text buffers to encode.
+        add_special_tokens: Whether to add the tokenizer's special tokens.
+        max_length: If set, truncate to this many tokens.
+        stride: The number of tokens of overlap between overflowing windows.
+        pad_to_length: If set, pad to this many tokens.
+        pad_to_multiple_of: If set, pad the length up to a multiple of this value.
+        pad_id: The padding token ID.
+
+    Returns:
+        One encoding per text buffer.
+
+    Raises:
+        ValueError: If the tokenizer cannot be loaded or the texts cannot be encoded.
+    """
+    pass
 
 def encode_str(tokenizer: str, text: str) -> List[int]:
     """
     Encode a text buffer using the given tokenizer.
 
     Args:
-        tokenizer: The tokenizer to use.
+        tokenizer: The tokenizer to use, as a registered name, a tokenizer.json path or a
+            Hugging Face hub identifier.
         text: The text buffer to encode.
 
     Returns:
         The encoded text buffer.
+
+    Raises:
+        ValueError: If the tokenizer cannot be loaded or the text cannot be encoded.
     """
     pass
 
@@ -22,7 +372,8 @@ def decode_str(tokenizer: str, tokens: List[int]) -> str:
     Decode a list of tokens using the given tokenizer.
 
     Args:
-        tokenizer: The tokenizer to use.
+        tokenizer: The tokenizer to use, as a registered name, a tokenizer.json path or a
+            Hugging Face hub identifier.
         tokens: The list of tokens to decode.
 
     Returns:
@@ -35,7 +386,8 @@ def encode_str_list(tokenizer: str, text_list: List[str]) -> List[List[int]]:
     Encode a list of text buffers using the given tokenizer.
 
     Args:
-        tokenizer: The tokenizer to use.
+        tokenizer: The tokenizer to use, as a registered name, a tokenizer.json path or a
+            Hugging Face hub identifier.
         text_list: The list of text buffers to encode.
 
     Returns:
@@ -43,12 +395,34 @@ def encode_str_list(tokenizer: str, text_list: List[str]) -> List[List[int]]:
     """
     pass
 
+def encode_str_list_numpy(
+    tokenizer: str, text_list: List[str], pad_id: int = 0
+) -> Tuple[numpy.ndarray, numpy.ndarray]:
+    """
+    Encode a list of text buffers into a padded NumPy array.
+
+    Requires numpy. The token IDs are copied once into the array, avoiding the nested lists
+    built by encode_str_list.
+
+    Args:
+        tokenizer: The tokenizer to use, as a registered name, a tokenizer.json path or a
+            Hugging Face hub identifier.
+        text_list: The list of text buffers to encode.
+        pad_id: The ID used to pad shorter encodings.
+
+    Returns:
+        A uint32 array of shape (len(text_list), longest encoding) and an int64 array of the
+        encoding lengths.
+    """
+    pass
+
 def decode_str_list(tokenizer: str, tokens_list: List[List[int]]) -> List[str]:
     """
     Decode a list of lists of tokens using the given tokenizer.
 
     Args:
-        tokenizer: The tokenizer to use.
+        tokenizer: The tokenizer to use, as a registered name, a tokenizer.json path or a
+            Hugging Face hub identifier.
         tokens_list: The list of lists of tokens to decode.
 
     Returns:
@@ -3,7 +3,7 @@ Unicode algorithms for preprocessing text data.
 """
 
 # import submodules
-from . import categories, normalizations, segmentations
+from . import categories, fixes, normalizations, scripts, segmentations
 
 # export submodules
-__all__ = ["categories", "normalizations", "segmentations"]
+__all__ = ["categories", "fixes", "normalizations", "scripts", "segmentations"]
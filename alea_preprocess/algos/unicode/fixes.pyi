@@ -0,0 +1,86 @@
+from typing import List, Tuple
+
+def fix_text(
+    buffer: str,
+    fix_mojibake: bool = True,
+    fix_html_entities: bool = True,
+    uncurl_quotes: bool = False,
+    fix_ligatures: bool = False,
+) -> Tuple[str, List[str]]:
+    """
+    Repairs common encoding and escaping errors in a string and reports which fixes were applied.
+
+    Fixes are applied in order: mojibake, HTML entities, curly quotes, then ligatures.
+
+    Args:
+        buffer (str): The string to repair.
+        fix_mojibake (bool): Repair UTF-8 that was decoded as Windows-1252 or Latin-1.
+        fix_html_entities (bool): Collapse multiply-escaped HTML entities and decode them.
+        uncurl_quotes (bool): Replace curly quotes with ASCII quotes.
+        fix_ligatures (bool): Expand typographic ligatures such as "ﬁ".
+
+    Returns:
+        Tuple[str, List[str]]: The repaired string and the names of the fixes that changed it,
+        from "mojibake", "html_entities", "curly_quotes" and "ligatures".
+    """
+    ...
+
+def fix_mojibake(buffer: str) -> str:
+    """
+    Repairs UTF-8 text that was decoded as Windows-1252 or Latin-1, e.g., "cafÃ©" to "café".
+
+    Args:
+        buffer (str): The string to repair.
+
+    Returns:
+        str: The repaired string.
+    """
+    ...
+
+def has_mojibake(buffer: str) -> bool:
+    """
+    Returns True if the string contains repairable mojibake.
+
+    Args:
+        buffer (str): The string to check.
+
+    Returns:
+        bool: True if fix_mojibake would change the string.
+    """
+    ...
+
+def fix_html_entities(buffer: str) -> str:
+    """
+    Collapses entities that were escaped more than once, e.g., "&amp;amp;", and decodes all HTML entities.
+
+    Args:
+        buffer (str): The string to repair.
+
+    Returns:
+        str: The string with HTML entities decoded.
+    """
+    ...
+
+def uncurl_quotes(buffer: str) -> str:
+    """
+    Replaces curly single and double quotes with their ASCII equivalents.
+
+    Args:
+        buffer (str): The input string.
+
+    Returns:
+        str: The string with straight quotes.
+    """
+    ...
+
+def fix_ligatures(buffer: str) -> str:
+    """
+    Expands Latin typographic ligatures, e.g., "ﬁ" to "fi".
+
+    Args:
+        buffer (str): The input string.
+
+    Returns:
+        str: The string with ligatures expanded.
+    """
+    ...
@@ -53,3 +53,35 @@ def nfkc_str(buffer: str) -> str:
         str: A string representing the buffer normalized to NFKC.
     """
     ...
+
+def normalize_whitespace(
+    buffer: str,
+    collapse_whitespace: bool = True,
+    unify_spaces: bool = True,
+    strip_zero_width: bool = True,
+    strip_bidi_controls: bool = True,
+    strip_control_chars: bool = True,
+    normalize_newlines: bool = True,
+    trim: bool = True,
+) -> tuple[str, list[int]]:
+    """
+    Returns a buffer with normalized whitespace and control characters and an offset map.
+
+    Collapses whitespace runs, unifies exotic spaces (NBSP, U+2000-U+200B, etc.) to U+0020,
+    strips zero-width characters and bidi controls, and normalizes line endings to "\\n".
+
+    Args:
+        buffer (str): A string to be normalized.
+        collapse_whitespace (bool): Collapse runs of spaces and tabs to a single space.
+        unify_spaces (bool): Replace exotic spaces with U+0020.
+        strip_zero_width (bool): Remove zero-width characters, soft hyphens and BOMs.
+        strip_bidi_controls (bool): Remove bidirectional formatting controls.
+        strip_control_chars (bool): Remove control characters other than tabs and newlines.
+        normalize_newlines (bool): Replace CRLF, CR and Unicode line separators with "\\n".
+        trim (bool): Remove leading and trailing whitespace.
+
+    Returns:
+        tuple[str, list[int]]: The normalized string and, for each of its characters, the
+        character offset in the original string, followed by a final end offset.
+    """
+    ...
@@ -0,0 +1,117 @@
+from typing import Dict, List, Optional, Tuple
+
+def char_to_script(ch: str) -> str:
+    """
+    Returns the Unicode script for a given character.
+
+    Args:
+        ch (str): A single character.
+
+    Returns:
+        str: The long Unicode script name, e.g., "Latin" or "Han".
+    """
+    ...
+
+def char_to_script_group(ch: str) -> str:
+    """
+    Returns the script group for a given character.
+
+    Script groups are one of "Latin", "Cyrillic", "Greek", "Arabic", "Hebrew", "CJK", "Indic",
+    "Other", or "Common" (for Common, Inherited and Unknown characters).
+
+    Args:
+        ch (str): A single character.
+
+    Returns:
+        str: A string representing the script group.
+    """
+    ...
+
+def to_script_vector(input_data: str) -> List[str]:
+    """
+    Returns the Unicode script for each character in a given string.
+
+    Args:
+        input_data (str): A string.
+
+    Returns:
+        List[str]: A list of script names for each character in the input string.
+    """
+    ...
+
+def to_script_group_vector(input_data: str) -> List[str]:
+    """
+    Returns the script group for each character in a given string.
+
+    Args:
+        input_data (str): A string.
+
+    Returns:
+        List[str]: A list of script group names for each character in the input string.
+    """
+    ...
+
+def get_script_counts(input_data: str) -> Dict[str, int]:
+    """
+    Returns a histogram of Unicode scripts in a given string, including Common and Inherited.
+
+    Args:
+        input_data (str): A string.
+
+    Returns:
+        Dict[str, int]: A mapping from script name to character count.
+    """
+    ...
+
+def get_script_ratios(input_data: str) -> Dict[str, float]:
+    """
+    Returns the fraction of script-bearing characters in each script.
+
+    Common, Inherited and Unknown characters (digits, punctuation, combining marks) are excluded.
+
+    Args:
+        input_data (str): A string.
+
+    Returns:
+        Dict[str, float]: A mapping from script name to ratio; empty if there are no script-bearing characters.
+    """
+    ...
+
+def get_script_group_ratios(input_data: str) -> Dict[str, float]:
+    """
+    Returns the fraction of script-bearing characters in each script group, e.g., "Latin" or "CJK".
+
+    Args:
+        input_data (str): A string.
+
+    Returns:
+        Dict[str, float]: A mapping from script group name to ratio.
+    """
+    ...
+
+def get_dominant_script(input_data: str) -> Optional[str]:
+    """
+    Returns the most frequent script-bearing script in a given string.
+
+    Args:
+        input_data (str): A string.
+
+    Returns:
+        Optional[str]: The dominant script name, or None if there are no script-bearing characters.
+    """
+    ...
+
+def get_script_runs(input_data: str) -> List[Tuple[int, int, str, str]]:
+    """
+    Splits the input string into runs of characters in the same script.
+
+    Common and Inherited characters are attached to the surrounding run.
+
+    Args:
+        input_data (str): A string.
+
+    Returns:
+        List[Tuple[int, int, str, str]]: A list of tuples, each containing the start index, end index,
+        script name, and the run as a string.
+    """
+    ...
@@ -0,0 +1,40 @@
+class AleaPreprocessError(ValueError):
+    """
+    Base class for errors raised by alea_preprocess.
+
+    Subclasses ValueError, so handlers written before the typed errors still match.
+    """
+
+    ...
+
+class ParseError(AleaPreprocessError):
+    """
+    A document could not be parsed, e.g. a PDF that pdfium cannot load.
+
+    Raised instead of returning empty text, so an empty document can be told apart from a
+    failed one.
+    """
+
+    ...
+
+class TokenizerError(AleaPreprocessError):
+    """
+    A tokenizer could not be loaded or failed to encode or decode.
+    """
+
+    ...
+
+class TikaError(AleaPreprocessError):
+    """
+    A request to the Tika server failed or returned an error status.
+    """
+
+    ...
+
+class DecodeError(AleaPreprocessError):
+    """
+    Encoded content could not be decoded, e.g. invalid base64, a corrupt compressed stream or
+    invalid UTF-8.
+    """
+
+    ...
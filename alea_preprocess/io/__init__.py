@@ -3,7 +3,7 @@ IO routines for alea-preprocess
 """
 
 # import submodules
-from . import fs
+from . import encoding, fs, shards
 
 # export modules
-__all__ = ["fs"]
+__all__ = ["encoding", "fs", "shards"]
@@ -0,0 +1,101 @@
+"""
+Character encoding detection and decoding for non-UTF-8 byte inputs.
+"""
+
+from typing import Optional, Tuple
+
+def detect_encoding(buffer: bytes) -> Tuple[str, float, str]:
+    """
+    Detect the character encoding of a buffer.
+
+    Detection uses, in order: byte order marks, UTF-8 validity, HTML meta charset
+    declarations, and statistical detection for legacy encodings such as windows-1252,
+    Shift_JIS or gb18030.
+
+    Args:
+        buffer (bytes): The input bytes.
+
+    Returns:
+        Tuple[str, float, str]: The encoding name, a confidence between 0.0 and 1.0, and the
+        detection source, one of "bom", "utf8", "html_meta" or "statistical".
+    """
+    ...
+
+def detect_html_meta_charset(buffer: bytes) -> Optional[str]:
+    """
+    Detect the encoding declared by an HTML meta tag in the first 4096 bytes.
+
+    Args:
+        buffer (bytes): The input bytes.
+
+    Returns:
+        Optional[str]: The declared encoding name, or None if there is no recognized declaration.
+    """
+    ...
+
+def is_text(buffer: bytes) -> bool:
+    """
+    Check whether a buffer looks like text rather than binary data.
+
+    Args:
+        buffer (bytes): The input bytes.
+
+    Returns:
+        bool: True if the buffer has a BOM or no NUL bytes in its first 8192 bytes.
+    """
+    ...
+
+def decode_bytes(buffer: bytes) -> Tuple[str, str, float, bool]:
+    """
+    Detect the encoding of a buffer and decode it.
+
+    Args:
+        buffer (bytes): The input bytes.
+
+    Returns:
+        Tuple[str, str, float, bool]: The decoded text, the encoding name, the detection
+        confidence, and whether any malformed sequences were replaced.
+    """
+    ...
+
+def decode_with_encoding(buffer: bytes, encoding: str) -> str:
+    """
+    Decode a buffer with a named encoding, replacing malformed sequences.
+
+    Args:
+        buffer (bytes): The input bytes.
+        encoding (str): An encoding label, e.g., "utf-8", "latin1", "cp1252" or "shift_jis".
+
+    Returns:
+        str: The decoded text.
+
+    Raises:
+        ValueError: If the encoding label is not recognized.
+    """
+    ...
+
+def decode_file(path: str) -> Tuple[str, str, float, bool]:
+    """
+    Read a file, detect its encoding, and decode it.
+
+    Args:
+        path (str): The path to the file.
+
+    Returns:
+        Tuple[str, str, float, bool]: The decoded text, the encoding name, the detection
+        confidence, and whether any malformed sequences were replaced.
+    """
+    ...
+
+def decode_gz_file(path: str) -> Tuple[str, str, float, bool]:
+    """
+    Read a Gzipped file, detect its encoding, and decode it.
+
+    Args:
+        path (str): The path to the file.
+
+    Returns:
+        Tuple[str, str, float, bool]: The decoded text, the encoding name, the detection
+        confidence, and whether any malformed sequences were replaced.
+    """
+    ...
@@ -4,6 +4,7 @@ class FileInfo:
     media_type: str
     extension: str
     kind: str
+    encoding: str
 
 def get_file_info_from_buffer(buffer: bytes) -> FileInfo:
     """
@@ -26,5 +27,8 @@ def get_file_info_from_file(path: str) -> FileInfo:
 
     Returns:
         FileInfo.
+
+    Raises:
+        OSError: If the file cannot be read.
     """
     ...
@@ -0,0 +1,62 @@
+"""
+Binary token shards in the Megatron-LM .bin/.idx and NumPy .npy formats.
+"""
+
+from typing import List, Tuple
+
+class ShardReader:
+    """
+    A memory-mapped shard of token sequences.
+
+    Opens `{prefix}.idx` and maps `{prefix}.bin`, or `{prefix}.npy` if there is no
+    `.bin` file. Any Megatron-LM indexed dataset with a uint8, uint16 or int32 dtype
+    can be read.
+    """
+
+    dtype: str
+    num_tokens: int
+    sizes: List[int]
+    document_indices: List[int]
+
+    def __init__(self, prefix: str) -> None:
+        """
+        Open a shard.
+
+        Args:
+            prefix (str): The shard path without the .bin/.npy/.idx extension.
+
+        Raises:
+            IOError: If the files are missing or the index is invalid.
+        """
+        ...
+
+    def __len__(self) -> int: ...
+    def __getitem__(self, index: int) -> List[int]: ...
+
+def write_shard(
+    sequences: List[List[int]],
+    output_prefix: str,
+    dtype: str = "uint16",
+    format: str = "bin",
+) -> Tuple[int, int]:
+    """
+    Write token sequences to a shard, one document per sequence.
+
+    Writes `{output_prefix}.bin` (or `.npy`) with the sequences back to back and
+    `{output_prefix}.idx` with their lengths, byte offsets and document boundaries in
+    the Megatron-LM MMIDIDX layout. A `.bin` file alone is a NanoGPT-style token stream,
+    and a `.npy` file can be opened with `np.load(path, mmap_mode="r")`.
+
+    Args:
+        sequences (List[List[int]]): The token sequences.
+        output_prefix (str): The shard path without extension; existing files are overwritten.
+        dtype (str): One of "uint8", "uint16", "uint32" or "int32".
+        format (str): "bin" or "npy".
+
+    Returns:
+        Tuple[int, int]: The number of sequences and tokens written.
+
+    Raises:
+        ValueError: If the dtype or format is invalid, or a token does not fit in the dtype.
+    """
+    ...
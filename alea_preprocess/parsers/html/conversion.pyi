@@ -25,3 +25,32 @@ def extract_buffer_markdown(
         str: The Markdown representation of the HTML buffer.
     """
     ...
+
+def extract_buffer_text_batch(buffers: list[str]) -> list[str]:
+    """
+    Extracts the text representation of several HTML buffers in parallel, without holding the GIL.
+
+    Args:
+        buffers (list[str]): The HTML buffers.
+
+    Returns:
+        list[str]: The text representation of each buffer, in input order.
+    """
+    ...
+
+def extract_buffer_markdown_batch(
+    buffers: list[str], output_links: bool, output_images: bool
+) -> list[str]:
+    """
+    Extracts the Markdown representation of several HTML buffers in parallel, without holding
+    the GIL.
+
+    Args:
+        buffers (list[str]): The HTML buffers.
+        output_links (bool): Whether to output links.
+        output_images (bool): Whether to output images.
+
+    Returns:
+        list[str]: The Markdown representation of each buffer, in input order.
+    """
+    ...
@@ -1,3 +1,5 @@
+from typing import Optional
+
 """
 
 #[pyfunction]
@@ -29,7 +31,11 @@ def extract_buffer_text(buffer: bytes) -> str:
         buffer: The buffer to extract text from.
 
     Returns:
-        The text extracted from the buffer.
+        The text extracted from the buffer, which is empty
+        if the document has no text.
+
+    Raises:
+        ParseError: If the PDF cannot be loaded.
     """
     ...
 
@@ -41,7 +47,11 @@ def extract_file_text(file_path: str) -> str:
         file_path: The path to the file to extract text from.
 
     Returns:
-        The text extracted from the file.
+        The text extracted from the file, which is empty
+        if the document has no text.
+
+    Raises:
+        ParseError: If the PDF cannot be loaded.
     """
     ...
 
@@ -53,7 +63,11 @@ def extract_buffer_markdown(buffer: bytes) -> str:
         buffer: The buffer to extract text from.
 
     Returns:
-        The text with position extracted from the buffer.
+        The text with position extracted from the buffer, which is empty
+        if the document has no text.
+
+    Raises:
+        ParseError: If the PDF cannot be loaded.
     """
     ...
 
@@ -65,6 +79,65 @@ def extract_file_markdown(file_path: str) -> str:
         file_path: The path to the file to extract text from.
 
     Returns:
-        The text with position extracted from the file.
+        The text with position extracted from the file, which is empty
+        if the document has no text.
+
+    Raises:
+        ParseError: If the PDF cannot be loaded.
+    """
+    ...
+
+def extract_buffer_text_batch(buffers: list[bytes]) -> list[Optional[str]]:
+    """
+    Extract text from several buffers without holding the GIL.
+
+    pdfium holds a process-wide lock for each whole extraction, so the PDFs are processed one
+    at a time; other Python threads can run in the meantime.
+
+    Args:
+        buffers: The PDF buffers to extract text from.
+
+    Returns:
+        The text extracted from each buffer, in input order, or None
+        where the PDF cannot be loaded.
+    """
+    ...
+
+def extract_file_text_batch(file_paths: list[str]) -> list[Optional[str]]:
+    """
+    Extract text from several files one at a time, without holding the GIL.
+
+    Args:
+        file_paths: The paths to the files to extract text from.
+
+    Returns:
+        The text extracted from each file, in input order, or None
+        where the PDF cannot be loaded.
+    """
+    ...
+
+def extract_buffer_markdown_batch(buffers: list[bytes]) -> list[Optional[str]]:
+    """
+    Extract the text with position from several buffers one at a time, without holding the GIL.
+
+    Args:
+        buffers: The PDF buffers to extract text from.
+
+    Returns:
+        The text with position extracted from each buffer, in input order, or None
+        where the PDF cannot be loaded.
+    """
+    ...
+
+def extract_file_markdown_batch(file_paths: list[str]) -> list[Optional[str]]:
+    """
+    Extract the text with position from several files one at a time, without holding the GIL.
+
+    Args:
+        file_paths: The paths to the files to extract text from.
+
+    Returns:
+        The text with position extracted from each file, in input order, or None
+        where the PDF cannot be loaded.
     """
     ...
@@ -18,6 +18,9 @@ def detect_buffer_type(buffer: bytes) -> Any:
 
     Returns:
         The type of document detected.
+
+    Raises:
+        ParseError: If the PDF cannot be loaded.
     """
     ...
 
@@ -30,5 +33,27 @@ def detect_file_type(file_path: str) -> Any:
 
     Returns:
         The type of document detected.
+
+    Raises:
+        ParseError: If the PDF cannot be loaded.
+    """
+    ...
+
+def is_ocr_text_unusable(buffer: bytes, text: str) -> bool:
+    """
+    Check whether the text layer of an OCR'd PDF is too noisy to use.
+
+    Only ImagePostOCR documents are checked, with the default thresholds of
+    algos.quality.noise.get_noise_report; other document types return False.
+
+    Args:
+        buffer: The PDF file content.
+        text: The text extracted from the PDF.
+
+    Returns:
+        True if the document is ImagePostOCR and its text is unusable.
+
+    Raises:
+        ParseError: If the PDF cannot be loaded.
     """
     ...
@@ -10,6 +10,9 @@ def get_recursive_metadata_buffer(buffer: bytes, server_url: str) -> List[Any]:
 
     Returns:
         A list of metadata dictionaries.
+
+    Raises:
+        TikaError: If the request to the Tika server fails.
     """
     ...
 
@@ -23,6 +26,10 @@ def get_recursive_metadata_file(file_path: str, server_url: str) -> List[Any]:
 
     Returns:
         A list of metadata dictionaries.
+
+    Raises:
+        OSError: If the file cannot be read.
+        TikaError: If the request to the Tika server fails.
     """
     ...
 
@@ -36,6 +43,9 @@ def get_recursive_content_html_buffer(buffer: bytes, server_url: str) -> List[An
 
     Returns:
         A list of content dictionaries.
+
+    Raises:
+        TikaError: If the request to the Tika server fails.
     """
     ...
 
@@ -49,6 +59,10 @@ def get_recursive_content_html_file(file_path: str, server_url: str) -> List[Any
 
     Returns:
         A list of content dictionaries.
+
+    Raises:
+        OSError: If the file cannot be read.
+        TikaError: If the request to the Tika server fails.
     """
     ...
 
@@ -66,6 +80,9 @@ def get_recursive_content_markdown_buffer(
 
     Returns:
         A list of content dictionaries.
+
+    Raises:
+        TikaError: If the request to the Tika server fails.
     """
     ...
 
@@ -83,5 +100,9 @@ def get_recursive_content_markdown_file(
 
     Returns:
         A list of content dictionaries.
+
+    Raises:
+        OSError: If the file cannot be read.
+        TikaError: If the request to the Tika server fails.
     """
     ...
@@ -3,7 +3,7 @@ LLM task package.
 """
 
 # imports
-from . import sequences
+from . import dpo, fim, mlm, packing, sequences, sft, span_corruption
 
 # exports
-__all__ = ["sequences"]
+__all__ = ["dpo", "fim", "mlm", "packing", "sequences", "sft", "span_corruption"]
@@ -0,0 +1,95 @@
+"""
+Direct Preference Optimization (DPO) preference pairs.
+"""
+
+# imports
+from typing import List, Optional
+
+class DpoSample:
+    """
+    A tokenized preference pair.
+
+    The chosen and rejected sequences share the same prompt_length prompt tokens, and their labels
+    mask the prompt and padding. similarity is the normalized edit similarity of the responses,
+    one minus their Levenshtein distance over the character length of the longer one.
+    """
+
+    chosen_input_ids: List[int]
+    chosen_attention_mask: List[int]
+    chosen_labels: List[int]
+    rejected_input_ids: List[int]
+    rejected_attention_mask: List[int]
+    rejected_labels: List[int]
+    prompt_length: int
+    similarity: float
+    is_degenerate: bool
+
+def get_dpo_sample(
+    prompt: str,
+    chosen: str,
+    rejected: str,
+    tokenizer_name: str,
+    max_seq_length: int,
+    eos_token_id: Optional[int] = None,
+    pad_token_id: int = 0,
+    label_mask_id: int = -100,
+    max_prompt_length: int = 256,
+    similarity_threshold: float = 0.95,
+) -> DpoSample:
+    """
+    Get a DPO sample from a prompt and chosen and rejected responses.
+
+    If the prompt and the longer response do not fit, the prompt is first cut to its last
+    max_prompt_length tokens, and then both responses are cut to the remaining length.
+
+    Args:
+        prompt: The prompt text.
+        chosen: The preferred response.
+        rejected: The dispreferred response.
+        tokenizer_name: The name of the tokenizer.
+        max_seq_length: The length of each padded sequence.
+        eos_token_id: The ID of the token appended to each response, if any.
+        pad_token_id: The ID of the PAD token.
+        label_mask_id: The label ID for prompt and padding tokens.
+        max_prompt_length: The maximum number of prompt tokens kept when truncating.
+        similarity_threshold: The response similarity at or above which a pair is degenerate.
+
+    Returns:
+        The DPO sample.
+
+    Raises:
+        ValueError: If the tokenizer cannot be loaded or the text cannot be encoded.
+    """
+    ...
+
+def get_dpo_samples_from_file(
+    path: str,
+    tokenizer_name: str,
+    max_seq_length: int,
+    eos_token_id: Optional[int] = None,
+    pad_token_id: int = 0,
+    label_mask_id: int = -100,
+    max_prompt_length: int = 256,
+    similarity_threshold: float = 0.95,
+) -> List[DpoSample]:
+    """
+    Get DPO samples from a JSONL or gzipped JSONL file with "prompt", "chosen", and "rejected" fields.
+
+    Args:
+        path: The path to the file.
+        tokenizer_name: The name of the tokenizer.
+        max_seq_length: The length of each padded sequence.
+        eos_token_id: The ID of the token appended to each response, if any.
+        pad_token_id: The ID of the PAD token.
+        label_mask_id: The label ID for prompt and padding tokens.
+        max_prompt_length: The maximum number of prompt tokens kept when truncating.
+        similarity_threshold: The response similarity at or above which a pair is degenerate.
+
+    Returns:
+        One sample per line.
+
+    Raises:
+        ValueError: If the file cannot be read, a line lacks a field, or the tokenizer cannot be
+            loaded.
+    """
+    ...
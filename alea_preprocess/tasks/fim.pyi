@@ -0,0 +1,137 @@
+"""
+Fill-in-the-middle (FIM) transformations for causal language modeling.
+"""
+
+# imports
+from typing import List, Optional
+
+def get_fim_samples_from_tokens(
+    tokens: List[int],
+    max_seq_length: int,
+    prefix_token_id: int,
+    middle_token_id: int,
+    suffix_token_id: int,
+    eos_token_id: int,
+    pad_token_id: int,
+    label_mask_id: int,
+    fim_rate: float = 0.5,
+    spm_rate: float = 0.5,
+    seed: Optional[int] = None,
+) -> List[tuple[List[int], List[int], List[int]]]:
+    """
+    Get fill-in-the-middle samples from a list of tokens.
+
+    Tokens are split with split_sequence_max into chunks of max_seq_length - 4 to leave room for
+    the three sentinels and the EOS token. Each chunk is transformed with probability fim_rate,
+    splitting at two random token positions. PSM produces <PRE> prefix <SUF> suffix <MID> middle,
+    and SPM, used with probability spm_rate, produces <PRE> <SUF> suffix <MID> prefix middle.
+
+    Args:
+        tokens: The input tokens.
+        max_seq_length: The maximum sequence length; samples are padded to this length.
+        prefix_token_id: The ID of the prefix sentinel.
+        middle_token_id: The ID of the middle sentinel.
+        suffix_token_id: The ID of the suffix sentinel.
+        eos_token_id: The ID of the EOS token appended to each sample.
+        pad_token_id: The ID of the PAD token.
+        label_mask_id: The label ID at padding positions.
+        fim_rate: The probability of transforming a sample.
+        spm_rate: The probability of SPM instead of PSM for transformed samples.
+        seed: The seed for reproducible sampling; combined with a Blake3 hash of the input.
+            If None, sampling is random on every call.
+
+    Returns:
+        A list of tuples containing the input IDs, attention mask, and labels.
+
+    Raises:
+        ValueError: If max_seq_length is not greater than 4.
+    """
+    ...
+
+def get_fim_samples_from_text(
+    text: str,
+    max_seq_length: int,
+    tokenizer_name: str,
+    prefix_token_id: int,
+    middle_token_id: int,
+    suffix_token_id: int,
+    eos_token_id: int,
+    pad_token_id: int,
+    label_mask_id: int,
+    fim_rate: float = 0.5,
+    spm_rate: float = 0.5,
+    boundary: str = "character",
+    seed: Optional[int] = None,
+) -> List[tuple[List[int], List[int], List[int]]]:
+    """
+    Get fill-in-the-middle samples from text.
+
+    The text is tokenized and split into chunks like get_fim_samples_from_tokens. Transformed chunks
+    are split into prefix, middle, and suffix text at the boundary, and each part is tokenized on
+    its own; if the parts no longer fit, the end of the suffix is trimmed.
+
+    Args:
+        text: The input text.
+        max_seq_length: The maximum sequence length; samples are padded to this length.
+        tokenizer_name: The name of the tokenizer.
+        prefix_token_id: The ID of the prefix sentinel.
+        middle_token_id: The ID of the middle sentinel.
+        suffix_token_id: The ID of the suffix sentinel.
+        eos_token_id: The ID of the EOS token appended to each sample.
+        pad_token_id: The ID of the PAD token.
+        label_mask_id: The label ID at padding positions.
+        fim_rate: The probability of transforming a sample.
+        spm_rate: The probability of SPM instead of PSM for transformed samples.
+        boundary: Where to split: "character", "line", or "sentence".
+        seed: The seed for reproducible sampling; combined with a Blake3 hash of the input.
+
+    Returns:
+        A list of tuples containing the input IDs, attention mask, and labels.
+
+    Raises:
+        ValueError: If the boundary is invalid, max_seq_length is not greater than 4, or the
+            tokenizer cannot be loaded or the text cannot be encoded.
+    """
+    ...
+
+def get_fim_samples_from_content(
+    encoded_content: str,
+    max_seq_length: int,
+    tokenizer_name: str,
+    prefix_token_id: int,
+    middle_token_id: int,
+    suffix_token_id: int,
+    eos_token_id: int,
+    pad_token_id: int,
+    label_mask_id: int,
+    fim_rate: float = 0.5,
+    spm_rate: float = 0.5,
+    boundary: str = "character",
+    seed: Optional[int] = None,
+) -> List[tuple[List[int], List[int], List[int]]]:
+    """
+    Get fill-in-the-middle samples from encoded content.
+
+    Args:
+        encoded_content: The encoded content as a string.
+        max_seq_length: The maximum sequence length; samples are padded to this length.
+        tokenizer_name: The name of the tokenizer.
+        prefix_token_id: The ID of the prefix sentinel.
+        middle_token_id: The ID of the middle sentinel.
+        suffix_token_id: The ID of the suffix sentinel.
+        eos_token_id: The ID of the EOS token appended to each sample.
+        pad_token_id: The ID of the PAD token.
+        label_mask_id: The label ID at padding positions.
+        fim_rate: The probability of transforming a sample.
+        spm_rate: The probability of SPM instead of PSM for transformed samples.
+        boundary: Where to split: "character", "line", or "sentence".
+        seed: The seed for reproducible sampling; combined with a Blake3 hash of the input.
+
+    Returns:
+        A list of tuples containing the input IDs, attention mask, and labels.
+
+    Raises:
+        ValueError: If the boundary is invalid, max_seq_length is not greater than 4, or the
+            tokenizer cannot be loaded or the content cannot be encoded.
+    """
+    ...
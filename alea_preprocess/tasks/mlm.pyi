@@ -3,7 +3,9 @@ Sequence splitting and convenience functions.
 """
 
 # imports
-from typing import List
+from typing import List, Optional, Tuple
+
+import numpy
 
 def get_masked_sample(
     tokens: List[int],
@@ -14,6 +16,14 @@ def get_masked_sample(
     label_mask_id: int,
     max_seq_length: int,
     prob_mask: float,
+    prob_mask_token: float = 1.0,
+    prob_random_token: float = 0.0,
+    vocab_size: int = 0,
+    whole_word_masking: bool = False,
+    word_ids: Optional[List[int]] = None,
+    excluded_token_ids: List[int] = [],
+    min_masked_tokens: int = 1,
+    seed: Optional[int] = None,
 ) -> tuple[List[int], List[int], List[int], List[int]]:
     """
     Get a masked sample from the tokens.
@@ -27,6 +37,18 @@ def get_masked_sample(
         label_mask_id: The ID of the label mask.
         max_seq_length: The maximum sequence length.
         prob_mask: The probability of masking a token.
+        prob_mask_token: The probability that a selected token is replaced with the MASK token.
+        prob_random_token: The probability that a selected token is replaced with a random token;
+            the rest are kept unchanged, e.g., 0.8 and 0.1 for BERT-style 80/10/10 masking.
+        vocab_size: The number of token IDs to draw random replacements from; 0 disables them.
+        whole_word_masking: Whether to mask all tokens of a word together.
+        word_ids: The word index of each token, or -1 for tokens outside words; required for
+            whole-word masking.
+        excluded_token_ids: Token IDs that are never masked, e.g., special tokens.
+        min_masked_tokens: The minimum number of tokens to mask per sample.
+        seed: The seed for reproducible masking; combined with a Blake3 hash of the input so that
+            identical inputs give identical samples and different inputs get different masks.
+            If None, masking is random on every call.
 
     Returns:
         A tuple containing the input IDs, attention mask, token type IDs, and label IDs.
@@ -42,6 +64,14 @@ def get_masked_samples_from_tokens(
     pad_token_id: int,
     label_mask_id: int,
     prob_mask: float,
+    prob_mask_token: float = 1.0,
+    prob_random_token: float = 0.0,
+    vocab_size: int = 0,
+    whole_word_masking: bool = False,
+    word_ids: Optional[List[int]] = None,
+    excluded_token_ids: List[int] = [],
+    min_masked_tokens: int = 1,
+    seed: Optional[int] = None,
 ) -> List[tuple[List[int], List[int], List[int], List[int]]]:
     """
     Get masked samples from a list of tokens.
@@ -55,6 +85,18 @@ def get_masked_samples_from_tokens(
         pad_token_id: The ID of the PAD token.
         label_mask_id: The ID of the label mask.
         prob_mask: The probability of masking a token.
+        prob_mask_token: The probability that a selected token is replaced with the MASK token.
+        prob_random_token: The probability that a selected token is replaced with a random token;
+            the rest are kept unchanged, e.g., 0.8 and 0.1 for BERT-style 80/10/10 masking.
+        vocab_size: The number of token IDs to draw random replacements from; 0 disables them.
+        whole_word_masking: Whether to mask all tokens of a word together.
+        word_ids: The word index of each token, or -1 for tokens outside words; required for
+            whole-word masking.
+        excluded_token_ids: Token IDs that are never masked, e.g., special tokens.
+        min_masked_tokens: The minimum number of tokens to mask per sample.
+        seed: The seed for reproducible masking; combined with a Blake3 hash of the input so that
+            identical inputs give identical samples and different inputs get different masks.
+            If None, masking is random on every call.
 
     Returns:
         A list of tuples containing the input IDs, attention mask, token type IDs, and label IDs.
@@ -71,6 +113,13 @@ def get_masked_samples_from_content(
     pad_token_id: int,
     label_mask_id: int,
     prob_mask: float,
+    prob_mask_token: float = 1.0,
+    prob_random_token: float = 0.0,
+    vocab_size: int = 0,
+    whole_word_masking: bool = False,
+    excluded_token_ids: List[int] = [],
+    min_masked_tokens: int = 1,
+    seed: Optional[int] = None,
 ) -> List[tuple[List[int], List[int], List[int], List[int]]]:
     """
     Get masked samples from encoded content.
@@ -85,8 +134,142 @@ def get_masked_samples_from_content(
         pad_token_id: The ID of the PAD token.
         label_mask_id: The ID of the label mask.
         prob_mask: The probability of masking a token.
+        prob_mask_token: The probability that a selected token is replaced with the MASK token.
+        prob_random_token: The probability that a selected token is replaced with a random token;
+            the rest are kept unchanged, e.g., 0.8 and 0.1 for BERT-style 80/10/10 masking.
+        vocab_size: The number of token IDs to draw random replacements from; 0 disables them.
+        whole_word_masking: Whether to mask all tokens of a word together.
+        excluded_token_ids: Token IDs that are never masked, e.g., special tokens.
+        min_masked_tokens: The minimum number of tokens to mask per sample.
+        seed: The seed for reproducible masking; combined with a Blake3 hash of the input so that
+            identical inputs give identical samples and different inputs get different masks.
+            If None, masking is random on every call.
 
     Returns:
         A list of tuples containing the input IDs, attention mask, token type IDs, and label IDs.
+
+    Raises:
+        ValueError: If the tokenizer cannot be loaded or the content cannot be encoded.
+    """
+    ...
+
+def get_masked_samples_from_tokens_numpy(
+    tokens: List[int],
+    max_seq_length: int,
+    cls_token_id: int,
+    mask_token_id: int,
+    sep_token_id: int,
+    pad_token_id: int,
+    label_mask_id: int,
+    prob_mask: float,
+    prob_mask_token: float = 1.0,
+    prob_random_token: float = 0.0,
+    vocab_size: int = 0,
+    whole_word_masking: bool = False,
+    word_ids: Optional[List[int]] = None,
+    excluded_token_ids: List[int] = [],
+    min_masked_tokens: int = 1,
+    seed: Optional[int] = None,
+) -> Tuple[numpy.ndarray, numpy.ndarray, numpy.ndarray, numpy.ndarray]:
+    """
+    Get masked samples from tokens as NumPy arrays.
+
+    Requires numpy. Takes the same arguments as get_masked_samples_from_tokens, but returns one
+    int32 array of shape (num_samples, max_seq_length) per field instead of a list of tuples.
+
+    Returns:
+        The input IDs, attention mask, token type IDs, and label IDs arrays.
+    """
+    ...
+
+def get_masked_samples_from_content_numpy(
+    encoded_content: str,
+    max_seq_length: int,
+    tokenizer_name: str,
+    cls_token_id: int,
+    mask_token_id: int,
+    sep_token_id: int,
+    pad_token_id: int,
+    label_mask_id: int,
+    prob_mask: float,
+    prob_mask_token: float = 1.0,
+    prob_random_token: float = 0.0,
+    vocab_size: int = 0,
+    whole_word_masking: bool = False,
+    excluded_token_ids: List[int] = [],
+    min_masked_tokens: int = 1,
+    seed: Optional[int] = None,
+) -> Tuple[numpy.ndarray, numpy.ndarray, numpy.ndarray, numpy.ndarray]:
+    """
+    Get masked samples from encoded content as NumPy arrays.
+
+    Requires numpy. Takes the same arguments as get_masked_samples_from_content, but returns one
+    int32 array of shape (num_samples, max_seq_length) per field instead of a list of tuples.
+
+    Returns:
+        The input IDs, attention mask, token type IDs, and label IDs arrays.
+
+    Raises:
+        ValueError: If the tokenizer cannot be loaded or the content cannot be encoded.
+    """
+    ...
+
+def write_masked_samples_from_content(
+    encoded_contents: List[str],
+    output_prefix: str,
+    max_seq_length: int,
+    tokenizer_name: str,
+    cls_token_id: int,
+    mask_token_id: int,
+    sep_token_id: int,
+    pad_token_id: int,
+    label_mask_id: int,
+    prob_mask: float,
+    prob_mask_token: float = 1.0,
+    prob_random_token: float = 0.0,
+    vocab_size: int = 0,
+    whole_word_masking: bool = False,
+    excluded_token_ids: List[int] = [],
+    min_masked_tokens: int = 1,
+    dtype: str = "uint16",
+    format: str = "bin",
+    seed: Optional[int] = None,
+) -> int:
+    """
+    Write masked samples from encoded contents to token shards without building Python lists.
+
+    Each field is written to its own shard, `{output_prefix}.input_ids`, `.attention_mask`,
+    `.token_type_ids` and `.labels`, each a .bin (or .npy) data file with a Megatron-LM .idx
+    index; each encoded content is one document. Masks are stored as uint8 and labels as int32
+    so that the label mask ID can be negative. Read them back with
+    alea_preprocess.io.shards.ShardReader.
+
+    Args:
+        encoded_contents: The encoded contents, one per document.
+        output_prefix: The shard path prefix; existing files are overwritten.
+        max_seq_length: The maximum sequence length.
+        tokenizer_name: The name of the tokenizer.
+        cls_token_id: The ID of the CLS token.
+        mask_token_id: The ID of the MASK token.
+        sep_token_id: The ID of the SEP token.
+        pad_token_id: The ID of the PAD token.
+        label_mask_id: The ID of the label mask.
+        prob_mask: The probability of masking a token.
+        prob_mask_token: The probability that a selected token is replaced with the MASK token.
+        prob_random_token: The probability that a selected token is replaced with a random token.
+        vocab_size: The number of token IDs to draw random replacements from; 0 disables them.
+        whole_word_masking: Whether to mask all tokens of a word together.
+        excluded_token_ids: Token IDs that are never masked, e.g., special tokens.
+        min_masked_tokens: The minimum number of tokens to mask per sample.
+        dtype: The input ID dtype, "uint16" or "uint32".
+        format: "bin" or "npy".
+        seed: The seed for reproducible masking, as in get_masked_samples_from_content.
+
+    Returns:
+        The number of samples written.
+
+    Raises:
+        ValueError: If the dtype or format is invalid, the content cannot be encoded, or a token
+            does not fit in the dtype.
     """
     ...
@@ -0,0 +1,130 @@
+"""
+Causal language modeling sequence packing.
+"""
+
+# imports
+from typing import List, Optional
+
+class PackedBlock:
+    """
+    A fixed-length block of packed documents.
+
+    segment_ids number the document segments in the block from 1, with 0 for padding, so a position
+    may attend to another only if both have the same non-zero segment ID. position_ids restart at 0
+    at the start of each segment. documents holds one (document_index, start, end) entry per segment.
+    """
+
+    input_ids: List[int]
+    attention_mask: List[int]
+    position_ids: List[int]
+    segment_ids: List[int]
+    documents: List[tuple[int, int, int]]
+
+    def __len__(self) -> int: ...
+
+def pack_sequences(
+    documents: List[List[int]],
+    block_size: int,
+    bos_token_id: Optional[int] = None,
+    eos_token_id: Optional[int] = None,
+    pad_token_id: int = 0,
+    strategy: str = "concatenate",
+    drop_last: bool = False,
+) -> List[PackedBlock]:
+    """
+    Pack token sequences into fixed-length blocks for causal language modeling.
+
+    Each document is wrapped as [bos] tokens [eos], with either token omitted if None.
+
+    Args:
+        documents: The token IDs of each document.
+        block_size: The length of each block.
+        bos_token_id: The ID of the BOS token.
+        eos_token_id: The ID of the EOS token.
+        pad_token_id: The ID of the PAD token.
+        strategy: "concatenate" to stream documents in order and cut blocks at fixed offsets, or
+            "best_fit_decreasing" ("bfd") to keep each document, or block-sized chunk of a long
+            document, in a single block while minimizing padding.
+        drop_last: Whether to drop a final partial block instead of padding it with "concatenate".
+
+    Returns:
+        The packed blocks.
+
+    Raises:
+        ValueError: If the strategy is invalid or block_size is 0.
+    """
+    ...
+
+def pack_sequences_from_content(
+    encoded_contents: List[str],
+    tokenizer_name: str,
+    block_size: int,
+    bos_token_id: Optional[int] = None,
+    eos_token_id: Optional[int] = None,
+    pad_token_id: int = 0,
+    strategy: str = "concatenate",
+    drop_last: bool = False,
+) -> List[PackedBlock]:
+    """
+    Tokenize and pack encoded contents into fixed-length blocks for causal language modeling.
+
+    Args:
+        encoded_contents: The encoded contents, one per document.
+        tokenizer_name: The name of the tokenizer.
+        block_size: The length of each block.
+        bos_token_id: The ID of the BOS token.
+        eos_token_id: The ID of the EOS token.
+        pad_token_id: The ID of the PAD token.
+        strategy: "concatenate" or "best_fit_decreasing".
+        drop_last: Whether to drop a final partial block instead of padding it with "concatenate".
+
+    Returns:
+        The packed blocks.
+
+    Raises:
+        ValueError: If the strategy is invalid, or the tokenizer cannot be loaded or the content
+            cannot be encoded.
+    """
+    ...
+
+def write_packed_sequences_from_content(
+    encoded_contents: List[str],
+    output_prefix: str,
+    tokenizer_name: str,
+    block_size: int,
+    bos_token_id: Optional[int] = None,
+    eos_token_id: Optional[int] = None,
+    pad_token_id: int = 0,
+    strategy: str = "concatenate",
+    drop_last: bool = False,
+    dtype: str = "uint16",
+    format: str = "bin",
+) -> int:
+    """
+    Tokenize and pack encoded contents into token shards without building Python lists.
+
+    Each field is written to its own shard, `{output_prefix}.input_ids`, `.attention_mask`,
+    `.position_ids` and `.segment_ids`, each a .bin (or .npy) data file with a Megatron-LM .idx
+    index and one sequence per block. Read them back with alea_preprocess.io.shards.ShardReader.
+
+    Args:
+        encoded_contents: The encoded contents, one per document.
+        output_prefix: The shard path prefix; existing files are overwritten.
+        tokenizer_name: The name of the tokenizer.
+        block_size: The length of each block.
+        bos_token_id: The ID of the BOS token.
+        eos_token_id: The ID of the EOS token.
+        pad_token_id: The ID of the PAD token.
+        strategy: "concatenate" or "best_fit_decreasing".
+        drop_last: Whether to drop a final partial block instead of padding it with "concatenate".
+        dtype: The input ID dtype, "uint16" or "uint32".
+        format: "bin" or "npy".
+
+    Returns:
+        The number of blocks written.
+
+    Raises:
+        ValueError: If the strategy, dtype or format is invalid, the content cannot be encoded,
+            or a token does not fit in the dtype.
+    """
+    ...
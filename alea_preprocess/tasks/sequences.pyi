@@ -3,7 +3,7 @@ Sequence splitting and convenience functions.
 """
 
 # imports
-from typing import List
+from typing import List, Optional
 
 def split_sequence_max(sequence: List[int], max_size: int) -> List[List[int]]:
     """
@@ -17,3 +17,51 @@ def split_sequence_max(sequence: List[int], max_size: int) -> List[List[int]]:
         A list of subsequences.
     """
     ...
+
+def extract_content(encoded_content: str, codec: Optional[str] = None) -> bytes:
+    """
+    Decode base64 content and decompress it.
+
+    Args:
+        encoded_content: The base64-encoded content.
+        codec: One of "raw", "zlib", "gzip", or "zstd"; detected from the
+            decoded bytes if None, in which case UTF-8 text that looks like zlib
+            but fails to inflate is returned as is.
+
+    Returns:
+        The decoded content bytes.
+
+    Raises:
+        ValueError: If the content is not valid base64, cannot be decompressed,
+            or the codec is unknown.
+    """
+    ...
+
+def encode_content(content: bytes, codec: str = "zlib") -> str:
+    """
+    Compress content and base64 encode it; the inverse of extract_content.
+
+    Args:
+        content: The content bytes.
+        codec: One of "raw", "zlib", "gzip", or "zstd".
+
+    Returns:
+        The base64-encoded content.
+    """
+    ...
+
+def detect_codec(encoded_content: str) -> str:
+    """
+    Detect the compression codec of base64 content from its magic bytes.
+
+    zlib is detected from its header alone, which some plain text also passes;
+    extract_content falls back to the raw bytes when such content is UTF-8 text that does not
+    inflate.
+
+    Args:
+        encoded_content: The base64-encoded content.
+
+    Returns:
+        One of "raw", "zlib", "gzip", or "zstd".
+    """
+    ...
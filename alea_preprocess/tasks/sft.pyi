@@ -0,0 +1,88 @@
+"""
+Supervised fine-tuning (SFT) chat templates and assistant-only labels.
+"""
+
+# imports
+from typing import Dict, List
+
+def render_conversation(
+    messages: List[Dict[str, str]],
+    template: str = "chatml",
+) -> str:
+    """
+    Render a conversation with a chat template.
+
+    Args:
+        messages: The conversation turns, each with "role" and "content" fields.
+        template: "chatml", "llama" (Llama 3), or a custom turn template with {{ content }} and
+            optionally {{ role }} placeholders, e.g., "<|{{ role }}|>\\n{{ content }}</s>\\n".
+
+    Returns:
+        The rendered conversation.
+
+    Raises:
+        ValueError: If the template is invalid or a message lacks a field.
+    """
+    ...
+
+def get_sft_sample(
+    messages: List[Dict[str, str]],
+    tokenizer_name: str,
+    max_seq_length: int,
+    template: str = "chatml",
+    pad_token_id: int = 0,
+    label_mask_id: int = -100,
+) -> tuple[List[int], List[int], List[int]]:
+    """
+    Get an SFT sample from a conversation.
+
+    Only tokens in the content and closing tokens of assistant turns are labeled. If the
+    conversation does not fit, the oldest non-system turns are dropped so that the kept turns start
+    at a user message; if the most recent exchange alone does not fit, its end is truncated.
+
+    Args:
+        messages: The conversation turns, each with "role" and "content" fields.
+        tokenizer_name: The name of the tokenizer.
+        max_seq_length: The length of the padded sample.
+        template: "chatml", "llama", or a custom turn template.
+        pad_token_id: The ID of the PAD token.
+        label_mask_id: The label ID for tokens that are not trained on.
+
+    Returns:
+        A tuple containing the input IDs, attention mask, and labels.
+
+    Raises:
+        ValueError: If the template is invalid, a message lacks a field, or the tokenizer cannot be
+            loaded.
+    """
+    ...
+
+def get_sft_samples_from_file(
+    path: str,
+    tokenizer_name: str,
+    max_seq_length: int,
+    template: str = "chatml",
+    field: str = "messages",
+    pad_token_id: int = 0,
+    label_mask_id: int = -100,
+) -> List[tuple[List[int], List[int], List[int]]]:
+    """
+    Get SFT samples from a JSONL or gzipped JSONL file with one conversation per line.
+
+    Args:
+        path: The path to the file.
+        tokenizer_name: The name of the tokenizer.
+        max_seq_length: The length of the padded samples.
+        template: "chatml", "llama", or a custom turn template.
+        field: The field holding the list of messages.
+        pad_token_id: The ID of the PAD token.
+        label_mask_id: The label ID for tokens that are not trained on.
+
+    Returns:
+        A list of tuples containing the input IDs, attention mask, and labels.
+
+    Raises:
+        ValueError: If the file cannot be read, a line is not a valid conversation, or the
+            tokenizer cannot be loaded.
+    """
+    ...
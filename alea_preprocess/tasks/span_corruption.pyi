@@ -0,0 +1,103 @@
+"""
+T5-style span corruption for encoder-decoder training.
+"""
+
+# imports
+from typing import List, Optional
+
+def get_span_corruption_lengths(
+    max_seq_length: int,
+    noise_density: float = 0.15,
+    mean_span_length: float = 3.0,
+) -> tuple[int, int]:
+    """
+    Get the number of raw tokens per sample and the target length for an input length.
+
+    Args:
+        max_seq_length: The encoder input length, including the EOS token; at least 2.
+        noise_density: The fraction of tokens to corrupt, strictly between 0 and 1.
+        mean_span_length: The mean length of corrupted spans; must be positive.
+
+    Returns:
+        A tuple of the largest number of tokens whose corrupted input fits in max_seq_length,
+        and the length of the matching targets.
+
+    Raises:
+        AleaPreprocessError: If the lengths or noise options are out of range.
+    """
+    ...
+
+def get_span_corruption_samples_from_tokens(
+    tokens: List[int],
+    max_seq_length: int,
+    sentinel_token_ids: List[int],
+    eos_token_id: int,
+    pad_token_id: int,
+    label_mask_id: int,
+    noise_density: float = 0.15,
+    mean_span_length: float = 3.0,
+    seed: Optional[int] = None,
+) -> List[tuple[List[int], List[int], List[int]]]:
+    """
+    Get span corruption samples from a list of tokens.
+
+    Long inputs are split with split_sequence_max into chunks that fit in max_seq_length after
+    corruption. Each noise span is replaced by the next sentinel token in the inputs, and the labels
+    list each sentinel followed by the tokens it replaced. Both end with the EOS token.
+
+    Args:
+        tokens: The input tokens.
+        max_seq_length: The encoder input length; inputs are padded to this length.
+        sentinel_token_ids: The sentinel token IDs in order of use, e.g., <extra_id_0>, <extra_id_1>, ...
+        eos_token_id: The ID of the EOS token.
+        pad_token_id: The ID of the PAD token.
+        label_mask_id: The ID used to pad labels.
+        noise_density: The fraction of tokens to corrupt.
+        mean_span_length: The mean length of corrupted spans.
+        seed: The seed for reproducible sampling; combined with a Blake3 hash of the input.
+            If None, sampling is random on every call.
+
+    Returns:
+        A list of tuples containing the input IDs, attention mask, and labels.
+
+    Raises:
+        AleaPreprocessError: If the lengths or noise options are out of range, or there are
+            fewer sentinel tokens than noise spans.
+    """
+    ...
+
+def get_span_corruption_samples_from_content(
+    encoded_content: str,
+    max_seq_length: int,
+    tokenizer_name: str,
+    sentinel_token_ids: List[int],
+    eos_token_id: int,
+    pad_token_id: int,
+    label_mask_id: int,
+    noise_density: float = 0.15,
+    mean_span_length: float = 3.0,
+    seed: Optional[int] = None,
+) -> List[tuple[List[int], List[int], List[int]]]:
+    """
+    Get span corruption samples from encoded content.
+
+    Args:
+        encoded_content: The encoded content as a string.
+        max_seq_length: The encoder input length; inputs are padded to this length.
+        tokenizer_name: The name of the tokenizer.
+        sentinel_token_ids: The sentinel token IDs in order of use.
+        eos_token_id: The ID of the EOS token.
+        pad_token_id: The ID of the PAD token.
+        label_mask_id: The ID used to pad labels.
+        noise_density: The fraction of tokens to corrupt.
+        mean_span_length: The mean length of corrupted spans.
+        seed: The seed for reproducible sampling; combined with a Blake3 hash of the input.
+
+    Returns:
+        A list of tuples containing the input IDs, attention mask, and labels.
+
+    Raises:
+        ValueError: If the tokenizer cannot be loaded, the content cannot be encoded, or there
+            are fewer sentinel tokens than noise spans.
+    """
+    ...
@@ -2,6 +2,8 @@ use crate::io::fs::files::{read_file_content, read_gz_file_content};
 /// This file contains the implementation of the blake3 hashing algorithm.
 use blake3;
 use hex;
+use rayon::prelude::*;
+use std::io;
 
 /// Hashes a buffer using the Blake3 algorithm.
 ///
@@ -26,6 +28,24 @@ pub fn hash_str(s: &str) -> String {
     hash_bytes(s.as_bytes())
 }
 
+/// Hashes a buffer with a seed using the Blake3 algorithm and returns the first 8 bytes as an integer.
+///
+/// Args:
+///   buffer (bytes): The buffer to hash.
+///   seed (u64): The seed, hashed as little-endian bytes before the buffer.
+///
+/// Returns:
+///   u64: The little-endian integer value of the first 8 bytes of the hash.
+pub fn hash_bytes_to_u64(buffer: &[u8], seed: u64) -> u64 {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&seed.to_le_bytes());
+    hasher.update(buffer);
+    let hash = hasher.finalize();
+    let mut value = [0u8; 8];
+    value.copy_from_slice(&hash.as_bytes()[..8]);
+    u64::from_le_bytes(value)
+}
+
 /// Hashes the content of a file using the Blake3 algorithm.
 ///
 /// Args:
@@ -48,6 +68,34 @@ pub fn hash_gz_file(path: &str) -> String {
     hash_bytes(read_gz_file_content(path).unwrap().as_slice())
 }
 
+/// Hashes several buffers in parallel using the Blake3 algorithm.
+///
+/// Args:
+///   buffers (Vec<bytes>): The buffers to hash.
+///
+/// Returns:
+///   Vec<str>: The hash of each buffer, in input order.
+pub fn hash_bytes_batch(buffers: &[Vec<u8>]) -> Vec<String> {
+    buffers
+        .par_iter()
+        .map(|buffer| hash_bytes(buffer))
+        .collect()
+}
+
+/// Hashes the content of several files in parallel using the Blake3 algorithm.
+///
+/// Args:
+///   paths (Vec<&str>): The paths to the files to hash.
+///
+/// Returns:
+///   Vec<Result<str>>: The hash of each file content, or the error reading it, in input order.
+pub fn hash_file_batch(paths: &[String]) -> Vec<io::Result<String>> {
+    paths
+        .par_iter()
+        .map(|path| Ok(hash_bytes(read_file_content(path)?.as_slice())))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,6 +135,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_hash_bytes_to_u64() {
+        let value = hash_bytes_to_u64(b"Hello, world!", 0);
+        assert_eq!(value, hash_bytes_to_u64(b"Hello, world!", 0));
+        assert_ne!(value, hash_bytes_to_u64(b"Hello, world!", 1));
+        assert_ne!(value, hash_bytes_to_u64(b"Hello, world?", 0));
+    }
+
     #[test]
     fn test_hash_file() {
         let path = get_test_file_path();
@@ -106,4 +162,16 @@ mod tests {
             "ede5c0b10f2ec4979c69b52f61e42ff5b413519ce09be0f14d098dcfe5f6f98d"
         );
     }
+
+    #[test]
+    fn test_hash_file_batch() {
+        let path = get_test_file_path().to_str().unwrap().to_string();
+        let hashes = hash_file_batch(&[path.clone(), "missing.txt".to_string(), path.clone()]);
+        assert_eq!(hashes[0].as_ref().unwrap(), &hash_file(&path));
+        assert_eq!(
+            hashes[1].as_ref().unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+        assert_eq!(hashes[2].as_ref().unwrap(), &hash_file(&path));
+    }
 }
@@ -3,7 +3,9 @@
 /// The algorithm is used to identify similar pieces of data in a large
 /// dataset, such as a file or a stream of data.
 use crate::algos::hashing::rolling::*;
-use crate::io::fs::files::read_gz_file_content;
+use crate::io::fs::files::{read_file_content, read_gz_file_content};
+use rayon::prelude::*;
+use std::io;
 
 pub struct CTPH {
     window_size: usize,
@@ -320,6 +322,49 @@ pub fn hash_gz_file(path: &str, window_size: usize, digest_size: usize, precisio
     )
 }
 
+/// Compute the CTPH hashes of several buffers in parallel.
+/// Arguments:
+/// - `buffers`: The data to hash.
+/// - `window_size`: The size of the sliding window used to hash the data.
+/// - `digest_size`: The size of the hash digest used to identify similar pieces of data.
+/// - `precision`: The precision of the rolling hash.
+/// Returns:
+/// - The CTPH hash of each buffer, in input order.
+pub fn hash_bytes_batch(
+    buffers: &[Vec<u8>],
+    window_size: usize,
+    digest_size: usize,
+    precision: u8,
+) -> Vec<String> {
+    buffers
+        .par_iter()
+        .map(|buffer| hash_bytes(buffer, window_size, digest_size, precision))
+        .collect()
+}
+
+/// Compute the CTPH hashes of several files in parallel.
+/// Arguments:
+/// - `paths`: The paths to the files to hash.
+/// - `window_size`: The size of the sliding window used to hash the data.
+/// - `digest_size`: The size of the hash digest used to identify similar pieces of data.
+/// - `precision`: The precision of the rolling hash.
+/// Returns:
+/// - The CTPH hash of each file, or the error reading it, in input order.
+pub fn hash_file_batch(
+    paths: &[String],
+    window_size: usize,
+    digest_size: usize,
+    precision: u8,
+) -> Vec<io::Result<String>> {
+    paths
+        .par_iter()
+        .map(|path| {
+            let data = read_file_content(path)?;
+            Ok(hash_bytes(&data, window_size, digest_size, precision))
+        })
+        .collect()
+}
+
 /// Compare two CTPH hashes and return the similarity score as the Jacccard similarity.
 /// If the digest and window sizes are not the same, the similarity score is 0.
 /// Arguments:
@@ -423,4 +468,23 @@ mod tests {
         let hash = dbg!(ctph.compute(&data));
         assert_eq!(&hash[0..5], "8:4:1");
     }
+
+    #[test]
+    fn test_hash_bytes_batch() {
+        let buffers = vec![b"hello world".to_vec(), b"goodbye world".to_vec()];
+        let hashes = hash_bytes_batch(&buffers, 8, 4, 8);
+        assert_eq!(hashes[0], hash_bytes(&buffers[0], 8, 4, 8));
+        assert_eq!(hashes[1], hash_bytes(&buffers[1], 8, 4, 8));
+    }
+
+    #[test]
+    fn test_hash_file_batch() {
+        let path = get_test_file_path().to_str().unwrap().to_string();
+        let hashes = hash_file_batch(&[path.clone(), "missing.txt".to_string()], 8, 4, 8);
+        assert_eq!(hashes[0].as_ref().unwrap(), &hash_file(&path, 8, 4, 8));
+        assert_eq!(
+            hashes[1].as_ref().unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
 }
@@ -59,6 +59,18 @@ pub fn hash_tokens(tokens: &[i64], window_size: usize) -> String {
     BASE64_STANDARD.encode(&hash.to_be_bytes())
 }
 
+/// Returns the rolling hash after each token, so entry `i` hashes the window ending at token `i`.
+pub fn hash_token_windows(tokens: &[i64], window_size: usize) -> Vec<u64> {
+    let mut rolling_hash = RollingTokenHash::new(window_size);
+    tokens
+        .iter()
+        .map(|&token| {
+            rolling_hash.update(token);
+            rolling_hash.hash()
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -210,4 +222,13 @@ mod tests {
 
         assert!(hash1 != hash2);
     }
+
+    #[test]
+    fn test_hash_token_windows() {
+        let tokens = vec![1, 2, 3, 4, 5];
+        let hashes = hash_token_windows(&tokens, 3);
+        assert_eq!(hashes.len(), tokens.len());
+        assert_eq!(hashes[4], RollingTokenHash::from(&tokens, 3).hash());
+        assert_eq!(hashes[1], RollingTokenHash::from(&tokens[..2], 3).hash());
+    }
 }
@@ -7,6 +7,7 @@
 ///
 pub mod hashing;
 pub mod ngrams;
+pub mod quality;
 pub mod segmentation;
 pub mod similarity;
 pub mod splitting;
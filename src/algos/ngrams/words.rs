@@ -1,7 +1,7 @@
 /// Extracts n-grams from a list of words.
 use crate::algos::unicode::segmentations::segment_words;
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 
 /// Transform the input_data into a sequence of n-grams.
@@ -144,6 +144,37 @@ pub fn extract_5(input_data: &str) -> HashMap<Vec<String>, u32> {
     extract::<5>(&input_data)
 }
 
+/// Counts the n-grams of a sequence of words that is already segmented.
+/// Arguments:
+/// - `words`: The words, e.g., from `segment_words` after filtering.
+/// - `n`: The size of the n-grams.
+/// Returns:
+/// - A HashMap containing the n-grams as keys and their counts as values.
+pub fn extract_from_words<'a, 'b>(words: &'b [&'a str], n: usize) -> HashMap<&'b [&'a str], u32> {
+    let mut counts = HashMap::new();
+    for ngram in words.windows(n) {
+        *counts.entry(ngram).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Marks the words covered by an n-gram that repeats an earlier n-gram.
+/// Arguments:
+/// - `words`: The words, e.g., from `segment_words` after filtering.
+/// - `n`: The size of the n-grams.
+/// Returns:
+/// - One flag per word, true if the word is part of a repeated n-gram.
+pub fn mark_repeated_from_words(words: &[&str], n: usize) -> Vec<bool> {
+    let mut seen = HashSet::new();
+    let mut is_repeated = vec![false; words.len()];
+    for (index, ngram) in words.windows(n).enumerate() {
+        if !seen.insert(ngram) {
+            is_repeated[index..index + n].fill(true);
+        }
+    }
+    is_repeated
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -322,4 +353,24 @@ mod tests {
             1
         );
     }
+
+    #[test]
+    fn test_extract_from_words() {
+        let words = ["a", "b", "a", "b", "c"];
+        let result = extract_from_words(&words, 2);
+        assert_eq!(result[&["a", "b"][..]], 2);
+        assert_eq!(result[&["b", "c"][..]], 1);
+        assert_eq!(result.len(), 3);
+        assert!(extract_from_words(&words, 6).is_empty());
+    }
+
+    #[test]
+    fn test_mark_repeated_from_words() {
+        let words = ["a", "b", "c", "a", "b", "d"];
+        assert_eq!(
+            mark_repeated_from_words(&words, 2),
+            vec![false, false, false, true, true, false]
+        );
+        assert_eq!(mark_repeated_from_words(&words, 3), vec![false; 6]);
+    }
 }
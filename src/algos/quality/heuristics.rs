@@ -0,0 +1,464 @@
+/// Heuristic document quality signals and filters, in the style of the Gopher (Rae et al., 2021)
+/// and C4 (Raffel et al., 2020) rules.
+///
+/// Words are the word segments that contain at least one letter or number, so punctuation and
+/// symbols do not count as words, except in `alphabetic_ratio`, which is over all word segments. Lines are the mandatory-break line segments and paragraphs are
+/// separated by blank lines; blank lines and paragraphs are ignored.
+// external imports
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::collections::HashSet;
+
+// internal imports
+use crate::algos::ngrams::words::{extract_from_words, mark_repeated_from_words};
+use crate::algos::unicode::categories::{char_to_category_group, UnicodeCategoryGroup};
+use crate::algos::unicode::segmentations::{segment_lines, segment_words};
+
+/// Gopher stop words; documents need a few of these to look like natural English prose
+pub const STOP_WORDS: [&str; 8] = ["the", "be", "to", "of", "and", "that", "have", "with"];
+
+/// Prefixes that mark a line as a bullet point
+const BULLET_PREFIXES: [&str; 12] = ["•", "●", "○", "◦", "▪", "▫", "■", "□", "‣", "⁃", "- ", "* "];
+
+/// The n-gram sizes for the most frequent n-gram fractions
+pub const TOP_NGRAM_SIZES: [usize; 3] = [2, 3, 4];
+
+/// The n-gram sizes for the duplicate n-gram fractions
+pub const DUPLICATE_NGRAM_SIZES: [usize; 6] = [5, 6, 7, 8, 9, 10];
+
+/// Quality thresholds
+///
+/// Defaults are the Gopher values. `max_top_ngram_char_fractions` has one threshold per size in
+/// `TOP_NGRAM_SIZES`, and `max_duplicate_ngram_char_fractions` one per size in
+/// `DUPLICATE_NGRAM_SIZES`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityConfig {
+    pub min_words: usize,
+    pub max_words: usize,
+    pub min_mean_word_length: f64,
+    pub max_mean_word_length: f64,
+    pub max_symbol_to_word_ratio: f64,
+    pub max_ellipsis_line_fraction: f64,
+    pub max_bullet_line_fraction: f64,
+    pub max_duplicate_line_fraction: f64,
+    pub max_duplicate_line_char_fraction: f64,
+    pub max_duplicate_paragraph_fraction: f64,
+    pub max_duplicate_paragraph_char_fraction: f64,
+    pub max_top_ngram_char_fractions: Vec<f64>,
+    pub max_duplicate_ngram_char_fractions: Vec<f64>,
+    pub min_stop_words: usize,
+    pub min_alphabetic_ratio: f64,
+}
+
+impl Default for QualityConfig {
+    fn default() -> Self {
+        QualityConfig {
+            min_words: 50,
+            max_words: 100_000,
+            min_mean_word_length: 3.0,
+            max_mean_word_length: 10.0,
+            max_symbol_to_word_ratio: 0.1,
+            max_ellipsis_line_fraction: 0.3,
+            max_bullet_line_fraction: 0.9,
+            max_duplicate_line_fraction: 0.3,
+            max_duplicate_line_char_fraction: 0.2,
+            max_duplicate_paragraph_fraction: 0.3,
+            max_duplicate_paragraph_char_fraction: 0.2,
+            max_top_ngram_char_fractions: vec![0.2, 0.18, 0.16],
+            max_duplicate_ngram_char_fractions: vec![0.15, 0.14, 0.13, 0.12, 0.11, 0.1],
+            min_stop_words: 2,
+            min_alphabetic_ratio: 0.8,
+        }
+    }
+}
+
+/// Document quality signals
+///
+/// Duplicate fractions count the lines or paragraphs that repeat an earlier one; their char
+/// variants weigh them by length. `top_ngram_char_fractions` is the fraction of word characters
+/// covered by the most frequent n-gram, and `duplicate_ngram_char_fractions` the fraction covered
+/// by n-grams that repeat an earlier one. `alphabetic_ratio` is the fraction of word segments,
+/// including punctuation and symbols, with at least one letter.
+#[pyclass]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QualitySignals {
+    #[pyo3(get)]
+    pub word_count: usize,
+    #[pyo3(get)]
+    pub mean_word_length: f64,
+    #[pyo3(get)]
+    pub symbol_to_word_ratio: f64,
+    #[pyo3(get)]
+    pub ellipsis_line_fraction: f64,
+    #[pyo3(get)]
+    pub bullet_line_fraction: f64,
+    #[pyo3(get)]
+    pub duplicate_line_fraction: f64,
+    #[pyo3(get)]
+    pub duplicate_line_char_fraction: f64,
+    #[pyo3(get)]
+    pub duplicate_paragraph_fraction: f64,
+    #[pyo3(get)]
+    pub duplicate_paragraph_char_fraction: f64,
+    #[pyo3(get)]
+    pub top_ngram_char_fractions: Vec<f64>,
+    #[pyo3(get)]
+    pub duplicate_ngram_char_fractions: Vec<f64>,
+    #[pyo3(get)]
+    pub stop_word_count: usize,
+    #[pyo3(get)]
+    pub alphabetic_ratio: f64,
+}
+
+#[pymethods]
+impl QualitySignals {
+    fn __str__(&self) -> String {
+        format!(
+            "QualitySignals(word_count={}, mean_word_length={:.2}, alphabetic_ratio={:.3})",
+            self.word_count, self.mean_word_length, self.alphabetic_ratio
+        )
+    }
+}
+
+impl QualitySignals {
+    /// Get the names of the checks the signals fail, in signal order
+    pub fn get_failures(&self, config: &QualityConfig) -> Vec<String> {
+        let mut failures = Vec::new();
+        let mut check = |failed: bool, name: &str| {
+            if failed {
+                failures.push(name.to_string());
+            }
+        };
+
+        check(self.word_count < config.min_words, "min_words");
+        check(self.word_count > config.max_words, "max_words");
+        check(
+            self.mean_word_length < config.min_mean_word_length
+                || self.mean_word_length > config.max_mean_word_length,
+            "mean_word_length",
+        );
+        check(
+            self.symbol_to_word_ratio > config.max_symbol_to_word_ratio,
+            "symbol_to_word_ratio",
+        );
+        check(
+            self.ellipsis_line_fraction > config.max_ellipsis_line_fraction,
+            "ellipsis_line_fraction",
+        );
+        check(
+            self.bullet_line_fraction > config.max_bullet_line_fraction,
+            "bullet_line_fraction",
+        );
+        check(
+            self.duplicate_line_fraction > config.max_duplicate_line_fraction,
+            "duplicate_line_fraction",
+        );
+        check(
+            self.duplicate_line_char_fraction > config.max_duplicate_line_char_fraction,
+            "duplicate_line_char_fraction",
+        );
+        check(
+            self.duplicate_paragraph_fraction > config.max_duplicate_paragraph_fraction,
+            "duplicate_paragraph_fraction",
+        );
+        check(
+            self.duplicate_paragraph_char_fraction > config.max_duplicate_paragraph_char_fraction,
+            "duplicate_paragraph_char_fraction",
+        );
+        for ((n, fraction), max_fraction) in TOP_NGRAM_SIZES
+            .iter()
+            .zip(self.top_ngram_char_fractions.iter())
+            .zip(config.max_top_ngram_char_fractions.iter())
+        {
+            check(
+                fraction > max_fraction,
+                &format!("top_{}gram_char_fraction", n),
+            );
+        }
+        for ((n, fraction), max_fraction) in DUPLICATE_NGRAM_SIZES
+            .iter()
+            .zip(self.duplicate_ngram_char_fractions.iter())
+            .zip(config.max_duplicate_ngram_char_fractions.iter())
+        {
+            check(
+                fraction > max_fraction,
+                &format!("duplicate_{}gram_char_fraction", n),
+            );
+        }
+        check(self.stop_word_count < config.min_stop_words, "stop_words");
+        check(
+            self.alphabetic_ratio < config.min_alphabetic_ratio,
+            "alphabetic_ratio",
+        );
+
+        failures
+    }
+
+    /// Check whether the signals pass every threshold
+    pub fn passes(&self, config: &QualityConfig) -> bool {
+        self.get_failures(config).is_empty()
+    }
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+/// Get the fraction of items that repeat an earlier item, by count and by characters
+fn get_duplicate_fractions(items: &[&str]) -> (f64, f64) {
+    let mut seen = HashSet::new();
+    let mut duplicate_count = 0;
+    let mut duplicate_chars = 0;
+    let mut total_chars = 0;
+    for item in items {
+        let chars = item.chars().count();
+        total_chars += chars;
+        if !seen.insert(*item) {
+            duplicate_count += 1;
+            duplicate_chars += chars;
+        }
+    }
+    (
+        ratio(duplicate_count, items.len()),
+        ratio(duplicate_chars, total_chars),
+    )
+}
+
+/// Get the fraction of word characters covered by the most frequent n-gram, if it repeats
+fn get_top_ngram_char_fraction(words: &[&str], word_lengths: &[usize], n: usize) -> f64 {
+    let total_chars: usize = word_lengths.iter().sum();
+    // weigh each n-gram by its occurrences and length, breaking ties on the n-gram itself
+    extract_from_words(words, n)
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .map(|(ngram, count)| {
+            let chars: usize = ngram.iter().map(|word| word.chars().count()).sum();
+            (count as usize * chars, ngram)
+        })
+        .max()
+        .map_or(0.0, |(chars, _)| ratio(chars, total_chars))
+}
+
+/// Get the fraction of word characters covered by n-grams that repeat an earlier n-gram
+fn get_duplicate_ngram_char_fraction(words: &[&str], word_lengths: &[usize], n: usize) -> f64 {
+    let total_chars: usize = word_lengths.iter().sum();
+    let duplicate_chars: usize = word_lengths
+        .iter()
+        .zip(mark_repeated_from_words(words, n).iter())
+        .filter(|(_, duplicate)| **duplicate)
+        .map(|(length, _)| length)
+        .sum();
+    ratio(duplicate_chars, total_chars)
+}
+
+fn is_bullet_line(line: &str) -> bool {
+    BULLET_PREFIXES
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+}
+
+/// Get the quality signals of a document
+///
+/// # Arguments
+/// * `text` - The document text
+///
+/// # Returns
+/// The quality signals
+pub fn get_quality_signals(text: &str) -> QualitySignals {
+    let segments = segment_words(text);
+    let words: Vec<&str> = segments
+        .iter()
+        .map(|segment| segment.as_str())
+        .filter(|segment| {
+            segment.chars().any(|c| {
+                matches!(
+                    char_to_category_group(c),
+                    UnicodeCategoryGroup::L | UnicodeCategoryGroup::N
+                )
+            })
+        })
+        .collect();
+    let word_lengths: Vec<usize> = words.iter().map(|word| word.chars().count()).collect();
+    let word_count = words.len();
+
+    let symbol_count =
+        text.matches('#').count() + text.matches("...").count() + text.matches('…').count();
+    let alphabetic_count = segments
+        .iter()
+        .filter(|segment| {
+            segment
+                .chars()
+                .any(|c| char_to_category_group(c) == UnicodeCategoryGroup::L)
+        })
+        .count();
+    let stop_word_count = words
+        .iter()
+        .filter(|word| STOP_WORDS.contains(&word.to_lowercase().as_str()))
+        .count();
+
+    let lines = segment_lines(text);
+    let lines: Vec<&str> = lines
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+    let ellipsis_lines = lines
+        .iter()
+        .filter(|line| line.ends_with("...") || line.ends_with('…'))
+        .count();
+    let bullet_lines = lines.iter().filter(|line| is_bullet_line(line)).count();
+    let (duplicate_line_fraction, duplicate_line_char_fraction) = get_duplicate_fractions(&lines);
+
+    let paragraphs: Vec<&str> = text
+        .split("\n\n")
+        .map(|paragraph| paragraph.trim())
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect();
+    let (duplicate_paragraph_fraction, duplicate_paragraph_char_fraction) =
+        get_duplicate_fractions(&paragraphs);
+
+    QualitySignals {
+        word_count,
+        mean_word_length: ratio(word_lengths.iter().sum(), word_count),
+        symbol_to_word_ratio: ratio(symbol_count, word_count),
+        ellipsis_line_fraction: ratio(ellipsis_lines, lines.len()),
+        bullet_line_fraction: ratio(bullet_lines, lines.len()),
+        duplicate_line_fraction,
+        duplicate_line_char_fraction,
+        duplicate_paragraph_fraction,
+        duplicate_paragraph_char_fraction,
+        top_ngram_char_fractions: TOP_NGRAM_SIZES
+            .iter()
+            .map(|n| get_top_ngram_char_fraction(&words, &word_lengths, *n))
+            .collect(),
+        duplicate_ngram_char_fractions: DUPLICATE_NGRAM_SIZES
+            .iter()
+            .map(|n| get_duplicate_ngram_char_fraction(&words, &word_lengths, *n))
+            .collect(),
+        stop_word_count,
+        alphabetic_ratio: ratio(alphabetic_count, segments.len()),
+    }
+}
+
+/// Get the quality signals of several documents in parallel
+pub fn get_quality_signals_batch(texts: &[String]) -> Vec<QualitySignals> {
+    texts
+        .par_iter()
+        .map(|text| get_quality_signals(text))
+        .collect()
+}
+
+/// Get the quality signals of a document and the names of the checks it fails
+///
+/// # Arguments
+/// * `text` - The document text
+/// * `config` - The thresholds
+///
+/// # Returns
+/// The quality signals and the failed checks; the document passes if there are none
+pub fn check_quality(text: &str, config: &QualityConfig) -> (QualitySignals, Vec<String>) {
+    let signals = get_quality_signals(text);
+    let failures = signals.get_failures(config);
+    (signals, failures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_prose() -> String {
+        [
+            "The court held that the statute applies to all of the parties with standing.",
+            "Each party shall have the right to appeal the decision within thirty days.",
+            "The agreement was executed by the buyer and the seller on the closing date.",
+            "Notice must be given to the other party in writing before any termination.",
+            "Nothing in this section limits the remedies available to the injured party.",
+        ]
+        .join("\n\n")
+    }
+
+    #[test]
+    fn test_get_quality_signals_prose() {
+        let signals = get_quality_signals(&get_prose());
+        assert_eq!(signals.word_count, 66);
+        assert!(signals.mean_word_length > 3.0 && signals.mean_word_length < 10.0);
+        assert_eq!(signals.symbol_to_word_ratio, 0.0);
+        assert_eq!(signals.duplicate_line_fraction, 0.0);
+        assert_eq!(signals.duplicate_paragraph_fraction, 0.0);
+        assert_eq!(signals.duplicate_ngram_char_fractions, vec![0.0; 6]);
+        assert!(signals.stop_word_count >= 2);
+        // the five periods are word segments without a letter
+        assert!((signals.alphabetic_ratio - 66.0 / 71.0).abs() < 1e-9);
+        assert!(signals.passes(&QualityConfig::default()));
+    }
+
+    #[test]
+    fn test_get_quality_signals_empty() {
+        let signals = get_quality_signals("");
+        assert_eq!(signals.word_count, 0);
+        assert_eq!(signals.mean_word_length, 0.0);
+        assert_eq!(signals.top_ngram_char_fractions, vec![0.0; 3]);
+        let failures = signals.get_failures(&QualityConfig::default());
+        assert!(failures.contains(&"min_words".to_string()));
+        assert!(failures.contains(&"stop_words".to_string()));
+    }
+
+    #[test]
+    fn test_lines() {
+        let signals = get_quality_signals("• one\n• two\nthree...\n\n• one\n");
+        assert_eq!(signals.bullet_line_fraction, 0.75);
+        assert_eq!(signals.ellipsis_line_fraction, 0.25);
+        assert_eq!(signals.duplicate_line_fraction, 0.25);
+        assert_eq!(signals.symbol_to_word_ratio, 0.25);
+    }
+
+    #[test]
+    fn test_duplicate_paragraphs() {
+        let text = format!("{}\n\n{}", get_prose(), get_prose());
+        let signals = get_quality_signals(&text);
+        assert_eq!(signals.duplicate_paragraph_fraction, 0.5);
+        assert_eq!(signals.duplicate_paragraph_char_fraction, 0.5);
+        assert_eq!(signals.duplicate_ngram_char_fractions[0], 0.5);
+        let failures = signals.get_failures(&QualityConfig::default());
+        assert!(failures.contains(&"duplicate_paragraph_fraction".to_string()));
+        assert!(failures.contains(&"duplicate_5gram_char_fraction".to_string()));
+    }
+
+    #[test]
+    fn test_top_ngram() {
+        let signals = get_quality_signals("a b a b c d");
+        // "a b" occurs twice and covers 4 of the 6 word characters
+        assert!((signals.top_ngram_char_fractions[0] - 4.0 / 6.0).abs() < 1e-9);
+        assert_eq!(signals.top_ngram_char_fractions[1], 0.0);
+    }
+
+    #[test]
+    fn test_alphabetic_ratio() {
+        let signals = get_quality_signals("12 34 abc, de.");
+        assert_eq!(signals.word_count, 4);
+        assert!((signals.alphabetic_ratio - 2.0 / 6.0).abs() < 1e-9);
+
+        // symbols are not words, but they still count against the ratio
+        let signals = get_quality_signals("### $$$ %%% the and");
+        assert_eq!(signals.word_count, 2);
+        assert!(signals.alphabetic_ratio < 0.5);
+    }
+
+    #[test]
+    fn test_check_quality() {
+        let config = QualityConfig {
+            min_words: 1,
+            min_stop_words: 0,
+            ..Default::default()
+        };
+        let (signals, failures) = check_quality("#one #two #three", &config);
+        assert_eq!(signals.word_count, 3);
+        assert_eq!(signals.symbol_to_word_ratio, 1.0);
+        // the hashes are word segments without a letter too
+        assert_eq!(failures, vec!["symbol_to_word_ratio", "alphabetic_ratio"]);
+    }
+}
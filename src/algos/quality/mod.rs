@@ -0,0 +1,3 @@
+/// Document quality signals and filters
+pub mod heuristics;
+pub mod noise;
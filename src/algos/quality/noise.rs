@@ -0,0 +1,415 @@
+/// Unicode category signals for OCR garbage, binary data decoded as text and encoding noise.
+///
+/// Clean text is mostly letters separated by spaces and punctuation; noisy text has other
+/// symbols (So), private-use (Co) and unassigned (Cn) characters, control characters, and
+/// transitions between categories that rarely occur inside words, e.g., "w¤rd^x" or "tHe".
+/// Signals are computed per line and for the whole document, so a document with a few bad lines
+/// can be told apart from one whose text layer is unusable.
+// external imports
+use pyo3::prelude::*;
+use rayon::prelude::*;
+
+// internal imports
+use crate::algos::ngrams::categories::transform_category_2;
+use crate::algos::unicode::categories::{
+    category_to_group, char_to_category, UnicodeCategory, UnicodeCategoryGroup,
+};
+use crate::algos::unicode::segmentations::segment_lines;
+
+/// Noise thresholds
+///
+/// Lines with fewer than `min_line_chars` non-whitespace characters are too short to judge and
+/// are never noisy. A document is unusable if its own signals are noisy or if more than
+/// `max_noisy_char_fraction` of the characters in judged lines are in noisy lines.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoiseConfig {
+    pub max_unusual_transition_ratio: f64,
+    pub max_other_symbol_ratio: f64,
+    pub max_private_use_ratio: f64,
+    pub max_unassigned_ratio: f64,
+    pub max_control_ratio: f64,
+    pub min_letter_ratio: f64,
+    pub min_line_chars: usize,
+    pub max_noisy_char_fraction: f64,
+}
+
+impl NoiseConfig {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        max_unusual_transition_ratio: f64,
+        max_other_symbol_ratio: f64,
+        max_private_use_ratio: f64,
+        max_unassigned_ratio: f64,
+        max_control_ratio: f64,
+        min_letter_ratio: f64,
+        min_line_chars: usize,
+        max_noisy_char_fraction: f64,
+    ) -> Self {
+        NoiseConfig {
+            max_unusual_transition_ratio,
+            max_other_symbol_ratio,
+            max_private_use_ratio,
+            max_unassigned_ratio,
+            max_control_ratio,
+            min_letter_ratio,
+            min_line_chars,
+            max_noisy_char_fraction,
+        }
+    }
+}
+
+impl Default for NoiseConfig {
+    fn default() -> Self {
+        NoiseConfig::new(0.1, 0.05, 0.01, 0.01, 0.01, 0.5, 8, 0.3)
+    }
+}
+
+/// Noise signals of a line or document
+///
+/// Character ratios are over non-whitespace characters; `unusual_transition_ratio` is over
+/// adjacent character pairs. `letter_ratio` is the fraction of letters among letters, symbols
+/// and punctuation, or 1.0 if there are none (e.g., a line of numbers).
+#[pyclass]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NoiseSignals {
+    #[pyo3(get)]
+    pub char_count: usize,
+    #[pyo3(get)]
+    pub unusual_transition_ratio: f64,
+    #[pyo3(get)]
+    pub other_symbol_ratio: f64,
+    #[pyo3(get)]
+    pub private_use_ratio: f64,
+    #[pyo3(get)]
+    pub unassigned_ratio: f64,
+    #[pyo3(get)]
+    pub control_ratio: f64,
+    #[pyo3(get)]
+    pub letter_ratio: f64,
+}
+
+#[pymethods]
+impl NoiseSignals {
+    fn __str__(&self) -> String {
+        format!(
+            "NoiseSignals(char_count={}, unusual_transition_ratio={:.3}, letter_ratio={:.3})",
+            self.char_count, self.unusual_transition_ratio, self.letter_ratio
+        )
+    }
+}
+
+impl NoiseSignals {
+    /// Check whether the signals exceed any threshold
+    pub fn is_noisy(&self, config: &NoiseConfig) -> bool {
+        self.unusual_transition_ratio > config.max_unusual_transition_ratio
+            || self.other_symbol_ratio > config.max_other_symbol_ratio
+            || self.private_use_ratio > config.max_private_use_ratio
+            || self.unassigned_ratio > config.max_unassigned_ratio
+            || self.control_ratio > config.max_control_ratio
+            || self.letter_ratio < config.min_letter_ratio
+    }
+}
+
+/// Document noise report
+///
+/// `noisy_char_fraction` is the fraction of characters in judged lines that are in noisy lines.
+#[pyclass]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NoiseReport {
+    #[pyo3(get)]
+    pub document: NoiseSignals,
+    #[pyo3(get)]
+    pub line_count: usize,
+    #[pyo3(get)]
+    pub noisy_line_count: usize,
+    #[pyo3(get)]
+    pub noisy_char_fraction: f64,
+    #[pyo3(get)]
+    pub is_unusable: bool,
+}
+
+#[pymethods]
+impl NoiseReport {
+    fn __str__(&self) -> String {
+        format!(
+            "NoiseReport(line_count={}, noisy_line_count={}, noisy_char_fraction={:.3}, is_unusable={})",
+            self.line_count, self.noisy_line_count, self.noisy_char_fraction, self.is_unusable
+        )
+    }
+}
+
+/// Character and transition counts, summed over lines for documents
+#[derive(Debug, Clone, Copy, Default)]
+struct NoiseCounts {
+    chars: usize,
+    letters: usize,
+    symbols: usize,
+    other_symbols: usize,
+    private_use: usize,
+    unassigned: usize,
+    controls: usize,
+    transitions: usize,
+    unusual_transitions: usize,
+}
+
+impl NoiseCounts {
+    fn add(&mut self, other: &NoiseCounts) {
+        self.chars += other.chars;
+        self.letters += other.letters;
+        self.symbols += other.symbols;
+        self.other_symbols += other.other_symbols;
+        self.private_use += other.private_use;
+        self.unassigned += other.unassigned;
+        self.controls += other.controls;
+        self.transitions += other.transitions;
+        self.unusual_transitions += other.unusual_transitions;
+    }
+
+    fn to_signals(self) -> NoiseSignals {
+        let ratio = |numerator: usize, denominator: usize| {
+            if denominator == 0 {
+                0.0
+            } else {
+                numerator as f64 / denominator as f64
+            }
+        };
+        let letters_and_symbols = self.letters + self.symbols;
+        NoiseSignals {
+            char_count: self.chars,
+            unusual_transition_ratio: ratio(self.unusual_transitions, self.transitions),
+            other_symbol_ratio: ratio(self.other_symbols, self.chars),
+            private_use_ratio: ratio(self.private_use, self.chars),
+            unassigned_ratio: ratio(self.unassigned, self.chars),
+            control_ratio: ratio(self.controls, self.chars),
+            letter_ratio: if letters_and_symbols == 0 {
+                1.0
+            } else {
+                ratio(self.letters, letters_and_symbols)
+            },
+        }
+    }
+}
+
+/// Check whether a pair of adjacent categories rarely occurs in clean text.
+///
+/// Only pairs inside a run of non-whitespace characters count, so a symbol on its own like "© "
+/// is not unusual. Repeats of a category are never unusual, so runs like "■■■" or "---" only
+/// count through their character ratios. Control characters are left to `control_ratio` since
+/// tabs share their category.
+pub fn is_unusual_transition(first: UnicodeCategory, second: UnicodeCategory) -> bool {
+    use UnicodeCategory::*;
+
+    let is_space =
+        |category| category == Cc || category_to_group(category) == UnicodeCategoryGroup::Z;
+    if first == second || is_space(first) || is_space(second) {
+        return false;
+    }
+    let is_letter = |category| category_to_group(category) == UnicodeCategoryGroup::L;
+    match (first, second) {
+        (So | Co | Cn | Cs, _) | (_, So | Co | Cn | Cs) => true,
+        // lowercase to uppercase inside a word, e.g., "tHe"
+        (Ll, Lu) => true,
+        // letters next to math, currency or modifier symbols, e.g., "a^b" or "x$y"
+        (Sm | Sc | Sk, category) | (category, Sm | Sc | Sk) => is_letter(category),
+        _ => false,
+    }
+}
+
+fn get_counts(text: &str) -> NoiseCounts {
+    let mut counts = NoiseCounts::default();
+    for c in text.chars().filter(|c| !c.is_whitespace()) {
+        counts.chars += 1;
+        let category = char_to_category(c);
+        match category_to_group(category) {
+            UnicodeCategoryGroup::L => counts.letters += 1,
+            UnicodeCategoryGroup::S | UnicodeCategoryGroup::P => counts.symbols += 1,
+            _ => {}
+        }
+        match category {
+            UnicodeCategory::So => counts.other_symbols += 1,
+            UnicodeCategory::Co => counts.private_use += 1,
+            UnicodeCategory::Cn => counts.unassigned += 1,
+            UnicodeCategory::Cc | UnicodeCategory::Cs => counts.controls += 1,
+            _ => {}
+        }
+    }
+
+    let bigrams = transform_category_2(text);
+    counts.transitions = bigrams.len();
+    counts.unusual_transitions = bigrams
+        .iter()
+        .filter(|bigram| is_unusual_transition(bigram[0], bigram[1]))
+        .count();
+    counts
+}
+
+/// Get the non-empty lines of a text without their line breaks
+fn get_lines(text: &str) -> Vec<String> {
+    segment_lines(text)
+        .into_iter()
+        .map(|line| {
+            line.trim_end_matches(['\r', '\n', '\u{85}', '\u{2028}', '\u{2029}'])
+                .to_string()
+        })
+        .filter(|line| !line.trim().is_empty())
+        .collect()
+}
+
+/// Get the noise signals of a text as a whole
+///
+/// # Arguments
+/// * `text` - The text
+///
+/// # Returns
+/// The noise signals, summed over its lines
+pub fn get_noise_signals(text: &str) -> NoiseSignals {
+    let mut counts = NoiseCounts::default();
+    for line in get_lines(text) {
+        counts.add(&get_counts(&line));
+    }
+    counts.to_signals()
+}
+
+/// Get the noise signals of each non-empty line of a text
+///
+/// # Arguments
+/// * `text` - The text
+///
+/// # Returns
+/// The noise signals of each non-empty line, in order
+pub fn get_line_noise_signals(text: &str) -> Vec<NoiseSignals> {
+    get_lines(text)
+        .par_iter()
+        .map(|line| get_counts(line).to_signals())
+        .collect()
+}
+
+/// Get the document and line noise signals of a text and whether it is unusable
+///
+/// # Arguments
+/// * `text` - The text, e.g., the text layer of an OCR'd PDF
+/// * `config` - The thresholds
+///
+/// # Returns
+/// The noise report
+pub fn get_noise_report(text: &str, config: &NoiseConfig) -> NoiseReport {
+    let line_counts: Vec<NoiseCounts> = get_lines(text)
+        .par_iter()
+        .map(|line| get_counts(line))
+        .collect();
+
+    let mut document_counts = NoiseCounts::default();
+    let mut noisy_line_count = 0;
+    let mut judged_chars = 0;
+    let mut noisy_chars = 0;
+    for counts in line_counts.iter() {
+        document_counts.add(counts);
+        if counts.chars < config.min_line_chars {
+            continue;
+        }
+        judged_chars += counts.chars;
+        if counts.to_signals().is_noisy(config) {
+            noisy_line_count += 1;
+            noisy_chars += counts.chars;
+        }
+    }
+
+    let document = document_counts.to_signals();
+    let noisy_char_fraction = if judged_chars == 0 {
+        0.0
+    } else {
+        noisy_chars as f64 / judged_chars as f64
+    };
+    NoiseReport {
+        is_unusable: document.is_noisy(config)
+            || noisy_char_fraction > config.max_noisy_char_fraction,
+        document,
+        line_count: line_counts.len(),
+        noisy_line_count,
+        noisy_char_fraction,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLEAN: &str = "The quick brown fox jumps over the lazy dog.\nIt was the best of times, it was the worst of times.\n";
+
+    #[test]
+    fn test_is_unusual_transition() {
+        use UnicodeCategory::*;
+        assert!(is_unusual_transition(Ll, So));
+        assert!(is_unusual_transition(Ll, Lu));
+        assert!(is_unusual_transition(Sk, Ll));
+        assert!(!is_unusual_transition(Lu, Ll));
+        assert!(!is_unusual_transition(Ll, Po));
+        assert!(!is_unusual_transition(Sc, Nd));
+        assert!(!is_unusual_transition(So, So));
+        assert!(!is_unusual_transition(So, Zs));
+    }
+
+    #[test]
+    fn test_clean_text() {
+        let signals = get_noise_signals(CLEAN);
+        assert_eq!(signals.unusual_transition_ratio, 0.0);
+        assert_eq!(signals.other_symbol_ratio, 0.0);
+        assert!(signals.letter_ratio > 0.9);
+        assert!(!signals.is_noisy(&NoiseConfig::default()));
+
+        let report = get_noise_report(CLEAN, &NoiseConfig::default());
+        assert_eq!(report.line_count, 2);
+        assert_eq!(report.noisy_line_count, 0);
+        assert!(!report.is_unusable);
+    }
+
+    #[test]
+    fn test_ocr_garbage() {
+        let garbage = "¤¤ tHe ^q~u^ick ¦¦ ■ fo¬x \u{e000}\u{e001} j$u%m^p¨s\n";
+        let signals = get_noise_signals(garbage);
+        assert!(signals.unusual_transition_ratio > 0.1);
+        assert!(signals.private_use_ratio > 0.01);
+        assert!(signals.is_noisy(&NoiseConfig::default()));
+        assert!(get_noise_report(garbage, &NoiseConfig::default()).is_unusable);
+    }
+
+    #[test]
+    fn test_binary_as_text() {
+        let text = String::from_utf8_lossy(&[0x00, 0x01, 0xff, 0xfe, 0x41, 0x02, 0x03, 0xc3, 0x28])
+            .to_string();
+        let signals = get_noise_signals(&text);
+        assert!(signals.control_ratio > 0.01);
+        assert!(signals.is_noisy(&NoiseConfig::default()));
+    }
+
+    #[test]
+    fn test_line_signals() {
+        let text = format!("{}■□■□ ▪▫▪▫ ¤¤¤¤ ¦¦¦¦\n", CLEAN);
+        let lines = get_line_noise_signals(&text);
+        assert_eq!(lines.len(), 3);
+        assert!(!lines[0].is_noisy(&NoiseConfig::default()));
+        assert!(lines[2].is_noisy(&NoiseConfig::default()));
+
+        let report = get_noise_report(&text, &NoiseConfig::default());
+        assert_eq!(report.noisy_line_count, 1);
+        assert!(report.noisy_char_fraction > 0.0 && report.noisy_char_fraction < 0.3);
+        // the bad line is a small part of the document, but its symbols push the document over
+        assert!(report.document.other_symbol_ratio > 0.05);
+        assert!(report.is_unusable);
+    }
+
+    #[test]
+    fn test_short_lines_and_numbers() {
+        let report = get_noise_report("¤\n12 345 678 910\n", &NoiseConfig::default());
+        assert_eq!(report.line_count, 2);
+        assert_eq!(report.noisy_line_count, 0);
+        assert_eq!(get_noise_signals("12 345").letter_ratio, 1.0);
+        assert_eq!(
+            get_noise_signals(""),
+            NoiseSignals {
+                letter_ratio: 1.0,
+                ..Default::default()
+            }
+        );
+    }
+}
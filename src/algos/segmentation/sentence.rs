@@ -128,7 +128,7 @@ pub fn get_sentence_boundaries(input_text: &str) -> Vec<usize> {
     //  - if the next token is whitespace, and the next alphabetic token is uppercase, and the
     // prior token is not an abbreviation, then it is a sentence boundary.
 
-    for i in 1..word_indices.len() - 2 {
+    for i in 1..word_indices.len().saturating_sub(2) {
         // check if the current token is terminal punctuation
         let (c_start, c_end, c_token) = &word_indices[i];
         if terminal_punctuation.contains(&c_token.as_str()) {
@@ -166,7 +166,9 @@ pub fn get_sentence_boundaries(input_text: &str) -> Vec<usize> {
     }
 
     // add the last token as a boundary
-    boundaries.push(word_indices.last().unwrap().1);
+    if let Some(last) = word_indices.last() {
+        boundaries.push(last.1);
+    }
 
     boundaries
 }
@@ -196,6 +198,12 @@ mod tests {
         let abbrevs = get_abbreviations_regex(&text);
     }
 
+    #[test]
+    fn test_get_sentence_boundaries_short() {
+        assert!(get_sentence_boundaries("").is_empty());
+        assert_eq!(get_sentence_boundaries("Hello"), vec![5]);
+    }
+
     #[test]
     fn test_get_sentence_boundaries() {
         //let text = get_test_data();
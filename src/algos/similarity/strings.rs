@@ -66,7 +66,7 @@ pub fn hamming_similarity(a: &str, b: &str) -> f64 {
 /// - The Levenshtein distance between the two strings divided by the maximum length of the two strings
 /// (consistent with others, not normalized Levenshtein distance)
 pub fn levenshtein_distance(a: &str, b: &str) -> f64 {
-    edit_distance_to_distance(dbg!(strsim::levenshtein(a, b) as i64), a, b)
+    edit_distance_to_distance(strsim::levenshtein(a, b) as i64, a, b)
 }
 
 /// Compute the normalized Levenshtein distance between two strings
@@ -0,0 +1,289 @@
+/// Tokenizer comparison and fertility analysis.
+use crate::algos::tokenizers::tokenizers::get_tokenizer;
+use crate::algos::unicode::segmentations::segment_words;
+use crate::io::fs::files::iter_documents;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use tokenizers::Result;
+
+/// Fertility metrics for one tokenizer over a set of documents.
+///
+/// The `document_*` fields hold one value per document, in input order; the other fields are
+/// corpus-level aggregates. Ratios are 0.0 when their denominator is zero.
+#[pyclass]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TokenizerComparison {
+    #[pyo3(get)]
+    pub tokenizer: String,
+    #[pyo3(get)]
+    pub vocab_size: usize,
+    #[pyo3(get)]
+    pub num_documents: usize,
+    #[pyo3(get)]
+    pub total_words: usize,
+    #[pyo3(get)]
+    pub total_chars: usize,
+    #[pyo3(get)]
+    pub total_tokens: usize,
+    #[pyo3(get)]
+    pub tokens_per_word: f64,
+    #[pyo3(get)]
+    pub chars_per_token: f64,
+    #[pyo3(get)]
+    pub domain_term_split_rate: f64,
+    #[pyo3(get)]
+    pub split_domain_terms: Vec<String>,
+    #[pyo3(get)]
+    pub document_tokens: Vec<usize>,
+    #[pyo3(get)]
+    pub document_tokens_per_word: Vec<f64>,
+    #[pyo3(get)]
+    pub document_chars_per_token: Vec<f64>,
+}
+
+#[pymethods]
+impl TokenizerComparison {
+    fn __str__(&self) -> String {
+        format!(
+            "TokenizerComparison(tokenizer='{}', total_tokens={}, tokens_per_word={:.3}, chars_per_token={:.3}, domain_term_split_rate={:.3})",
+            self.tokenizer,
+            self.total_tokens,
+            self.tokens_per_word,
+            self.chars_per_token,
+            self.domain_term_split_rate
+        )
+    }
+}
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+/// Returns the number of words in a text, ignoring punctuation-only segments.
+pub fn count_words(text: &str) -> usize {
+    segment_words(text)
+        .iter()
+        .filter(|word| word.chars().any(|c| c.is_alphanumeric()))
+        .count()
+}
+
+/// Returns the domain terms that a tokenizer splits into more than one token.
+fn get_split_terms(tokenizer: &str, terms: &[String]) -> Result<Vec<String>> {
+    let tokenizer = get_tokenizer(tokenizer)?;
+    let encodings = tokenizer.encode_batch(terms.to_vec(), false)?;
+    Ok(terms
+        .iter()
+        .zip(encodings.iter())
+        .filter(|(_, encoding)| encoding.len() > 1)
+        .map(|(term, _)| term.clone())
+        .collect())
+}
+
+/// Compares the fertility of several tokenizers on the same documents.
+///
+/// Words are counted with `unicode::segmentations::segment_words`, ignoring segments without
+/// letters or digits. Each domain term is encoded on its own, and counts as split if it takes
+/// more than one token.
+///
+/// # Arguments
+/// * `tokenizers` - The tokenizer names, paths or hub identifiers
+/// * `texts` - The documents
+/// * `domain_terms` - Terms that should ideally be single tokens, e.g., "plaintiff" or "U.S.C."
+///
+/// # Returns
+/// A map from tokenizer name to its metrics
+pub fn compare_tokenizers(
+    tokenizers: &[String],
+    texts: &[String],
+    domain_terms: &[String],
+) -> Result<HashMap<String, TokenizerComparison>> {
+    let document_words: Vec<usize> = texts.par_iter().map(|text| count_words(text)).collect();
+    let document_chars: Vec<usize> = texts.iter().map(|text| text.chars().count()).collect();
+    let total_words: usize = document_words.iter().sum();
+    let total_chars: usize = document_chars.iter().sum();
+
+    let mut comparisons = HashMap::new();
+    for name in tokenizers {
+        let tokenizer = get_tokenizer(name)?;
+        let document_tokens: Vec<usize> = tokenizer
+            .encode_batch(texts.to_vec(), false)?
+            .iter()
+            .map(|encoding| encoding.len())
+            .collect();
+        let total_tokens: usize = document_tokens.iter().sum();
+        let split_domain_terms = get_split_terms(name, domain_terms)?;
+
+        comparisons.insert(
+            name.clone(),
+            TokenizerComparison {
+                tokenizer: name.clone(),
+                vocab_size: tokenizer.get_vocab_size(true),
+                num_documents: texts.len(),
+                total_words,
+                total_chars,
+                total_tokens,
+                tokens_per_word: ratio(total_tokens, total_words),
+                chars_per_token: ratio(total_chars, total_tokens),
+                domain_term_split_rate: ratio(split_domain_terms.len(), domain_terms.len()),
+                split_domain_terms,
+                document_tokens_per_word: document_tokens
+                    .iter()
+                    .zip(document_words.iter())
+                    .map(|(&tokens, &words)| ratio(tokens, words))
+                    .collect(),
+                document_chars_per_token: document_chars
+                    .iter()
+                    .zip(document_tokens.iter())
+                    .map(|(&chars, &tokens)| ratio(chars, tokens))
+                    .collect(),
+                document_tokens,
+            },
+        );
+    }
+
+    Ok(comparisons)
+}
+
+/// Compares the fertility of several tokenizers on the documents of a text, JSONL or Gzipped file.
+///
+/// # Arguments
+/// * `tokenizers` - The tokenizer names, paths or hub identifiers
+/// * `path` - The path to the file, read with `io::fs::files::iter_documents`
+/// * `field` - The JSON field holding the text, e.g., "text" for JSONL files
+/// * `domain_terms` - Terms that should ideally be single tokens
+///
+/// # Returns
+/// A map from tokenizer name to its metrics
+pub fn compare_tokenizers_from_file(
+    tokenizers: &[String],
+    path: &str,
+    field: Option<&str>,
+    domain_terms: &[String],
+) -> Result<HashMap<String, TokenizerComparison>> {
+    let texts = iter_documents(path, field)?.collect::<io::Result<Vec<String>>>()?;
+    compare_tokenizers(tokenizers, &texts, domain_terms)
+}
+
+/// Returns the vocabulary overlap between two tokenizers.
+///
+/// Tokens are compared as strings, so tokenizers with different whitespace conventions, e.g.,
+/// byte-level "Ġthe" and metaspace "▁the", will show little overlap.
+///
+/// # Arguments
+/// * `tokenizer_a` - The first tokenizer
+/// * `tokenizer_b` - The second tokenizer
+///
+/// # Returns
+/// A tuple of the number of shared tokens, the Jaccard similarity, and the fraction of each
+/// vocabulary that is shared
+pub fn get_vocab_overlap(tokenizer_a: &str, tokenizer_b: &str) -> Result<(usize, f64, f64, f64)> {
+    let vocab_a: HashSet<String> = get_tokenizer(tokenizer_a)?
+        .get_vocab(true)
+        .into_keys()
+        .collect();
+    let vocab_b: HashSet<String> = get_tokenizer(tokenizer_b)?
+        .get_vocab(true)
+        .into_keys()
+        .collect();
+
+    let shared = vocab_a.intersection(&vocab_b).count();
+    let union = vocab_a.len() + vocab_b.len() - shared;
+    Ok((
+        shared,
+        ratio(shared, union),
+        ratio(shared, vocab_a.len()),
+        ratio(shared, vocab_b.len()),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::tokenizers::tokenizers::load_tokenizer_from_str;
+
+    fn get_resource_path(name: &str) -> String {
+        format!("{}/resources/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    /// Registers a word-level copy of the test tokenizer with every word as a single token.
+    fn load_word_level_tokenizer(name: &str) {
+        let json = std::fs::read_to_string(get_resource_path("tokenizer.wordpiece.json")).unwrap();
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        value["model"] = serde_json::json!({
+            "type": "WordLevel",
+            "vocab": {"[UNK]": 0, "the": 1, "plaintiff": 2, "jumps": 3, "fox": 4},
+            "unk_token": "[UNK]"
+        });
+        load_tokenizer_from_str(name, &value.to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_count_words() {
+        assert_eq!(count_words("The fox, jumps!"), 3);
+        assert_eq!(count_words("... --"), 0);
+    }
+
+    #[test]
+    fn test_compare_tokenizers() {
+        load_word_level_tokenizer("test-word-level");
+        let wordpiece = get_resource_path("tokenizer.wordpiece.json");
+        let tokenizers = vec![wordpiece.clone(), "test-word-level".to_string()];
+        let texts = vec!["The fox jumps".to_string(), "".to_string()];
+        let terms = vec!["plaintiff".to_string(), "jumps".to_string()];
+
+        let comparisons = compare_tokenizers(&tokenizers, &texts, &terms).unwrap();
+        assert_eq!(comparisons.len(), 2);
+
+        let wordpiece = &comparisons[&wordpiece];
+        assert_eq!(wordpiece.total_words, 3);
+        assert_eq!(wordpiece.total_tokens, 4);
+        assert_eq!(wordpiece.document_tokens, vec![4, 0]);
+        assert!((wordpiece.tokens_per_word - 4.0 / 3.0).abs() < 1e-9);
+        assert!((wordpiece.chars_per_token - 13.0 / 4.0).abs() < 1e-9);
+        assert_eq!(wordpiece.document_tokens_per_word[1], 0.0);
+        // "plaintiff" is a single token, "jumps" is "jump" + "##s"
+        assert_eq!(wordpiece.split_domain_terms, vec!["jumps"]);
+        assert_eq!(wordpiece.domain_term_split_rate, 0.5);
+
+        let word_level = &comparisons["test-word-level"];
+        assert_eq!(word_level.total_tokens, 3);
+        assert_eq!(word_level.tokens_per_word, 1.0);
+        assert!(word_level.split_domain_terms.is_empty());
+    }
+
+    #[test]
+    fn test_compare_tokenizers_from_file() {
+        let wordpiece = get_resource_path("tokenizer.wordpiece.json");
+        let comparisons = compare_tokenizers_from_file(
+            &[wordpiece.clone()],
+            &get_resource_path("usc.100.jsonl"),
+            Some("text"),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(comparisons[&wordpiece].num_documents, 100);
+        assert_eq!(comparisons[&wordpiece].domain_term_split_rate, 0.0);
+    }
+
+    #[test]
+    fn test_get_vocab_overlap() {
+        load_word_level_tokenizer("test-word-level-overlap");
+        let wordpiece = get_resource_path("tokenizer.wordpiece.json");
+        let (shared, jaccard, fraction_a, fraction_b) =
+            get_vocab_overlap(&wordpiece, "test-word-level-overlap").unwrap();
+
+        // the word-level copy keeps the added special tokens of the original
+        assert!(shared >= 4);
+        assert!(jaccard > 0.0 && jaccard < 1.0);
+        assert!(fraction_a < fraction_b);
+
+        let (_, jaccard, _, _) = get_vocab_overlap(&wordpiece, &wordpiece).unwrap();
+        assert_eq!(jaccard, 1.0);
+    }
+}
@@ -1,54 +1,421 @@
+pub mod analysis;
+pub mod stats;
+pub mod training;
+
 pub mod tokenizers {
+    use pyo3::prelude::*;
     use std::collections::HashMap;
+    use std::path::Path;
+    use std::str::FromStr;
     use std::sync::{LazyLock, Mutex};
-    use tokenizers::Tokenizer;
+    use tokenizers::{
+        Encoding, PaddingParams, PaddingStrategy, Result, Tokenizer, TruncationParams,
+    };
 
     static TOKENIZER_CACHE: LazyLock<Mutex<HashMap<String, Tokenizer>>> =
         LazyLock::new(|| Mutex::new(HashMap::new()));
 
-    fn get_tokenizer(tokenizer: &str) -> Tokenizer {
+    fn register_tokenizer(name: &str, tokenizer: Tokenizer) {
         let mut cache = TOKENIZER_CACHE.lock().unwrap();
-        cache
-            .entry(tokenizer.to_string())
-            .or_insert_with(|| {
-                Tokenizer::from_pretrained(tokenizer, None).expect("Failed to load tokenizer")
-            })
-            .clone()
-    }
-
-    pub fn encode_str(tokenizer: &str, text: &str) -> Vec<u32> {
-        let tokenizer = get_tokenizer(tokenizer);
-        let encoding = tokenizer
-            .encode(text, false)
-            .expect("Failed to encode text");
-        encoding.get_ids().to_vec()
-    }
-
-    pub fn decode_str(tokenizer: &str, ids: Vec<u32>) -> String {
-        let tokenizer = get_tokenizer(tokenizer);
-        tokenizer
-            .decode(ids.as_slice(), false)
-            .expect("Failed to decode text")
-    }
-
-    pub fn encode_str_list(tokenizer: &str, texts: Vec<String>) -> Vec<Vec<u32>> {
-        let tokenizer = get_tokenizer(tokenizer);
-        tokenizer
-            .encode_batch(texts, false)
-            .expect("Failed to encode text")
+        cache.insert(name.to_string(), tokenizer);
+    }
+
+    /// Loads a tokenizer from a local `tokenizer.json` file and registers it under a name.
+    ///
+    /// # Arguments
+    /// * `name` - The name to register the tokenizer under
+    /// * `path` - The path to the `tokenizer.json` file
+    ///
+    /// # Returns
+    /// An error if the file cannot be read or parsed
+    pub fn load_tokenizer_from_file(name: &str, path: &str) -> Result<()> {
+        let tokenizer = Tokenizer::from_file(path)?;
+        register_tokenizer(name, tokenizer);
+        Ok(())
+    }
+
+    /// Loads a tokenizer from a `tokenizer.json` string and registers it under a name.
+    ///
+    /// # Arguments
+    /// * `name` - The name to register the tokenizer under
+    /// * `json` - The serialized tokenizer
+    ///
+    /// # Returns
+    /// An error if the JSON cannot be parsed
+    pub fn load_tokenizer_from_str(name: &str, json: &str) -> Result<()> {
+        let tokenizer = Tokenizer::from_str(json)?;
+        register_tokenizer(name, tokenizer);
+        Ok(())
+    }
+
+    /// Returns a tokenizer by name, loading it on first use.
+    ///
+    /// Names registered with `load_tokenizer_from_file` or `load_tokenizer_from_str` are
+    /// returned from the cache. Otherwise, a name that points to an existing file is loaded as a
+    /// local `tokenizer.json`, and any other name is loaded from the Hugging Face hub.
+    pub fn get_tokenizer(tokenizer: &str) -> Result<Tokenizer> {
+        if let Some(cached) = TOKENIZER_CACHE.lock().unwrap().get(tokenizer) {
+            return Ok(cached.clone());
+        }
+
+        // load outside of the lock so that slow downloads don't block other tokenizers
+        let loaded = if Path::new(tokenizer).is_file() {
+            Tokenizer::from_file(tokenizer)?
+        } else {
+            Tokenizer::from_pretrained(tokenizer, None)?
+        };
+        register_tokenizer(tokenizer, loaded.clone());
+        Ok(loaded)
+    }
+
+    /// Drops a cached tokenizer, e.g., after its file is overwritten, so the next use reloads it.
+    pub(crate) fn evict_tokenizer(name: &str) {
+        TOKENIZER_CACHE.lock().unwrap().remove(name);
+    }
+
+    /// Returns true if a tokenizer is registered under the name.
+    pub fn is_tokenizer_loaded(tokenizer: &str) -> bool {
+        TOKENIZER_CACHE.lock().unwrap().contains_key(tokenizer)
+    }
+
+    /// Options for `encode_str_with_config` and `encode_str_list_with_config`.
+    ///
+    /// Truncation is enabled by `max_length`, with `stride` tokens of overlap between the
+    /// truncated encoding and each overflowing window. Padding is enabled by `pad_to_length` or
+    /// `pad_to_multiple_of`, which alone pads to the longest encoding in the batch rounded up to
+    /// the multiple. If `pad_id` is not set, the tokenizer's own padding settings or its
+    /// `[PAD]`/`<pad>` token are used, falling back to 0.
+    #[derive(Debug, Clone, Default)]
+    pub struct EncodeConfig {
+        pub add_special_tokens: bool,
+        pub max_length: Option<usize>,
+        pub stride: usize,
+        pub pad_to_length: Option<usize>,
+        pub pad_to_multiple_of: Option<usize>,
+        pub pad_id: Option<u32>,
+    }
+
+    impl EncodeConfig {
+        pub fn new(
+            add_special_tokens: bool,
+            max_length: Option<usize>,
+            stride: usize,
+            pad_to_length: Option<usize>,
+            pad_to_multiple_of: Option<usize>,
+            pad_id: Option<u32>,
+        ) -> EncodeConfig {
+            EncodeConfig {
+                add_special_tokens,
+                max_length,
+                stride,
+                pad_to_length,
+                pad_to_multiple_of,
+                pad_id,
+            }
+        }
+    }
+
+    /// An encoded text with its attention mask, special token mask and character offsets.
+    ///
+    /// `offsets` are (start, end) character offsets into the original text; special and padding
+    /// tokens have (0, 0) offsets. When truncating, the windows that did not fit in `max_length`
+    /// are returned in `overflowing`.
+    #[pyclass]
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct TokenEncoding {
+        #[pyo3(get)]
+        pub ids: Vec<u32>,
+        #[pyo3(get)]
+        pub tokens: Vec<String>,
+        #[pyo3(get)]
+        pub attention_mask: Vec<u32>,
+        #[pyo3(get)]
+        pub special_tokens_mask: Vec<u32>,
+        #[pyo3(get)]
+        pub offsets: Vec<(usize, usize)>,
+        #[pyo3(get)]
+        pub overflowing: Vec<TokenEncoding>,
+    }
+
+    #[pymethods]
+    impl TokenEncoding {
+        fn __len__(&self) -> usize {
+            self.ids.len()
+        }
+
+        fn __str__(&self) -> String {
+            format!(
+                "TokenEncoding(num_tokens={}, num_overflowing={})",
+                self.ids.len(),
+                self.overflowing.len()
+            )
+        }
+    }
+
+    impl From<&Encoding> for TokenEncoding {
+        fn from(encoding: &Encoding) -> Self {
+            TokenEncoding {
+                ids: encoding.get_ids().to_vec(),
+                tokens: encoding.get_tokens().to_vec(),
+                attention_mask: encoding.get_attention_mask().to_vec(),
+                special_tokens_mask: encoding.get_special_tokens_mask().to_vec(),
+                offsets: encoding.get_offsets().to_vec(),
+                overflowing: encoding
+                    .get_overflowing()
+                    .iter()
+                    .map(TokenEncoding::from)
+                    .collect(),
+            }
+        }
+    }
+
+    /// Returns the padding parameters for a config, or None if padding is disabled.
+    fn get_padding_params(tokenizer: &Tokenizer, config: &EncodeConfig) -> Option<PaddingParams> {
+        let strategy = match (config.pad_to_length, config.pad_to_multiple_of) {
+            (Some(length), _) => PaddingStrategy::Fixed(length),
+            (None, Some(_)) => PaddingStrategy::BatchLongest,
+            (None, None) => return None,
+        };
+
+        let mut params = tokenizer.get_padding().cloned().unwrap_or_else(|| {
+            let (pad_id, pad_token) = ["[PAD]", "<pad>"]
+                .iter()
+                .find_map(|token| {
+                    tokenizer
+                        .token_to_id(token)
+                        .map(|id| (id, token.to_string()))
+                })
+                .unwrap_or((0, "[PAD]".to_string()));
+            PaddingParams {
+                pad_id,
+                pad_token,
+                ..Default::default()
+            }
+        });
+        if let Some(pad_id) = config.pad_id {
+            params.pad_id = pad_id;
+            params.pad_token = tokenizer.id_to_token(pad_id).unwrap_or(params.pad_token);
+        }
+        params.strategy = strategy;
+        params.pad_to_multiple_of = config.pad_to_multiple_of;
+        Some(params)
+    }
+
+    /// Returns a copy of the tokenizer with truncation and padding configured.
+    fn configure_tokenizer(tokenizer: &str, config: &EncodeConfig) -> Result<Tokenizer> {
+        let mut tokenizer = get_tokenizer(tokenizer)?;
+
+        let truncation = config.max_length.map(|max_length| TruncationParams {
+            max_length,
+            stride: config.stride,
+            ..Default::default()
+        });
+        let padding = get_padding_params(&tokenizer, config);
+
+        tokenizer.with_truncation(truncation)?.with_padding(padding);
+        Ok(tokenizer)
+    }
+
+    /// Encodes a text with special tokens, truncation and padding.
+    ///
+    /// # Arguments
+    /// * `tokenizer` - The tokenizer name, path or hub identifier
+    /// * `text` - The text to encode
+    /// * `config` - The encoding options
+    ///
+    /// # Returns
+    /// The encoding with attention mask, character offsets and overflowing windows
+    pub fn encode_str_with_config(
+        tokenizer: &str,
+        text: &str,
+        config: &EncodeConfig,
+    ) -> Result<TokenEncoding> {
+        let tokenizer = configure_tokenizer(tokenizer, config)?;
+        let encoding = tokenizer.encode_char_offsets(text, config.add_special_tokens)?;
+        Ok(TokenEncoding::from(&encoding))
+    }
+
+    /// Encodes a list of texts in parallel with special tokens, truncation and padding.
+    ///
+    /// # Arguments
+    /// * `tokenizer` - The tokenizer name, path or hub identifier
+    /// * `texts` - The texts to encode
+    /// * `config` - The encoding options
+    ///
+    /// # Returns
+    /// One encoding per text
+    pub fn encode_str_list_with_config(
+        tokenizer: &str,
+        texts: Vec<String>,
+        config: &EncodeConfig,
+    ) -> Result<Vec<TokenEncoding>> {
+        let tokenizer = configure_tokenizer(tokenizer, config)?;
+        Ok(tokenizer
+            .encode_batch_char_offsets(texts, config.add_special_tokens)?
+            .iter()
+            .map(TokenEncoding::from)
+            .collect())
+    }
+
+    pub fn encode_str(tokenizer: &str, text: &str) -> Result<Vec<u32>> {
+        let tokenizer = get_tokenizer(tokenizer)?;
+        let encoding = tokenizer.encode(text, false)?;
+        Ok(encoding.get_ids().to_vec())
+    }
+
+    /// Returns the number of tokens in a text without special tokens.
+    pub fn count_tokens(tokenizer: &str, text: &str) -> Result<usize> {
+        let tokenizer = get_tokenizer(tokenizer)?;
+        Ok(tokenizer.encode(text, false)?.len())
+    }
+
+    /// Returns the number of tokens in each text, encoding the texts in parallel.
+    pub fn count_tokens_list(tokenizer: &str, texts: Vec<String>) -> Result<Vec<usize>> {
+        let tokenizer = get_tokenizer(tokenizer)?;
+        Ok(tokenizer
+            .encode_batch(texts, false)?
+            .iter()
+            .map(|encoding| encoding.len())
+            .collect())
+    }
+
+    pub fn decode_str(tokenizer: &str, ids: Vec<u32>) -> Result<String> {
+        let tokenizer = get_tokenizer(tokenizer)?;
+        tokenizer.decode(ids.as_slice(), false)
+    }
+
+    pub fn encode_str_list(tokenizer: &str, texts: Vec<String>) -> Result<Vec<Vec<u32>>> {
+        let tokenizer = get_tokenizer(tokenizer)?;
+        Ok(tokenizer
+            .encode_batch(texts, false)?
             .iter()
             .map(|encoding| encoding.get_ids().to_vec())
-            .collect()
+            .collect())
     }
 
-    pub fn decode_str_list(tokenizer: &str, ids: Vec<Vec<u32>>) -> Vec<String> {
-        let tokenizer = get_tokenizer(tokenizer);
+    pub fn decode_str_list(tokenizer: &str, ids: Vec<Vec<u32>>) -> Result<Vec<String>> {
+        let tokenizer = get_tokenizer(tokenizer)?;
         ids.into_iter()
-            .map(|ids| {
-                tokenizer
-                    .decode(ids.as_slice(), false)
-                    .expect("Failed to decode text")
-            })
+            .map(|ids| tokenizer.decode(ids.as_slice(), false))
             .collect()
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn get_test_tokenizer_path() -> String {
+            let manifest_dir = env!("CARGO_MANIFEST_DIR");
+            Path::new(manifest_dir)
+                .join("resources/tokenizer.wordpiece.json")
+                .to_string_lossy()
+                .to_string()
+        }
+
+        #[test]
+        fn test_load_tokenizer_from_file() {
+            load_tokenizer_from_file("test-wordpiece-file", &get_test_tokenizer_path()).unwrap();
+            assert!(is_tokenizer_loaded("test-wordpiece-file"));
+
+            let ids = encode_str("test-wordpiece-file", "The quick brown fox jumps").unwrap();
+            assert_eq!(ids, vec![5, 6, 7, 8, 9, 10]);
+            assert_eq!(
+                decode_str("test-wordpiece-file", ids).unwrap(),
+                "the quick brown fox jumps"
+            );
+        }
+
+        #[test]
+        fn test_load_tokenizer_from_str() {
+            let json = std::fs::read_to_string(get_test_tokenizer_path()).unwrap();
+            load_tokenizer_from_str("test-wordpiece-str", &json).unwrap();
+
+            let ids = encode_str_list(
+                "test-wordpiece-str",
+                vec!["hello world".to_string(), "lazy dog".to_string()],
+            )
+            .unwrap();
+            assert_eq!(ids, vec![vec![16, 17], vec![14, 15]]);
+            assert_eq!(
+                decode_str_list("test-wordpiece-str", ids).unwrap(),
+                vec!["hello world", "lazy dog"]
+            );
+        }
+
+        #[test]
+        fn test_get_tokenizer_by_path() {
+            let path = get_test_tokenizer_path();
+            assert!(encode_str(&path, "hello").is_ok());
+            assert!(is_tokenizer_loaded(&path));
+        }
+
+        #[test]
+        fn test_encode_str_with_config() {
+            let path = get_test_tokenizer_path();
+            let config = EncodeConfig::new(true, None, 0, Some(8), None, None);
+            let encoding = encode_str_with_config(&path, "Héllo, xyzzy", &config).unwrap();
+
+            assert_eq!(encoding.ids, vec![2, 16, 19, 1, 3, 0, 0, 0]);
+            assert_eq!(encoding.attention_mask, vec![1, 1, 1, 1, 1, 0, 0, 0]);
+            assert_eq!(encoding.special_tokens_mask, vec![1, 0, 0, 0, 1, 1, 1, 1]);
+            assert_eq!(encoding.tokens[0], "[CLS]");
+            assert_eq!(encoding.tokens[7], "[PAD]");
+            // character offsets, not byte offsets
+            assert_eq!(encoding.offsets[3], (7, 12));
+            assert!(encoding.overflowing.is_empty());
+        }
+
+        #[test]
+        fn test_encode_str_with_config_truncation() {
+            let path = get_test_tokenizer_path();
+            let text = "the quick brown fox jumps over the lazy dog";
+            let config = EncodeConfig::new(false, Some(4), 1, None, None, None);
+            let encoding = encode_str_with_config(&path, text, &config).unwrap();
+
+            assert_eq!(encoding.ids, vec![5, 6, 7, 8]);
+            assert_eq!(encoding.overflowing.len(), 2);
+            // windows overlap by the stride
+            assert_eq!(encoding.overflowing[0].ids, vec![8, 9, 10, 13]);
+            assert_eq!(encoding.overflowing[0].offsets[0], (16, 19));
+
+            let config = EncodeConfig::new(true, Some(4), 0, None, Some(8), Some(4));
+            let encoding = encode_str_with_config(&path, text, &config).unwrap();
+            assert_eq!(encoding.ids, vec![2, 5, 6, 3, 4, 4, 4, 4]);
+        }
+
+        #[test]
+        fn test_encode_str_list_with_config() {
+            let path = get_test_tokenizer_path();
+            let config = EncodeConfig::new(false, None, 0, None, Some(4), None);
+            let encodings = encode_str_list_with_config(
+                &path,
+                vec!["hello".to_string(), "the quick brown fox jumps".to_string()],
+                &config,
+            )
+            .unwrap();
+
+            // padded to the longest encoding in the batch, rounded up to a multiple of 4
+            assert_eq!(encodings[0].ids, vec![16, 0, 0, 0, 0, 0, 0, 0]);
+            assert_eq!(encodings[1].ids.len(), 8);
+            assert_eq!(encodings[1].attention_mask.iter().sum::<u32>(), 6);
+        }
+
+        #[test]
+        fn test_count_tokens() {
+            let path = get_test_tokenizer_path();
+            assert_eq!(count_tokens(&path, "The quick brown fox jumps").unwrap(), 6);
+            assert_eq!(count_tokens(&path, "").unwrap(), 0);
+            assert_eq!(
+                count_tokens_list(&path, vec!["hello world".to_string(), "".to_string()]).unwrap(),
+                vec![2, 0]
+            );
+        }
+
+        #[test]
+        fn test_load_tokenizer_errors() {
+            assert!(load_tokenizer_from_file("missing", "/does/not/exist.json").is_err());
+            assert!(load_tokenizer_from_str("invalid", "{not json").is_err());
+            assert!(!is_tokenizer_loaded("invalid"));
+        }
+    }
 }
@@ -0,0 +1,276 @@
+/// Corpus-level token statistics for budgeting and tokenizer evaluation.
+use crate::algos::tokenizers::tokenizers::get_tokenizer;
+use crate::io::fs::files::iter_documents;
+use pyo3::prelude::*;
+use std::collections::{BTreeMap, HashSet};
+use tokenizers::{Result, Tokenizer};
+
+/// Number of documents encoded per batch when reading from files.
+const BATCH_SIZE: usize = 1024;
+
+/// Token statistics over a set of documents.
+///
+/// `length_histogram` maps power-of-two bucket lower bounds (0, 1, 2, 4, 8, ...) to the number of
+/// documents whose token count falls in `[bound, 2 * bound)`.
+#[pyclass]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TokenStats {
+    #[pyo3(get)]
+    pub num_documents: usize,
+    #[pyo3(get)]
+    pub total_tokens: usize,
+    #[pyo3(get)]
+    pub total_bytes: usize,
+    #[pyo3(get)]
+    pub min_length: usize,
+    #[pyo3(get)]
+    pub max_length: usize,
+    #[pyo3(get)]
+    pub mean_length: f64,
+    #[pyo3(get)]
+    pub length_histogram: BTreeMap<usize, usize>,
+    #[pyo3(get)]
+    pub unique_tokens: usize,
+    #[pyo3(get)]
+    pub vocab_size: usize,
+    #[pyo3(get)]
+    pub vocab_coverage: f64,
+    #[pyo3(get)]
+    pub unk_tokens: usize,
+    #[pyo3(get)]
+    pub unk_rate: f64,
+    #[pyo3(get)]
+    pub bytes_per_token: f64,
+}
+
+#[pymethods]
+impl TokenStats {
+    fn __str__(&self) -> String {
+        format!(
+            "TokenStats(num_documents={}, total_tokens={}, mean_length={:.2}, vocab_coverage={:.4}, unk_rate={:.4}, bytes_per_token={:.2})",
+            self.num_documents,
+            self.total_tokens,
+            self.mean_length,
+            self.vocab_coverage,
+            self.unk_rate,
+            self.bytes_per_token
+        )
+    }
+}
+
+/// Returns the power-of-two histogram bucket for a document length.
+pub fn get_length_bucket(length: usize) -> usize {
+    if length == 0 {
+        0
+    } else {
+        1 << (usize::BITS - 1 - length.leading_zeros())
+    }
+}
+
+/// Returns the ID of the tokenizer's unknown token, if its model has one.
+fn get_unk_id(tokenizer: &Tokenizer) -> Option<u32> {
+    // every model serializes either an unk_token or an unk_id
+    let model = serde_json::to_value(tokenizer.get_model()).ok()?;
+    match (model.get("unk_token"), model.get("unk_id")) {
+        (Some(serde_json::Value::String(token)), _) => tokenizer.token_to_id(token),
+        (_, Some(serde_json::Value::Number(id))) => id.as_u64().map(|id| id as u32),
+        _ => None,
+    }
+}
+
+/// Accumulates token statistics one batch of documents at a time.
+struct TokenStatsAccumulator {
+    stats: TokenStats,
+    seen: HashSet<u32>,
+    unk_id: Option<u32>,
+}
+
+impl TokenStatsAccumulator {
+    fn new(tokenizer: &Tokenizer) -> Self {
+        TokenStatsAccumulator {
+            stats: TokenStats {
+                min_length: usize::MAX,
+                vocab_size: tokenizer.get_vocab_size(true),
+                ..Default::default()
+            },
+            seen: HashSet::new(),
+            unk_id: get_unk_id(tokenizer),
+        }
+    }
+
+    fn update(&mut self, tokenizer: &Tokenizer, texts: Vec<String>) -> Result<()> {
+        let total_bytes: usize = texts.iter().map(|text| text.len()).sum();
+        let encodings = tokenizer.encode_batch(texts, false)?;
+
+        let stats = &mut self.stats;
+        stats.total_bytes += total_bytes;
+        for encoding in encodings.iter() {
+            let ids = encoding.get_ids();
+            stats.num_documents += 1;
+            stats.total_tokens += ids.len();
+            stats.min_length = stats.min_length.min(ids.len());
+            stats.max_length = stats.max_length.max(ids.len());
+            *stats
+                .length_histogram
+                .entry(get_length_bucket(ids.len()))
+                .or_insert(0) += 1;
+            if let Some(unk_id) = self.unk_id {
+                stats.unk_tokens += ids.iter().filter(|&&id| id == unk_id).count();
+            }
+            self.seen.extend(ids);
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> TokenStats {
+        let stats = &mut self.stats;
+        if stats.num_documents == 0 {
+            stats.min_length = 0;
+        } else {
+            stats.mean_length = stats.total_tokens as f64 / stats.num_documents as f64;
+        }
+        if stats.total_tokens > 0 {
+            stats.unk_rate = stats.unk_tokens as f64 / stats.total_tokens as f64;
+            stats.bytes_per_token = stats.total_bytes as f64 / stats.total_tokens as f64;
+        }
+        stats.unique_tokens = self.seen.len();
+        if stats.vocab_size > 0 {
+            stats.vocab_coverage = stats.unique_tokens as f64 / stats.vocab_size as f64;
+        }
+        self.stats
+    }
+}
+
+/// Returns token statistics for a list of documents.
+///
+/// # Arguments
+/// * `tokenizer` - The tokenizer name, path or hub identifier
+/// * `texts` - The documents
+///
+/// # Returns
+/// The token statistics
+pub fn get_token_stats(tokenizer: &str, texts: Vec<String>) -> Result<TokenStats> {
+    let tokenizer = get_tokenizer(tokenizer)?;
+    let mut accumulator = TokenStatsAccumulator::new(&tokenizer);
+    accumulator.update(&tokenizer, texts)?;
+    Ok(accumulator.finish())
+}
+
+/// Returns token statistics for the lines of a text, JSONL or gzipped file.
+///
+/// Files ending in `.gz` are decompressed while reading. Each line is a document, unless `field`
+/// is set, in which case each line is parsed as a JSON object and the document is that string
+/// field; lines that are blank or lack the field are skipped.
+///
+/// # Arguments
+/// * `tokenizer` - The tokenizer name, path or hub identifier
+/// * `path` - The path to the file
+/// * `field` - The JSON field holding the text, e.g., "text" for JSONL files
+///
+/// # Returns
+/// The token statistics
+pub fn get_token_stats_from_file(
+    tokenizer: &str,
+    path: &str,
+    field: Option<&str>,
+) -> Result<TokenStats> {
+    let tokenizer = get_tokenizer(tokenizer)?;
+
+    let mut accumulator = TokenStatsAccumulator::new(&tokenizer);
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    for document in iter_documents(path, field)? {
+        batch.push(document?);
+        if batch.len() == BATCH_SIZE {
+            accumulator.update(&tokenizer, std::mem::take(&mut batch))?;
+        }
+    }
+    accumulator.update(&tokenizer, batch)?;
+
+    Ok(accumulator.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_resource_path(name: &str) -> String {
+        format!("{}/resources/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    #[test]
+    fn test_get_length_bucket() {
+        assert_eq!(get_length_bucket(0), 0);
+        assert_eq!(get_length_bucket(1), 1);
+        assert_eq!(get_length_bucket(3), 2);
+        assert_eq!(get_length_bucket(4), 4);
+        assert_eq!(get_length_bucket(1000), 512);
+    }
+
+    #[test]
+    fn test_get_token_stats() {
+        let tokenizer = get_resource_path("tokenizer.wordpiece.json");
+        let stats = get_token_stats(
+            &tokenizer,
+            vec![
+                "the quick brown fox".to_string(),
+                "hello xyzzy world".to_string(),
+                "".to_string(),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(stats.num_documents, 3);
+        assert_eq!(stats.total_tokens, 7);
+        assert_eq!(stats.total_bytes, 36);
+        assert_eq!(stats.min_length, 0);
+        assert_eq!(stats.max_length, 4);
+        assert_eq!(stats.length_histogram[&0], 1);
+        assert_eq!(stats.length_histogram[&2], 1);
+        assert_eq!(stats.length_histogram[&4], 1);
+        assert_eq!(stats.unique_tokens, 7);
+        assert_eq!(stats.unk_tokens, 1);
+        assert!((stats.unk_rate - 1.0 / 7.0).abs() < 1e-9);
+        assert!((stats.bytes_per_token - 36.0 / 7.0).abs() < 1e-9);
+        assert!((stats.vocab_coverage - 7.0 / stats.vocab_size as f64).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_get_token_stats_from_file() {
+        let tokenizer = get_resource_path("tokenizer.wordpiece.json");
+        let stats = get_token_stats_from_file(
+            &tokenizer,
+            &get_resource_path("usc.100.jsonl"),
+            Some("text"),
+        )
+        .unwrap();
+        assert_eq!(stats.num_documents, 100);
+        assert!(stats.total_tokens > 0);
+        assert_eq!(stats.length_histogram.values().sum::<usize>(), 100);
+
+        let gz_stats = get_token_stats_from_file(
+            &tokenizer,
+            &get_resource_path("usc.100.jsonl.gz"),
+            Some("text"),
+        )
+        .unwrap();
+        assert_eq!(gz_stats, stats);
+
+        let stats =
+            get_token_stats_from_file(&tokenizer, &get_resource_path("hello-world.txt"), None)
+                .unwrap();
+        assert_eq!(stats.num_documents, 1);
+        assert_eq!(stats.total_tokens, 4);
+    }
+
+    #[test]
+    fn test_get_token_stats_from_file_errors() {
+        let tokenizer = get_resource_path("tokenizer.wordpiece.json");
+        assert!(get_token_stats_from_file(&tokenizer, "/does/not/exist.txt", None).is_err());
+        assert!(get_token_stats_from_file(
+            &tokenizer,
+            &get_resource_path("hello-world.txt"),
+            Some("text")
+        )
+        .is_err());
+    }
+}
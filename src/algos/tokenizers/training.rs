@@ -0,0 +1,503 @@
+/// Training BPE and Unigram tokenizers from local corpus files.
+use crate::algos::tokenizers::tokenizers::evict_tokenizer;
+use crate::algos::unicode::normalizations::{get_whitespace_replacements, WhitespaceConfig};
+use crate::io::fs::files::iter_documents;
+use std::io;
+use std::sync::{Arc, Mutex};
+use tokenizers::decoders::byte_level::ByteLevel as ByteLevelDecoder;
+use tokenizers::decoders::metaspace::Metaspace as MetaspaceDecoder;
+use tokenizers::decoders::wordpiece::WordPiece as WordPieceDecoder;
+use tokenizers::models::bpe::{BpeTrainer, BPE};
+use tokenizers::models::unigram::{Unigram, UnigramTrainer};
+use tokenizers::models::TrainerWrapper;
+use tokenizers::normalizers::replace::ReplacePattern;
+use tokenizers::normalizers::{Lowercase, Replace, Sequence, Strip, NFC, NFD, NFKC, NFKD};
+use tokenizers::pre_tokenizers::bert::BertPreTokenizer;
+use tokenizers::pre_tokenizers::byte_level::ByteLevel;
+use tokenizers::pre_tokenizers::metaspace::{Metaspace, PrependScheme};
+use tokenizers::pre_tokenizers::sequence::Sequence as PreTokenizerSequence;
+use tokenizers::pre_tokenizers::whitespace::Whitespace;
+use tokenizers::{
+    AddedToken, DecoderWrapper, ModelWrapper, NormalizerWrapper, PreTokenizerWrapper, Result,
+    Tokenizer,
+};
+
+/// Prefix marking subwords that continue a word, for pre-tokenizers that split on whitespace.
+const CONTINUING_SUBWORD_PREFIX: &str = "##";
+
+#[derive(Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum TokenizerModelType {
+    Bpe,
+    Unigram,
+}
+
+#[derive(Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum PreTokenization {
+    ByteLevel, // GPT-2 style byte-level BPE
+    Whitespace,
+    Bert,
+    Metaspace, // SentencePiece style, recommended for Unigram
+}
+
+/// Unicode normalization forms, as in `unicode::normalizations`.
+#[derive(Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum NormalizationForm {
+    Nfc,
+    Nfd,
+    Nfkc,
+    Nfkd,
+}
+
+pub fn model_type_from_string(name: &str) -> Option<TokenizerModelType> {
+    match name.to_lowercase().as_str() {
+        "bpe" => Some(TokenizerModelType::Bpe),
+        "unigram" => Some(TokenizerModelType::Unigram),
+        _ => None,
+    }
+}
+
+pub fn pre_tokenization_from_string(name: &str) -> Option<PreTokenization> {
+    match name.to_lowercase().as_str() {
+        "byte_level" => Some(PreTokenization::ByteLevel),
+        "whitespace" => Some(PreTokenization::Whitespace),
+        "bert" => Some(PreTokenization::Bert),
+        "metaspace" => Some(PreTokenization::Metaspace),
+        _ => None,
+    }
+}
+
+pub fn normalization_form_from_string(name: &str) -> Option<NormalizationForm> {
+    match name.to_lowercase().as_str() {
+        "nfc" => Some(NormalizationForm::Nfc),
+        "nfd" => Some(NormalizationForm::Nfd),
+        "nfkc" => Some(NormalizationForm::Nfkc),
+        "nfkd" => Some(NormalizationForm::Nfkd),
+        _ => None,
+    }
+}
+
+/// Configuration for `train_tokenizer`.
+///
+/// `unk_token` is added to `special_tokens` if missing. `clean_text`, `normalization` and
+/// `lowercase` are saved in the tokenizer, so they apply at encoding time as well as during
+/// training. `clean_text` replays `normalize_whitespace` with the patterns from
+/// `get_whitespace_replacements`, so that zero-width characters, bidi controls and exotic spaces
+/// do not consume merges or vocabulary entries. The normalization forms use the tokenizers
+/// crate's own NFC, NFD, NFKC and NFKD, which are the ones a saved tokenizer can hold.
+#[derive(Debug, Clone)]
+pub struct TrainingConfig {
+    pub model_type: TokenizerModelType,
+    pub vocab_size: usize,
+    pub min_frequency: u64,
+    pub special_tokens: Vec<String>,
+    pub unk_token: Option<String>,
+    pub pre_tokenization: PreTokenization,
+    pub normalization: Option<NormalizationForm>,
+    pub lowercase: bool,
+    pub clean_text: bool,
+}
+
+impl TrainingConfig {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        model_type: TokenizerModelType,
+        vocab_size: usize,
+        min_frequency: u64,
+        special_tokens: Vec<String>,
+        unk_token: Option<String>,
+        pre_tokenization: PreTokenization,
+        normalization: Option<NormalizationForm>,
+        lowercase: bool,
+        clean_text: bool,
+    ) -> TrainingConfig {
+        TrainingConfig {
+            model_type,
+            vocab_size,
+            min_frequency,
+            special_tokens,
+            unk_token,
+            pre_tokenization,
+            normalization,
+            lowercase,
+            clean_text,
+        }
+    }
+}
+
+impl Default for TrainingConfig {
+    fn default() -> Self {
+        TrainingConfig::new(
+            TokenizerModelType::Bpe,
+            32000,
+            2,
+            Vec::new(),
+            None,
+            PreTokenization::ByteLevel,
+            Some(NormalizationForm::Nfc),
+            false,
+            true,
+        )
+    }
+}
+
+fn get_normalizer(config: &TrainingConfig) -> Result<Option<NormalizerWrapper>> {
+    let mut normalizers: Vec<NormalizerWrapper> = Vec::new();
+    if config.clean_text {
+        let whitespace_config = WhitespaceConfig::default();
+        for (pattern, content) in get_whitespace_replacements(&whitespace_config) {
+            let pattern = ReplacePattern::Regex(pattern.to_string());
+            normalizers.push(Replace::new(pattern, content)?.into());
+        }
+        if whitespace_config.trim {
+            normalizers.push(Strip::new(true, true).into());
+        }
+    }
+    match config.normalization {
+        Some(NormalizationForm::Nfc) => normalizers.push(NFC.into()),
+        Some(NormalizationForm::Nfd) => normalizers.push(NFD.into()),
+        Some(NormalizationForm::Nfkc) => normalizers.push(NFKC.into()),
+        Some(NormalizationForm::Nfkd) => normalizers.push(NFKD.into()),
+        None => {}
+    }
+    if config.lowercase {
+        normalizers.push(Lowercase.into());
+    }
+
+    Ok(match normalizers.len() {
+        0 => None,
+        1 => normalizers.pop(),
+        _ => Some(Sequence::new(normalizers).into()),
+    })
+}
+
+fn get_pre_tokenizer_and_decoder(config: &TrainingConfig) -> (PreTokenizerWrapper, DecoderWrapper) {
+    match (config.model_type, config.pre_tokenization) {
+        // GPT-2 style, without a prefix space so that decoding round-trips the input
+        (_, PreTokenization::ByteLevel) => (
+            ByteLevel::new(false, true, true).into(),
+            ByteLevelDecoder::default().into(),
+        ),
+        (TokenizerModelType::Bpe, PreTokenization::Whitespace) => (
+            Whitespace {}.into(),
+            WordPieceDecoder::new(CONTINUING_SUBWORD_PREFIX.to_string(), true).into(),
+        ),
+        (TokenizerModelType::Bpe, PreTokenization::Bert) => (
+            BertPreTokenizer.into(),
+            WordPieceDecoder::new(CONTINUING_SUBWORD_PREFIX.to_string(), true).into(),
+        ),
+        // Unigram has no continuing subword prefix, so each word is marked with a leading
+        // metaspace instead and the decoder turns the marks back into spaces
+        (TokenizerModelType::Unigram, PreTokenization::Whitespace | PreTokenization::Bert) => {
+            let word_pre_tokenizer: PreTokenizerWrapper =
+                if config.pre_tokenization == PreTokenization::Whitespace {
+                    Whitespace {}.into()
+                } else {
+                    BertPreTokenizer.into()
+                };
+            let metaspace = Metaspace::new('▁', PrependScheme::Always, false);
+            (
+                PreTokenizerSequence::new(vec![word_pre_tokenizer, metaspace.clone().into()])
+                    .into(),
+                metaspace.into(),
+            )
+        }
+        (_, PreTokenization::Metaspace) => (
+            Metaspace::default().into(),
+            MetaspaceDecoder::default().into(),
+        ),
+    }
+}
+
+fn get_model_and_trainer(config: &TrainingConfig) -> Result<(ModelWrapper, TrainerWrapper)> {
+    let mut special_tokens = config.special_tokens.clone();
+    if let Some(unk_token) = &config.unk_token {
+        if !special_tokens.contains(unk_token) {
+            special_tokens.insert(0, unk_token.clone());
+        }
+    }
+    let special_tokens: Vec<AddedToken> = special_tokens
+        .into_iter()
+        .map(|token| AddedToken::from(token, true))
+        .collect();
+
+    match config.model_type {
+        TokenizerModelType::Bpe => {
+            let mut model = BPE::builder();
+            if let Some(unk_token) = &config.unk_token {
+                model = model.unk_token(unk_token.clone());
+            }
+
+            let mut trainer = BpeTrainer::builder()
+                .vocab_size(config.vocab_size)
+                .min_frequency(config.min_frequency)
+                .special_tokens(special_tokens)
+                .show_progress(false);
+            match config.pre_tokenization {
+                PreTokenization::ByteLevel => {
+                    trainer = trainer.initial_alphabet(ByteLevel::alphabet());
+                }
+                PreTokenization::Whitespace | PreTokenization::Bert => {
+                    model = model.continuing_subword_prefix(CONTINUING_SUBWORD_PREFIX.to_string());
+                    trainer =
+                        trainer.continuing_subword_prefix(CONTINUING_SUBWORD_PREFIX.to_string());
+                }
+                PreTokenization::Metaspace => {}
+            }
+
+            Ok((model.build()?.into(), trainer.build().into()))
+        }
+        TokenizerModelType::Unigram => {
+            let mut trainer = UnigramTrainer::builder();
+            trainer
+                .vocab_size(config.vocab_size as u32)
+                .special_tokens(special_tokens)
+                .unk_token(config.unk_token.clone())
+                .show_progress(false);
+            if config.pre_tokenization == PreTokenization::ByteLevel {
+                trainer.initial_alphabet(ByteLevel::alphabet());
+            }
+
+            Ok((Unigram::default().into(), trainer.build()?.into()))
+        }
+    }
+}
+
+/// Trains a tokenizer on the documents of text, JSONL or Gzipped files.
+///
+/// # Arguments
+/// * `paths` - The corpus files, read with `io::fs::files::iter_documents`
+/// * `field` - The JSON field holding the text, e.g., "text" for JSONL files
+/// * `config` - The training configuration
+///
+/// # Returns
+/// The trained tokenizer
+pub fn train_tokenizer(
+    paths: &[String],
+    field: Option<&str>,
+    config: &TrainingConfig,
+) -> Result<Tokenizer> {
+    let (model, mut trainer) = get_model_and_trainer(config)?;
+    let (pre_tokenizer, decoder) = get_pre_tokenizer_and_decoder(config);
+
+    let mut tokenizer = Tokenizer::new(model);
+    tokenizer
+        .with_normalizer(get_normalizer(config)?)
+        .with_pre_tokenizer(Some(pre_tokenizer))
+        .with_decoder(Some(decoder));
+
+    // open every file up front so that missing files fail before training starts
+    let documents = paths
+        .iter()
+        .map(|path| iter_documents(path, field))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    // the trainer consumes a plain iterator, so read errors stop it and are reported afterwards
+    let error: Arc<Mutex<Option<io::Error>>> = Arc::new(Mutex::new(None));
+    let error_slot = Arc::clone(&error);
+    let sequences = documents
+        .into_iter()
+        .flatten()
+        .map_while(move |document| match document {
+            Ok(text) => Some(text),
+            Err(e) => {
+                *error_slot.lock().unwrap() = Some(e);
+                None
+            }
+        });
+
+    tokenizer.train(&mut trainer, sequences)?;
+
+    if let Some(e) = error.lock().unwrap().take() {
+        return Err(e.into());
+    }
+    Ok(tokenizer)
+}
+
+/// Trains a tokenizer and saves it as a `tokenizer.json` file.
+///
+/// The saved file can be loaded by path with `encode_str` or `load_tokenizer_from_file`; a
+/// tokenizer already cached under the path is dropped, so retraining takes effect.
+///
+/// # Arguments
+/// * `paths` - The corpus files
+/// * `field` - The JSON field holding the text, e.g., "text" for JSONL files
+/// * `config` - The training configuration
+/// * `output_path` - The path to save the tokenizer to
+///
+/// # Returns
+/// An error if training or saving fails
+pub fn train_tokenizer_to_file(
+    paths: &[String],
+    field: Option<&str>,
+    config: &TrainingConfig,
+    output_path: &str,
+) -> Result<()> {
+    let tokenizer = train_tokenizer(paths, field, config)?;
+    tokenizer.save(output_path, true)?;
+    evict_tokenizer(output_path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algos::tokenizers::tokenizers::{decode_str, encode_str};
+
+    fn get_resource_path(name: &str) -> String {
+        format!("{}/resources/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    fn get_output_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("alea-preprocess-{}-{}", std::process::id(), name))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[test]
+    fn test_parse_options() {
+        assert_eq!(model_type_from_string("BPE"), Some(TokenizerModelType::Bpe));
+        assert_eq!(
+            pre_tokenization_from_string("metaspace"),
+            Some(PreTokenization::Metaspace)
+        );
+        assert_eq!(
+            normalization_form_from_string("nfkc"),
+            Some(NormalizationForm::Nfkc)
+        );
+        assert_eq!(model_type_from_string("wordpiece"), None);
+    }
+
+    #[test]
+    fn test_train_bpe_tokenizer() {
+        let config = TrainingConfig {
+            vocab_size: 1000,
+            special_tokens: vec!["<|endoftext|>".to_string()],
+            ..Default::default()
+        };
+        let output_path = get_output_path("bpe.json");
+        train_tokenizer_to_file(
+            &[get_resource_path("usc.100.jsonl.gz")],
+            Some("text"),
+            &config,
+            &output_path,
+        )
+        .unwrap();
+
+        let text = "§ 101. Definitions of the United States Code";
+        let ids = encode_str(&output_path, text).unwrap();
+        assert!(!ids.is_empty());
+        assert!(ids.len() < text.len());
+        assert_eq!(decode_str(&output_path, ids).unwrap(), text);
+        assert_eq!(encode_str(&output_path, "<|endoftext|>").unwrap(), vec![0]);
+
+        std::fs::remove_file(output_path).unwrap();
+    }
+
+    #[test]
+    fn test_retrain_tokenizer_to_file() {
+        let output_path = get_output_path("retrain.json");
+        for special_tokens in [vec!["<|endoftext|>"], vec!["<|pad|>", "<|endoftext|>"]] {
+            let config = TrainingConfig {
+                vocab_size: 300,
+                special_tokens: special_tokens.iter().map(|t| t.to_string()).collect(),
+                ..Default::default()
+            };
+            train_tokenizer_to_file(
+                &[get_resource_path("usc.100.jsonl")],
+                Some("text"),
+                &config,
+                &output_path,
+            )
+            .unwrap();
+
+            let expected = vec![special_tokens.len() as u32 - 1];
+            assert_eq!(encode_str(&output_path, "<|endoftext|>").unwrap(), expected);
+        }
+
+        std::fs::remove_file(output_path).unwrap();
+    }
+
+    #[test]
+    fn test_train_unigram_tokenizer() {
+        let config = TrainingConfig {
+            model_type: TokenizerModelType::Unigram,
+            vocab_size: 500,
+            unk_token: Some("<unk>".to_string()),
+            pre_tokenization: PreTokenization::Metaspace,
+            normalization: Some(NormalizationForm::Nfkc),
+            lowercase: true,
+            ..Default::default()
+        };
+        let tokenizer =
+            train_tokenizer(&[get_resource_path("usc.100.jsonl")], Some("text"), &config).unwrap();
+
+        assert!(tokenizer.get_vocab_size(true) <= 500);
+        assert_eq!(tokenizer.token_to_id("<unk>"), Some(0));
+        let encoding = tokenizer.encode("The United States", false).unwrap();
+        assert_eq!(
+            tokenizer.decode(encoding.get_ids(), true).unwrap(),
+            "the united states"
+        );
+    }
+
+    #[test]
+    fn test_train_unigram_whitespace_tokenizer() {
+        let config = TrainingConfig {
+            model_type: TokenizerModelType::Unigram,
+            vocab_size: 500,
+            unk_token: Some("<unk>".to_string()),
+            pre_tokenization: PreTokenization::Whitespace,
+            ..Default::default()
+        };
+        let tokenizer =
+            train_tokenizer(&[get_resource_path("usc.100.jsonl")], Some("text"), &config).unwrap();
+
+        let text = "Definitions of the United States Code";
+        let encoding = tokenizer.encode(text, false).unwrap();
+        assert!(encoding.get_ids().len() > 6);
+        assert_eq!(tokenizer.decode(encoding.get_ids(), true).unwrap(), text);
+    }
+
+    #[test]
+    fn test_clean_text_normalizer() {
+        use crate::algos::unicode::normalizations::normalize_whitespace;
+        use tokenizers::{NormalizedString, Normalizer};
+
+        let config = TrainingConfig {
+            normalization: None,
+            ..Default::default()
+        };
+        let normalizer = get_normalizer(&config).unwrap().unwrap();
+        for text in [
+            "  Hello\u{00A0}\u{2003} world \r\n\u{200B}next\u{200D}\u{202E}line\x07  ",
+            "a\r\u{FEFF}\nb \x00\t\u{0085}c\u{2028}\u{3000}d",
+        ] {
+            let mut normalized = NormalizedString::from(text);
+            normalizer.normalize(&mut normalized).unwrap();
+            assert_eq!(
+                normalized.get(),
+                normalize_whitespace(text, &WhitespaceConfig::default()).text
+            );
+        }
+
+        let config = TrainingConfig {
+            clean_text: false,
+            normalization: None,
+            ..Default::default()
+        };
+        assert!(get_normalizer(&config).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_train_tokenizer_errors() {
+        let config = TrainingConfig::default();
+        assert!(train_tokenizer(&["/does/not/exist.txt".to_string()], None, &config).is_err());
+        assert!(train_tokenizer(
+            &[get_resource_path("hello-world.txt")],
+            Some("text"),
+            &config
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,373 @@
+/// Text repair for common encoding and escaping errors, in the spirit of Python's ftfy.
+///
+/// Text extracted from older EDGAR filings or returned by Tika frequently contains UTF-8
+/// that was decoded as Latin-1 or Windows-1252 ("mojibake"), e.g., "cafÃ©" or "donâ€™t",
+/// as well as HTML entities that were escaped more than once, e.g., "&amp;amp;".
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    static ref MULTIPLY_ESCAPED_ENTITY: Regex =
+        Regex::new(r"&amp;((?:amp;)*)(#[0-9]+;|#[xX][0-9a-fA-F]+;|[A-Za-z][A-Za-z0-9]*;)").unwrap();
+}
+
+/// Maximum number of mojibake passes; each pass undoes one layer of mis-decoding.
+const MAX_MOJIBAKE_PASSES: usize = 3;
+
+/// Windows-1252 characters for bytes 0x80-0x9F; None marks bytes that are undefined in
+/// Windows-1252 and which most decoders pass through as the C1 control with the same value.
+const WINDOWS_1252_HIGH: [Option<char>; 32] = [
+    Some('\u{20AC}'),
+    None,
+    Some('\u{201A}'),
+    Some('\u{0192}'),
+    Some('\u{201E}'),
+    Some('\u{2026}'),
+    Some('\u{2020}'),
+    Some('\u{2021}'),
+    Some('\u{02C6}'),
+    Some('\u{2030}'),
+    Some('\u{0160}'),
+    Some('\u{2039}'),
+    Some('\u{0152}'),
+    None,
+    Some('\u{017D}'),
+    None,
+    None,
+    Some('\u{2018}'),
+    Some('\u{2019}'),
+    Some('\u{201C}'),
+    Some('\u{201D}'),
+    Some('\u{2022}'),
+    Some('\u{2013}'),
+    Some('\u{2014}'),
+    Some('\u{02DC}'),
+    Some('\u{2122}'),
+    Some('\u{0161}'),
+    Some('\u{203A}'),
+    Some('\u{0153}'),
+    None,
+    Some('\u{017E}'),
+    Some('\u{0178}'),
+];
+
+#[derive(Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum TextFix {
+    Mojibake,
+    HtmlEntities,
+    CurlyQuotes,
+    Ligatures,
+}
+
+pub fn text_fix_to_string(fix: TextFix) -> &'static str {
+    match fix {
+        TextFix::Mojibake => "mojibake",
+        TextFix::HtmlEntities => "html_entities",
+        TextFix::CurlyQuotes => "curly_quotes",
+        TextFix::Ligatures => "ligatures",
+    }
+}
+
+/// Configuration for `fix_text`.
+///
+/// By default, mojibake and HTML entities are repaired, while the lossy quote and ligature
+/// normalizations are left off.
+#[derive(Debug, Clone, Copy)]
+pub struct TextFixConfig {
+    pub fix_mojibake: bool,
+    pub fix_html_entities: bool,
+    pub uncurl_quotes: bool,
+    pub fix_ligatures: bool,
+}
+
+impl TextFixConfig {
+    pub fn new(
+        fix_mojibake: bool,
+        fix_html_entities: bool,
+        uncurl_quotes: bool,
+        fix_ligatures: bool,
+    ) -> TextFixConfig {
+        TextFixConfig {
+            fix_mojibake,
+            fix_html_entities,
+            uncurl_quotes,
+            fix_ligatures,
+        }
+    }
+}
+
+impl Default for TextFixConfig {
+    fn default() -> Self {
+        TextFixConfig::new(true, true, false, false)
+    }
+}
+
+/// Returns the single byte a character would have been decoded from under Windows-1252,
+/// falling back to Latin-1 for the bytes that Windows-1252 leaves undefined.
+fn char_to_single_byte(c: char) -> Option<u8> {
+    let code = c as u32;
+    if code < 0x80 || (0xA0..=0xFF).contains(&code) {
+        return Some(code as u8);
+    }
+    if let Some(index) = WINDOWS_1252_HIGH.iter().position(|&x| x == Some(c)) {
+        return Some(0x80 + index as u8);
+    }
+    if (0x80..=0x9F).contains(&code) && WINDOWS_1252_HIGH[(code - 0x80) as usize].is_none() {
+        return Some(code as u8);
+    }
+    None
+}
+
+/// Returns the expected length of a UTF-8 sequence starting with the given lead byte.
+fn utf8_sequence_length(lead: u8) -> Option<usize> {
+    match lead {
+        0xC2..=0xDF => Some(2),
+        0xE0..=0xEF => Some(3),
+        0xF0..=0xF4 => Some(4),
+        _ => None,
+    }
+}
+
+/// Returns true if a two-byte sequence is plausible mojibake rather than legitimate text.
+///
+/// Every three- and four-byte sequence is accepted, but a two-byte sequence is just an
+/// accented capital followed by a symbol, e.g., "É»", which also occurs in real text. Those
+/// are only repaired for the Latin-1 supplement, Latin Extended-A and Cyrillic ranges, where
+/// mis-decoding is by far the most common, or when the second byte is a C1/Windows-1252
+/// punctuation byte that rarely follows a letter.
+fn is_plausible_two_byte_sequence(lead: u8, continuation: u8) -> bool {
+    matches!(lead, 0xC2 | 0xC3 | 0xC5 | 0xD0 | 0xD1) || continuation < 0xA0
+}
+
+/// Repairs one layer of UTF-8 that was mis-decoded as Windows-1252 or Latin-1.
+fn fix_mojibake_pass(buffer: &str) -> String {
+    let chars: Vec<char> = buffer.chars().collect();
+    let mut output = String::with_capacity(buffer.len());
+
+    let mut i = 0;
+    while i < chars.len() {
+        let decoded = char_to_single_byte(chars[i])
+            .and_then(utf8_sequence_length)
+            .filter(|&length| i + length <= chars.len())
+            .and_then(|length| {
+                let bytes: Vec<u8> = chars[i..i + length]
+                    .iter()
+                    .map(|&c| char_to_single_byte(c))
+                    .collect::<Option<Vec<u8>>>()?;
+                if length == 2 && !is_plausible_two_byte_sequence(bytes[0], bytes[1]) {
+                    return None;
+                }
+                let decoded = std::str::from_utf8(&bytes).ok()?.chars().next()?;
+                Some((decoded, length))
+            });
+
+        match decoded {
+            Some((c, length)) => {
+                output.push(c);
+                i += length;
+            }
+            None => {
+                output.push(chars[i]);
+                i += 1;
+            }
+        }
+    }
+
+    output
+}
+
+/// Repairs UTF-8 text that was decoded as Windows-1252 or Latin-1, possibly more than once.
+///
+/// # Arguments
+/// * `buffer` - The input string to repair
+///
+/// # Returns
+/// The repaired string
+pub fn fix_mojibake(buffer: &str) -> String {
+    let mut output = buffer.to_string();
+    for _ in 0..MAX_MOJIBAKE_PASSES {
+        let fixed = fix_mojibake_pass(&output);
+        if fixed == output {
+            break;
+        }
+        output = fixed;
+    }
+    output
+}
+
+/// Returns true if the string contains repairable mojibake.
+///
+/// # Arguments
+/// * `buffer` - The input string to check
+///
+/// # Returns
+/// True if `fix_mojibake` would change the string
+pub fn has_mojibake(buffer: &str) -> bool {
+    // fast path: mojibake always contains a non-ASCII character
+    !buffer.is_ascii() && fix_mojibake_pass(buffer) != buffer
+}
+
+/// Collapses entities that were escaped more than once, e.g., "&amp;amp;lt;", and then
+/// decodes all HTML entities.
+///
+/// # Arguments
+/// * `buffer` - The input string to repair
+///
+/// # Returns
+/// The string with HTML entities decoded
+pub fn fix_html_entities(buffer: &str) -> String {
+    let collapsed = MULTIPLY_ESCAPED_ENTITY.replace_all(buffer, "&$2");
+    html_escape::decode_html_entities(&collapsed).to_string()
+}
+
+/// Replaces curly single and double quotes with their ASCII equivalents.
+///
+/// # Arguments
+/// * `buffer` - The input string
+///
+/// # Returns
+/// The string with straight quotes
+pub fn uncurl_quotes(buffer: &str) -> String {
+    buffer
+        .chars()
+        .map(|c| match c {
+            '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => '\'',
+            '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => '"',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Expands Latin typographic ligatures, e.g., "ﬁ" to "fi".
+///
+/// # Arguments
+/// * `buffer` - The input string
+///
+/// # Returns
+/// The string with ligatures expanded
+pub fn fix_ligatures(buffer: &str) -> String {
+    let mut output = String::with_capacity(buffer.len());
+    for c in buffer.chars() {
+        match c {
+            '\u{FB00}' => output.push_str("ff"),
+            '\u{FB01}' => output.push_str("fi"),
+            '\u{FB02}' => output.push_str("fl"),
+            '\u{FB03}' => output.push_str("ffi"),
+            '\u{FB04}' => output.push_str("ffl"),
+            '\u{FB05}' | '\u{FB06}' => output.push_str("st"),
+            '\u{0132}' => output.push_str("IJ"),
+            '\u{0133}' => output.push_str("ij"),
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
+/// Applies the configured text fixes and reports which of them changed the text.
+///
+/// Fixes are applied in a fixed order: mojibake, HTML entities, quotes, then ligatures.
+/// Mojibake is repaired before entities are decoded, so characters that were deliberately
+/// entity-encoded are never reinterpreted as mis-decoded bytes.
+///
+/// # Arguments
+/// * `buffer` - The input string to repair
+/// * `config` - The fixes to apply
+///
+/// # Returns
+/// A tuple of the repaired string and the list of fixes that changed it
+pub fn fix_text(buffer: &str, config: &TextFixConfig) -> (String, Vec<TextFix>) {
+    let mut output = buffer.to_string();
+    let mut fixes = Vec::new();
+
+    let mut apply = |enabled: bool, fix: TextFix, function: fn(&str) -> String| {
+        if enabled {
+            let fixed = function(&output);
+            if fixed != output {
+                fixes.push(fix);
+                output = fixed;
+            }
+        }
+    };
+
+    apply(config.fix_mojibake, TextFix::Mojibake, fix_mojibake);
+    apply(
+        config.fix_html_entities,
+        TextFix::HtmlEntities,
+        fix_html_entities,
+    );
+    apply(config.uncurl_quotes, TextFix::CurlyQuotes, uncurl_quotes);
+    apply(config.fix_ligatures, TextFix::Ligatures, fix_ligatures);
+
+    (output, fixes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fix_mojibake_latin1() {
+        assert_eq!(fix_mojibake("cafÃ©"), "café");
+        assert_eq!(fix_mojibake("naÃ¯ve faÃ§ade"), "naïve façade");
+        assert_eq!(fix_mojibake("100Â°F"), "100°F");
+        assert_eq!(fix_mojibake("ÐŸÑ€Ð¸Ð²ÐµÑ‚"), "Привет");
+    }
+
+    #[test]
+    fn test_fix_mojibake_windows_1252() {
+        assert_eq!(fix_mojibake("donâ€™t"), "don’t");
+        assert_eq!(fix_mojibake("â€œquotedâ€\u{9d}"), "“quoted”");
+        assert_eq!(fix_mojibake("Section 1 â€“ Scope"), "Section 1 – Scope");
+    }
+
+    #[test]
+    fn test_fix_mojibake_double() {
+        assert_eq!(fix_mojibake("cafÃƒÂ©"), "café");
+    }
+
+    #[test]
+    fn test_fix_mojibake_clean_text() {
+        for text in ["café", "Hello, world!", "日本語", "CAFÉ»", "Привет"] {
+            assert_eq!(fix_mojibake(text), text);
+            assert!(!has_mojibake(text));
+        }
+        assert!(has_mojibake("cafÃ©"));
+    }
+
+    #[test]
+    fn test_fix_html_entities() {
+        assert_eq!(fix_html_entities("AT&amp;amp;T"), "AT&T");
+        assert_eq!(fix_html_entities("&amp;lt;p&amp;gt;"), "<p>");
+        assert_eq!(fix_html_entities("&amp;amp;#8217;"), "’");
+        assert_eq!(fix_html_entities("Q&amp;A"), "Q&A");
+        assert_eq!(fix_html_entities("Q & A"), "Q & A");
+    }
+
+    #[test]
+    fn test_uncurl_quotes() {
+        assert_eq!(uncurl_quotes("“don’t”"), "\"don't\"");
+    }
+
+    #[test]
+    fn test_fix_ligatures() {
+        assert_eq!(fix_ligatures("ﬁnancial ﬂow"), "financial flow");
+    }
+
+    #[test]
+    fn test_fix_text() {
+        let (text, fixes) = fix_text("donâ€™t &amp;amp; ﬁx", &TextFixConfig::default());
+        assert_eq!(text, "don’t & ﬁx");
+        assert_eq!(fixes, vec![TextFix::Mojibake, TextFix::HtmlEntities]);
+
+        let (text, fixes) = fix_text("donâ€™t ﬁx", &TextFixConfig::new(true, true, true, true));
+        assert_eq!(text, "don't fix");
+        assert_eq!(
+            fixes,
+            vec![TextFix::Mojibake, TextFix::CurlyQuotes, TextFix::Ligatures]
+        );
+
+        let (text, fixes) = fix_text("clean text", &TextFixConfig::default());
+        assert_eq!(text, "clean text");
+        assert!(fixes.is_empty());
+    }
+}
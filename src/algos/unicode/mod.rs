@@ -1,3 +1,5 @@
 pub mod categories;
+pub mod fixes;
 pub mod normalizations;
+pub mod scripts;
 pub mod segmentations;
@@ -75,6 +75,262 @@ pub fn nfkc_printable_str(buffer: &str) -> String {
         .collect()
 }
 
+/// Configuration for `normalize_whitespace`.
+///
+/// By default, every normalization is enabled.
+#[derive(Debug, Clone, Copy)]
+pub struct WhitespaceConfig {
+    pub collapse_whitespace: bool,
+    pub unify_spaces: bool,
+    pub strip_zero_width: bool,
+    pub strip_bidi_controls: bool,
+    pub strip_control_chars: bool,
+    pub normalize_newlines: bool,
+    pub trim: bool,
+}
+
+impl WhitespaceConfig {
+    pub fn new(
+        collapse_whitespace: bool,
+        unify_spaces: bool,
+        strip_zero_width: bool,
+        strip_bidi_controls: bool,
+        strip_control_chars: bool,
+        normalize_newlines: bool,
+        trim: bool,
+    ) -> WhitespaceConfig {
+        WhitespaceConfig {
+            collapse_whitespace,
+            unify_spaces,
+            strip_zero_width,
+            strip_bidi_controls,
+            strip_control_chars,
+            normalize_newlines,
+            trim,
+        }
+    }
+}
+
+impl Default for WhitespaceConfig {
+    fn default() -> Self {
+        WhitespaceConfig::new(true, true, true, true, true, true, true)
+    }
+}
+
+/// A normalized string with a byte offset map back to the original string.
+///
+/// `offsets` has one entry per byte of `text` plus a final entry, so that `offsets[i]` is the
+/// byte offset in the original string of the character that produced byte `i`, and
+/// `offsets[text.len()]` is the end of the last original character that was consumed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalizedText {
+    pub text: String,
+    pub offsets: Vec<usize>,
+}
+
+impl NormalizedText {
+    fn push(&mut self, c: char, original_offset: usize) {
+        self.text.push(c);
+        self.offsets
+            .extend(std::iter::repeat_n(original_offset, c.len_utf8()));
+    }
+
+    /// Returns the offset map in characters rather than bytes.
+    ///
+    /// Arguments:
+    /// - `original` - The string that was normalized.
+    ///
+    /// Returns:
+    /// - One original character offset per normalized character, plus a final end offset.
+    pub fn char_offsets(&self, original: &str) -> Vec<usize> {
+        // byte offset -> char offset in the original, including the end of the string
+        let mut original_chars = vec![0; original.len() + 1];
+        for (char_index, (byte_index, _)) in original.char_indices().enumerate() {
+            original_chars[byte_index] = char_index;
+        }
+        original_chars[original.len()] = original.chars().count();
+
+        self.text
+            .char_indices()
+            .map(|(byte_index, _)| original_chars[self.offsets[byte_index]])
+            .chain(std::iter::once(
+                original_chars[self.offsets[self.text.len()]],
+            ))
+            .collect()
+    }
+}
+
+/// Returns true for exotic space characters that should be unified to U+0020.
+fn is_exotic_space(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00A0}' | '\u{1680}' | '\u{2000}'..='\u{200B}' | '\u{202F}' | '\u{205F}' | '\u{3000}'
+    )
+}
+
+/// Returns true for invisible zero-width characters, including the soft hyphen and BOM.
+fn is_zero_width(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00AD}' | '\u{180E}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{FEFF}'
+    )
+}
+
+/// Returns true for bidirectional formatting controls.
+fn is_bidi_control(c: char) -> bool {
+    matches!(
+        c,
+        '\u{061C}' | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+/// Returns true for characters that end a line.
+fn is_newline(c: char) -> bool {
+    matches!(
+        c,
+        '\n' | '\r' | '\u{000B}' | '\u{000C}' | '\u{0085}' | '\u{2028}' | '\u{2029}'
+    )
+}
+
+/// Regex patterns for the characters handled by `normalize_whitespace`, kept in step with the
+/// predicates above.
+const NEWLINE_PATTERN: &str = r"\r\n|[\r\x{B}\x{C}\x{85}\x{2028}\x{2029}]";
+const ZERO_WIDTH_PATTERN: &str = r"[\x{AD}\x{180E}\x{200C}\x{200D}\x{2060}\x{FEFF}]";
+const BIDI_CONTROL_PATTERN: &str = r"[\x{61C}\x{200E}\x{200F}\x{202A}-\x{202E}\x{2066}-\x{2069}]";
+const EXOTIC_SPACE_PATTERN: &str = r"[\x{A0}\x{1680}\x{2000}-\x{200B}\x{202F}\x{205F}\x{3000}]";
+const CONTROL_CHAR_PATTERN: &str = r"[\x{0}-\x{8}\x{E}-\x{1F}\x{7F}-\x{84}\x{86}-\x{9F}]";
+
+/// Returns regex replacements that produce the same text as `normalize_whitespace`.
+///
+/// This is for pipelines that can only store pattern rules, such as the normalizer of a saved
+/// tokenizer. The replacements must be applied in order, and trimming, which is not a
+/// replacement, is left to the caller.
+///
+/// Arguments:
+/// - `config` - The normalizations to apply.
+///
+/// Returns:
+/// - The (pattern, replacement) pairs.
+pub fn get_whitespace_replacements(config: &WhitespaceConfig) -> Vec<(&'static str, &'static str)> {
+    let mut replacements = Vec::new();
+    // newlines come first, so that a \r\n pair split by a stripped character stays two newlines
+    if config.normalize_newlines {
+        replacements.push((NEWLINE_PATTERN, "\n"));
+    }
+    if config.strip_zero_width {
+        replacements.push((ZERO_WIDTH_PATTERN, ""));
+    }
+    if config.strip_bidi_controls {
+        replacements.push((BIDI_CONTROL_PATTERN, ""));
+    }
+    if config.unify_spaces {
+        replacements.push((EXOTIC_SPACE_PATTERN, " "));
+    }
+    if config.strip_control_chars {
+        replacements.push((CONTROL_CHAR_PATTERN, ""));
+    }
+    if config.collapse_whitespace {
+        replacements.push((r"[ \t]+\n", "\n"));
+        replacements.push((r"[ \t]+", " "));
+    }
+    replacements
+}
+
+/// Returns a buffer with normalized whitespace and control characters, along with an offset map
+/// from the normalized string back to the original.
+///
+/// Unlike `nfkc_printable_str`, this keeps track of where each output character came from, so
+/// that annotations computed on the normalized text can be projected onto the source.
+///
+/// Arguments:
+/// - `buffer` - A string.
+/// - `config` - The normalizations to apply.
+///
+/// Returns:
+/// - The normalized string and its offset map.
+pub fn normalize_whitespace(buffer: &str, config: &WhitespaceConfig) -> NormalizedText {
+    let mut output = NormalizedText {
+        text: String::with_capacity(buffer.len()),
+        offsets: Vec::with_capacity(buffer.len() + 1),
+    };
+
+    // offset of a pending collapsed space, emitted only if followed by a non-newline character
+    let mut pending_space: Option<usize> = None;
+    let mut end = 0;
+    let mut chars = buffer.char_indices().peekable();
+
+    while let Some((offset, mut c)) = chars.next() {
+        if (config.strip_zero_width && is_zero_width(c))
+            || (config.strip_bidi_controls && is_bidi_control(c))
+        {
+            continue;
+        }
+
+        if config.normalize_newlines && is_newline(c) {
+            // consume the \n of a \r\n pair as part of the same newline
+            if c == '\r' {
+                if let Some(&(_, '\n')) = chars.peek() {
+                    chars.next();
+                }
+            }
+            c = '\n';
+        } else if config.unify_spaces && is_exotic_space(c) {
+            c = ' ';
+        } else if config.strip_control_chars
+            && c != '\t'
+            && !is_newline(c)
+            && char_to_category(c) == UnicodeCategory::Cc
+        {
+            continue;
+        }
+
+        let next_end = chars.peek().map_or(buffer.len(), |&(i, _)| i);
+
+        if config.collapse_whitespace && (c == ' ' || c == '\t') {
+            if pending_space.is_none() {
+                pending_space = Some(offset);
+            }
+            continue;
+        }
+
+        // when trimming, drop any whitespace at the start of the text
+        let at_start = config.trim && output.text.is_empty();
+        if let Some(space_offset) = pending_space.take() {
+            // drop collapsed spaces before a newline
+            if !(c == '\n' || at_start) {
+                output.push(' ', space_offset);
+            }
+        }
+        if at_start && c.is_whitespace() {
+            continue;
+        }
+
+        output.push(c, offset);
+        end = next_end;
+    }
+
+    // trailing collapsed spaces are kept unless trimming
+    if let Some(space_offset) = pending_space {
+        if !config.trim {
+            output.push(' ', space_offset);
+            end = buffer.len();
+        }
+    }
+
+    if config.trim {
+        // the end now falls at the start of the first removed character
+        let trimmed_length = output.text.trim_end().len();
+        if trimmed_length < output.text.len() {
+            end = output.offsets[trimmed_length];
+            output.text.truncate(trimmed_length);
+            output.offsets.truncate(trimmed_length);
+        }
+    }
+
+    output.offsets.push(end);
+    output
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -113,4 +369,87 @@ mod tests {
         let result = nfkc_printable_str(buffer);
         assert_eq!(result, "recreation\nand parks");
     }
+
+    #[test]
+    fn test_normalize_whitespace() {
+        let buffer = "  Hello\u{00A0}\u{2003} world \r\n\u{200B}next\u{200D}\u{202E}line\x07  ";
+        let result = normalize_whitespace(buffer, &WhitespaceConfig::default());
+        assert_eq!(result.text, "Hello world\n nextline");
+        assert_eq!(result.offsets.len(), result.text.len() + 1);
+
+        // every output character maps back to an equivalent or replaced source character
+        assert_eq!(&buffer[result.offsets[0]..result.offsets[0] + 5], "Hello");
+        assert_eq!(result.offsets[5], 7);
+        assert_eq!(&buffer[result.offsets[6]..result.offsets[6] + 5], "world");
+        assert_eq!(&buffer[result.offsets[11]..result.offsets[11] + 2], "\r\n");
+        assert_eq!(
+            buffer[result.offsets[12]..].chars().next(),
+            Some('\u{200B}')
+        );
+    }
+
+    #[test]
+    fn test_normalize_whitespace_offsets() {
+        let buffer = "The\t\tquick \u{200E}brown fox";
+        let result = normalize_whitespace(buffer, &WhitespaceConfig::default());
+        assert_eq!(result.text, "The quick brown fox");
+
+        let start = result.text.find("brown").unwrap();
+        let (original_start, original_end) = (result.offsets[start], result.offsets[start + 5]);
+        assert_eq!(&buffer[original_start..original_end], "brown");
+
+        let (original_start, original_end) = (result.offsets[0], result.offsets[result.text.len()]);
+        assert_eq!(&buffer[original_start..original_end], buffer);
+
+        let char_offsets = result.char_offsets(buffer);
+        assert_eq!(char_offsets.len(), result.text.chars().count() + 1);
+        assert_eq!(char_offsets[10], 12);
+        assert_eq!(char_offsets.last(), Some(&buffer.chars().count()));
+    }
+
+    #[test]
+    fn test_get_whitespace_replacements() {
+        let buffers = [
+            "  Hello\u{00A0}\u{2003} world \r\n\u{200B}next\u{200D}\u{202E}line\x07  ",
+            "The\t\tquick \u{200E}brown fox",
+            "a\r\u{FEFF}\nb \x00\t\u{0085}c\u{2028}\u{3000}d",
+        ];
+        let configs = [
+            WhitespaceConfig::default(),
+            WhitespaceConfig::new(false, true, true, false, true, false, false),
+            WhitespaceConfig::new(true, false, false, true, false, true, false),
+        ];
+        for config in configs.iter() {
+            for buffer in buffers {
+                let mut text = buffer.to_string();
+                for (pattern, replacement) in get_whitespace_replacements(config) {
+                    text = regex::Regex::new(pattern)
+                        .unwrap()
+                        .replace_all(&text, replacement)
+                        .to_string();
+                }
+                if config.trim {
+                    text = text.trim().to_string();
+                }
+                assert_eq!(text, normalize_whitespace(buffer, config).text);
+            }
+        }
+    }
+
+    #[test]
+    fn test_normalize_whitespace_config() {
+        let config = WhitespaceConfig::new(false, true, true, false, true, false, false);
+        let buffer = " a\u{00A0}\u{00A0}b\u{200F}\r\n";
+        let result = normalize_whitespace(buffer, &config);
+        assert_eq!(result.text, " a  b\u{200F}\r\n");
+        assert_eq!(result.offsets.last(), Some(&buffer.len()));
+
+        let result = normalize_whitespace("", &WhitespaceConfig::default());
+        assert_eq!(result.text, "");
+        assert_eq!(result.offsets, vec![0]);
+
+        let result = normalize_whitespace(" \n\t ", &WhitespaceConfig::default());
+        assert_eq!(result.text, "");
+        assert_eq!(result.offsets.len(), 1);
+    }
 }
@@ -0,0 +1,296 @@
+use icu::properties::names::PropertyEnumToValueNameLinearMapperBorrowed;
+use icu::properties::{maps, Script};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+static SCRIPT_LONG_NAMES: PropertyEnumToValueNameLinearMapperBorrowed<'static, Script> =
+    Script::enum_to_long_name_mapper();
+
+#[derive(Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum UnicodeScriptGroup {
+    Latin,
+    Cyrillic,
+    Greek,
+    Arabic,
+    Hebrew,
+    Cjk, // Han, Hiragana, Katakana, Hangul, Bopomofo
+    Indic,
+    Other,
+    Common, // Common, Inherited, Unknown
+}
+
+/// Returns the Unicode script for a character.
+///
+/// # Arguments
+/// * `ch` - A character.
+///
+/// # Returns
+/// The Unicode script property value for the character.
+pub fn char_to_script(ch: char) -> Script {
+    maps::script().get(ch)
+}
+
+/// Returns true if the script carries no script identity of its own, i.e.,
+/// Common (punctuation, digits, symbols), Inherited (combining marks) or Unknown.
+pub fn is_neutral_script(script: Script) -> bool {
+    script == Script::Common || script == Script::Inherited || script == Script::Unknown
+}
+
+pub fn script_to_group(script: Script) -> UnicodeScriptGroup {
+    match script {
+        Script::Latin => UnicodeScriptGroup::Latin,
+        Script::Cyrillic => UnicodeScriptGroup::Cyrillic,
+        Script::Greek => UnicodeScriptGroup::Greek,
+        Script::Arabic | Script::Syriac | Script::Thaana => UnicodeScriptGroup::Arabic,
+        Script::Hebrew => UnicodeScriptGroup::Hebrew,
+        Script::Han | Script::Hiragana | Script::Katakana | Script::Hangul | Script::Bopomofo => {
+            UnicodeScriptGroup::Cjk
+        }
+        Script::Devanagari
+        | Script::Bengali
+        | Script::Gurmukhi
+        | Script::Gujarati
+        | Script::Oriya
+        | Script::Tamil
+        | Script::Telugu
+        | Script::Kannada
+        | Script::Malayalam
+        | Script::Sinhala => UnicodeScriptGroup::Indic,
+        Script::Common | Script::Inherited | Script::Unknown => UnicodeScriptGroup::Common,
+        _ => UnicodeScriptGroup::Other,
+    }
+}
+
+pub fn char_to_script_group(ch: char) -> UnicodeScriptGroup {
+    script_to_group(char_to_script(ch))
+}
+
+/// Returns the long Unicode name for a script, e.g., "Latin" or "Han".
+pub fn script_to_string(script: Script) -> &'static str {
+    SCRIPT_LONG_NAMES.get(script).unwrap_or("Unknown")
+}
+
+pub fn script_group_to_string(group: UnicodeScriptGroup) -> &'static str {
+    match group {
+        UnicodeScriptGroup::Latin => "Latin",
+        UnicodeScriptGroup::Cyrillic => "Cyrillic",
+        UnicodeScriptGroup::Greek => "Greek",
+        UnicodeScriptGroup::Arabic => "Arabic",
+        UnicodeScriptGroup::Hebrew => "Hebrew",
+        UnicodeScriptGroup::Cjk => "CJK",
+        UnicodeScriptGroup::Indic => "Indic",
+        UnicodeScriptGroup::Other => "Other",
+        UnicodeScriptGroup::Common => "Common",
+    }
+}
+
+pub fn to_script_vector(text: &str) -> Vec<Script> {
+    text.chars()
+        .collect::<Vec<char>>()
+        .par_iter()
+        .map(|&c| char_to_script(c))
+        .collect()
+}
+
+pub fn to_script_group_vector(text: &str) -> Vec<UnicodeScriptGroup> {
+    text.chars()
+        .collect::<Vec<char>>()
+        .par_iter()
+        .map(|&c| char_to_script_group(c))
+        .collect()
+}
+
+/// Returns a histogram of scripts in the text, including neutral scripts.
+///
+/// # Arguments
+/// * `text` - A string.
+///
+/// # Returns
+/// A map from script to the number of characters in that script.
+pub fn get_script_counts(text: &str) -> HashMap<Script, usize> {
+    let mut counts = HashMap::new();
+    for c in text.chars() {
+        *counts.entry(char_to_script(c)).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Returns the fraction of script-bearing characters in each script.
+///
+/// Neutral characters (Common, Inherited, Unknown) are excluded from both the
+/// numerator and the denominator, so that digits and punctuation do not dilute the ratios.
+///
+/// # Arguments
+/// * `text` - A string.
+///
+/// # Returns
+/// A map from script to the fraction of script-bearing characters in that script.
+pub fn get_script_ratios(text: &str) -> HashMap<Script, f64> {
+    let counts: HashMap<Script, usize> = get_script_counts(text)
+        .into_iter()
+        .filter(|(script, _)| !is_neutral_script(*script))
+        .collect();
+    let total: usize = counts.values().sum();
+
+    counts
+        .into_iter()
+        .map(|(script, count)| (script, count as f64 / total as f64))
+        .collect()
+}
+
+/// Returns the fraction of script-bearing characters in each script group.
+///
+/// # Arguments
+/// * `text` - A string.
+///
+/// # Returns
+/// A map from script group to the fraction of script-bearing characters in that group.
+pub fn get_script_group_ratios(text: &str) -> HashMap<UnicodeScriptGroup, f64> {
+    let mut ratios = HashMap::new();
+    for (script, ratio) in get_script_ratios(text) {
+        *ratios.entry(script_to_group(script)).or_insert(0.0) += ratio;
+    }
+    ratios
+}
+
+/// Returns the most frequent script-bearing script in the text.
+///
+/// # Arguments
+/// * `text` - A string.
+///
+/// # Returns
+/// The dominant script, or None if the text has no script-bearing characters.
+pub fn get_dominant_script(text: &str) -> Option<Script> {
+    get_script_counts(text)
+        .into_iter()
+        .filter(|(script, _)| !is_neutral_script(*script))
+        // break ties on the script code so the result is deterministic
+        .max_by_key(|(script, count)| (*count, std::cmp::Reverse(script.0)))
+        .map(|(script, _)| script)
+}
+
+/// Splits the text into runs of characters in the same script.
+///
+/// Neutral characters (Common, Inherited, Unknown) are attached to the preceding run, or to the
+/// following run at the start of the text, so that spaces and punctuation do not break runs.
+///
+/// # Arguments
+/// * `text` - A string.
+///
+/// # Returns
+/// A vector of tuples, each containing the start index, end index, script, and the run as a String
+pub fn get_script_runs(text: &str) -> Vec<(usize, usize, Script, String)> {
+    let mut runs: Vec<(usize, usize, Script)> = Vec::new();
+
+    for (index, c) in text.char_indices() {
+        let script = char_to_script(c);
+        let end = index + c.len_utf8();
+        match runs.last_mut() {
+            Some(run) if run.2 == script || is_neutral_script(script) => run.1 = end,
+            Some(run) if is_neutral_script(run.2) => {
+                // leading neutral run takes the first real script
+                run.1 = end;
+                run.2 = script;
+            }
+            _ => runs.push((index, end, script)),
+        }
+    }
+
+    runs.into_iter()
+        .map(|(start, end, script)| (start, end, script, text[start..end].to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_char_to_script() {
+        assert_eq!(char_to_script('a'), Script::Latin);
+        assert_eq!(char_to_script('Ж'), Script::Cyrillic);
+        assert_eq!(char_to_script('木'), Script::Han);
+        assert_eq!(char_to_script('ب'), Script::Arabic);
+        assert_eq!(char_to_script('1'), Script::Common);
+        assert_eq!(char_to_script('\u{0300}'), Script::Inherited);
+    }
+
+    #[test]
+    fn test_script_to_group() {
+        assert_eq!(script_to_group(Script::Latin), UnicodeScriptGroup::Latin);
+        assert_eq!(script_to_group(Script::Hiragana), UnicodeScriptGroup::Cjk);
+        assert_eq!(script_to_group(Script::Hangul), UnicodeScriptGroup::Cjk);
+        assert_eq!(script_to_group(Script::Tamil), UnicodeScriptGroup::Indic);
+        assert_eq!(script_to_group(Script::Common), UnicodeScriptGroup::Common);
+        assert_eq!(script_to_group(Script::Thai), UnicodeScriptGroup::Other);
+    }
+
+    #[test]
+    fn test_script_to_string() {
+        assert_eq!(script_to_string(Script::Latin), "Latin");
+        assert_eq!(script_to_string(Script::Han), "Han");
+    }
+
+    #[test]
+    fn test_to_script_vector() {
+        let text = "Hi, 世界";
+        let scripts = to_script_vector(text);
+        assert_eq!(scripts.len(), text.chars().count());
+        assert_eq!(scripts[0], Script::Latin);
+        assert_eq!(scripts[2], Script::Common);
+        assert_eq!(scripts[4], Script::Han);
+    }
+
+    #[test]
+    fn test_get_script_counts() {
+        let counts = get_script_counts("abc 123 ЖЖ");
+        assert_eq!(counts[&Script::Latin], 3);
+        assert_eq!(counts[&Script::Common], 5);
+        assert_eq!(counts[&Script::Cyrillic], 2);
+    }
+
+    #[test]
+    fn test_get_script_ratios() {
+        let ratios = get_script_ratios("abc, 123 Ж");
+        assert_eq!(ratios.len(), 2);
+        assert_eq!(ratios[&Script::Latin], 0.75);
+        assert_eq!(ratios[&Script::Cyrillic], 0.25);
+
+        assert!(get_script_ratios("123 !?").is_empty());
+    }
+
+    #[test]
+    fn test_get_script_group_ratios() {
+        let ratios = get_script_group_ratios("日本語のテキスト");
+        assert_eq!(ratios.len(), 1);
+        assert!((ratios[&UnicodeScriptGroup::Cjk] - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_get_dominant_script() {
+        assert_eq!(get_dominant_script("Hello, мир"), Some(Script::Latin));
+        assert_eq!(get_dominant_script("Привет, world"), Some(Script::Cyrillic));
+        assert_eq!(get_dominant_script("123 ..."), None);
+        assert_eq!(get_dominant_script(""), None);
+    }
+
+    #[test]
+    fn test_get_script_runs() {
+        let text = "(Hello) мир, 世界!";
+        let runs = get_script_runs(text);
+        assert_eq!(
+            runs,
+            vec![
+                (0, 8, Script::Latin, "(Hello) ".to_string()),
+                (8, 16, Script::Cyrillic, "мир, ".to_string()),
+                (16, text.len(), Script::Han, "世界!".to_string()),
+            ]
+        );
+
+        assert_eq!(
+            get_script_runs("123"),
+            vec![(0, 3, Script::Common, "123".to_string())]
+        );
+        assert!(get_script_runs("").is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+// Crate error type
+
+// external imports
+use std::fmt;
+use std::io;
+use std::string::FromUtf8Error;
+
+// internal imports
+use crate::tasks::sequences::ContentError;
+
+/// Errors raised by the parsers, tokenizers and task helpers
+///
+/// Each variant maps to its own Python exception, so callers can tell a document that parsed
+/// to nothing apart from one that failed to parse.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Parse(String),
+    Tokenizer(String),
+    Tika(String),
+    Decode(String),
+    Config(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Parse(message) => write!(f, "failed to parse: {}", message),
+            Error::Tokenizer(message) => write!(f, "tokenizer error: {}", message),
+            Error::Tika(message) => write!(f, "tika request failed: {}", message),
+            Error::Decode(message) => write!(f, "failed to decode content: {}", message),
+            Error::Config(message) => write!(f, "invalid configuration: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<ContentError> for Error {
+    fn from(e: ContentError) -> Self {
+        Error::Decode(e.to_string())
+    }
+}
+
+impl From<FromUtf8Error> for Error {
+    fn from(e: FromUtf8Error) -> Self {
+        Error::Decode(e.to_string())
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Tika(e.to_string())
+    }
+}
+
+/// Task helpers return `tokenizers::Result` and box crate, content and JSON errors into it, so
+/// those are unboxed or mapped back to `Decode` and `Parse`; everything else is a tokenizer error.
+impl From<tokenizers::Error> for Error {
+    fn from(e: tokenizers::Error) -> Self {
+        let e = match e.downcast::<Error>() {
+            Ok(e) => return *e,
+            Err(e) => e,
+        };
+        if e.is::<ContentError>() || e.is::<FromUtf8Error>() {
+            Error::Decode(e.to_string())
+        } else if e.is::<serde_json::Error>() {
+            Error::Parse(e.to_string())
+        } else {
+            Error::Tokenizer(e.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::sequences::extract_content;
+
+    #[test]
+    fn test_from_tokenizers_error() {
+        let error: tokenizers::Error = extract_content("not base64!").unwrap_err().into();
+        assert!(matches!(Error::from(error), Error::Decode(_)));
+
+        let error: tokenizers::Error = "unknown token".into();
+        assert!(matches!(Error::from(error), Error::Tokenizer(_)));
+
+        let error: tokenizers::Error = Box::new(Error::Config("bad".to_string()));
+        assert!(matches!(Error::from(error), Error::Config(_)));
+    }
+
+    #[test]
+    fn test_display() {
+        let error = Error::Parse("no pages".to_string());
+        assert_eq!(error.to_string(), "failed to parse: no pages");
+    }
+}
@@ -0,0 +1,317 @@
+/// Character encoding detection and decoding for non-UTF-8 byte inputs.
+///
+/// Detection runs in order of reliability: byte order marks, UTF-8 validity, HTML meta
+/// charset declarations, and finally statistical detection for legacy encodings such as
+/// Windows-1252, Shift_JIS, EUC-KR or GB18030.
+use chardetng::{EncodingDetector, Iso2022JpDetection, Utf8Detection};
+use encoding_rs::{Encoding, UTF_8};
+use lazy_static::lazy_static;
+use regex::bytes::Regex;
+
+lazy_static! {
+    static ref META_CHARSET: Regex =
+        Regex::new(r#"(?i)<meta[^>]*?charset\s*=\s*["']?\s*([a-z0-9_:.\-]+)"#).unwrap();
+}
+
+/// Number of leading bytes scanned for an HTML meta charset declaration.
+const META_PRESCAN_SIZE: usize = 4096;
+
+/// Number of leading bytes checked for NUL bytes when deciding whether a buffer is text.
+const BINARY_PRESCAN_SIZE: usize = 8192;
+
+#[derive(Hash, Eq, PartialEq, Clone, Copy, Debug)]
+pub enum EncodingSource {
+    Bom,
+    Utf8,
+    HtmlMeta,
+    Statistical,
+}
+
+pub fn encoding_source_to_string(source: EncodingSource) -> &'static str {
+    match source {
+        EncodingSource::Bom => "bom",
+        EncodingSource::Utf8 => "utf8",
+        EncodingSource::HtmlMeta => "html_meta",
+        EncodingSource::Statistical => "statistical",
+    }
+}
+
+/// A detected encoding with a confidence between 0.0 and 1.0 and the evidence it came from.
+#[derive(Debug, Clone, Copy)]
+pub struct DetectedEncoding {
+    pub encoding: &'static Encoding,
+    pub confidence: f64,
+    pub source: EncodingSource,
+}
+
+/// A buffer decoded to UTF-8 along with the encoding used to decode it.
+///
+/// `had_errors` is true if any malformed sequences were replaced with U+FFFD.
+#[derive(Debug, Clone)]
+pub struct DecodedText {
+    pub text: String,
+    pub encoding: &'static Encoding,
+    pub confidence: f64,
+    pub had_errors: bool,
+}
+
+/// Detects the encoding from a byte order mark.
+///
+/// # Arguments
+/// * `buffer` - The input bytes
+///
+/// # Returns
+/// The encoding and the length of the BOM, or None if there is no BOM
+pub fn detect_bom(buffer: &[u8]) -> Option<(&'static Encoding, usize)> {
+    Encoding::for_bom(buffer)
+}
+
+/// Detects the encoding from an HTML `<meta charset>` or `<meta http-equiv>` declaration.
+///
+/// Only the first 4096 bytes are scanned. As in the HTML standard, UTF-16 declarations are
+/// treated as UTF-8, since a document whose meta tag can be read as ASCII cannot be UTF-16.
+///
+/// # Arguments
+/// * `buffer` - The input bytes
+///
+/// # Returns
+/// The declared encoding, or None if there is no recognized declaration
+pub fn detect_html_meta_charset(buffer: &[u8]) -> Option<&'static Encoding> {
+    let prefix = &buffer[..buffer.len().min(META_PRESCAN_SIZE)];
+    let label = META_CHARSET.captures(prefix)?.get(1)?.as_bytes();
+    Encoding::for_label(label).map(|encoding| encoding.output_encoding())
+}
+
+/// Detects the encoding of a buffer.
+///
+/// # Arguments
+/// * `buffer` - The input bytes
+///
+/// # Returns
+/// The detected encoding, confidence and detection source
+pub fn detect_encoding(buffer: &[u8]) -> DetectedEncoding {
+    if let Some((encoding, _)) = detect_bom(buffer) {
+        return DetectedEncoding {
+            encoding,
+            confidence: 1.0,
+            source: EncodingSource::Bom,
+        };
+    }
+
+    if std::str::from_utf8(buffer).is_ok() {
+        return DetectedEncoding {
+            encoding: UTF_8,
+            confidence: 1.0,
+            source: EncodingSource::Utf8,
+        };
+    }
+
+    // a declared UTF-8 charset is wrong here, since the buffer is not valid UTF-8
+    if let Some(encoding) = detect_html_meta_charset(buffer).filter(|&e| e != UTF_8) {
+        let (_, had_errors) = encoding.decode_without_bom_handling(buffer);
+        return DetectedEncoding {
+            encoding,
+            confidence: if had_errors { 0.5 } else { 0.9 },
+            source: EncodingSource::HtmlMeta,
+        };
+    }
+
+    let mut detector = EncodingDetector::new(Iso2022JpDetection::Allow);
+    detector.feed(buffer, true);
+    let encoding = detector.guess(None, Utf8Detection::Deny);
+
+    // confidence grows with the amount of non-ASCII evidence and is halved on decode errors
+    let non_ascii = buffer.iter().filter(|&&b| b >= 0x80).count();
+    let (_, had_errors) = encoding.decode_without_bom_handling(buffer);
+    let mut confidence = 0.5 + 0.4 * (non_ascii as f64 / 32.0).min(1.0);
+    if had_errors {
+        confidence *= 0.5;
+    }
+
+    DetectedEncoding {
+        encoding,
+        confidence,
+        source: EncodingSource::Statistical,
+    }
+}
+
+/// Returns true if the buffer looks like text rather than binary data.
+///
+/// Buffers with a BOM are always text; otherwise, any NUL byte in the first 8192 bytes marks
+/// the buffer as binary.
+pub fn is_text(buffer: &[u8]) -> bool {
+    detect_bom(buffer).is_some() || !buffer[..buffer.len().min(BINARY_PRESCAN_SIZE)].contains(&0)
+}
+
+/// Drops an incomplete UTF-8 sequence from the end of a buffer prefix.
+///
+/// A prefix read from a longer file may cut its last character short, which would otherwise
+/// rule out UTF-8. Buffers that are complete or invalid UTF-8 elsewhere are returned unchanged.
+///
+/// # Arguments
+/// * `buffer` - The buffer prefix
+///
+/// # Returns
+/// The prefix without an incomplete trailing sequence
+pub fn trim_incomplete_utf8(buffer: &[u8]) -> &[u8] {
+    match std::str::from_utf8(buffer) {
+        Err(e) if e.error_len().is_none() => &buffer[..e.valid_up_to()],
+        _ => buffer,
+    }
+}
+
+/// Detects the encoding of a buffer only if it looks like text.
+///
+/// # Arguments
+/// * `buffer` - The input bytes
+///
+/// # Returns
+/// The detected encoding, or None for binary data
+pub fn detect_text_encoding(buffer: &[u8]) -> Option<DetectedEncoding> {
+    if is_text(buffer) {
+        Some(detect_encoding(buffer))
+    } else {
+        None
+    }
+}
+
+/// Decodes a buffer with a specific encoding, removing a matching BOM if present.
+///
+/// # Arguments
+/// * `buffer` - The input bytes
+/// * `encoding` - The encoding to decode with
+///
+/// # Returns
+/// A tuple of the decoded string and whether any malformed sequences were replaced
+pub fn decode_with_encoding(buffer: &[u8], encoding: &'static Encoding) -> (String, bool) {
+    let (text, had_errors) = encoding.decode_with_bom_removal(buffer);
+    (text.into_owned(), had_errors)
+}
+
+/// Detects the encoding of a buffer and decodes it to UTF-8.
+///
+/// # Arguments
+/// * `buffer` - The input bytes
+///
+/// # Returns
+/// The decoded text with the encoding and confidence
+pub fn decode_bytes(buffer: &[u8]) -> DecodedText {
+    let detected = detect_encoding(buffer);
+    let (text, had_errors) = decode_with_encoding(buffer, detected.encoding);
+
+    DecodedText {
+        text,
+        encoding: detected.encoding,
+        confidence: detected.confidence,
+        had_errors,
+    }
+}
+
+/// Returns the encoding for a WHATWG label, e.g., "latin1", "cp1252" or "shift_jis".
+pub fn encoding_for_label(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::{GB18030, SHIFT_JIS, UTF_16LE, WINDOWS_1252};
+
+    #[test]
+    fn test_detect_bom() {
+        assert_eq!(detect_bom(b"\xEF\xBB\xBFabc"), Some((UTF_8, 3)));
+        assert_eq!(detect_bom(b"\xFF\xFEa\x00"), Some((UTF_16LE, 2)));
+        assert_eq!(detect_bom(b"abc"), None);
+    }
+
+    #[test]
+    fn test_detect_html_meta_charset() {
+        assert_eq!(
+            detect_html_meta_charset(b"<html><head><meta charset=\"windows-1252\">"),
+            Some(WINDOWS_1252)
+        );
+        assert_eq!(
+            detect_html_meta_charset(
+                b"<meta http-equiv=\"content-type\" content=\"text/html; charset=ISO-8859-1\">"
+            ),
+            Some(WINDOWS_1252)
+        );
+        assert_eq!(detect_html_meta_charset(b"<html><body>"), None);
+    }
+
+    #[test]
+    fn test_detect_encoding_utf8() {
+        let detected = detect_encoding("café".as_bytes());
+        assert_eq!(detected.encoding, UTF_8);
+        assert_eq!(detected.source, EncodingSource::Utf8);
+        assert_eq!(detected.confidence, 1.0);
+    }
+
+    #[test]
+    fn test_trim_incomplete_utf8() {
+        // a prefix ending in the middle of "é"
+        let buffer = "Le café ".repeat(100).into_bytes();
+        let prefix = &buffer[..buffer.len() - 2];
+        assert_eq!(trim_incomplete_utf8(prefix), &buffer[..buffer.len() - 3]);
+        assert_eq!(
+            detect_encoding(trim_incomplete_utf8(prefix)).encoding,
+            UTF_8
+        );
+
+        // complete or invalid buffers are left alone
+        assert_eq!(trim_incomplete_utf8(b"abc"), b"abc");
+        assert_eq!(trim_incomplete_utf8(b"caf\xe9 au lait"), b"caf\xe9 au lait");
+    }
+
+    #[test]
+    fn test_detect_encoding_meta() {
+        let detected = detect_encoding(b"<meta charset=\"iso-8859-1\"><p>caf\xe9</p>");
+        assert_eq!(detected.encoding, WINDOWS_1252);
+        assert_eq!(detected.source, EncodingSource::HtmlMeta);
+    }
+
+    #[test]
+    fn test_detect_encoding_statistical() {
+        let text = "これは日本語のテキストです。文字コードの判定をテストします。";
+        let (buffer, _, _) = SHIFT_JIS.encode(text);
+        let detected = detect_encoding(&buffer);
+        assert_eq!(detected.encoding, SHIFT_JIS);
+        assert_eq!(detected.source, EncodingSource::Statistical);
+        assert!(detected.confidence > 0.5);
+
+        let text = "这是一个用于测试字符编码检测的中文句子。";
+        let (buffer, _, _) = GB18030.encode(text);
+        let decoded = decode_bytes(&buffer);
+        assert_eq!(decoded.text, text);
+        assert!(!decoded.had_errors);
+    }
+
+    #[test]
+    fn test_decode_bytes() {
+        let decoded = decode_bytes(b"The caf\xe9 \x93quoted\x94 text");
+        assert_eq!(decoded.encoding, WINDOWS_1252);
+        assert_eq!(decoded.text, "The café “quoted” text");
+
+        // a trailing Latin-1 byte is not a truncated UTF-8 character
+        let decoded = decode_bytes(b"Caf\xe9");
+        assert_eq!(decoded.text, "Café");
+
+        let decoded = decode_bytes(b"\xEF\xBB\xBFHello");
+        assert_eq!(decoded.text, "Hello");
+        assert_eq!(decoded.confidence, 1.0);
+    }
+
+    #[test]
+    fn test_detect_text_encoding() {
+        assert!(detect_text_encoding(b"plain text").is_some());
+        assert!(detect_text_encoding(b"\x00\x01\x02binary").is_none());
+        assert!(detect_text_encoding(b"\xFF\xFEa\x00").is_some());
+    }
+
+    #[test]
+    fn test_encoding_for_label() {
+        assert_eq!(encoding_for_label("cp1252"), Some(WINDOWS_1252));
+        assert_eq!(encoding_for_label("Shift_JIS"), Some(SHIFT_JIS));
+        assert_eq!(encoding_for_label("not-an-encoding"), None);
+    }
+}
@@ -28,18 +28,18 @@ pub fn get_entries(path: &str) -> Vec<String> {
 }
 
 //pub fn get_all_file_info(path: &str) -> Vec<FileInfo> {
-// tuple of (path, FileInfo)
+// tuple of (path, FileInfo); files that cannot be read are skipped
 pub fn get_all_file_info(path: &str) -> Vec<(String, FileInfo)> {
     // run get_file_info_from_file on all
     WalkDir::new(path)
         .into_iter()
         .filter_map(|e| e.ok())
         .filter(|e| e.file_type().is_file())
-        .map(|e| {
-            (
-                e.path().display().to_string(),
-                get_file_info_from_file(&e.path().display().to_string()),
-            )
+        .filter_map(|e| {
+            let path = e.path().display().to_string();
+            get_file_info_from_file(&path)
+                .ok()
+                .map(|file_info| (path, file_info))
         })
         .collect()
 }
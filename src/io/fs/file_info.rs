@@ -1,6 +1,12 @@
+use crate::errors::Result;
+use crate::io::encoding::{detect_text_encoding, trim_incomplete_utf8};
+use crate::io::fs::files::read_first_n_bytes;
 use file_format::FileFormat;
 use pyo3::prelude::*;
 
+// number of leading bytes used to detect the text encoding of a file
+const ENCODING_PREFIX_SIZE: usize = 64 * 1024;
+
 /// A struct representing comprehensive file format information.
 #[pyclass]
 #[derive(Debug, Clone)]
@@ -15,19 +21,22 @@ pub struct FileInfo {
     pub extension: String,
     #[pyo3(get)]
     pub kind: String,
+    #[pyo3(get)]
+    pub encoding: String,
 }
 
 /// A struct representing comprehensive file format information.
 #[pymethods]
 impl FileInfo {
     #[new]
-    #[pyo3(signature = (name, short_name, media_type, extension, kind))]
+    #[pyo3(signature = (name, short_name, media_type, extension, kind, encoding=String::new()))]
     fn new(
         name: String,
         short_name: String,
         media_type: String,
         extension: String,
         kind: String,
+        encoding: String,
     ) -> Self {
         FileInfo {
             name,
@@ -35,6 +44,7 @@ impl FileInfo {
             media_type,
             extension,
             kind,
+            encoding,
         }
     }
 
@@ -46,12 +56,12 @@ impl FileInfo {
     /// Example:
     ///    >>> file_info = FileInfo("Portable Network Graphics", "PNG", "image/png", "png", "Image")
     ///   >>> str(file_info)
-    ///  "FileInfo(name='Portable Network Graphics', short_name='PNG', media_type='image/png', extension='png', kind='Image')"
+    ///  "FileInfo(name='Portable Network Graphics', short_name='PNG', media_type='image/png', extension='png', kind='Image', encoding='')"
     ///
     fn __str__(&self) -> String {
         format!(
-            "FileInfo(name='{}', short_name='{}', media_type='{}', extension='{}', kind='{}')",
-            self.name, self.short_name, self.media_type, self.extension, self.kind
+            "FileInfo(name='{}', short_name='{}', media_type='{}', extension='{}', kind='{}', encoding='{}')",
+            self.name, self.short_name, self.media_type, self.extension, self.kind, self.encoding
         )
     }
 }
@@ -59,15 +69,19 @@ impl FileInfo {
 /// Convert a FileFormat object to a FileInfo object.
 /// Args:
 /// fmt (FileFormat): The FileFormat object to convert.
+/// prefix (bytes): The leading bytes of the file, used to detect the text encoding.
 /// Returns:
 /// FileInfo: The FileInfo object.
-fn file_format_to_file_info(fmt: FileFormat) -> FileInfo {
+fn file_format_to_file_info(fmt: FileFormat, prefix: &[u8]) -> FileInfo {
     FileInfo {
         name: fmt.name().to_string(),
         short_name: fmt.short_name().map(|s| s.to_string()).unwrap_or_default(),
         media_type: fmt.media_type().to_string(),
         extension: fmt.extension().to_string(),
         kind: format!("{:?}", fmt.kind()),
+        encoding: detect_text_encoding(prefix)
+            .map(|detected| detected.encoding.name().to_string())
+            .unwrap_or_default(),
     }
 }
 
@@ -77,26 +91,21 @@ fn file_format_to_file_info(fmt: FileFormat) -> FileInfo {
 /// Returns:
 /// FileInfo: The file information.
 pub fn get_file_info_from_buffer(buffer: &[u8]) -> FileInfo {
-    file_format_to_file_info(FileFormat::from_bytes(buffer))
+    file_format_to_file_info(FileFormat::from_bytes(buffer), buffer)
 }
 
 /// Get file information from a file.
 /// Args:
 /// path (str): The path to the file to get information from.
 /// Returns:
-/// FileInfo: The file information.
-pub fn get_file_info_from_file(path: &str) -> FileInfo {
-    let file_format_result = FileFormat::from_file(path);
-    match file_format_result {
-        Ok(file_format) => file_format_to_file_info(file_format),
-        Err(_) => FileInfo::new(
-            "".to_string(),
-            "".to_string(),
-            "".to_string(),
-            "".to_string(),
-            "".to_string(),
-        ),
-    }
+/// FileInfo: The file information, or an IO error if the file could not be read.
+pub fn get_file_info_from_file(path: &str) -> Result<FileInfo> {
+    let file_format = FileFormat::from_file(path)?;
+    let prefix = read_first_n_bytes(path, ENCODING_PREFIX_SIZE)?;
+    Ok(file_format_to_file_info(
+        file_format,
+        trim_incomplete_utf8(&prefix),
+    ))
 }
 
 #[cfg(test)]
@@ -119,18 +128,20 @@ mod tests {
         assert_eq!(file_info.media_type, "text/html");
         assert_eq!(file_info.extension, "html");
         assert_eq!(file_info.kind, "Other");
+        assert_eq!(file_info.encoding, "UTF-8");
     }
 
     #[test]
     fn test_get_html_file_info_from_file() {
         // get CARGO_MANIFEST_DIR/resources/file1.html
         let path = format!("{}/resources/file1.html", env!("CARGO_MANIFEST_DIR"));
-        let file_info = get_file_info_from_file(&path);
+        let file_info = get_file_info_from_file(&path).unwrap();
         assert_eq!(file_info.name, "HyperText Markup Language");
         assert_eq!(file_info.short_name, "HTML");
         assert_eq!(file_info.media_type, "text/html");
         assert_eq!(file_info.extension, "html");
         assert_eq!(file_info.kind, "Other");
+        assert_eq!(file_info.encoding, "UTF-8");
     }
 
     #[test]
@@ -144,18 +155,20 @@ mod tests {
         assert_eq!(file_info.media_type, "application/pdf");
         assert_eq!(file_info.extension, "pdf");
         assert_eq!(file_info.kind, "Document");
+        assert_eq!(file_info.encoding, "");
     }
 
     #[test]
     fn test_get_pdf_file_info_from_file() {
         // get CARGO_MANIFEST_DIR/resources/test1.pdf
         let path = format!("{}/resources/test1.pdf", env!("CARGO_MANIFEST_DIR"));
-        let file_info = get_file_info_from_file(&path);
+        let file_info = get_file_info_from_file(&path).unwrap();
         assert_eq!(file_info.name, "Portable Document Format");
         assert_eq!(file_info.short_name, "PDF");
         assert_eq!(file_info.media_type, "application/pdf");
         assert_eq!(file_info.extension, "pdf");
         assert_eq!(file_info.kind, "Document");
+        assert_eq!(file_info.encoding, "");
     }
 
     // test random1, which is random bytes
@@ -170,5 +183,22 @@ mod tests {
         assert_eq!(file_info.media_type, "application/octet-stream");
         assert_eq!(file_info.extension, "bin");
         assert_eq!(file_info.kind, "Other");
+        assert_eq!(file_info.encoding, "");
+    }
+
+    #[test]
+    fn test_get_utf8_file_info_from_file() {
+        // the encoding prefix ends in the middle of an "é"
+        let path = std::env::temp_dir().join("alea_preprocess_test_file_info.txt");
+        std::fs::write(&path, "Le café ".repeat(10000)).unwrap();
+        let file_info = get_file_info_from_file(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(file_info.encoding, "UTF-8");
+    }
+
+    #[test]
+    fn test_get_missing_file_info_from_file() {
+        let path = format!("{}/resources/missing_file", env!("CARGO_MANIFEST_DIR"));
+        assert!(get_file_info_from_file(&path).is_err());
     }
 }
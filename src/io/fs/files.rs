@@ -1,3 +1,4 @@
+use crate::io::encoding::{decode_bytes, DecodedText};
 use flate2::read::GzDecoder;
 /// Utility functions to process files.
 ///
@@ -31,6 +32,54 @@ pub fn iter_gz_lines(path: &str) -> Result<Lines<BufReader<GzDecoder<File>>>, io
     Ok(reader.lines())
 }
 
+/// Returns the text of a line, either as-is or from a string field of a JSON object.
+fn get_line_text(line: String, field: Option<&str>) -> Result<Option<String>, io::Error> {
+    let field = match field {
+        Some(field) => field,
+        None => return Ok(Some(line)),
+    };
+    if line.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let value: serde_json::Value = serde_json::from_str(&line)?;
+    match value.get(field) {
+        Some(serde_json::Value::String(text)) => Ok(Some(text.to_string())),
+        Some(serde_json::Value::Null) | None => Ok(None),
+        Some(_) => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Field '{}' is not a string", field),
+        )),
+    }
+}
+
+/// Shared method to iterate over the documents of a text, JSONL or Gzipped file.
+///
+/// Files ending in `.gz` are decompressed while reading. Each line is a document, unless `field`
+/// is set, in which case each line is parsed as a JSON object and the document is that string
+/// field; lines that are blank or lack the field are skipped.
+///
+/// Args:
+/// path (&str): The path to the file to process.
+/// field (Option<&str>): The JSON field holding the text, e.g., "text" for JSONL files.
+pub fn iter_documents(
+    path: &str,
+    field: Option<&str>,
+) -> Result<Box<dyn Iterator<Item = Result<String, io::Error>> + Send>, io::Error> {
+    let lines: Box<dyn Iterator<Item = Result<String, io::Error>> + Send> = if path.ends_with(".gz")
+    {
+        Box::new(iter_gz_lines(path)?)
+    } else {
+        Box::new(iter_lines(path)?)
+    };
+
+    let field = field.map(|field| field.to_string());
+    Ok(Box::new(lines.filter_map(move |line| {
+        line.and_then(|line| get_line_text(line, field.as_deref()))
+            .transpose()
+    })))
+}
+
 /// Shared method to read whole file content.
 ///
 /// Args:
@@ -51,6 +100,22 @@ pub fn read_gz_file_content(path: &str) -> Result<Vec<u8>, io::Error> {
     Ok(buffer)
 }
 
+/// Shared method to read whole file content and decode it to UTF-8 with encoding detection.
+///
+/// Args:
+///   path (&str): The path to the file to process.
+pub fn read_file_text(path: &str) -> Result<DecodedText, io::Error> {
+    Ok(decode_bytes(&read_file_content(path)?))
+}
+
+/// Shared method to read whole Gzipped file content and decode it to UTF-8 with encoding detection.
+///
+/// Args:
+///   path (&str): The path to the file to process.
+pub fn read_gz_file_text(path: &str) -> Result<DecodedText, io::Error> {
+    Ok(decode_bytes(&read_gz_file_content(path)?))
+}
+
 /// Shared method to read all lines into a Vec of byte vectors.
 ///
 /// Args:
@@ -175,6 +240,28 @@ mod tests {
         assert_eq!(lines[0].chars().next(), Some('{'));
     }
 
+    #[test]
+    fn test_iter_documents() {
+        let path = format!("{}/resources/usc.100.jsonl.gz", env!("CARGO_MANIFEST_DIR"));
+        let documents: Vec<String> = iter_documents(&path, Some("text"))
+            .unwrap()
+            .map(|d| d.unwrap())
+            .collect();
+        assert_eq!(documents.len(), 100);
+        assert!(!documents[0].starts_with('{'));
+
+        let path = format!("{}/resources/hello-world.txt", env!("CARGO_MANIFEST_DIR"));
+        let documents: Vec<String> = iter_documents(&path, None)
+            .unwrap()
+            .map(|d| d.unwrap())
+            .collect();
+        assert_eq!(documents, vec!["Hello, world!"]);
+
+        // plain text lines are not valid JSON
+        let mut documents = iter_documents(&path, Some("text")).unwrap();
+        assert!(documents.next().unwrap().is_err());
+    }
+
     #[test]
     fn test_read_file_content() {
         // get CARGO_MANIFEST_DIR/resources/hello-world.txt
@@ -194,6 +281,24 @@ mod tests {
         assert_eq!(content, b"Hello, world!");
     }
 
+    #[test]
+    fn test_read_file_text() {
+        let path = format!("{}/resources/hello-world.txt", env!("CARGO_MANIFEST_DIR"));
+        let decoded = read_file_text(&path).unwrap();
+        assert_eq!(decoded.text, "Hello, world!\n");
+        assert_eq!(decoded.encoding.name(), "UTF-8");
+    }
+
+    #[test]
+    fn test_read_gz_file_text() {
+        let path = format!(
+            "{}/resources/hello-world.txt.gz",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let decoded = read_gz_file_text(&path).unwrap();
+        assert_eq!(decoded.text, "Hello, world!");
+    }
+
     #[test]
     fn test_read_lines() {
         // get CARGO_MANIFEST_DIR/resources/hello-world.txt
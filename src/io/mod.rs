@@ -1 +1,3 @@
+pub mod encoding;
 pub mod fs;
+pub mod shards;
@@ -0,0 +1,695 @@
+/// Binary token shards for training data.
+///
+/// A shard is a data file holding every sequence back to back as little-endian integers, plus an
+/// index file of sequence lengths, byte offsets and document boundaries. The index uses the
+/// Megatron-LM `MMIDIDX` layout, so `{prefix}.bin`/`{prefix}.idx` pairs load directly with
+/// Megatron's `MMapIndexedDataset`, and the `.bin` file alone is a NanoGPT-style token stream.
+/// With `ShardFormat::Npy` the data file is instead a 1-D `.npy` array that `np.load(path,
+/// mmap_mode="r")` can map, and index offsets are relative to the start of the array data.
+use memmap2::Mmap;
+use pyo3::prelude::*;
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Magic bytes at the start of a Megatron-LM index file.
+const INDEX_MAGIC: &[u8; 9] = b"MMIDIDX\x00\x00";
+
+/// Index file format version.
+const INDEX_VERSION: u64 = 1;
+
+/// Total size of the `.npy` preamble; fixed so the header can be rewritten in place on finish.
+const NPY_HEADER_SIZE: usize = 128;
+
+/// Integer type of the values stored in a shard
+///
+/// `U16` fits vocabularies up to 65,536 tokens and `U32` larger ones. `U8` suits attention and
+/// type masks, and `I32` suits labels that use negative ids such as -100.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShardDtype {
+    U8,
+    U16,
+    U32,
+    I32,
+}
+
+pub fn shard_dtype_from_string(name: &str) -> Option<ShardDtype> {
+    match name.to_lowercase().as_str() {
+        "uint8" | "u8" => Some(ShardDtype::U8),
+        "uint16" | "u16" => Some(ShardDtype::U16),
+        "uint32" | "u32" => Some(ShardDtype::U32),
+        "int32" | "i32" => Some(ShardDtype::I32),
+        _ => None,
+    }
+}
+
+impl ShardDtype {
+    /// Size of one value in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            ShardDtype::U8 => 1,
+            ShardDtype::U16 => 2,
+            ShardDtype::U32 | ShardDtype::I32 => 4,
+        }
+    }
+
+    /// NumPy dtype name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShardDtype::U8 => "uint8",
+            ShardDtype::U16 => "uint16",
+            ShardDtype::U32 => "uint32",
+            ShardDtype::I32 => "int32",
+        }
+    }
+
+    /// NumPy array-protocol type string, as written in `.npy` headers.
+    fn descr(&self) -> &'static str {
+        match self {
+            ShardDtype::U8 => "|u1",
+            ShardDtype::U16 => "<u2",
+            ShardDtype::U32 => "<u4",
+            ShardDtype::I32 => "<i4",
+        }
+    }
+
+    /// Megatron-LM dtype code.
+    ///
+    /// Megatron has no unsigned 32-bit code, so `U32` is tagged as int32, which reads back
+    /// identically for token ids below 2^31.
+    fn code(&self) -> u8 {
+        match self {
+            ShardDtype::U8 => 1,
+            ShardDtype::U16 => 8,
+            ShardDtype::U32 | ShardDtype::I32 => 4,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<ShardDtype> {
+        match code {
+            1 => Some(ShardDtype::U8),
+            8 => Some(ShardDtype::U16),
+            4 => Some(ShardDtype::I32),
+            _ => None,
+        }
+    }
+
+    /// Checks that a value is representable in this dtype.
+    fn contains(&self, value: i32) -> bool {
+        match self {
+            ShardDtype::U8 => (0..=u8::MAX as i32).contains(&value),
+            ShardDtype::U16 => (0..=u16::MAX as i32).contains(&value),
+            ShardDtype::U32 => value >= 0,
+            ShardDtype::I32 => true,
+        }
+    }
+
+    /// Decodes the value at `index` of a little-endian buffer.
+    fn read(&self, buffer: &[u8], index: usize) -> i32 {
+        let start = index * self.size();
+        match self {
+            ShardDtype::U8 => buffer[start] as i32,
+            ShardDtype::U16 => u16::from_le_bytes([buffer[start], buffer[start + 1]]) as i32,
+            ShardDtype::U32 | ShardDtype::I32 => i32::from_le_bytes([
+                buffer[start],
+                buffer[start + 1],
+                buffer[start + 2],
+                buffer[start + 3],
+            ]),
+        }
+    }
+}
+
+/// Layout of a shard's data file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShardFormat {
+    Bin,
+    Npy,
+}
+
+pub fn shard_format_from_string(name: &str) -> Option<ShardFormat> {
+    match name.to_lowercase().as_str() {
+        "bin" | "raw" => Some(ShardFormat::Bin),
+        "npy" => Some(ShardFormat::Npy),
+        _ => None,
+    }
+}
+
+impl ShardFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ShardFormat::Bin => "bin",
+            ShardFormat::Npy => "npy",
+        }
+    }
+}
+
+/// Returns the data file path for a shard prefix.
+pub fn get_data_path(prefix: &str, format: ShardFormat) -> String {
+    format!("{}.{}", prefix, format.extension())
+}
+
+/// Returns the index file path for a shard prefix.
+pub fn get_index_path(prefix: &str) -> String {
+    format!("{}.idx", prefix)
+}
+
+/// Returns a `.npy` v1.0 preamble for a 1-D array, padded to `NPY_HEADER_SIZE` bytes.
+fn get_npy_header(dtype: ShardDtype, length: usize) -> Vec<u8> {
+    let mut dict = format!(
+        "{{'descr': '{}', 'fortran_order': False, 'shape': ({},), }}",
+        dtype.descr(),
+        length
+    );
+    let header_len = NPY_HEADER_SIZE - 10;
+    while dict.len() < header_len - 1 {
+        dict.push(' ');
+    }
+    dict.push('\n');
+
+    let mut header = Vec::with_capacity(NPY_HEADER_SIZE);
+    header.extend_from_slice(b"\x93NUMPY\x01\x00");
+    header.extend_from_slice(&(header_len as u16).to_le_bytes());
+    header.extend_from_slice(dict.as_bytes());
+    header
+}
+
+/// Returns the offset of the array data in a `.npy` file from its preamble.
+fn get_npy_data_offset(buffer: &[u8]) -> io::Result<usize> {
+    if buffer.len() < 10 || &buffer[0..6] != b"\x93NUMPY" {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Invalid .npy header",
+        ));
+    }
+    match buffer[6] {
+        1 => Ok(10 + u16::from_le_bytes([buffer[8], buffer[9]]) as usize),
+        _ => {
+            if buffer.len() < 12 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Invalid .npy header",
+                ));
+            }
+            Ok(12 + u32::from_le_bytes([buffer[8], buffer[9], buffer[10], buffer[11]]) as usize)
+        }
+    }
+}
+
+/// Streams sequences into a shard.
+///
+/// Call `end_document` after the sequences of each document to record document boundaries, and
+/// `finish` to write the index; a shard that is dropped without `finish` has no index.
+pub struct ShardWriter {
+    prefix: String,
+    dtype: ShardDtype,
+    format: ShardFormat,
+    data: BufWriter<File>,
+    sizes: Vec<i32>,
+    pointers: Vec<i64>,
+    document_indices: Vec<i64>,
+    num_tokens: usize,
+    buffer: Vec<u8>,
+}
+
+impl ShardWriter {
+    /// Creates `{prefix}.bin` or `{prefix}.npy`, truncating any existing shard.
+    pub fn create(prefix: &str, dtype: ShardDtype, format: ShardFormat) -> io::Result<Self> {
+        let mut data = BufWriter::new(File::create(get_data_path(prefix, format))?);
+        if format == ShardFormat::Npy {
+            data.write_all(&get_npy_header(dtype, 0))?;
+        }
+
+        Ok(ShardWriter {
+            prefix: prefix.to_string(),
+            dtype,
+            format,
+            data,
+            sizes: Vec::new(),
+            pointers: Vec::new(),
+            document_indices: vec![0],
+            num_tokens: 0,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Appends a sequence, failing if a value does not fit the shard dtype.
+    pub fn write_sequence(&mut self, sequence: &[i32]) -> io::Result<()> {
+        self.buffer.clear();
+        self.buffer.reserve(sequence.len() * self.dtype.size());
+        for &value in sequence {
+            if !self.dtype.contains(value) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Value {} does not fit in {}", value, self.dtype.name()),
+                ));
+            }
+            match self.dtype {
+                ShardDtype::U8 => self.buffer.push(value as u8),
+                ShardDtype::U16 => self.buffer.extend_from_slice(&(value as u16).to_le_bytes()),
+                ShardDtype::U32 | ShardDtype::I32 => {
+                    self.buffer.extend_from_slice(&value.to_le_bytes())
+                }
+            }
+        }
+        self.data.write_all(&self.buffer)?;
+
+        self.pointers
+            .push((self.num_tokens * self.dtype.size()) as i64);
+        self.sizes.push(sequence.len() as i32);
+        self.num_tokens += sequence.len();
+        Ok(())
+    }
+
+    /// Marks the end of a document; does nothing if no sequences were written since the last one.
+    pub fn end_document(&mut self) {
+        if self.document_indices.last() != Some(&(self.sizes.len() as i64)) {
+            self.document_indices.push(self.sizes.len() as i64);
+        }
+    }
+
+    /// Flushes the data file and writes the index, closing any open document.
+    ///
+    /// # Returns
+    /// The number of sequences and values written
+    pub fn finish(mut self) -> io::Result<(usize, usize)> {
+        self.end_document();
+
+        if self.format == ShardFormat::Npy {
+            self.data.seek(SeekFrom::Start(0))?;
+            self.data
+                .write_all(&get_npy_header(self.dtype, self.num_tokens))?;
+        }
+        self.data.flush()?;
+
+        let mut index = BufWriter::new(File::create(get_index_path(&self.prefix))?);
+        index.write_all(INDEX_MAGIC)?;
+        index.write_all(&INDEX_VERSION.to_le_bytes())?;
+        index.write_all(&[self.dtype.code()])?;
+        index.write_all(&(self.sizes.len() as u64).to_le_bytes())?;
+        index.write_all(&(self.document_indices.len() as u64).to_le_bytes())?;
+        for size in &self.sizes {
+            index.write_all(&size.to_le_bytes())?;
+        }
+        for pointer in &self.pointers {
+            index.write_all(&pointer.to_le_bytes())?;
+        }
+        for document_index in &self.document_indices {
+            index.write_all(&document_index.to_le_bytes())?;
+        }
+        index.flush()?;
+
+        Ok((self.sizes.len(), self.num_tokens))
+    }
+}
+
+/// Writes one shard per field of a multi-field sample, e.g. input ids, masks and labels.
+///
+/// Field `name` is written to `{prefix}.{name}.bin` (or `.npy`) and `{prefix}.{name}.idx`.
+pub struct SampleShardWriter {
+    writers: Vec<ShardWriter>,
+}
+
+impl SampleShardWriter {
+    pub fn create(
+        prefix: &str,
+        fields: &[(&str, ShardDtype)],
+        format: ShardFormat,
+    ) -> io::Result<Self> {
+        let writers = fields
+            .iter()
+            .map(|(name, dtype)| {
+                ShardWriter::create(&format!("{}.{}", prefix, name), *dtype, format)
+            })
+            .collect::<io::Result<Vec<ShardWriter>>>()?;
+        Ok(SampleShardWriter { writers })
+    }
+
+    /// Appends one sample, given as one sequence per field in field order.
+    pub fn write_sample(&mut self, sample: &[&[i32]]) -> io::Result<()> {
+        if sample.len() != self.writers.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Expected {} fields, got {}",
+                    self.writers.len(),
+                    sample.len()
+                ),
+            ));
+        }
+        for (writer, sequence) in self.writers.iter_mut().zip(sample) {
+            writer.write_sequence(sequence)?;
+        }
+        Ok(())
+    }
+
+    pub fn end_document(&mut self) {
+        self.writers.iter_mut().for_each(ShardWriter::end_document);
+    }
+
+    /// Writes the index of every field.
+    ///
+    /// # Returns
+    /// The number of samples written
+    pub fn finish(self) -> io::Result<usize> {
+        let num_samples = self.writers.first().map_or(0, |writer| writer.sizes.len());
+        for writer in self.writers {
+            writer.finish()?;
+        }
+        Ok(num_samples)
+    }
+}
+
+/// A memory-mapped shard written by `ShardWriter`, or any Megatron-LM `.bin`/`.idx` pair with a
+/// uint8, uint16 or int32 dtype.
+#[pyclass]
+pub struct ShardReader {
+    dtype: ShardDtype,
+    sizes: Vec<i32>,
+    pointers: Vec<i64>,
+    document_indices: Vec<i64>,
+    data: Mmap,
+    data_offset: usize,
+}
+
+impl ShardReader {
+    /// Opens `{prefix}.idx` and maps `{prefix}.bin`, or `{prefix}.npy` if there is no `.bin`.
+    pub fn open(prefix: &str) -> io::Result<Self> {
+        let mut index = Vec::new();
+        File::open(get_index_path(prefix))?.read_to_end(&mut index)?;
+        let (dtype, sizes, pointers, document_indices) = parse_index(&index)?;
+
+        let bin_path = get_data_path(prefix, ShardFormat::Bin);
+        let format = if Path::new(&bin_path).exists() {
+            ShardFormat::Bin
+        } else {
+            ShardFormat::Npy
+        };
+        let file = File::open(get_data_path(prefix, format))?;
+        // SAFETY: shards are written once and not modified while mapped
+        let data = unsafe { Mmap::map(&file)? };
+        let data_offset = match format {
+            ShardFormat::Bin => 0,
+            ShardFormat::Npy => get_npy_data_offset(&data)?,
+        };
+
+        // check every sequence lies within the data file
+        let data_len = data.len().saturating_sub(data_offset);
+        for (&size, &pointer) in sizes.iter().zip(&pointers) {
+            let (Ok(size), Ok(pointer)) = (usize::try_from(size), usize::try_from(pointer)) else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Index has a negative size or pointer",
+                ));
+            };
+            let end = size
+                .checked_mul(dtype.size())
+                .and_then(|length| length.checked_add(pointer));
+            if !matches!(end, Some(end) if end <= data_len) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Index refers past the end of the data file",
+                ));
+            }
+        }
+
+        Ok(ShardReader {
+            dtype,
+            sizes,
+            pointers,
+            document_indices,
+            data,
+            data_offset,
+        })
+    }
+
+    pub fn dtype(&self) -> ShardDtype {
+        self.dtype
+    }
+
+    /// Get the number of sequences
+    pub fn len(&self) -> usize {
+        self.sizes.len()
+    }
+
+    /// Check if the shard has no sequences
+    pub fn is_empty(&self) -> bool {
+        self.sizes.is_empty()
+    }
+
+    /// Get the total number of values
+    pub fn num_tokens(&self) -> usize {
+        self.sizes.iter().map(|&size| size as usize).sum()
+    }
+
+    /// Get the sequence lengths
+    pub fn sizes(&self) -> &[i32] {
+        &self.sizes
+    }
+
+    /// Get the document boundaries: document `i` spans sequences
+    /// `document_indices[i]..document_indices[i + 1]`
+    pub fn document_indices(&self) -> &[i64] {
+        &self.document_indices
+    }
+
+    /// Get the raw little-endian bytes of sequence `index`, or None if out of range.
+    pub fn get_bytes(&self, index: usize) -> Option<&[u8]> {
+        let size = *self.sizes.get(index)? as usize;
+        let start = self.data_offset + self.pointers[index] as usize;
+        Some(&self.data[start..start + size * self.dtype.size()])
+    }
+
+    /// Get sequence `index`, or None if out of range.
+    pub fn get(&self, index: usize) -> Option<Vec<i32>> {
+        let bytes = self.get_bytes(index)?;
+        let size = self.sizes[index] as usize;
+        Some((0..size).map(|i| self.dtype.read(bytes, i)).collect())
+    }
+}
+
+#[pymethods]
+impl ShardReader {
+    #[new]
+    fn py_new(prefix: &str) -> PyResult<Self> {
+        ShardReader::open(prefix).map_err(|e| pyo3::exceptions::PyIOError::new_err(e.to_string()))
+    }
+
+    fn __len__(&self) -> usize {
+        self.len()
+    }
+
+    fn __getitem__(&self, index: isize) -> PyResult<Vec<i32>> {
+        let length = self.len() as isize;
+        let position = if index < 0 { index + length } else { index };
+        if position < 0 || position >= length {
+            return Err(pyo3::exceptions::PyIndexError::new_err(
+                "Shard index out of range",
+            ));
+        }
+        Ok(self.get(position as usize).unwrap_or_default())
+    }
+
+    fn __str__(&self) -> String {
+        format!(
+            "ShardReader(num_sequences={}, num_tokens={}, dtype={})",
+            self.len(),
+            self.num_tokens(),
+            self.dtype.name()
+        )
+    }
+
+    #[getter(dtype)]
+    fn py_dtype(&self) -> &'static str {
+        self.dtype.name()
+    }
+
+    #[getter(num_tokens)]
+    fn py_num_tokens(&self) -> usize {
+        self.num_tokens()
+    }
+
+    #[getter(sizes)]
+    fn py_sizes(&self) -> Vec<i32> {
+        self.sizes.clone()
+    }
+
+    #[getter(document_indices)]
+    fn py_document_indices(&self) -> Vec<i64> {
+        self.document_indices.clone()
+    }
+}
+
+type ShardIndex = (ShardDtype, Vec<i32>, Vec<i64>, Vec<i64>);
+
+/// Parses a Megatron-LM index file into its dtype, sizes, pointers and document indices.
+fn parse_index(buffer: &[u8]) -> io::Result<ShardIndex> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+
+    if buffer.len() < 34 || &buffer[0..9] != INDEX_MAGIC {
+        return Err(invalid("Invalid shard index header"));
+    }
+    let read_u64 =
+        |offset: usize| u64::from_le_bytes(buffer[offset..offset + 8].try_into().unwrap());
+    if read_u64(9) != INDEX_VERSION {
+        return Err(invalid("Unsupported shard index version"));
+    }
+    let dtype =
+        ShardDtype::from_code(buffer[17]).ok_or_else(|| invalid("Unsupported shard dtype"))?;
+    let num_sequences = read_u64(18) as usize;
+    let num_documents = read_u64(26) as usize;
+
+    let expected_len = num_sequences
+        .checked_mul(12)
+        .zip(num_documents.checked_mul(8))
+        .and_then(|(sequences_len, documents_len)| sequences_len.checked_add(documents_len))
+        .and_then(|len| len.checked_add(34));
+    let expected_len = match expected_len {
+        Some(len) if len <= buffer.len() => len,
+        _ => return Err(invalid("Truncated shard index")),
+    };
+
+    let sizes_start = 34;
+    let pointers_start = sizes_start + num_sequences * 4;
+    let documents_start = pointers_start + num_sequences * 8;
+    let sizes = buffer[sizes_start..pointers_start]
+        .chunks_exact(4)
+        .map(|chunk| i32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect();
+    let pointers = buffer[pointers_start..documents_start]
+        .chunks_exact(8)
+        .map(|chunk| i64::from_le_bytes(chunk.try_into().unwrap()))
+        .collect();
+    let document_indices = buffer[documents_start..expected_len]
+        .chunks_exact(8)
+        .map(|chunk| i64::from_le_bytes(chunk.try_into().unwrap()))
+        .collect();
+
+    Ok((dtype, sizes, pointers, document_indices))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_test_prefix(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("alea_preprocess_test_shards_{}", name))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[test]
+    fn test_shard_dtype_from_string() {
+        assert_eq!(shard_dtype_from_string("UINT16"), Some(ShardDtype::U16));
+        assert_eq!(shard_dtype_from_string("i32"), Some(ShardDtype::I32));
+        assert_eq!(shard_dtype_from_string("float32"), None);
+    }
+
+    #[test]
+    fn test_write_read_bin() {
+        let prefix = get_test_prefix("bin");
+        let mut writer = ShardWriter::create(&prefix, ShardDtype::U16, ShardFormat::Bin).unwrap();
+        writer.write_sequence(&[5, 6, 7]).unwrap();
+        writer.write_sequence(&[65535]).unwrap();
+        writer.end_document();
+        writer.write_sequence(&[]).unwrap();
+        writer.write_sequence(&[8, 9]).unwrap();
+        assert_eq!(writer.finish().unwrap(), (4, 6));
+
+        let data = std::fs::read(get_data_path(&prefix, ShardFormat::Bin)).unwrap();
+        assert_eq!(data.len(), 12);
+
+        let reader = ShardReader::open(&prefix).unwrap();
+        assert_eq!(reader.dtype(), ShardDtype::U16);
+        assert_eq!(reader.len(), 4);
+        assert_eq!(reader.num_tokens(), 6);
+        assert_eq!(reader.sizes(), &[3, 1, 0, 2]);
+        assert_eq!(reader.document_indices(), &[0, 2, 4]);
+        assert_eq!(reader.get(0), Some(vec![5, 6, 7]));
+        assert_eq!(reader.get(1), Some(vec![65535]));
+        assert_eq!(reader.get(2), Some(vec![]));
+        assert_eq!(reader.get(3), Some(vec![8, 9]));
+        assert_eq!(reader.get(4), None);
+    }
+
+    #[test]
+    fn test_write_read_npy() {
+        let prefix = get_test_prefix("npy");
+        let mut writer = ShardWriter::create(&prefix, ShardDtype::I32, ShardFormat::Npy).unwrap();
+        writer.write_sequence(&[-100, 5, -100]).unwrap();
+        writer.write_sequence(&[70000]).unwrap();
+        writer.finish().unwrap();
+
+        let data = std::fs::read(get_data_path(&prefix, ShardFormat::Npy)).unwrap();
+        assert_eq!(data.len(), NPY_HEADER_SIZE + 16);
+        assert_eq!(data[NPY_HEADER_SIZE - 1], b'\n');
+        let header = String::from_utf8_lossy(&data[10..NPY_HEADER_SIZE]);
+        assert!(header.contains("'descr': '<i4'"));
+        assert!(header.contains("'shape': (4,)"));
+
+        let reader = ShardReader::open(&prefix).unwrap();
+        assert_eq!(reader.get(0), Some(vec![-100, 5, -100]));
+        assert_eq!(reader.get(1), Some(vec![70000]));
+        assert_eq!(reader.document_indices(), &[0, 2]);
+    }
+
+    #[test]
+    fn test_open_invalid_index() {
+        let prefix = get_test_prefix("invalid");
+        let mut writer = ShardWriter::create(&prefix, ShardDtype::U16, ShardFormat::Bin).unwrap();
+        writer.write_sequence(&[5, 6, 7]).unwrap();
+        writer.finish().unwrap();
+        let index_path = get_index_path(&prefix);
+        let index = std::fs::read(&index_path).unwrap();
+
+        // a pointer or size that would overflow, or is negative, is rejected rather than wrapped
+        for (offset, value) in [
+            (38, i64::MAX.to_le_bytes().to_vec()),
+            (38, (-1i64).to_le_bytes().to_vec()),
+            (34, (-1i32).to_le_bytes().to_vec()),
+            (18, u64::MAX.to_le_bytes().to_vec()),
+        ] {
+            let mut corrupted = index.clone();
+            corrupted[offset..offset + value.len()].copy_from_slice(&value);
+            std::fs::write(&index_path, &corrupted).unwrap();
+            let error = ShardReader::open(&prefix).err().unwrap();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn test_write_out_of_range() {
+        let prefix = get_test_prefix("range");
+        let mut writer = ShardWriter::create(&prefix, ShardDtype::U16, ShardFormat::Bin).unwrap();
+        assert!(writer.write_sequence(&[65536]).is_err());
+        assert!(writer.write_sequence(&[-1]).is_err());
+        assert_eq!(writer.finish().unwrap(), (0, 0));
+    }
+
+    #[test]
+    fn test_sample_shard_writer() {
+        let prefix = get_test_prefix("samples");
+        let mut writer = SampleShardWriter::create(
+            &prefix,
+            &[
+                ("input_ids", ShardDtype::U32),
+                ("attention_mask", ShardDtype::U8),
+            ],
+            ShardFormat::Bin,
+        )
+        .unwrap();
+        writer.write_sample(&[&[2, 5, 3], &[1, 1, 1]]).unwrap();
+        writer.write_sample(&[&[2, 3, 0], &[1, 1, 0]]).unwrap();
+        assert!(writer.write_sample(&[&[2, 3, 0]]).is_err());
+        assert_eq!(writer.finish().unwrap(), 2);
+
+        let input_ids = ShardReader::open(&format!("{}.input_ids", prefix)).unwrap();
+        let attention_mask = ShardReader::open(&format!("{}.attention_mask", prefix)).unwrap();
+        assert_eq!(input_ids.dtype(), ShardDtype::I32);
+        assert_eq!(input_ids.get(1), Some(vec![2, 3, 0]));
+        assert_eq!(attention_mask.dtype(), ShardDtype::U8);
+        assert_eq!(attention_mask.get(1), Some(vec![1, 1, 0]));
+    }
+}
@@ -4,10 +4,77 @@ use pyo3::prelude::*;
 use pyo3::types::*;
 use serde_json::Value;
 
-mod algos;
-mod io;
-mod parsers;
-mod tasks;
+pub mod algos;
+pub mod errors;
+pub mod io;
+pub mod parsers;
+pub mod pipeline;
+pub mod tasks;
+
+// python exception hierarchy; every error subclasses ValueError so existing handlers still match
+pyo3::create_exception!(
+    alea_preprocess.errors,
+    AleaPreprocessError,
+    PyValueError,
+    "Base class for errors raised by alea_preprocess."
+);
+pyo3::create_exception!(
+    alea_preprocess.errors,
+    ParseError,
+    AleaPreprocessError,
+    "A document could not be parsed."
+);
+pyo3::create_exception!(
+    alea_preprocess.errors,
+    TokenizerError,
+    AleaPreprocessError,
+    "A tokenizer could not be loaded or failed to encode or decode."
+);
+pyo3::create_exception!(
+    alea_preprocess.errors,
+    TikaError,
+    AleaPreprocessError,
+    "A request to the Tika server failed."
+);
+pyo3::create_exception!(
+    alea_preprocess.errors,
+    DecodeError,
+    AleaPreprocessError,
+    "Encoded content could not be decoded."
+);
+
+impl From<crate::errors::Error> for PyErr {
+    fn from(e: crate::errors::Error) -> PyErr {
+        use crate::errors::Error;
+        match e {
+            Error::Io(e) => e.into(),
+            Error::Parse(_) => ParseError::new_err(e.to_string()),
+            Error::Tokenizer(_) => TokenizerError::new_err(e.to_string()),
+            Error::Tika(_) => TikaError::new_err(e.to_string()),
+            Error::Decode(_) => DecodeError::new_err(e.to_string()),
+            Error::Config(_) => AleaPreprocessError::new_err(e.to_string()),
+        }
+    }
+}
+
+// function to convert crate, tokenizer and content errors to python exceptions
+fn to_py_err<E: Into<crate::errors::Error>>(e: E) -> PyErr {
+    e.into().into()
+}
+
+fn get_shard_options(
+    dtype: &str,
+    format: &str,
+) -> PyResult<(
+    crate::io::shards::ShardDtype,
+    crate::io::shards::ShardFormat,
+)> {
+    let shard_dtype = crate::io::shards::shard_dtype_from_string(dtype)
+        .ok_or_else(|| PyValueError::new_err(format!("Invalid dtype: {}", dtype)))?;
+    let shard_format = crate::io::shards::shard_format_from_string(format)
+        .ok_or_else(|| PyValueError::new_err(format!("Invalid format: {}", format)))?;
+    Ok((shard_dtype, shard_format))
+}
 
 fn convert_values(py: Python, values: Vec<Value>) -> PyResult<Vec<PyObject>> {
     values
@@ -49,6 +116,90 @@ fn value_to_pyobject(py: Python, value: &Value) -> PyResult<PyObject> {
     }
 }
 
+/// Element types that can be returned as NumPy arrays
+trait NumpyElement: Copy {
+    const DTYPE: &'static str;
+
+    fn write_ne_bytes(self, buffer: &mut [u8]);
+}
+
+macro_rules! impl_numpy_element {
+    ($t:ty, $dtype:expr) => {
+        impl NumpyElement for $t {
+            const DTYPE: &'static str = $dtype;
+
+            fn write_ne_bytes(self, buffer: &mut [u8]) {
+                buffer.copy_from_slice(&self.to_ne_bytes());
+            }
+        }
+    };
+}
+
+impl_numpy_element!(u8, "uint8");
+impl_numpy_element!(i32, "int32");
+impl_numpy_element!(u32, "uint32");
+impl_numpy_element!(i64, "int64");
+impl_numpy_element!(u64, "uint64");
+
+/// Builds a NumPy array of the given shape by filling a bytearray and wrapping it with
+/// `numpy.frombuffer`, so the values are copied once and the array is writable.
+fn to_numpy_array<T: NumpyElement>(
+    py: Python,
+    shape: &[usize],
+    fill: impl FnOnce(&mut [u8]),
+) -> PyResult<PyObject> {
+    let length = shape.iter().product::<usize>() * std::mem::size_of::<T>();
+    let buffer = PyByteArray::new_bound_with(py, length, |bytes| {
+        fill(bytes);
+        Ok(())
+    })?;
+    let array = py
+        .import_bound("numpy")?
+        .call_method1("frombuffer", (buffer, T::DTYPE))?
+        .call_method1("reshape", (PyTuple::new_bound(py, shape),))?;
+    Ok(array.unbind())
+}
+
+/// Returns a 1-D NumPy array of the values.
+fn to_numpy_1d<T: NumpyElement>(py: Python, values: &[T]) -> PyResult<PyObject> {
+    to_numpy_array::<T>(py, &[values.len()], |bytes| {
+        for (value, chunk) in values
+            .iter()
+            .zip(bytes.chunks_exact_mut(std::mem::size_of::<T>()))
+        {
+            value.write_ne_bytes(chunk);
+        }
+    })
+}
+
+/// Returns ragged rows as a 2-D NumPy array padded with `pad_value` to the longest row, and a
+/// 1-D int64 array of the row lengths.
+fn to_numpy_padded<T: NumpyElement>(
+    py: Python,
+    rows: &[Vec<T>],
+    pad_value: T,
+) -> PyResult<(PyObject, PyObject)> {
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let array = to_numpy_array::<T>(py, &[rows.len(), width], |bytes| {
+        if width == 0 {
+            return;
+        }
+        let row_size = width * std::mem::size_of::<T>();
+        for (row, row_bytes) in rows.iter().zip(bytes.chunks_exact_mut(row_size)) {
+            let padding = std::iter::repeat_n(&pad_value, width - row.len());
+            for (value, chunk) in row
+                .iter()
+                .chain(padding)
+                .zip(row_bytes.chunks_exact_mut(std::mem::size_of::<T>()))
+            {
+                value.write_ne_bytes(chunk);
+            }
+        }
+    })?;
+    let lengths: Vec<i64> = rows.iter().map(|row| row.len() as i64).collect();
+    Ok((array, to_numpy_1d(py, &lengths)?))
+}
+
 // pyo3 declarative style for defining python module
 #[pymodule]
 mod alea_preprocess {
@@ -71,26 +222,26 @@ mod alea_preprocess {
 
                 // function to hash bytes using blake2
                 #[pyfunction]
-                fn hash_bytes(bytes: &[u8]) -> PyResult<String> {
-                    Ok(crate::algos::hashing::blake2::hash_bytes(bytes))
+                fn hash_bytes(py: Python, bytes: &[u8]) -> PyResult<String> {
+                    Ok(py.allow_threads(|| crate::algos::hashing::blake2::hash_bytes(bytes)))
                 }
 
                 // function to hash string using blake2
                 #[pyfunction]
-                fn hash_str(s: &str) -> PyResult<String> {
-                    Ok(crate::algos::hashing::blake2::hash_str(s))
+                fn hash_str(py: Python, s: &str) -> PyResult<String> {
+                    Ok(py.allow_threads(|| crate::algos::hashing::blake2::hash_str(s)))
                 }
 
                 // function to hash file using blake2
                 #[pyfunction]
-                fn hash_file(path: &str) -> PyResult<String> {
-                    Ok(crate::algos::hashing::blake2::hash_file(path))
+                fn hash_file(py: Python, path: &str) -> PyResult<String> {
+                    Ok(py.allow_threads(|| crate::algos::hashing::blake2::hash_file(path)))
                 }
 
                 // function to hash gz file using blake2
                 #[pyfunction]
-                fn hash_gz_file(path: &str) -> PyResult<String> {
-                    Ok(crate::algos::hashing::blake2::hash_gz_file(path))
+                fn hash_gz_file(py: Python, path: &str) -> PyResult<String> {
+                    Ok(py.allow_threads(|| crate::algos::hashing::blake2::hash_gz_file(path)))
                 }
             }
 
@@ -101,26 +252,43 @@ mod alea_preprocess {
 
                 // function to hash bytes using blake3
                 #[pyfunction]
-                fn hash_bytes(bytes: &[u8]) -> PyResult<String> {
-                    Ok(crate::algos::hashing::blake3::hash_bytes(bytes))
+                fn hash_bytes(py: Python, bytes: &[u8]) -> PyResult<String> {
+                    Ok(py.allow_threads(|| crate::algos::hashing::blake3::hash_bytes(bytes)))
                 }
 
                 // function to hash string using blake3
                 #[pyfunction]
-                fn hash_str(s: &str) -> PyResult<String> {
-                    Ok(crate::algos::hashing::blake3::hash_str(s))
+                fn hash_str(py: Python, s: &str) -> PyResult<String> {
+                    Ok(py.allow_threads(|| crate::algos::hashing::blake3::hash_str(s)))
                 }
 
                 // function to hash file using blake3
                 #[pyfunction]
-                fn hash_file(path: &str) -> PyResult<String> {
-                    Ok(crate::algos::hashing::blake3::hash_file(path))
+                fn hash_file(py: Python, path: &str) -> PyResult<String> {
+                    Ok(py.allow_threads(|| crate::algos::hashing::blake3::hash_file(path)))
+                }
+
+                // function to hash several buffers in parallel using blake3
+                #[pyfunction]
+                fn hash_bytes_batch(py: Python, buffers: Vec<Vec<u8>>) -> PyResult<Vec<String>> {
+                    Ok(py.allow_threads(|| {
+                        crate::algos::hashing::blake3::hash_bytes_batch(&buffers)
+                    }))
+                }
+
+                // function to hash several files in parallel using blake3
+                #[pyfunction]
+                fn hash_file_batch(py: Python, paths: Vec<String>) -> PyResult<Vec<String>> {
+                    py.allow_threads(|| crate::algos::hashing::blake3::hash_file_batch(&paths))
+                        .into_iter()
+                        .collect::<std::io::Result<Vec<String>>>()
+                        .map_err(to_py_err)
                 }
 
                 // function to hash gz file using blake3
                 #[pyfunction]
-                fn hash_gz_file(path: &str) -> PyResult<String> {
-                    Ok(crate::algos::hashing::blake3::hash_gz_file(path))
+                fn hash_gz_file(py: Python, path: &str) -> PyResult<String> {
+                    Ok(py.allow_threads(|| crate::algos::hashing::blake3::hash_gz_file(path)))
                 }
             }
 
@@ -131,42 +299,54 @@ mod alea_preprocess {
 
                 // function to hash bytes using rolling
                 #[pyfunction]
-                fn hash_bytes(bytes: &[u8], window_size: usize, precision: u8) -> PyResult<String> {
-                    Ok(crate::algos::hashing::rolling::hash_bytes(
-                        bytes,
-                        window_size,
-                        precision,
-                    ))
+                fn hash_bytes(
+                    py: Python,
+                    bytes: &[u8],
+                    window_size: usize,
+                    precision: u8,
+                ) -> PyResult<String> {
+                    Ok(py.allow_threads(|| {
+                        crate::algos::hashing::rolling::hash_bytes(bytes, window_size, precision)
+                    }))
                 }
 
                 // function to hash string using rolling
                 #[pyfunction]
-                fn hash_str(s: &str, window_size: usize, precision: u8) -> PyResult<String> {
-                    Ok(crate::algos::hashing::rolling::hash_str(
-                        s,
-                        window_size,
-                        precision,
-                    ))
+                fn hash_str(
+                    py: Python,
+                    s: &str,
+                    window_size: usize,
+                    precision: u8,
+                ) -> PyResult<String> {
+                    Ok(py.allow_threads(|| {
+                        crate::algos::hashing::rolling::hash_str(s, window_size, precision)
+                    }))
                 }
 
                 // function to hash file using rolling
                 #[pyfunction]
-                fn hash_file(path: &str, window_size: usize, precision: u8) -> PyResult<String> {
-                    Ok(crate::algos::hashing::rolling::hash_file(
-                        path,
-                        window_size,
-                        precision,
-                    ))
+                fn hash_file(
+                    py: Python,
+                    path: &str,
+                    window_size: usize,
+                    precision: u8,
+                ) -> PyResult<String> {
+                    Ok(py.allow_threads(|| {
+                        crate::algos::hashing::rolling::hash_file(path, window_size, precision)
+                    }))
                 }
 
                 // function to hash gz file using rolling
                 #[pyfunction]
-                fn hash_gz_file(path: &str, window_size: usize, precision: u8) -> PyResult<String> {
-                    Ok(crate::algos::hashing::rolling::hash_gz_file(
-                        path,
-                        window_size,
-                        precision,
-                    ))
+                fn hash_gz_file(
+                    py: Python,
+                    path: &str,
+                    window_size: usize,
+                    precision: u8,
+                ) -> PyResult<String> {
+                    Ok(py.allow_threads(|| {
+                        crate::algos::hashing::rolling::hash_gz_file(path, window_size, precision)
+                    }))
                 }
             }
 
@@ -181,6 +361,22 @@ mod alea_preprocess {
                         window_size,
                     ))
                 }
+
+                // function to get the rolling hash after each token as a uint64 NumPy array
+                #[pyfunction]
+                fn hash_token_windows_numpy(
+                    py: Python,
+                    tokens: Vec<i64>,
+                    window_size: usize,
+                ) -> PyResult<PyObject> {
+                    to_numpy_1d(
+                        py,
+                        &crate::algos::hashing::token_rolling::hash_token_windows(
+                            &tokens,
+                            window_size,
+                        ),
+                    )
+                }
             }
 
             // submodule for ctph
@@ -191,65 +387,118 @@ mod alea_preprocess {
                 // function to hash bytes using ctph
                 #[pyfunction]
                 fn hash_bytes(
+                    py: Python,
                     bytes: &[u8],
                     window_size: usize,
                     digest_size: usize,
                     precision: u8,
                 ) -> PyResult<String> {
-                    Ok(crate::algos::hashing::ctph::hash_bytes(
-                        bytes,
-                        window_size,
-                        digest_size,
-                        precision,
-                    ))
+                    Ok(py.allow_threads(|| {
+                        crate::algos::hashing::ctph::hash_bytes(
+                            bytes,
+                            window_size,
+                            digest_size,
+                            precision,
+                        )
+                    }))
                 }
 
                 // function to hash string using ctph
                 #[pyfunction]
                 fn hash_str(
+                    py: Python,
                     s: &str,
                     window_size: usize,
                     digest_size: usize,
                     precision: u8,
                 ) -> PyResult<String> {
-                    Ok(crate::algos::hashing::ctph::hash_str(
-                        s,
-                        window_size,
-                        digest_size,
-                        precision,
-                    ))
+                    Ok(py.allow_threads(|| {
+                        crate::algos::hashing::ctph::hash_str(
+                            s,
+                            window_size,
+                            digest_size,
+                            precision,
+                        )
+                    }))
                 }
 
                 // function to hash file using ctph
                 #[pyfunction]
                 fn hash_file(
+                    py: Python,
                     path: &str,
                     window_size: usize,
                     digest_size: usize,
                     precision: u8,
                 ) -> PyResult<String> {
-                    Ok(crate::algos::hashing::ctph::hash_file(
-                        path,
-                        window_size,
-                        digest_size,
-                        precision,
-                    ))
+                    Ok(py.allow_threads(|| {
+                        crate::algos::hashing::ctph::hash_file(
+                            path,
+                            window_size,
+                            digest_size,
+                            precision,
+                        )
+                    }))
                 }
 
                 // function to hash gz file using ctph
                 #[pyfunction]
                 fn hash_gz_file(
+                    py: Python,
                     path: &str,
                     window_size: usize,
                     digest_size: usize,
                     precision: u8,
                 ) -> PyResult<String> {
-                    Ok(crate::algos::hashing::ctph::hash_gz_file(
-                        path,
-                        window_size,
-                        digest_size,
-                        precision,
-                    ))
+                    Ok(py.allow_threads(|| {
+                        crate::algos::hashing::ctph::hash_gz_file(
+                            path,
+                            window_size,
+                            digest_size,
+                            precision,
+                        )
+                    }))
+                }
+
+                // function to hash several buffers in parallel using ctph
+                #[pyfunction]
+                fn hash_bytes_batch(
+                    py: Python,
+                    buffers: Vec<Vec<u8>>,
+                    window_size: usize,
+                    digest_size: usize,
+                    precision: u8,
+                ) -> PyResult<Vec<String>> {
+                    Ok(py.allow_threads(|| {
+                        crate::algos::hashing::ctph::hash_bytes_batch(
+                            &buffers,
+                            window_size,
+                            digest_size,
+                            precision,
+                        )
+                    }))
+                }
+
+                // function to hash several files in parallel using ctph
+                #[pyfunction]
+                fn hash_file_batch(
+                    py: Python,
+                    paths: Vec<String>,
+                    window_size: usize,
+                    digest_size: usize,
+                    precision: u8,
+                ) -> PyResult<Vec<String>> {
+                    py.allow_threads(|| {
+                        crate::algos::hashing::ctph::hash_file_batch(
+                            &paths,
+                            window_size,
+                            digest_size,
+                            precision,
+                        )
+                    })
+                    .into_iter()
+                    .collect::<std::io::Result<Vec<String>>>()
+                    .map_err(to_py_err)
                 }
 
                 // function to compare ctph hashes
@@ -314,6 +563,23 @@ mod alea_preprocess {
                     }
                 }
 
+                // function to get the byte n-gram windows as a 2-D uint8 NumPy array
+                #[pyfunction]
+                fn transform_numpy(py: Python, input_data: &[u8], n: usize) -> PyResult<PyObject> {
+                    if n == 0 || n > 9 {
+                        return Err(pyo3::exceptions::PyValueError::new_err(
+                            "ngrams of size 1 to 9 are supported",
+                        ));
+                    }
+                    let num_windows = input_data.len().saturating_sub(n - 1);
+                    to_numpy_array::<u8>(py, &[num_windows, n], |bytes| {
+                        for (window, chunk) in input_data.windows(n).zip(bytes.chunks_exact_mut(n))
+                        {
+                            chunk.copy_from_slice(window);
+                        }
+                    })
+                }
+
                 // function to extract bytegrams from bytes
                 #[pyfunction]
                 fn extract(py: Python, input_data: &[u8], n: usize) -> PyResult<Py<PyDict>> {
@@ -604,6 +870,148 @@ mod alea_preprocess {
             }
         }
 
+        #[pymodule]
+        mod quality {
+            use super::*;
+
+            // submodule for heuristic quality signals
+            #[pymodule(submodule)]
+            mod heuristics {
+                use super::*;
+                use crate::algos::quality::heuristics::QualityConfig;
+
+                #[pymodule_export]
+                use crate::algos::quality::heuristics::QualitySignals;
+
+                // function to get the quality signals of a document
+                #[pyfunction]
+                fn get_quality_signals(py: Python, text: &str) -> QualitySignals {
+                    py.allow_threads(|| {
+                        crate::algos::quality::heuristics::get_quality_signals(text)
+                    })
+                }
+
+                // function to get the quality signals of several documents in parallel
+                #[pyfunction]
+                fn get_quality_signals_batch(
+                    py: Python,
+                    texts: Vec<String>,
+                ) -> Vec<QualitySignals> {
+                    py.allow_threads(|| {
+                        crate::algos::quality::heuristics::get_quality_signals_batch(&texts)
+                    })
+                }
+
+                // function to get the quality signals of a document and the checks it fails
+                #[pyfunction]
+                #[pyo3(signature = (text, min_words=50, max_words=100_000, min_mean_word_length=3.0, max_mean_word_length=10.0, max_symbol_to_word_ratio=0.1, max_ellipsis_line_fraction=0.3, max_bullet_line_fraction=0.9, max_duplicate_line_fraction=0.3, max_duplicate_line_char_fraction=0.2, max_duplicate_paragraph_fraction=0.3, max_duplicate_paragraph_char_fraction=0.2, max_top_ngram_char_fractions=vec![0.2, 0.18, 0.16], max_duplicate_ngram_char_fractions=vec![0.15, 0.14, 0.13, 0.12, 0.11, 0.1], min_stop_words=2, min_alphabetic_ratio=0.8))]
+                #[allow(clippy::too_many_arguments)]
+                fn check_quality(
+                    py: Python,
+                    text: &str,
+                    min_words: usize,
+                    max_words: usize,
+                    min_mean_word_length: f64,
+                    max_mean_word_length: f64,
+                    max_symbol_to_word_ratio: f64,
+                    max_ellipsis_line_fraction: f64,
+                    max_bullet_line_fraction: f64,
+                    max_duplicate_line_fraction: f64,
+                    max_duplicate_line_char_fraction: f64,
+                    max_duplicate_paragraph_fraction: f64,
+                    max_duplicate_paragraph_char_fraction: f64,
+                    max_top_ngram_char_fractions: Vec<f64>,
+                    max_duplicate_ngram_char_fractions: Vec<f64>,
+                    min_stop_words: usize,
+                    min_alphabetic_ratio: f64,
+                ) -> PyResult<(QualitySignals, Vec<String>)> {
+                    if max_top_ngram_char_fractions.len() != 3
+                        || max_duplicate_ngram_char_fractions.len() != 6
+                    {
+                        return Err(PyValueError::new_err(
+                            "expected 3 top n-gram thresholds (n=2..4) and 6 duplicate n-gram thresholds (n=5..10)",
+                        ));
+                    }
+                    let config = QualityConfig {
+                        min_words,
+                        max_words,
+                        min_mean_word_length,
+                        max_mean_word_length,
+                        max_symbol_to_word_ratio,
+                        max_ellipsis_line_fraction,
+                        max_bullet_line_fraction,
+                        max_duplicate_line_fraction,
+                        max_duplicate_line_char_fraction,
+                        max_duplicate_paragraph_fraction,
+                        max_duplicate_paragraph_char_fraction,
+                        max_top_ngram_char_fractions,
+                        max_duplicate_ngram_char_fractions,
+                        min_stop_words,
+                        min_alphabetic_ratio,
+                    };
+                    Ok(py.allow_threads(|| {
+                        crate::algos::quality::heuristics::check_quality(text, &config)
+                    }))
+                }
+            }
+
+            // submodule for unicode category noise signals
+            #[pymodule(submodule)]
+            mod noise {
+                use super::*;
+                use crate::algos::quality::noise::NoiseConfig;
+
+                #[pymodule_export]
+                use crate::algos::quality::noise::NoiseReport;
+
+                #[pymodule_export]
+                use crate::algos::quality::noise::NoiseSignals;
+
+                // function to get the noise signals of a text as a whole
+                #[pyfunction]
+                fn get_noise_signals(py: Python, text: &str) -> NoiseSignals {
+                    py.allow_threads(|| crate::algos::quality::noise::get_noise_signals(text))
+                }
+
+                // function to get the noise signals of each non-empty line of a text
+                #[pyfunction]
+                fn get_line_noise_signals(py: Python, text: &str) -> Vec<NoiseSignals> {
+                    py.allow_threads(|| crate::algos::quality::noise::get_line_noise_signals(text))
+                }
+
+                // function to get the document and line noise signals and whether the text is unusable
+                #[pyfunction]
+                #[pyo3(signature = (text, max_unusual_transition_ratio=0.1, max_other_symbol_ratio=0.05, max_private_use_ratio=0.01, max_unassigned_ratio=0.01, max_control_ratio=0.01, min_letter_ratio=0.5, min_line_chars=8, max_noisy_char_fraction=0.3))]
+                #[allow(clippy::too_many_arguments)]
+                fn get_noise_report(
+                    py: Python,
+                    text: &str,
+                    max_unusual_transition_ratio: f64,
+                    max_other_symbol_ratio: f64,
+                    max_private_use_ratio: f64,
+                    max_unassigned_ratio: f64,
+                    max_control_ratio: f64,
+                    min_letter_ratio: f64,
+                    min_line_chars: usize,
+                    max_noisy_char_fraction: f64,
+                ) -> NoiseReport {
+                    let config = NoiseConfig::new(
+                        max_unusual_transition_ratio,
+                        max_other_symbol_ratio,
+                        max_private_use_ratio,
+                        max_unassigned_ratio,
+                        max_control_ratio,
+                        min_letter_ratio,
+                        min_line_chars,
+                        max_noisy_char_fraction,
+                    );
+                    py.allow_threads(|| {
+                        crate::algos::quality::noise::get_noise_report(text, &config)
+                    })
+                }
+            }
+        }
+
         #[pymodule]
         mod segmentation {
             use super::*;
@@ -769,6 +1177,62 @@ mod alea_preprocess {
                 }
             }
 
+            #[pymodule(submodule)]
+            mod fixes {
+                use super::*;
+                use crate::algos::unicode::fixes::{text_fix_to_string, TextFixConfig};
+
+                // function to apply text fixes and report which were applied
+                #[pyfunction]
+                #[pyo3(signature = (buffer, fix_mojibake=true, fix_html_entities=true, uncurl_quotes=false, fix_ligatures=false))]
+                fn fix_text(
+                    buffer: &str,
+                    fix_mojibake: bool,
+                    fix_html_entities: bool,
+                    uncurl_quotes: bool,
+                    fix_ligatures: bool,
+                ) -> (String, Vec<&'static str>) {
+                    let config = TextFixConfig::new(
+                        fix_mojibake,
+                        fix_html_entities,
+                        uncurl_quotes,
+                        fix_ligatures,
+                    );
+                    let (text, fixes) = crate::algos::unicode::fixes::fix_text(buffer, &config);
+                    (text, fixes.into_iter().map(text_fix_to_string).collect())
+                }
+
+                // function to repair utf-8 mis-decoded as windows-1252 or latin-1
+                #[pyfunction]
+                fn fix_mojibake(buffer: &str) -> String {
+                    crate::algos::unicode::fixes::fix_mojibake(buffer)
+                }
+
+                // function to check for repairable mojibake
+                #[pyfunction]
+                fn has_mojibake(buffer: &str) -> bool {
+                    crate::algos::unicode::fixes::has_mojibake(buffer)
+                }
+
+                // function to collapse multiply-escaped entities and decode them
+                #[pyfunction]
+                fn fix_html_entities(buffer: &str) -> String {
+                    crate::algos::unicode::fixes::fix_html_entities(buffer)
+                }
+
+                // function to replace curly quotes with ascii quotes
+                #[pyfunction]
+                fn uncurl_quotes(buffer: &str) -> String {
+                    crate::algos::unicode::fixes::uncurl_quotes(buffer)
+                }
+
+                // function to expand typographic ligatures
+                #[pyfunction]
+                fn fix_ligatures(buffer: &str) -> String {
+                    crate::algos::unicode::fixes::fix_ligatures(buffer)
+                }
+            }
+
             #[pymodule]
             mod normalizations {
                 use super::*;
@@ -796,6 +1260,123 @@ mod alea_preprocess {
                 fn nfkc_str(buffer: &str) -> String {
                     crate::algos::unicode::normalizations::nfkc_str(buffer)
                 }
+
+                // function to normalize whitespace and controls with a character offset map
+                #[pyfunction]
+                #[pyo3(signature = (buffer, collapse_whitespace=true, unify_spaces=true, strip_zero_width=true, strip_bidi_controls=true, strip_control_chars=true, normalize_newlines=true, trim=true))]
+                #[allow(clippy::too_many_arguments)]
+                fn normalize_whitespace(
+                    buffer: &str,
+                    collapse_whitespace: bool,
+                    unify_spaces: bool,
+                    strip_zero_width: bool,
+                    strip_bidi_controls: bool,
+                    strip_control_chars: bool,
+                    normalize_newlines: bool,
+                    trim: bool,
+                ) -> (String, Vec<usize>) {
+                    let config = crate::algos::unicode::normalizations::WhitespaceConfig::new(
+                        collapse_whitespace,
+                        unify_spaces,
+                        strip_zero_width,
+                        strip_bidi_controls,
+                        strip_control_chars,
+                        normalize_newlines,
+                        trim,
+                    );
+                    let result = crate::algos::unicode::normalizations::normalize_whitespace(
+                        buffer, &config,
+                    );
+                    let offsets = result.char_offsets(buffer);
+                    (result.text, offsets)
+                }
+            }
+
+            #[pymodule(submodule)]
+            mod scripts {
+                use super::*;
+                use crate::algos::unicode::scripts::{script_group_to_string, script_to_string};
+                use std::collections::HashMap;
+
+                // function to get unicode script for a character
+                #[pyfunction]
+                fn char_to_script(ch: char) -> &'static str {
+                    script_to_string(crate::algos::unicode::scripts::char_to_script(ch))
+                }
+
+                // function to get unicode script group for a character
+                #[pyfunction]
+                fn char_to_script_group(ch: char) -> &'static str {
+                    script_group_to_string(crate::algos::unicode::scripts::char_to_script_group(ch))
+                }
+
+                // function to get unicode script for a string
+                #[pyfunction]
+                fn to_script_vector(input_data: &str) -> Vec<String> {
+                    crate::algos::unicode::scripts::to_script_vector(input_data)
+                        .iter()
+                        .map(|script| script_to_string(*script).to_string())
+                        .collect()
+                }
+
+                // function to get unicode script group for a string
+                #[pyfunction]
+                fn to_script_group_vector(input_data: &str) -> Vec<String> {
+                    crate::algos::unicode::scripts::to_script_group_vector(input_data)
+                        .iter()
+                        .map(|group| script_group_to_string(*group).to_string())
+                        .collect()
+                }
+
+                // function to get script histogram for a string
+                #[pyfunction]
+                fn get_script_counts(input_data: &str) -> HashMap<String, usize> {
+                    crate::algos::unicode::scripts::get_script_counts(input_data)
+                        .into_iter()
+                        .map(|(script, count)| (script_to_string(script).to_string(), count))
+                        .collect()
+                }
+
+                // function to get script ratios for a string
+                #[pyfunction]
+                fn get_script_ratios(input_data: &str) -> HashMap<String, f64> {
+                    crate::algos::unicode::scripts::get_script_ratios(input_data)
+                        .into_iter()
+                        .map(|(script, ratio)| (script_to_string(script).to_string(), ratio))
+                        .collect()
+                }
+
+                // function to get script group ratios for a string
+                #[pyfunction]
+                fn get_script_group_ratios(input_data: &str) -> HashMap<String, f64> {
+                    crate::algos::unicode::scripts::get_script_group_ratios(input_data)
+                        .into_iter()
+                        .map(|(group, ratio)| (script_group_to_string(group).to_string(), ratio))
+                        .collect()
+                }
+
+                // function to get the dominant script for a string
+                #[pyfunction]
+                fn get_dominant_script(input_data: &str) -> Option<&'static str> {
+                    crate::algos::unicode::scripts::get_dominant_script(input_data)
+                        .map(script_to_string)
+                }
+
+                // function to split a string into same-script runs
+                #[pyfunction]
+                fn get_script_runs(input_data: &str) -> Vec<(u64, u64, String, String)> {
+                    crate::algos::unicode::scripts::get_script_runs(input_data)
+                        .into_iter()
+                        .map(|(start, end, script, run)| {
+                            (
+                                start as u64,
+                                end as u64,
+                                script_to_string(script).to_string(),
+                                run,
+                            )
+                        })
+                        .collect()
+                }
             }
 
             #[pymodule(submodule)]
@@ -1007,48 +1588,374 @@ mod alea_preprocess {
         #[pymodule]
         mod tokenizers {
             use super::*;
+            use crate::algos::tokenizers::tokenizers::EncodeConfig;
+            use std::collections::HashMap;
 
-            #[pyfunction]
-            fn encode_str(tokenizer: &str, text: &str) -> Vec<u32> {
-                crate::algos::tokenizers::tokenizers::encode_str(tokenizer, text)
+            #[pymodule_export]
+            use crate::algos::tokenizers::tokenizers::TokenEncoding;
+
+            #[pymodule_export]
+            use crate::algos::tokenizers::analysis::TokenizerComparison;
+
+            #[pymodule_export]
+            use crate::algos::tokenizers::stats::TokenStats;
+
+            // function to convert tokenizer errors to python exceptions
+            fn to_py_err(e: ::tokenizers::Error) -> PyErr {
+                crate::errors::Error::Tokenizer(e.to_string()).into()
             }
 
+            // function to load a tokenizer from a tokenizer.json file
             #[pyfunction]
-            fn decode_str(tokenizer: &str, ids: Vec<u32>) -> String {
-                crate::algos::tokenizers::tokenizers::decode_str(tokenizer, ids)
+            fn load_tokenizer_from_file(name: &str, path: &str) -> PyResult<()> {
+                crate::algos::tokenizers::tokenizers::load_tokenizer_from_file(name, path)
+                    .map_err(to_py_err)
             }
 
+            // function to load a tokenizer from a tokenizer.json string
             #[pyfunction]
-            fn encode_str_list(tokenizer: &str, texts: Vec<String>) -> Vec<Vec<u32>> {
-                crate::algos::tokenizers::tokenizers::encode_str_list(tokenizer, texts)
+            fn load_tokenizer_from_str(name: &str, json: &str) -> PyResult<()> {
+                crate::algos::tokenizers::tokenizers::load_tokenizer_from_str(name, json)
+                    .map_err(to_py_err)
             }
 
+            // function to check if a tokenizer is registered
             #[pyfunction]
-            fn decode_str_list(tokenizer: &str, ids: Vec<Vec<u32>>) -> Vec<String> {
-                crate::algos::tokenizers::tokenizers::decode_str_list(tokenizer, ids)
+            fn is_tokenizer_loaded(name: &str) -> bool {
+                crate::algos::tokenizers::tokenizers::is_tokenizer_loaded(name)
             }
-        }
-    }
 
-    #[pymodule]
-    mod io {
-        use super::*;
+            // function to encode text with special tokens, truncation and padding
+            #[pyfunction]
+            #[pyo3(signature = (tokenizer, text, add_special_tokens=false, max_length=None, stride=0, pad_to_length=None, pad_to_multiple_of=None, pad_id=None))]
+            #[allow(clippy::too_many_arguments)]
+            fn encode(
+                tokenizer: &str,
+                text: &str,
+                add_special_tokens: bool,
+                max_length: Option<usize>,
+                stride: usize,
+                pad_to_length: Option<usize>,
+                pad_to_multiple_of: Option<usize>,
+                pad_id: Option<u32>,
+            ) -> PyResult<TokenEncoding> {
+                let config = EncodeConfig::new(
+                    add_special_tokens,
+                    max_length,
+                    stride,
+                    pad_to_length,
+                    pad_to_multiple_of,
+                    pad_id,
+                );
+                crate::algos::tokenizers::tokenizers::encode_str_with_config(
+                    tokenizer, text, &config,
+                )
+                .map_err(to_py_err)
+            }
 
-        #[pymodule(submodule)]
-        mod fs {
-            use super::*;
+            // function to encode a list of texts with special tokens, truncation and padding
+            #[pyfunction]
+            #[pyo3(signature = (tokenizer, texts, add_special_tokens=false, max_length=None, stride=0, pad_to_length=None, pad_to_multiple_of=None, pad_id=None))]
+            #[allow(clippy::too_many_arguments)]
+            fn encode_list(
+                tokenizer: &str,
+                texts: Vec<String>,
+                add_special_tokens: bool,
+                max_length: Option<usize>,
+                stride: usize,
+                pad_to_length: Option<usize>,
+                pad_to_multiple_of: Option<usize>,
+                pad_id: Option<u32>,
+            ) -> PyResult<Vec<TokenEncoding>> {
+                let config = EncodeConfig::new(
+                    add_special_tokens,
+                    max_length,
+                    stride,
+                    pad_to_length,
+                    pad_to_multiple_of,
+                    pad_id,
+                );
+                crate::algos::tokenizers::tokenizers::encode_str_list_with_config(
+                    tokenizer, texts, &config,
+                )
+                .map_err(to_py_err)
+            }
 
-            #[pymodule_export]
-            use crate::io::fs::file_info::FileInfo;
+            #[pyfunction]
+            fn encode_str(tokenizer: &str, text: &str) -> PyResult<Vec<u32>> {
+                crate::algos::tokenizers::tokenizers::encode_str(tokenizer, text).map_err(to_py_err)
+            }
 
-            #[pymodule(submodule)]
-            mod directories {
-                use super::*;
+            // function to count tokens in a text
+            #[pyfunction]
+            fn count_tokens(tokenizer: &str, text: &str) -> PyResult<usize> {
+                crate::algos::tokenizers::tokenizers::count_tokens(tokenizer, text)
+                    .map_err(to_py_err)
+            }
 
-                #[pyfunction]
-                pub fn get_files(path: &str) -> Vec<String> {
-                    crate::io::fs::directories::get_files(path)
-                }
+            // function to count tokens in each text of a list
+            #[pyfunction]
+            fn count_tokens_list(tokenizer: &str, texts: Vec<String>) -> PyResult<Vec<usize>> {
+                crate::algos::tokenizers::tokenizers::count_tokens_list(tokenizer, texts)
+                    .map_err(to_py_err)
+            }
+
+            // function to get token statistics for a list of documents
+            #[pyfunction]
+            fn get_token_stats(tokenizer: &str, texts: Vec<String>) -> PyResult<TokenStats> {
+                crate::algos::tokenizers::stats::get_token_stats(tokenizer, texts)
+                    .map_err(to_py_err)
+            }
+
+            // function to get token statistics for the lines of a text, jsonl or gz file
+            #[pyfunction]
+            #[pyo3(signature = (tokenizer, path, field=None))]
+            fn get_token_stats_from_file(
+                tokenizer: &str,
+                path: &str,
+                field: Option<&str>,
+            ) -> PyResult<TokenStats> {
+                crate::algos::tokenizers::stats::get_token_stats_from_file(tokenizer, path, field)
+                    .map_err(to_py_err)
+            }
+
+            // function to compare tokenizer fertility on a list of documents
+            #[pyfunction]
+            #[pyo3(signature = (tokenizers, texts, domain_terms=Vec::new()))]
+            fn compare_tokenizers(
+                tokenizers: Vec<String>,
+                texts: Vec<String>,
+                domain_terms: Vec<String>,
+            ) -> PyResult<HashMap<String, TokenizerComparison>> {
+                crate::algos::tokenizers::analysis::compare_tokenizers(
+                    &tokenizers,
+                    &texts,
+                    &domain_terms,
+                )
+                .map_err(to_py_err)
+            }
+
+            // function to compare tokenizer fertility on the documents of a file
+            #[pyfunction]
+            #[pyo3(signature = (tokenizers, path, field=None, domain_terms=Vec::new()))]
+            fn compare_tokenizers_from_file(
+                tokenizers: Vec<String>,
+                path: &str,
+                field: Option<&str>,
+                domain_terms: Vec<String>,
+            ) -> PyResult<HashMap<String, TokenizerComparison>> {
+                crate::algos::tokenizers::analysis::compare_tokenizers_from_file(
+                    &tokenizers,
+                    path,
+                    field,
+                    &domain_terms,
+                )
+                .map_err(to_py_err)
+            }
+
+            // function to get the vocabulary overlap between two tokenizers
+            #[pyfunction]
+            fn get_vocab_overlap(
+                tokenizer_a: &str,
+                tokenizer_b: &str,
+            ) -> PyResult<(usize, f64, f64, f64)> {
+                crate::algos::tokenizers::analysis::get_vocab_overlap(tokenizer_a, tokenizer_b)
+                    .map_err(to_py_err)
+            }
+
+            // function to train a bpe or unigram tokenizer and save it as tokenizer.json
+            #[pyfunction]
+            #[pyo3(signature = (paths, output_path, model_type="bpe", vocab_size=32000, min_frequency=2, special_tokens=Vec::new(), unk_token=None, pre_tokenization="byte_level", normalization=Some("nfc"), lowercase=false, clean_text=true, field=None))]
+            #[allow(clippy::too_many_arguments)]
+            fn train_tokenizer(
+                paths: Vec<String>,
+                output_path: &str,
+                model_type: &str,
+                vocab_size: usize,
+                min_frequency: u64,
+                special_tokens: Vec<String>,
+                unk_token: Option<String>,
+                pre_tokenization: &str,
+                normalization: Option<&str>,
+                lowercase: bool,
+                clean_text: bool,
+                field: Option<&str>,
+            ) -> PyResult<()> {
+                use crate::algos::tokenizers::training::{
+                    model_type_from_string, normalization_form_from_string,
+                    pre_tokenization_from_string, TrainingConfig,
+                };
+
+                let invalid = |name: &str, value: &str| {
+                    pyo3::exceptions::PyValueError::new_err(format!("Invalid {}: {}", name, value))
+                };
+                let model_type = model_type_from_string(model_type)
+                    .ok_or_else(|| invalid("model_type", model_type))?;
+                let pre_tokenization = pre_tokenization_from_string(pre_tokenization)
+                    .ok_or_else(|| invalid("pre_tokenization", pre_tokenization))?;
+                let normalization = match normalization {
+                    Some(name) => Some(
+                        normalization_form_from_string(name)
+                            .ok_or_else(|| invalid("normalization", name))?,
+                    ),
+                    None => None,
+                };
+
+                let config = TrainingConfig::new(
+                    model_type,
+                    vocab_size,
+                    min_frequency,
+                    special_tokens,
+                    unk_token,
+                    pre_tokenization,
+                    normalization,
+                    lowercase,
+                    clean_text,
+                );
+                crate::algos::tokenizers::training::train_tokenizer_to_file(
+                    &paths,
+                    field,
+                    &config,
+                    output_path,
+                )
+                .map_err(to_py_err)
+            }
+
+            #[pyfunction]
+            fn decode_str(tokenizer: &str, ids: Vec<u32>) -> PyResult<String> {
+                crate::algos::tokenizers::tokenizers::decode_str(tokenizer, ids).map_err(to_py_err)
+            }
+
+            #[pyfunction]
+            fn encode_str_list(tokenizer: &str, texts: Vec<String>) -> PyResult<Vec<Vec<u32>>> {
+                crate::algos::tokenizers::tokenizers::encode_str_list(tokenizer, texts)
+                    .map_err(to_py_err)
+            }
+
+            // function to encode a list of strings into a padded 2-D NumPy array and lengths
+            #[pyfunction]
+            #[pyo3(signature = (tokenizer, texts, pad_id=0))]
+            fn encode_str_list_numpy(
+                py: Python,
+                tokenizer: &str,
+                texts: Vec<String>,
+                pad_id: u32,
+            ) -> PyResult<(PyObject, PyObject)> {
+                let ids = crate::algos::tokenizers::tokenizers::encode_str_list(tokenizer, texts)
+                    .map_err(to_py_err)?;
+                to_numpy_padded(py, &ids, pad_id)
+            }
+
+            #[pyfunction]
+            fn decode_str_list(tokenizer: &str, ids: Vec<Vec<u32>>) -> PyResult<Vec<String>> {
+                crate::algos::tokenizers::tokenizers::decode_str_list(tokenizer, ids)
+                    .map_err(to_py_err)
+            }
+        }
+    }
+
+    #[pymodule]
+    mod io {
+        use super::*;
+
+        #[pymodule(submodule)]
+        mod encoding {
+            use super::*;
+            use crate::io::encoding::encoding_source_to_string;
+
+            // function to detect the encoding of a buffer
+            #[pyfunction]
+            pub fn detect_encoding(buffer: &[u8]) -> (&'static str, f64, &'static str) {
+                let detected = crate::io::encoding::detect_encoding(buffer);
+                (
+                    detected.encoding.name(),
+                    detected.confidence,
+                    encoding_source_to_string(detected.source),
+                )
+            }
+
+            // function to detect the declared html meta charset of a buffer
+            #[pyfunction]
+            pub fn detect_html_meta_charset(buffer: &[u8]) -> Option<&'static str> {
+                crate::io::encoding::detect_html_meta_charset(buffer).map(|e| e.name())
+            }
+
+            // function to check whether a buffer looks like text
+            #[pyfunction]
+            pub fn is_text(buffer: &[u8]) -> bool {
+                crate::io::encoding::is_text(buffer)
+            }
+
+            // function to detect the encoding of a buffer and decode it
+            #[pyfunction]
+            pub fn decode_bytes(buffer: &[u8]) -> (String, &'static str, f64, bool) {
+                let decoded = crate::io::encoding::decode_bytes(buffer);
+                (
+                    decoded.text,
+                    decoded.encoding.name(),
+                    decoded.confidence,
+                    decoded.had_errors,
+                )
+            }
+
+            // function to decode a buffer with a named encoding
+            #[pyfunction]
+            pub fn decode_with_encoding(buffer: &[u8], encoding: &str) -> PyResult<String> {
+                match crate::io::encoding::encoding_for_label(encoding) {
+                    Some(encoding) => {
+                        Ok(crate::io::encoding::decode_with_encoding(buffer, encoding).0)
+                    }
+                    None => Err(PyValueError::new_err(format!(
+                        "Unknown encoding: {}",
+                        encoding
+                    ))),
+                }
+            }
+
+            // function to read a file and decode it with encoding detection
+            #[pyfunction]
+            pub fn decode_file(path: &str) -> PyResult<(String, &'static str, f64, bool)> {
+                match crate::io::fs::files::read_file_text(path) {
+                    Ok(decoded) => Ok((
+                        decoded.text,
+                        decoded.encoding.name(),
+                        decoded.confidence,
+                        decoded.had_errors,
+                    )),
+                    Err(e) => Err(PyIOError::new_err(e.to_string())),
+                }
+            }
+
+            // function to read a gzipped file and decode it with encoding detection
+            #[pyfunction]
+            pub fn decode_gz_file(path: &str) -> PyResult<(String, &'static str, f64, bool)> {
+                match crate::io::fs::files::read_gz_file_text(path) {
+                    Ok(decoded) => Ok((
+                        decoded.text,
+                        decoded.encoding.name(),
+                        decoded.confidence,
+                        decoded.had_errors,
+                    )),
+                    Err(e) => Err(PyIOError::new_err(e.to_string())),
+                }
+            }
+        }
+
+        #[pymodule(submodule)]
+        mod fs {
+            use super::*;
+
+            #[pymodule_export]
+            use crate::io::fs::file_info::FileInfo;
+
+            #[pymodule(submodule)]
+            mod directories {
+                use super::*;
+
+                #[pyfunction]
+                pub fn get_files(path: &str) -> Vec<String> {
+                    crate::io::fs::directories::get_files(path)
+                }
 
                 #[pyfunction]
                 pub fn get_directories(path: &str) -> Vec<String> {
@@ -1076,11 +1983,43 @@ mod alea_preprocess {
                 }
 
                 #[pyfunction]
-                pub fn get_file_info_from_file(path: &str) -> FileInfo {
-                    crate::io::fs::file_info::get_file_info_from_file(path)
+                pub fn get_file_info_from_file(path: &str) -> PyResult<FileInfo> {
+                    crate::io::fs::file_info::get_file_info_from_file(path).map_err(to_py_err)
                 }
             }
         }
+
+        #[pymodule(submodule)]
+        mod shards {
+            use super::*;
+            use crate::io::shards::ShardWriter;
+
+            #[pymodule_export]
+            use crate::io::shards::ShardReader;
+
+            // function to write token sequences to a shard, one document per sequence
+            #[pyfunction]
+            #[pyo3(signature = (sequences, output_prefix, dtype="uint16", format="bin"))]
+            pub fn write_shard(
+                sequences: Vec<Vec<i32>>,
+                output_prefix: &str,
+                dtype: &str,
+                format: &str,
+            ) -> PyResult<(usize, usize)> {
+                let (dtype, format) = get_shard_options(dtype, format)?;
+                let mut writer = ShardWriter::create(output_prefix, dtype, format)
+                    .map_err(|e| PyIOError::new_err(e.to_string()))?;
+                for sequence in &sequences {
+                    writer
+                        .write_sequence(sequence)
+                        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+                    writer.end_document();
+                }
+                writer
+                    .finish()
+                    .map_err(|e| PyIOError::new_err(e.to_string()))
+            }
+        }
     }
 
     #[pymodule]
@@ -1099,24 +2038,55 @@ mod alea_preprocess {
                 };
 
                 #[pyfunction]
-                pub fn extract_buffer_text(buffer: &str) -> String {
-                    let parser =
-                        HtmlToPlainTextParser::new(ParserConfig::new(None, false, false), buffer);
-                    parser.to_plain_text()
+                pub fn extract_buffer_text(py: Python, buffer: &str) -> String {
+                    py.allow_threads(|| {
+                        let parser = HtmlToPlainTextParser::new(
+                            ParserConfig::new(None, false, false),
+                            buffer,
+                        );
+                        parser.to_plain_text()
+                    })
                 }
 
                 #[pyfunction]
                 pub fn extract_buffer_markdown(
+                    py: Python,
                     buffer: &str,
                     output_links: bool,
                     output_images: bool,
                 ) -> String {
-                    // crate::parsers::pdf::conversion::extract_buffer_text(buffer)
-                    let parser = HtmlToMarkdownParser::new(
-                        ParserConfig::new(None, output_links, output_images),
-                        buffer,
-                    );
-                    parser.to_markdown()
+                    py.allow_threads(|| {
+                        let parser = HtmlToMarkdownParser::new(
+                            ParserConfig::new(None, output_links, output_images),
+                            buffer,
+                        );
+                        parser.to_markdown()
+                    })
+                }
+
+                // function to convert several html buffers to plain text in parallel
+                #[pyfunction]
+                pub fn extract_buffer_text_batch(py: Python, buffers: Vec<String>) -> Vec<String> {
+                    py.allow_threads(|| {
+                        crate::parsers::html::conversion::extract_buffer_text_batch(&buffers)
+                    })
+                }
+
+                // function to convert several html buffers to markdown in parallel
+                #[pyfunction]
+                pub fn extract_buffer_markdown_batch(
+                    py: Python,
+                    buffers: Vec<String>,
+                    output_links: bool,
+                    output_images: bool,
+                ) -> Vec<String> {
+                    py.allow_threads(|| {
+                        crate::parsers::html::conversion::extract_buffer_markdown_batch(
+                            &buffers,
+                            output_links,
+                            output_images,
+                        )
+                    })
                 }
             }
         }
@@ -1142,27 +2112,49 @@ mod alea_preprocess {
                 }
 
                 #[pyfunction]
-                pub fn detect_buffer_type(buffer: &[u8]) -> PyDocumentType {
-                    match crate::parsers::pdf::detection::detect_buffer_type(buffer) {
+                pub fn detect_buffer_type(py: Python, buffer: &[u8]) -> PyResult<PyDocumentType> {
+                    let document_type = py.allow_threads(|| {
+                        crate::parsers::pdf::detection::detect_buffer_type(buffer)
+                    })?;
+                    Ok(match document_type {
                         DocumentType::Malformed => PyDocumentType::Unknown,
                         DocumentType::Text => PyDocumentType::Text,
                         DocumentType::ImagePreOCR => PyDocumentType::ImagePreOCR,
                         DocumentType::ImagePostOCR => PyDocumentType::ImagePostOCR,
                         DocumentType::Mixed => PyDocumentType::Mixed,
                         DocumentType::Unknown => PyDocumentType::Unknown,
-                    }
+                    })
                 }
 
+                // function to check whether an OCR'd PDF's text layer is too noisy to use
                 #[pyfunction]
-                pub fn detect_file_type(file_path: &str) -> PyDocumentType {
-                    match crate::parsers::pdf::detection::detect_file_type(file_path) {
+                pub fn is_ocr_text_unusable(
+                    py: Python,
+                    buffer: &[u8],
+                    text: &str,
+                ) -> PyResult<bool> {
+                    Ok(py.allow_threads(|| {
+                        crate::parsers::pdf::detection::is_ocr_text_unusable(
+                            buffer,
+                            text,
+                            &crate::algos::quality::noise::NoiseConfig::default(),
+                        )
+                    })?)
+                }
+
+                #[pyfunction]
+                pub fn detect_file_type(py: Python, file_path: &str) -> PyResult<PyDocumentType> {
+                    let document_type = py.allow_threads(|| {
+                        crate::parsers::pdf::detection::detect_file_type(file_path)
+                    })?;
+                    Ok(match document_type {
                         DocumentType::Malformed => PyDocumentType::Unknown,
                         DocumentType::Text => PyDocumentType::Text,
                         DocumentType::ImagePreOCR => PyDocumentType::ImagePreOCR,
                         DocumentType::ImagePostOCR => PyDocumentType::ImagePostOCR,
                         DocumentType::Mixed => PyDocumentType::Mixed,
                         DocumentType::Unknown => PyDocumentType::Unknown,
-                    }
+                    })
                 }
             }
 
@@ -1171,23 +2163,83 @@ mod alea_preprocess {
                 use super::*;
 
                 #[pyfunction]
-                pub fn extract_buffer_text(buffer: &[u8]) -> String {
-                    crate::parsers::pdf::conversion::extract_buffer_text(buffer)
+                pub fn extract_buffer_text(py: Python, buffer: &[u8]) -> PyResult<String> {
+                    Ok(py.allow_threads(|| {
+                        crate::parsers::pdf::conversion::extract_buffer_text(buffer)
+                    })?)
+                }
+
+                #[pyfunction]
+                pub fn extract_file_text(py: Python, file_path: &str) -> PyResult<String> {
+                    Ok(py.allow_threads(|| {
+                        crate::parsers::pdf::conversion::extract_file_text(file_path)
+                    })?)
+                }
+
+                #[pyfunction]
+                pub fn extract_buffer_markdown(py: Python, buffer: &[u8]) -> PyResult<String> {
+                    Ok(py.allow_threads(|| {
+                        crate::parsers::pdf::conversion::extract_buffer_markdown(buffer)
+                    })?)
+                }
+
+                #[pyfunction]
+                pub fn extract_file_markdown(py: Python, file_path: &str) -> PyResult<String> {
+                    Ok(py.allow_threads(|| {
+                        crate::parsers::pdf::conversion::extract_file_markdown(file_path)
+                    })?)
                 }
 
                 #[pyfunction]
-                pub fn extract_file_text(file_path: &str) -> String {
-                    crate::parsers::pdf::conversion::extract_file_text(file_path)
+                pub fn extract_buffer_text_batch(
+                    py: Python,
+                    buffers: Vec<Vec<u8>>,
+                ) -> Vec<Option<String>> {
+                    py.allow_threads(|| {
+                        crate::parsers::pdf::conversion::extract_buffer_text_batch(&buffers)
+                    })
+                    .into_iter()
+                    .map(Result::ok)
+                    .collect()
                 }
 
                 #[pyfunction]
-                pub fn extract_buffer_markdown(buffer: &[u8]) -> String {
-                    crate::parsers::pdf::conversion::extract_buffer_markdown(buffer)
+                pub fn extract_file_text_batch(
+                    py: Python,
+                    file_paths: Vec<String>,
+                ) -> Vec<Option<String>> {
+                    py.allow_threads(|| {
+                        crate::parsers::pdf::conversion::extract_file_text_batch(&file_paths)
+                    })
+                    .into_iter()
+                    .map(Result::ok)
+                    .collect()
                 }
 
                 #[pyfunction]
-                pub fn extract_file_markdown(file_path: &str) -> String {
-                    crate::parsers::pdf::conversion::extract_file_markdown(file_path)
+                pub fn extract_buffer_markdown_batch(
+                    py: Python,
+                    buffers: Vec<Vec<u8>>,
+                ) -> Vec<Option<String>> {
+                    py.allow_threads(|| {
+                        crate::parsers::pdf::conversion::extract_buffer_markdown_batch(&buffers)
+                    })
+                    .into_iter()
+                    .map(Result::ok)
+                    .collect()
+                }
+
+                #[pyfunction]
+                pub fn extract_file_markdown_batch(
+                    py: Python,
+                    file_paths: Vec<String>,
+                ) -> Vec<Option<String>> {
+                    py.allow_threads(|| {
+                        crate::parsers::pdf::conversion::extract_file_markdown_batch(&file_paths)
+                    })
+                    .into_iter()
+                    .map(Result::ok)
+                    .collect()
                 }
             }
         }
@@ -1209,8 +2261,10 @@ mod alea_preprocess {
                     buffer: &[u8],
                     server_url: &str,
                 ) -> PyResult<Vec<PyObject>> {
-                    let client = SyncTikaClient::new(server_url);
-                    convert_values(py, client.get_recursive_metadata_buffer(buffer))
+                    let values = py.allow_threads(|| {
+                        SyncTikaClient::new(server_url).get_recursive_metadata_buffer(buffer)
+                    })?;
+                    convert_values(py, values)
                 }
 
                 #[pyfunction]
@@ -1219,56 +2273,66 @@ mod alea_preprocess {
                     file_path: &str,
                     server_url: &str,
                 ) -> PyResult<Vec<PyObject>> {
-                    let client = SyncTikaClient::new(server_url);
-                    convert_values(py, client.get_recursive_metadata_file(file_path))
+                    let values = py.allow_threads(|| {
+                        SyncTikaClient::new(server_url).get_recursive_metadata_file(file_path)
+                    })?;
+                    convert_values(py, values)
                 }
 
                 #[pyfunction]
                 pub fn get_recursive_content_html_buffer(
+                    py: Python,
                     buffer: &[u8],
                     server_url: &str,
                 ) -> PyResult<Vec<String>> {
-                    let client = SyncTikaClient::new(server_url);
-                    Ok(client.get_recursive_content_html_buffer(buffer))
+                    Ok(py.allow_threads(|| {
+                        SyncTikaClient::new(server_url).get_recursive_content_html_buffer(buffer)
+                    })?)
                 }
 
                 #[pyfunction]
                 pub fn get_recursive_content_html_file(
+                    py: Python,
                     file_path: &str,
                     server_url: &str,
                 ) -> PyResult<Vec<String>> {
-                    let client = SyncTikaClient::new(server_url);
-                    Ok(client.get_recursive_content_html_file(file_path))
+                    Ok(py.allow_threads(|| {
+                        SyncTikaClient::new(server_url).get_recursive_content_html_file(file_path)
+                    })?)
                 }
 
                 #[pyfunction]
                 pub fn get_recursive_content_markdown_buffer(
+                    py: Python,
                     buffer: &[u8],
                     server_url: &str,
                     output_links: bool,
                     output_images: bool,
                 ) -> PyResult<Vec<String>> {
-                    let client = SyncTikaClient::new(server_url);
-                    Ok(client.get_recursive_content_markdown_buffer(
-                        buffer,
-                        output_links,
-                        output_images,
-                    ))
+                    Ok(py.allow_threads(|| {
+                        SyncTikaClient::new(server_url).get_recursive_content_markdown_buffer(
+                            buffer,
+                            output_links,
+                            output_images,
+                        )
+                    })?)
                 }
 
                 #[pyfunction]
                 pub fn get_recursive_content_markdown_file(
+                    py: Python,
                     file_path: &str,
                     server_url: &str,
                     output_links: bool,
                     output_images: bool,
                 ) -> PyResult<Vec<String>> {
-                    let client = SyncTikaClient::new(server_url);
-                    Ok(client.get_recursive_content_markdown_file(
-                        file_path,
-                        output_links,
-                        output_images,
-                    ))
+                    Ok(py.allow_threads(|| {
+                        SyncTikaClient::new(server_url).get_recursive_content_markdown_file(
+                            file_path,
+                            output_links,
+                            output_images,
+                        )
+                    })?)
                 }
             }
         }
@@ -1290,14 +2354,247 @@ mod alea_preprocess {
             ) -> PyResult<Vec<Vec<i32>>> {
                 Ok(sequences::split_sequence_max(&sequence, max_size))
             }
+
+            fn codec_from_option(codec: Option<&str>) -> PyResult<Option<sequences::ContentCodec>> {
+                match codec {
+                    None => Ok(None),
+                    Some(name) => match sequences::content_codec_from_string(name) {
+                        Some(codec) => Ok(Some(codec)),
+                        None => Err(PyValueError::new_err(format!("Invalid codec: {}", name))),
+                    },
+                }
+            }
+
+            // decode base64 content, detecting the compression codec if not given
+            #[pyfunction]
+            #[pyo3(signature = (encoded_content, codec=None))]
+            pub fn extract_content<'a>(
+                py: Python<'a>,
+                encoded_content: &str,
+                codec: Option<&str>,
+            ) -> PyResult<Bound<'a, PyBytes>> {
+                let codec = codec_from_option(codec)?;
+                sequences::decode_content(encoded_content, codec)
+                    .map(|content| PyBytes::new_bound(py, &content))
+                    .map_err(to_py_err)
+            }
+
+            // compress and base64 encode content
+            #[pyfunction]
+            #[pyo3(signature = (content, codec="zlib"))]
+            pub fn encode_content(content: &[u8], codec: &str) -> PyResult<String> {
+                let codec = codec_from_option(Some(codec))?.unwrap();
+                sequences::encode_content(content, codec).map_err(to_py_err)
+            }
+
+            // detect the compression codec of base64 content
+            #[pyfunction]
+            pub fn detect_codec(encoded_content: &str) -> PyResult<String> {
+                use base64::Engine;
+                let decoded_content = base64::prelude::BASE64_STANDARD
+                    .decode(encoded_content.as_bytes())
+                    .map_err(|e| to_py_err(sequences::ContentError::from(e)))?;
+                Ok(sequences::detect_codec(&decoded_content).to_string())
+            }
+        }
+
+        #[pymodule(submodule)]
+        mod dpo {
+            use super::*;
+            use crate::tasks::dpo;
+            use crate::tasks::dpo::DpoConfig;
+
+            #[pymodule_export]
+            use crate::tasks::dpo::DpoSample;
+
+            // function to get a DPO sample from a prompt and chosen and rejected responses
+            #[pyfunction]
+            #[pyo3(signature = (prompt, chosen, rejected, tokenizer_name, max_seq_length, eos_token_id=None, pad_token_id=0, label_mask_id=-100, max_prompt_length=256, similarity_threshold=0.95))]
+            #[allow(clippy::too_many_arguments)]
+            pub fn get_dpo_sample(
+                prompt: &str,
+                chosen: &str,
+                rejected: &str,
+                tokenizer_name: &str,
+                max_seq_length: usize,
+                eos_token_id: Option<i32>,
+                pad_token_id: i32,
+                label_mask_id: i32,
+                max_prompt_length: usize,
+                similarity_threshold: f64,
+            ) -> PyResult<DpoSample> {
+                dpo::get_dpo_sample(
+                    prompt,
+                    chosen,
+                    rejected,
+                    tokenizer_name,
+                    max_seq_length,
+                    eos_token_id,
+                    pad_token_id,
+                    label_mask_id,
+                    &DpoConfig::new(max_prompt_length, similarity_threshold),
+                )
+                .map_err(to_py_err)
+            }
+
+            // function to get DPO samples from a JSONL file of preference pairs
+            #[pyfunction]
+            #[pyo3(signature = (path, tokenizer_name, max_seq_length, eos_token_id=None, pad_token_id=0, label_mask_id=-100, max_prompt_length=256, similarity_threshold=0.95))]
+            #[allow(clippy::too_many_arguments)]
+            pub fn get_dpo_samples_from_file(
+                path: &str,
+                tokenizer_name: &str,
+                max_seq_length: usize,
+                eos_token_id: Option<i32>,
+                pad_token_id: i32,
+                label_mask_id: i32,
+                max_prompt_length: usize,
+                similarity_threshold: f64,
+            ) -> PyResult<Vec<DpoSample>> {
+                dpo::get_dpo_samples_from_file(
+                    path,
+                    tokenizer_name,
+                    max_seq_length,
+                    eos_token_id,
+                    pad_token_id,
+                    label_mask_id,
+                    &DpoConfig::new(max_prompt_length, similarity_threshold),
+                )
+                .map_err(to_py_err)
+            }
+        }
+
+        #[pymodule(submodule)]
+        mod fim {
+            use super::*;
+            use crate::tasks::fim;
+            use crate::tasks::fim::{fim_boundary_from_string, FimBoundary, FimConfig, FimSample};
+            use crate::tasks::sampling;
+
+            fn get_fim_config(fim_rate: f64, spm_rate: f64, boundary: &str) -> PyResult<FimConfig> {
+                let fim_boundary = fim_boundary_from_string(boundary).ok_or_else(|| {
+                    pyo3::exceptions::PyValueError::new_err(format!(
+                        "Invalid boundary: {}",
+                        boundary
+                    ))
+                })?;
+                Ok(FimConfig::new(fim_rate, spm_rate, fim_boundary))
+            }
+
+            // function to get fill-in-the-middle samples from tokens
+            #[pyfunction]
+            #[pyo3(signature = (tokens, max_seq_length, prefix_token_id, middle_token_id, suffix_token_id, eos_token_id, pad_token_id, label_mask_id, fim_rate=0.5, spm_rate=0.5, seed=None))]
+            #[allow(clippy::too_many_arguments)]
+            pub fn get_fim_samples_from_tokens(
+                tokens: Vec<i32>,
+                max_seq_length: usize,
+                prefix_token_id: i32,
+                middle_token_id: i32,
+                suffix_token_id: i32,
+                eos_token_id: i32,
+                pad_token_id: i32,
+                label_mask_id: i32,
+                fim_rate: f64,
+                spm_rate: f64,
+                seed: Option<u64>,
+            ) -> PyResult<Vec<FimSample>> {
+                fim::get_fim_samples_from_tokens(
+                    &tokens,
+                    max_seq_length,
+                    prefix_token_id,
+                    middle_token_id,
+                    suffix_token_id,
+                    eos_token_id,
+                    pad_token_id,
+                    label_mask_id,
+                    &FimConfig::new(fim_rate, spm_rate, FimBoundary::Character),
+                    &mut sampling::get_token_document_rng(seed, &tokens),
+                )
+                .map_err(to_py_err)
+            }
+
+            // function to get fill-in-the-middle samples from text
+            #[pyfunction]
+            #[pyo3(signature = (text, max_seq_length, tokenizer_name, prefix_token_id, middle_token_id, suffix_token_id, eos_token_id, pad_token_id, label_mask_id, fim_rate=0.5, spm_rate=0.5, boundary="character", seed=None))]
+            #[allow(clippy::too_many_arguments)]
+            pub fn get_fim_samples_from_text(
+                text: &str,
+                max_seq_length: usize,
+                tokenizer_name: &str,
+                prefix_token_id: i32,
+                middle_token_id: i32,
+                suffix_token_id: i32,
+                eos_token_id: i32,
+                pad_token_id: i32,
+                label_mask_id: i32,
+                fim_rate: f64,
+                spm_rate: f64,
+                boundary: &str,
+                seed: Option<u64>,
+            ) -> PyResult<Vec<FimSample>> {
+                fim::get_fim_samples_from_text(
+                    text,
+                    max_seq_length,
+                    tokenizer_name,
+                    prefix_token_id,
+                    middle_token_id,
+                    suffix_token_id,
+                    eos_token_id,
+                    pad_token_id,
+                    label_mask_id,
+                    &get_fim_config(fim_rate, spm_rate, boundary)?,
+                    &mut sampling::get_document_rng(seed, text.as_bytes()),
+                )
+                .map_err(to_py_err)
+            }
+
+            // function to get fill-in-the-middle samples from encoded content
+            #[pyfunction]
+            #[pyo3(signature = (encoded_content, max_seq_length, tokenizer_name, prefix_token_id, middle_token_id, suffix_token_id, eos_token_id, pad_token_id, label_mask_id, fim_rate=0.5, spm_rate=0.5, boundary="character", seed=None))]
+            #[allow(clippy::too_many_arguments)]
+            pub fn get_fim_samples_from_content(
+                encoded_content: &str,
+                max_seq_length: usize,
+                tokenizer_name: &str,
+                prefix_token_id: i32,
+                middle_token_id: i32,
+                suffix_token_id: i32,
+                eos_token_id: i32,
+                pad_token_id: i32,
+                label_mask_id: i32,
+                fim_rate: f64,
+                spm_rate: f64,
+                boundary: &str,
+                seed: Option<u64>,
+            ) -> PyResult<Vec<FimSample>> {
+                fim::get_fim_samples_from_content(
+                    encoded_content,
+                    max_seq_length,
+                    tokenizer_name,
+                    prefix_token_id,
+                    middle_token_id,
+                    suffix_token_id,
+                    eos_token_id,
+                    pad_token_id,
+                    label_mask_id,
+                    &get_fim_config(fim_rate, spm_rate, boundary)?,
+                    &mut sampling::get_document_rng(seed, encoded_content.as_bytes()),
+                )
+                .map_err(to_py_err)
+            }
         }
 
         #[pymodule(submodule)]
         mod mlm {
             use super::*;
+            use crate::io::shards::SampleShardWriter;
             use crate::tasks::mlm;
+            use crate::tasks::mlm::{MaskedSample, MaskingConfig};
+            use crate::tasks::sampling;
 
             #[pyfunction]
+            #[pyo3(signature = (tokens, cls_token_id, mask_token_id, sep_token_id, pad_token_id, label_mask_id, max_seq_length, prob_mask, prob_mask_token=1.0, prob_random_token=0.0, vocab_size=0, whole_word_masking=false, word_ids=None, excluded_token_ids=Vec::new(), min_masked_tokens=1, seed=None))]
+            #[allow(clippy::too_many_arguments)]
             pub fn get_masked_sample(
                 tokens: Vec<i32>,
                 cls_token_id: i32,
@@ -1307,20 +2604,41 @@ mod alea_preprocess {
                 label_mask_id: i32,
                 max_seq_length: usize,
                 prob_mask: f64,
-            ) -> (Vec<i32>, Vec<i32>, Vec<i32>, Vec<i32>) {
+                prob_mask_token: f64,
+                prob_random_token: f64,
+                vocab_size: usize,
+                whole_word_masking: bool,
+                word_ids: Option<Vec<i64>>,
+                excluded_token_ids: Vec<i32>,
+                min_masked_tokens: usize,
+                seed: Option<u64>,
+            ) -> MaskedSample {
+                let config = MaskingConfig::new(
+                    prob_mask,
+                    prob_mask_token,
+                    prob_random_token,
+                    vocab_size,
+                    whole_word_masking,
+                    excluded_token_ids,
+                    min_masked_tokens,
+                );
                 mlm::get_masked_sample(
                     &tokens,
+                    word_ids.as_deref(),
                     cls_token_id,
                     mask_token_id,
                     sep_token_id,
                     pad_token_id,
                     label_mask_id,
                     max_seq_length,
-                    prob_mask,
+                    &config,
+                    &mut sampling::get_token_document_rng(seed, &tokens),
                 )
             }
 
             #[pyfunction]
+            #[pyo3(signature = (tokens, max_seq_length, cls_token_id, mask_token_id, sep_token_id, pad_token_id, label_mask_id, prob_mask, prob_mask_token=1.0, prob_random_token=0.0, vocab_size=0, whole_word_masking=false, word_ids=None, excluded_token_ids=Vec::new(), min_masked_tokens=1, seed=None))]
+            #[allow(clippy::too_many_arguments)]
             pub fn get_masked_samples_from_tokens(
                 tokens: Vec<i32>,
                 max_seq_length: usize,
@@ -1330,20 +2648,41 @@ mod alea_preprocess {
                 pad_token_id: i32,
                 label_mask_id: i32,
                 prob_mask: f64,
-            ) -> Vec<(Vec<i32>, Vec<i32>, Vec<i32>, Vec<i32>)> {
+                prob_mask_token: f64,
+                prob_random_token: f64,
+                vocab_size: usize,
+                whole_word_masking: bool,
+                word_ids: Option<Vec<i64>>,
+                excluded_token_ids: Vec<i32>,
+                min_masked_tokens: usize,
+                seed: Option<u64>,
+            ) -> Vec<MaskedSample> {
+                let config = MaskingConfig::new(
+                    prob_mask,
+                    prob_mask_token,
+                    prob_random_token,
+                    vocab_size,
+                    whole_word_masking,
+                    excluded_token_ids,
+                    min_masked_tokens,
+                );
                 mlm::get_masked_samples_from_tokens(
                     &tokens,
+                    word_ids.as_deref(),
                     max_seq_length,
                     cls_token_id,
                     mask_token_id,
                     sep_token_id,
                     pad_token_id,
                     label_mask_id,
-                    prob_mask,
+                    &config,
+                    &mut sampling::get_token_document_rng(seed, &tokens),
                 )
             }
 
             #[pyfunction]
+            #[pyo3(signature = (encoded_content, max_seq_length, tokenizer_name, cls_token_id, mask_token_id, sep_token_id, pad_token_id, label_mask_id, prob_mask, prob_mask_token=1.0, prob_random_token=0.0, vocab_size=0, whole_word_masking=false, excluded_token_ids=Vec::new(), min_masked_tokens=1, seed=None))]
+            #[allow(clippy::too_many_arguments)]
             pub fn get_masked_samples_from_content(
                 encoded_content: &str,
                 max_seq_length: usize,
@@ -1354,7 +2693,23 @@ mod alea_preprocess {
                 pad_token_id: i32,
                 label_mask_id: i32,
                 prob_mask: f64,
-            ) -> Vec<(Vec<i32>, Vec<i32>, Vec<i32>, Vec<i32>)> {
+                prob_mask_token: f64,
+                prob_random_token: f64,
+                vocab_size: usize,
+                whole_word_masking: bool,
+                excluded_token_ids: Vec<i32>,
+                min_masked_tokens: usize,
+                seed: Option<u64>,
+            ) -> PyResult<Vec<MaskedSample>> {
+                let config = MaskingConfig::new(
+                    prob_mask,
+                    prob_mask_token,
+                    prob_random_token,
+                    vocab_size,
+                    whole_word_masking,
+                    excluded_token_ids,
+                    min_masked_tokens,
+                );
                 mlm::get_masked_samples_from_content(
                     encoded_content,
                     max_seq_length,
@@ -1364,10 +2719,517 @@ mod alea_preprocess {
                     sep_token_id,
                     pad_token_id,
                     label_mask_id,
+                    &config,
+                    &mut sampling::get_document_rng(seed, encoded_content.as_bytes()),
+                )
+                .map_err(to_py_err)
+            }
+
+            type MaskedArrays = (PyObject, PyObject, PyObject, PyObject);
+
+            // convert masked samples into one 2-D int32 array per field
+            fn masked_samples_to_numpy(
+                py: Python,
+                samples: Vec<MaskedSample>,
+                pad_token_id: i32,
+                label_mask_id: i32,
+            ) -> PyResult<MaskedArrays> {
+                let mut input_ids = Vec::with_capacity(samples.len());
+                let mut attention_mask = Vec::with_capacity(samples.len());
+                let mut token_type_ids = Vec::with_capacity(samples.len());
+                let mut labels = Vec::with_capacity(samples.len());
+                for sample in samples {
+                    input_ids.push(sample.0);
+                    attention_mask.push(sample.1);
+                    token_type_ids.push(sample.2);
+                    labels.push(sample.3);
+                }
+                Ok((
+                    to_numpy_padded(py, &input_ids, pad_token_id)?.0,
+                    to_numpy_padded(py, &attention_mask, 0)?.0,
+                    to_numpy_padded(py, &token_type_ids, 0)?.0,
+                    to_numpy_padded(py, &labels, label_mask_id)?.0,
+                ))
+            }
+
+            #[pyfunction]
+            #[pyo3(signature = (tokens, max_seq_length, cls_token_id, mask_token_id, sep_token_id, pad_token_id, label_mask_id, prob_mask, prob_mask_token=1.0, prob_random_token=0.0, vocab_size=0, whole_word_masking=false, word_ids=None, excluded_token_ids=Vec::new(), min_masked_tokens=1, seed=None))]
+            #[allow(clippy::too_many_arguments)]
+            pub fn get_masked_samples_from_tokens_numpy(
+                py: Python,
+                tokens: Vec<i32>,
+                max_seq_length: usize,
+                cls_token_id: i32,
+                mask_token_id: i32,
+                sep_token_id: i32,
+                pad_token_id: i32,
+                label_mask_id: i32,
+                prob_mask: f64,
+                prob_mask_token: f64,
+                prob_random_token: f64,
+                vocab_size: usize,
+                whole_word_masking: bool,
+                word_ids: Option<Vec<i64>>,
+                excluded_token_ids: Vec<i32>,
+                min_masked_tokens: usize,
+                seed: Option<u64>,
+            ) -> PyResult<MaskedArrays> {
+                let samples = get_masked_samples_from_tokens(
+                    tokens,
+                    max_seq_length,
+                    cls_token_id,
+                    mask_token_id,
+                    sep_token_id,
+                    pad_token_id,
+                    label_mask_id,
+                    prob_mask,
+                    prob_mask_token,
+                    prob_random_token,
+                    vocab_size,
+                    whole_word_masking,
+                    word_ids,
+                    excluded_token_ids,
+                    min_masked_tokens,
+                    seed,
+                );
+                masked_samples_to_numpy(py, samples, pad_token_id, label_mask_id)
+            }
+
+            #[pyfunction]
+            #[pyo3(signature = (encoded_content, max_seq_length, tokenizer_name, cls_token_id, mask_token_id, sep_token_id, pad_token_id, label_mask_id, prob_mask, prob_mask_token=1.0, prob_random_token=0.0, vocab_size=0, whole_word_masking=false, excluded_token_ids=Vec::new(), min_masked_tokens=1, seed=None))]
+            #[allow(clippy::too_many_arguments)]
+            pub fn get_masked_samples_from_content_numpy(
+                py: Python,
+                encoded_content: &str,
+                max_seq_length: usize,
+                tokenizer_name: &str,
+                cls_token_id: i32,
+                mask_token_id: i32,
+                sep_token_id: i32,
+                pad_token_id: i32,
+                label_mask_id: i32,
+                prob_mask: f64,
+                prob_mask_token: f64,
+                prob_random_token: f64,
+                vocab_size: usize,
+                whole_word_masking: bool,
+                excluded_token_ids: Vec<i32>,
+                min_masked_tokens: usize,
+                seed: Option<u64>,
+            ) -> PyResult<MaskedArrays> {
+                let samples = get_masked_samples_from_content(
+                    encoded_content,
+                    max_seq_length,
+                    tokenizer_name,
+                    cls_token_id,
+                    mask_token_id,
+                    sep_token_id,
+                    pad_token_id,
+                    label_mask_id,
+                    prob_mask,
+                    prob_mask_token,
+                    prob_random_token,
+                    vocab_size,
+                    whole_word_masking,
+                    excluded_token_ids,
+                    min_masked_tokens,
+                    seed,
+                )?;
+                masked_samples_to_numpy(py, samples, pad_token_id, label_mask_id)
+            }
+
+            // function to write masked samples from encoded contents to per-field shards
+            #[pyfunction]
+            #[pyo3(signature = (encoded_contents, output_prefix, max_seq_length, tokenizer_name, cls_token_id, mask_token_id, sep_token_id, pad_token_id, label_mask_id, prob_mask, prob_mask_token=1.0, prob_random_token=0.0, vocab_size=0, whole_word_masking=false, excluded_token_ids=Vec::new(), min_masked_tokens=1, dtype="uint16", format="bin", seed=None))]
+            #[allow(clippy::too_many_arguments)]
+            pub fn write_masked_samples_from_content(
+                encoded_contents: Vec<String>,
+                output_prefix: &str,
+                max_seq_length: usize,
+                tokenizer_name: &str,
+                cls_token_id: i32,
+                mask_token_id: i32,
+                sep_token_id: i32,
+                pad_token_id: i32,
+                label_mask_id: i32,
+                prob_mask: f64,
+                prob_mask_token: f64,
+                prob_random_token: f64,
+                vocab_size: usize,
+                whole_word_masking: bool,
+                excluded_token_ids: Vec<i32>,
+                min_masked_tokens: usize,
+                dtype: &str,
+                format: &str,
+                seed: Option<u64>,
+            ) -> PyResult<usize> {
+                let (dtype, format) = get_shard_options(dtype, format)?;
+                let config = MaskingConfig::new(
                     prob_mask,
+                    prob_mask_token,
+                    prob_random_token,
+                    vocab_size,
+                    whole_word_masking,
+                    excluded_token_ids,
+                    min_masked_tokens,
+                );
+                let mut writer = SampleShardWriter::create(
+                    output_prefix,
+                    &mlm::get_masked_sample_shard_fields(dtype),
+                    format,
                 )
+                .map_err(|e| PyIOError::new_err(e.to_string()))?;
+                for encoded_content in &encoded_contents {
+                    let samples = mlm::get_masked_samples_from_content(
+                        encoded_content,
+                        max_seq_length,
+                        tokenizer_name,
+                        cls_token_id,
+                        mask_token_id,
+                        sep_token_id,
+                        pad_token_id,
+                        label_mask_id,
+                        &config,
+                        &mut sampling::get_document_rng(seed, encoded_content.as_bytes()),
+                    )
+                    .map_err(to_py_err)?;
+                    mlm::write_masked_samples(&mut writer, &samples)
+                        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+                }
+                writer
+                    .finish()
+                    .map_err(|e| PyIOError::new_err(e.to_string()))
             }
         }
+
+        #[pymodule(submodule)]
+        mod packing {
+            use super::*;
+            use crate::io::shards::SampleShardWriter;
+            use crate::tasks::packing;
+            use crate::tasks::packing::{packing_strategy_from_string, PackingConfig};
+
+            #[pymodule_export]
+            use crate::tasks::packing::PackedBlock;
+
+            fn get_packing_config(
+                block_size: usize,
+                bos_token_id: Option<i32>,
+                eos_token_id: Option<i32>,
+                pad_token_id: i32,
+                strategy: &str,
+                drop_last: bool,
+            ) -> PyResult<PackingConfig> {
+                let packing_strategy = packing_strategy_from_string(strategy).ok_or_else(|| {
+                    pyo3::exceptions::PyValueError::new_err(format!(
+                        "Invalid strategy: {}",
+                        strategy
+                    ))
+                })?;
+                if block_size == 0 {
+                    return Err(pyo3::exceptions::PyValueError::new_err(
+                        "block_size must be positive",
+                    ));
+                }
+                Ok(PackingConfig::new(
+                    block_size,
+                    bos_token_id,
+                    eos_token_id,
+                    pad_token_id,
+                    packing_strategy,
+                    drop_last,
+                ))
+            }
+
+            // function to pack token sequences into fixed-length blocks
+            #[pyfunction]
+            #[pyo3(signature = (documents, block_size, bos_token_id=None, eos_token_id=None, pad_token_id=0, strategy="concatenate", drop_last=false))]
+            pub fn pack_sequences(
+                documents: Vec<Vec<i32>>,
+                block_size: usize,
+                bos_token_id: Option<i32>,
+                eos_token_id: Option<i32>,
+                pad_token_id: i32,
+                strategy: &str,
+                drop_last: bool,
+            ) -> PyResult<Vec<PackedBlock>> {
+                let config = get_packing_config(
+                    block_size,
+                    bos_token_id,
+                    eos_token_id,
+                    pad_token_id,
+                    strategy,
+                    drop_last,
+                )?;
+                Ok(packing::pack_sequences(&documents, &config))
+            }
+
+            // function to tokenize and pack encoded contents into fixed-length blocks
+            #[pyfunction]
+            #[pyo3(signature = (encoded_contents, tokenizer_name, block_size, bos_token_id=None, eos_token_id=None, pad_token_id=0, strategy="concatenate", drop_last=false))]
+            #[allow(clippy::too_many_arguments)]
+            pub fn pack_sequences_from_content(
+                encoded_contents: Vec<String>,
+                tokenizer_name: &str,
+                block_size: usize,
+                bos_token_id: Option<i32>,
+                eos_token_id: Option<i32>,
+                pad_token_id: i32,
+                strategy: &str,
+                drop_last: bool,
+            ) -> PyResult<Vec<PackedBlock>> {
+                let config = get_packing_config(
+                    block_size,
+                    bos_token_id,
+                    eos_token_id,
+                    pad_token_id,
+                    strategy,
+                    drop_last,
+                )?;
+                packing::pack_sequences_from_content(&encoded_contents, tokenizer_name, &config)
+                    .map_err(to_py_err)
+            }
+
+            // function to tokenize and pack encoded contents into per-field shards
+            #[pyfunction]
+            #[pyo3(signature = (encoded_contents, output_prefix, tokenizer_name, block_size, bos_token_id=None, eos_token_id=None, pad_token_id=0, strategy="concatenate", drop_last=false, dtype="uint16", format="bin"))]
+            #[allow(clippy::too_many_arguments)]
+            pub fn write_packed_sequences_from_content(
+                encoded_contents: Vec<String>,
+                output_prefix: &str,
+                tokenizer_name: &str,
+                block_size: usize,
+                bos_token_id: Option<i32>,
+                eos_token_id: Option<i32>,
+                pad_token_id: i32,
+                strategy: &str,
+                drop_last: bool,
+                dtype: &str,
+                format: &str,
+            ) -> PyResult<usize> {
+                let (dtype, format) = get_shard_options(dtype, format)?;
+                let config = get_packing_config(
+                    block_size,
+                    bos_token_id,
+                    eos_token_id,
+                    pad_token_id,
+                    strategy,
+                    drop_last,
+                )?;
+                let blocks = packing::pack_sequences_from_content(
+                    &encoded_contents,
+                    tokenizer_name,
+                    &config,
+                )
+                .map_err(to_py_err)?;
+                let mut writer = SampleShardWriter::create(
+                    output_prefix,
+                    &packing::get_packed_block_shard_fields(dtype),
+                    format,
+                )
+                .map_err(|e| PyIOError::new_err(e.to_string()))?;
+                packing::write_packed_blocks(&mut writer, &blocks)
+                    .map_err(|e| PyValueError::new_err(e.to_string()))?;
+                writer
+                    .finish()
+                    .map_err(|e| PyIOError::new_err(e.to_string()))
+            }
+        }
+
+        #[pymodule(submodule)]
+        mod sft {
+            use super::*;
+            use crate::tasks::sft;
+            use crate::tasks::sft::{
+                chat_template_from_string, ChatMessage, ChatTemplate, SftSample,
+            };
+            use std::collections::HashMap;
+
+            fn get_chat_template(template: &str) -> PyResult<ChatTemplate> {
+                chat_template_from_string(template).ok_or_else(|| {
+                    pyo3::exceptions::PyValueError::new_err(format!(
+                        "Invalid template: {}",
+                        template
+                    ))
+                })
+            }
+
+            fn get_chat_messages(
+                messages: Vec<HashMap<String, String>>,
+            ) -> PyResult<Vec<ChatMessage>> {
+                messages
+                    .iter()
+                    .map(
+                        |message| match (message.get("role"), message.get("content")) {
+                            (Some(role), Some(content)) => Ok(ChatMessage::new(role, content)),
+                            _ => Err(pyo3::exceptions::PyValueError::new_err(
+                                "Messages must have role and content fields",
+                            )),
+                        },
+                    )
+                    .collect()
+            }
+
+            // function to render a conversation with a chat template
+            #[pyfunction]
+            #[pyo3(signature = (messages, template="chatml"))]
+            pub fn render_conversation(
+                messages: Vec<HashMap<String, String>>,
+                template: &str,
+            ) -> PyResult<String> {
+                Ok(sft::render_conversation(
+                    &get_chat_messages(messages)?,
+                    &get_chat_template(template)?,
+                )
+                .text)
+            }
+
+            // function to get an SFT sample with assistant-only labels from a conversation
+            #[pyfunction]
+            #[pyo3(signature = (messages, tokenizer_name, max_seq_length, template="chatml", pad_token_id=0, label_mask_id=-100))]
+            pub fn get_sft_sample(
+                messages: Vec<HashMap<String, String>>,
+                tokenizer_name: &str,
+                max_seq_length: usize,
+                template: &str,
+                pad_token_id: i32,
+                label_mask_id: i32,
+            ) -> PyResult<SftSample> {
+                sft::get_sft_sample(
+                    &get_chat_messages(messages)?,
+                    tokenizer_name,
+                    &get_chat_template(template)?,
+                    max_seq_length,
+                    pad_token_id,
+                    label_mask_id,
+                )
+                .map_err(to_py_err)
+            }
+
+            // function to get SFT samples from a JSONL file of conversations
+            #[pyfunction]
+            #[pyo3(signature = (path, tokenizer_name, max_seq_length, template="chatml", field="messages", pad_token_id=0, label_mask_id=-100))]
+            pub fn get_sft_samples_from_file(
+                path: &str,
+                tokenizer_name: &str,
+                max_seq_length: usize,
+                template: &str,
+                field: &str,
+                pad_token_id: i32,
+                label_mask_id: i32,
+            ) -> PyResult<Vec<SftSample>> {
+                sft::get_sft_samples_from_file(
+                    path,
+                    field,
+                    tokenizer_name,
+                    &get_chat_template(template)?,
+                    max_seq_length,
+                    pad_token_id,
+                    label_mask_id,
+                )
+                .map_err(to_py_err)
+            }
+        }
+
+        #[pymodule(submodule)]
+        mod span_corruption {
+            use super::*;
+            use crate::tasks::sampling;
+            use crate::tasks::span_corruption;
+            use crate::tasks::span_corruption::{SpanCorruptionConfig, SpanCorruptionSample};
+
+            #[pyfunction]
+            #[pyo3(signature = (max_seq_length, noise_density=0.15, mean_span_length=3.0))]
+            pub fn get_span_corruption_lengths(
+                max_seq_length: usize,
+                noise_density: f64,
+                mean_span_length: f64,
+            ) -> PyResult<(usize, usize)> {
+                span_corruption::get_span_corruption_lengths(
+                    max_seq_length,
+                    &SpanCorruptionConfig::new(noise_density, mean_span_length)
+                        .map_err(to_py_err)?,
+                )
+                .map_err(to_py_err)
+            }
+
+            #[pyfunction]
+            #[pyo3(signature = (tokens, max_seq_length, sentinel_token_ids, eos_token_id, pad_token_id, label_mask_id, noise_density=0.15, mean_span_length=3.0, seed=None))]
+            #[allow(clippy::too_many_arguments)]
+            pub fn get_span_corruption_samples_from_tokens(
+                tokens: Vec<i32>,
+                max_seq_length: usize,
+                sentinel_token_ids: Vec<i32>,
+                eos_token_id: i32,
+                pad_token_id: i32,
+                label_mask_id: i32,
+                noise_density: f64,
+                mean_span_length: f64,
+                seed: Option<u64>,
+            ) -> PyResult<Vec<SpanCorruptionSample>> {
+                span_corruption::get_span_corruption_samples_from_tokens(
+                    &tokens,
+                    max_seq_length,
+                    &sentinel_token_ids,
+                    eos_token_id,
+                    pad_token_id,
+                    label_mask_id,
+                    &SpanCorruptionConfig::new(noise_density, mean_span_length)
+                        .map_err(to_py_err)?,
+                    &mut sampling::get_token_document_rng(seed, &tokens),
+                )
+                .map_err(to_py_err)
+            }
+
+            #[pyfunction]
+            #[pyo3(signature = (encoded_content, max_seq_length, tokenizer_name, sentinel_token_ids, eos_token_id, pad_token_id, label_mask_id, noise_density=0.15, mean_span_length=3.0, seed=None))]
+            #[allow(clippy::too_many_arguments)]
+            pub fn get_span_corruption_samples_from_content(
+                encoded_content: &str,
+                max_seq_length: usize,
+                tokenizer_name: &str,
+                sentinel_token_ids: Vec<i32>,
+                eos_token_id: i32,
+                pad_token_id: i32,
+                label_mask_id: i32,
+                noise_density: f64,
+                mean_span_length: f64,
+                seed: Option<u64>,
+            ) -> PyResult<Vec<SpanCorruptionSample>> {
+                span_corruption::get_span_corruption_samples_from_content(
+                    encoded_content,
+                    max_seq_length,
+                    tokenizer_name,
+                    &sentinel_token_ids,
+                    eos_token_id,
+                    pad_token_id,
+                    label_mask_id,
+                    &SpanCorruptionConfig::new(noise_density, mean_span_length)
+                        .map_err(to_py_err)?,
+                    &mut sampling::get_document_rng(seed, encoded_content.as_bytes()),
+                )
+                .map_err(to_py_err)
+            }
+        }
+    }
+
+    #[pymodule]
+    mod errors {
+        use super::*;
+
+        #[pymodule_init]
+        fn init(m: &Bound<'_, PyModule>) -> PyResult<()> {
+            let py = m.py();
+            m.add(
+                "AleaPreprocessError",
+                py.get_type_bound::<AleaPreprocessError>(),
+            )?;
+            m.add("ParseError", py.get_type_bound::<ParseError>())?;
+            m.add("TokenizerError", py.get_type_bound::<TokenizerError>())?;
+            m.add("TikaError", py.get_type_bound::<TikaError>())?;
+            m.add("DecodeError", py.get_type_bound::<DecodeError>())?;
+            Ok(())
+        }
     }
 
     #[pymodule_init]
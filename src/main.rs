@@ -0,0 +1,727 @@
+// Command-line interface for the core preprocessing routines
+//
+// Every subcommand reads files, directories (walked recursively) or JSONL records on stdin,
+// processes them in parallel, and writes one JSON object per input to stdout in input order.
+
+// external imports
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rayon::prelude::*;
+use serde_json::{json, Map, Value};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::process::ExitCode;
+
+// internal imports
+use alea_preprocess::algos::hashing::{blake3, ctph};
+use alea_preprocess::algos::ngrams::words;
+use alea_preprocess::algos::splitting::simple::split_str;
+use alea_preprocess::algos::tokenizers::tokenizers::encode_str;
+use alea_preprocess::errors::{Error, Result};
+use alea_preprocess::io::encoding::decode_bytes;
+use alea_preprocess::io::fs::directories::get_files;
+use alea_preprocess::io::fs::file_info::{
+    get_file_info_from_buffer, get_file_info_from_file, FileInfo,
+};
+use alea_preprocess::io::fs::files::read_file_content;
+use alea_preprocess::parsers::html::conversion::{
+    HtmlToMarkdownParser, HtmlToPlainTextParser, ParserConfig,
+};
+use alea_preprocess::parsers::pdf::{conversion, detection};
+use alea_preprocess::pipeline::stages::check_split_sizes;
+use alea_preprocess::pipeline::{
+    read_directory_documents, read_jsonl_documents, Document, Failure, Pipeline,
+};
+use alea_preprocess::tasks::sequences::decode_content;
+
+// number of inputs processed in parallel before their results are written
+const CHUNK_SIZE: usize = 1024;
+
+#[derive(Parser)]
+#[command(
+    name = "alea-preprocess",
+    version,
+    about = "ALEA preprocessing routines"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    /// Number of worker threads (defaults to the number of CPUs)
+    #[arg(long, global = true)]
+    threads: Option<usize>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Convert HTML to markdown or plain text
+    Html2md {
+        #[command(flatten)]
+        input: InputArgs,
+        /// Output plain text instead of markdown
+        #[arg(long)]
+        text: bool,
+        /// Keep links in the markdown
+        #[arg(long)]
+        links: bool,
+        /// Keep images in the markdown
+        #[arg(long)]
+        images: bool,
+    },
+    /// Convert PDFs to markdown or plain text, one at a time under pdfium's global lock
+    Pdf2md {
+        #[command(flatten)]
+        input: InputArgs,
+        /// Output plain text instead of markdown
+        #[arg(long)]
+        text: bool,
+    },
+    /// Detect whether PDFs are digital, scanned or OCR'd
+    PdfDetect {
+        #[command(flatten)]
+        input: InputArgs,
+    },
+    /// Hash content with blake3 or CTPH
+    Hash {
+        #[command(flatten)]
+        input: InputArgs,
+        #[arg(long, value_enum, default_value_t = HashAlgorithm::Blake3)]
+        algorithm: HashAlgorithm,
+        /// CTPH window size
+        #[arg(long, default_value_t = 32)]
+        window_size: usize,
+        /// CTPH digest size
+        #[arg(long, default_value_t = 16)]
+        digest_size: usize,
+        /// CTPH rolling hash precision (8, 16, 32 or 64)
+        #[arg(long, default_value_t = 32)]
+        precision: u8,
+    },
+    /// Count word n-grams
+    Ngrams {
+        #[command(flatten)]
+        input: InputArgs,
+        /// N-gram size, from 1 to 5
+        #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=5))]
+        n: u8,
+        /// Only output the most frequent n-grams
+        #[arg(long)]
+        top: Option<usize>,
+    },
+    /// Split text into chunks at the largest pattern under the maximum size
+    Split {
+        #[command(flatten)]
+        input: InputArgs,
+        #[arg(long, default_value_t = 64)]
+        min_size: usize,
+        #[arg(long, default_value_t = 1024)]
+        max_size: usize,
+        /// Split pattern, in order of preference; may be repeated
+        #[arg(long = "pattern", default_values_t = ["\n\n".to_string(), "\n".to_string(), ". ".to_string(), " ".to_string()])]
+        patterns: Vec<String>,
+    },
+    /// Detect the file format and text encoding
+    FileInfo {
+        #[command(flatten)]
+        input: InputArgs,
+    },
+    /// Run a pipeline of stages configured in a JSON or TOML file
+    Pipeline {
+        /// Files, JSONL files or directories to process; reads JSONL documents from stdin if
+        /// empty or "-"
+        paths: Vec<String>,
+        /// Pipeline config file (.toml or .json)
+        #[arg(short, long)]
+        config: String,
+        /// Write failed documents to this JSONL file instead of stderr
+        #[arg(long)]
+        failures: Option<String>,
+    },
+    /// Encode text with a tokenizer
+    Tokenize {
+        #[command(flatten)]
+        input: InputArgs,
+        /// Tokenizer name on the Hugging Face hub or path to a tokenizer.json file
+        #[arg(short, long)]
+        tokenizer: String,
+        /// Only output the number of tokens
+        #[arg(long)]
+        count: bool,
+    },
+}
+
+#[derive(Args)]
+struct InputArgs {
+    /// Files or directories to process; reads JSONL records from stdin if empty or "-"
+    paths: Vec<String>,
+
+    /// Field of each stdin record holding the content
+    #[arg(long, default_value = "text")]
+    field: String,
+
+    /// Treat the stdin field as base64 content, compressed or not (always on for binary inputs)
+    #[arg(long)]
+    encoded: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum HashAlgorithm {
+    Blake3,
+    Ctph,
+}
+
+/// A unit of work: a file on disk or the content of a stdin record
+enum Input {
+    File(String),
+    Record(String, Vec<u8>),
+}
+
+impl Input {
+    fn id(&self) -> &str {
+        match self {
+            Input::File(path) => path,
+            Input::Record(id, _) => id,
+        }
+    }
+
+    fn bytes(&self) -> Result<Vec<u8>> {
+        match self {
+            Input::File(path) => Ok(read_file_content(path)?),
+            Input::Record(_, content) => Ok(content.clone()),
+        }
+    }
+
+    /// File text is decoded from its detected encoding; record text is already UTF-8.
+    fn text(&self) -> Result<String> {
+        match self {
+            Input::File(path) => Ok(decode_bytes(&read_file_content(path)?).text),
+            Input::Record(_, content) => Ok(String::from_utf8(content.clone())?),
+        }
+    }
+}
+
+/// Parse a JSONL record into an input, using its `id` field or the line number as the id.
+fn parse_record(line: &str, line_number: usize, field: &str, encoded: bool) -> Result<Input> {
+    let record: Value = serde_json::from_str(line)
+        .map_err(|e| Error::Parse(format!("line {}: {}", line_number, e)))?;
+    let id = match &record["id"] {
+        Value::String(id) => id.clone(),
+        Value::Null => line_number.to_string(),
+        id => id.to_string(),
+    };
+    let content = record[field].as_str().ok_or_else(|| {
+        Error::Parse(format!(
+            "line {}: missing string field {}",
+            line_number, field
+        ))
+    })?;
+    let content = if encoded {
+        decode_content(content, None)?
+    } else {
+        content.as_bytes().to_vec()
+    };
+    Ok(Input::Record(id, content))
+}
+
+/// Expand the paths into files, walking directories in sorted order.
+fn get_path_inputs(paths: &[String]) -> Vec<Input> {
+    paths
+        .iter()
+        .flat_map(|path| {
+            if Path::new(path).is_dir() {
+                let mut files = get_files(path);
+                files.sort();
+                files
+            } else {
+                vec![path.clone()]
+            }
+        })
+        .map(Input::File)
+        .collect()
+}
+
+/// Run the command over the inputs in parallel and write a JSON line per input.
+/// Returns the number of inputs that failed.
+fn process<W: Write>(
+    inputs: &[Input],
+    run: &(dyn Fn(&Input) -> Result<Map<String, Value>> + Sync),
+    writer: &mut W,
+) -> io::Result<usize> {
+    let results: Vec<Value> = inputs
+        .par_iter()
+        .map(|input| {
+            let mut output = Map::new();
+            output.insert("id".to_string(), json!(input.id()));
+            match run(input) {
+                Ok(fields) => output.extend(fields),
+                Err(e) => {
+                    output.insert("error".to_string(), json!(e.to_string()));
+                }
+            }
+            Value::Object(output)
+        })
+        .collect();
+
+    let mut failures = 0;
+    for result in results {
+        if result.get("error").is_some() {
+            failures += 1;
+        }
+        serde_json::to_writer(&mut *writer, &result)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(failures)
+}
+
+/// Process the inputs named on the command line or stdin records, in chunks.
+fn process_input_args<W: Write>(
+    input_args: &InputArgs,
+    binary: bool,
+    run: &(dyn Fn(&Input) -> Result<Map<String, Value>> + Sync),
+    writer: &mut W,
+) -> io::Result<usize> {
+    let paths: Vec<String> = input_args
+        .paths
+        .iter()
+        .filter(|path| path.as_str() != "-")
+        .cloned()
+        .collect();
+    if !paths.is_empty() {
+        let mut failures = 0;
+        for chunk in get_path_inputs(&paths).chunks(CHUNK_SIZE) {
+            failures += process(chunk, run, writer)?;
+        }
+        return Ok(failures);
+    }
+
+    // binary content cannot be stored in JSON as-is, so it is always encoded
+    let encoded = input_args.encoded || binary;
+    let mut failures = 0;
+    let mut lines = io::stdin().lock().lines().enumerate().peekable();
+    while lines.peek().is_some() {
+        let mut chunk = Vec::with_capacity(CHUNK_SIZE);
+        for (index, line) in lines.by_ref().take(CHUNK_SIZE) {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match parse_record(&line, index + 1, &input_args.field, encoded) {
+                Ok(input) => chunk.push(input),
+                Err(e) => {
+                    failures += 1;
+                    eprintln!("skipping record: {}", e);
+                }
+            }
+        }
+        failures += process(&chunk, run, writer)?;
+    }
+    Ok(failures)
+}
+
+fn file_info_to_map(file_info: FileInfo) -> Map<String, Value> {
+    let mut output = Map::new();
+    output.insert("name".to_string(), json!(file_info.name));
+    output.insert("short_name".to_string(), json!(file_info.short_name));
+    output.insert("media_type".to_string(), json!(file_info.media_type));
+    output.insert("extension".to_string(), json!(file_info.extension));
+    output.insert("kind".to_string(), json!(file_info.kind));
+    output.insert("encoding".to_string(), json!(file_info.encoding));
+    output
+}
+
+fn field(key: &str, value: Value) -> Map<String, Value> {
+    let mut output = Map::new();
+    output.insert(key.to_string(), value);
+    output
+}
+
+/// Count word n-grams, most frequent first with ties broken by the n-gram.
+fn get_ngram_counts(text: &str, n: u8, top: Option<usize>) -> Vec<(String, u32)> {
+    let counts = match n {
+        1 => words::extract_1(text),
+        2 => words::extract_2(text),
+        3 => words::extract_3(text),
+        4 => words::extract_4(text),
+        _ => words::extract_5(text),
+    };
+    let mut counts: Vec<(String, u32)> = counts
+        .into_iter()
+        .map(|(ngram, count)| (ngram.join(" "), count))
+        .collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    if let Some(top) = top {
+        counts.truncate(top);
+    }
+    counts
+}
+
+/// Read pipeline documents from JSONL files, directories and other files in turn.
+fn get_pipeline_documents(
+    paths: &[String],
+) -> io::Result<Box<dyn Iterator<Item = std::result::Result<Document, Failure>>>> {
+    let paths: Vec<&String> = paths.iter().filter(|path| path.as_str() != "-").collect();
+    if paths.is_empty() {
+        return Ok(Box::new(read_jsonl_documents(io::stdin().lock())));
+    }
+
+    let mut documents: Box<dyn Iterator<Item = std::result::Result<Document, Failure>>> =
+        Box::new(std::iter::empty());
+    for path in paths {
+        let path_documents: Box<dyn Iterator<Item = _>> = if Path::new(path).is_dir() {
+            Box::new(read_directory_documents(path))
+        } else if path.ends_with(".jsonl") {
+            Box::new(read_jsonl_documents(BufReader::new(File::open(path)?)))
+        } else {
+            let path = path.clone();
+            Box::new(std::iter::once_with(move || {
+                Document::from_file(&path).map_err(|e| Failure::input(&path, e))
+            }))
+        };
+        documents = Box::new(documents.chain(path_documents));
+    }
+    Ok(documents)
+}
+
+/// Run a pipeline, writing failures to the side output and the stage counters to stderr.
+fn run_pipeline<W: Write>(
+    paths: &[String],
+    config: &str,
+    failures: &Option<String>,
+    writer: &mut W,
+) -> io::Result<usize> {
+    let pipeline = Pipeline::from_file(config).map_err(io::Error::other)?;
+    let mut failure_writer: Box<dyn Write> = match failures {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stderr().lock()),
+    };
+    let num_failures = pipeline
+        .run(get_pipeline_documents(paths)?, writer, &mut failure_writer)
+        .map_err(io::Error::other)?;
+
+    let stats: Vec<Value> = pipeline
+        .stats()
+        .iter()
+        .map(|stats| stats.to_json())
+        .collect();
+    eprintln!("{}", json!({ "stages": stats }));
+    Ok(num_failures)
+}
+
+fn run_command<W: Write>(command: &Command, writer: &mut W) -> io::Result<usize> {
+    match command {
+        Command::Html2md {
+            input,
+            text,
+            links,
+            images,
+        } => process_input_args(
+            input,
+            false,
+            &|item| {
+                let html = item.text()?;
+                if *text {
+                    let parser =
+                        HtmlToPlainTextParser::new(ParserConfig::new(None, false, false), &html);
+                    Ok(field("text", json!(parser.to_plain_text())))
+                } else {
+                    let parser =
+                        HtmlToMarkdownParser::new(ParserConfig::new(None, *links, *images), &html);
+                    Ok(field("markdown", json!(parser.to_markdown())))
+                }
+            },
+            writer,
+        ),
+        Command::Pdf2md { input, text } => process_input_args(
+            input,
+            true,
+            &|item| {
+                let extracted = match (item, *text) {
+                    (Input::File(path), true) => conversion::extract_file_text(path)?,
+                    (Input::File(path), false) => conversion::extract_file_markdown(path)?,
+                    (Input::Record(_, buffer), true) => conversion::extract_buffer_text(buffer)?,
+                    (Input::Record(_, buffer), false) => {
+                        conversion::extract_buffer_markdown(buffer)?
+                    }
+                };
+                Ok(field(
+                    if *text { "text" } else { "markdown" },
+                    json!(extracted),
+                ))
+            },
+            writer,
+        ),
+        Command::PdfDetect { input } => process_input_args(
+            input,
+            true,
+            &|item| {
+                let document_type = match item {
+                    Input::File(path) => detection::detect_file_type(path)?,
+                    Input::Record(_, buffer) => detection::detect_buffer_type(buffer)?,
+                };
+                Ok(field(
+                    "document_type",
+                    json!(format!("{:?}", document_type)),
+                ))
+            },
+            writer,
+        ),
+        Command::Hash {
+            input,
+            algorithm,
+            window_size,
+            digest_size,
+            precision,
+        } => process_input_args(
+            input,
+            true,
+            &|item| {
+                let buffer = item.bytes()?;
+                let hash = match algorithm {
+                    HashAlgorithm::Blake3 => blake3::hash_bytes(&buffer),
+                    HashAlgorithm::Ctph => {
+                        ctph::hash_bytes(&buffer, *window_size, *digest_size, *precision)
+                    }
+                };
+                Ok(field("hash", json!(hash)))
+            },
+            writer,
+        ),
+        Command::Ngrams { input, n, top } => process_input_args(
+            input,
+            false,
+            &|item| {
+                Ok(field(
+                    "ngrams",
+                    json!(get_ngram_counts(&item.text()?, *n, *top)),
+                ))
+            },
+            writer,
+        ),
+        Command::Split {
+            input,
+            min_size,
+            max_size,
+            patterns,
+        } => {
+            check_split_sizes(*min_size, *max_size).map_err(io::Error::other)?;
+            process_input_args(
+                input,
+                false,
+                &|item| {
+                    let chunks = split_str(&item.text()?, *min_size, *max_size, patterns.clone());
+                    Ok(field("chunks", json!(chunks)))
+                },
+                writer,
+            )
+        }
+        Command::FileInfo { input } => process_input_args(
+            input,
+            true,
+            &|item| {
+                let file_info = match item {
+                    Input::File(path) => get_file_info_from_file(path)?,
+                    Input::Record(_, buffer) => get_file_info_from_buffer(buffer),
+                };
+                Ok(file_info_to_map(file_info))
+            },
+            writer,
+        ),
+        Command::Pipeline {
+            paths,
+            config,
+            failures,
+        } => run_pipeline(paths, config, failures, writer),
+        Command::Tokenize {
+            input,
+            tokenizer,
+            count,
+        } => process_input_args(
+            input,
+            false,
+            &|item| {
+                let tokens = encode_str(tokenizer, &item.text()?)?;
+                if *count {
+                    Ok(field("num_tokens", json!(tokens.len())))
+                } else {
+                    Ok(field("tokens", json!(tokens)))
+                }
+            },
+            writer,
+        ),
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    if let Some(threads) = cli.threads {
+        if let Err(e) = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+        {
+            eprintln!("failed to start thread pool: {}", e);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let mut writer = BufWriter::new(io::stdout().lock());
+    let result = run_command(&cli.command, &mut writer).and_then(|failures| {
+        writer.flush()?;
+        Ok(failures)
+    });
+    match result {
+        Ok(0) => ExitCode::SUCCESS,
+        Ok(failures) => {
+            eprintln!("{} inputs failed", failures);
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_resource_path(name: &str) -> String {
+        format!("{}/resources/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    fn run_to_lines(
+        inputs: Vec<Input>,
+        run: &(dyn Fn(&Input) -> Result<Map<String, Value>> + Sync),
+    ) -> (Vec<Value>, usize) {
+        let mut buffer = Vec::new();
+        let failures = process(&inputs, run, &mut buffer).unwrap();
+        let lines = String::from_utf8(buffer)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        (lines, failures)
+    }
+
+    #[test]
+    fn test_parse_record() {
+        let input = parse_record(r#"{"id": 7, "text": "hello"}"#, 1, "text", false).unwrap();
+        assert_eq!(input.id(), "7");
+        assert_eq!(input.text().unwrap(), "hello");
+
+        // falls back to the line number and decodes base64 zlib content
+        let line = r#"{"content": "eJwryUhVSMuvUMgqzS0oVsgvSy1SKAEK5SRWVSqk5KcDALfLC3U="}"#;
+        let input = parse_record(line, 3, "content", true).unwrap();
+        assert_eq!(input.id(), "3");
+        assert_eq!(input.text().unwrap(), "the fox jumps over the lazy dog");
+
+        assert!(parse_record(r#"{"id": "a"}"#, 1, "text", false).is_err());
+        assert!(parse_record("not json", 1, "text", false).is_err());
+    }
+
+    #[test]
+    fn test_get_path_inputs() {
+        let inputs = get_path_inputs(&[get_resource_path(""), get_resource_path("file1.html")]);
+        let ids: Vec<&str> = inputs.iter().map(|input| input.id()).collect();
+        assert!(ids.len() > 10);
+        assert_eq!(ids.last().unwrap(), &get_resource_path("file1.html"));
+        assert!(ids.contains(&get_resource_path("test1.pdf").as_str()));
+    }
+
+    #[test]
+    fn test_process_order_and_failures() {
+        let inputs = vec![
+            Input::File(get_resource_path("file1.html")),
+            Input::File(get_resource_path("missing_file")),
+            Input::Record("r".to_string(), b"record".to_vec()),
+        ];
+        let (lines, failures) = run_to_lines(inputs, &|item| {
+            Ok(field("hash", json!(blake3::hash_bytes(&item.bytes()?))))
+        });
+        assert_eq!(failures, 1);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["id"], get_resource_path("file1.html"));
+        assert!(lines[1]["error"].is_string());
+        assert_eq!(lines[2]["hash"], blake3::hash_bytes(b"record"));
+    }
+
+    #[test]
+    fn test_process_input_args_paths() {
+        let input_args = InputArgs {
+            paths: vec![
+                get_resource_path("file1.html"),
+                "-".to_string(),
+                get_resource_path("missing_file"),
+            ],
+            field: "text".to_string(),
+            encoded: false,
+        };
+        let mut buffer = Vec::new();
+        let failures = process_input_args(
+            &input_args,
+            true,
+            &|item| Ok(field("hash", json!(blake3::hash_bytes(&item.bytes()?)))),
+            &mut buffer,
+        )
+        .unwrap();
+        assert_eq!(failures, 1);
+        assert_eq!(String::from_utf8(buffer).unwrap().lines().count(), 2);
+    }
+
+    #[test]
+    fn test_get_pipeline_documents() {
+        let documents: Vec<_> = get_pipeline_documents(&[
+            get_resource_path("file1.html"),
+            get_resource_path("missing.html"),
+        ])
+        .unwrap()
+        .collect();
+        assert_eq!(documents.len(), 2);
+        assert!(documents[0].as_ref().unwrap().bytes.is_some());
+        assert_eq!(documents[1].as_ref().unwrap_err().stage, "input");
+    }
+
+    #[test]
+    fn test_get_ngram_counts() {
+        let counts = get_ngram_counts("the fox and the dog and the cat", 1, Some(2));
+        assert_eq!(counts, vec![("the".to_string(), 3), ("and".to_string(), 2)]);
+    }
+
+    #[test]
+    fn test_cli_parse() {
+        let cli = Cli::try_parse_from(["alea-preprocess", "hash", "--algorithm", "ctph", "a.pdf"])
+            .unwrap();
+        match cli.command {
+            Command::Hash {
+                input, algorithm, ..
+            } => {
+                assert_eq!(input.paths, vec!["a.pdf"]);
+                assert!(matches!(algorithm, HashAlgorithm::Ctph));
+            }
+            _ => panic!("expected hash"),
+        }
+
+        assert!(Cli::try_parse_from(["alea-preprocess", "ngrams", "-n", "6"]).is_err());
+    }
+
+    #[test]
+    fn test_split_sizes() {
+        let path = get_resource_path("file1.html");
+        for sizes in [["0", "0"], ["10", "5"]] {
+            let cli = Cli::try_parse_from([
+                "alea-preprocess",
+                "split",
+                "--min-size",
+                sizes[0],
+                "--max-size",
+                sizes[1],
+                &path,
+            ])
+            .unwrap();
+            let mut buffer = Vec::new();
+            assert!(run_command(&cli.command, &mut buffer).is_err());
+            assert!(buffer.is_empty());
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use lazy_static::*;
+use rayon::prelude::*;
 use regex::*;
 use std::collections::{HashMap, HashSet};
 use tl;
@@ -1261,6 +1262,42 @@ impl<'a> HtmlToPlainTextParser<'a> {
     }
 }
 
+/// Convert several HTML buffers to markdown in parallel.
+/// Arguments:
+/// - buffers: The HTML buffers to convert.
+/// - output_links: Whether to output links in the output.
+/// - output_images: Whether to output images in the output.
+/// Returns:
+/// - The markdown of each buffer, in input order.
+pub fn extract_buffer_markdown_batch(
+    buffers: &[String],
+    output_links: bool,
+    output_images: bool,
+) -> Vec<String> {
+    buffers
+        .par_iter()
+        .map(|buffer| {
+            HtmlToMarkdownParser::new(ParserConfig::new(None, output_links, output_images), buffer)
+                .to_markdown()
+        })
+        .collect()
+}
+
+/// Convert several HTML buffers to plain text in parallel.
+/// Arguments:
+/// - buffers: The HTML buffers to convert.
+/// Returns:
+/// - The plain text of each buffer, in input order.
+pub fn extract_buffer_text_batch(buffers: &[String]) -> Vec<String> {
+    buffers
+        .par_iter()
+        .map(|buffer| {
+            HtmlToPlainTextParser::new(ParserConfig::new(None, false, false), buffer)
+                .to_plain_text()
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1329,6 +1366,16 @@ mod tests {
         assert_eq!(result, "Hello **world**.\n\nThis is a test.\n");
     }
 
+    #[test]
+    fn test_extract_buffer_markdown_batch() {
+        let samples = vec![get_sample_single_p(), get_sample_formatted_p()];
+        let results = extract_buffer_markdown_batch(&samples, true, true);
+        assert_eq!(
+            results,
+            vec!["Hello\n", "Hello **world**.\n\nThis is a test.\n"]
+        );
+    }
+
     // test markdown
     #[test]
     fn test_markdown_double_div() {
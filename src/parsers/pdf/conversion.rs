@@ -3,9 +3,12 @@
 /// different methods, such as simple text extraction and position-aware
 /// text extraction.
 use crate::algos::unicode::normalizations::nfkc_printable_str;
+use crate::errors::{Error, Result};
+use crate::parsers::pdf::detection::{detect_document_type, DocumentType};
 use crate::parsers::pdf::gaps::{calculate_percentiles, gap_to_string, get_gaps};
 use crate::parsers::pdf::utils::{get_font_size, get_font_weight};
 use pdfium_render::prelude::*;
+use rayon::prelude::*;
 use regex::Regex;
 
 /// Normalize PDF text by removing leading and trailing whitespace and
@@ -47,7 +50,7 @@ pub fn extract_text_simple(pdf_document: &PdfDocument) -> String {
         pdf_document
             .pages()
             .iter()
-            .map(|page| page.text().unwrap().all())
+            .map(|page| page.text().map(|text| text.all()).unwrap_or_default())
             .collect::<Vec<String>>()
             .join("\n")
             .trim(),
@@ -89,8 +92,10 @@ pub fn extract_text_positions(pdf_document: &PdfDocument) -> String {
                 if gap_y >= 0.0 {
                     y_gaps.push(gap_y);
                 }
-                x_last = text_object.bounds().unwrap().right.value;
-                y_last = text_object.bounds().unwrap().top.value;
+                if let Ok(bounds) = text_object.bounds() {
+                    x_last = bounds.right.value;
+                    y_last = bounds.top.value;
+                }
             }
         }
     }
@@ -108,7 +113,9 @@ pub fn extract_text_positions(pdf_document: &PdfDocument) -> String {
         let mut last_font_size = 0.0;
         for object in page.objects().iter() {
             if let Some(text_object) = object.as_text_object() {
-                let text_bbox = object.bounds().unwrap();
+                let Ok(text_bbox) = object.bounds() else {
+                    continue;
+                };
                 let text_object_text = &text_object.text();
 
                 // skip if it's empty
@@ -177,56 +184,133 @@ pub fn extract_text_positions(pdf_document: &PdfDocument) -> String {
     normalize_pdf_text(&text)
 }
 
-pub fn extract_buffer_text(buffer: &[u8]) -> String {
+/// Extract text from a PDF buffer.
+/// Returns:
+/// - The extracted text, which is empty for a document without text, or a
+///   parse error if the PDF could not be loaded.
+pub fn extract_buffer_text(buffer: &[u8]) -> Result<String> {
     // init pdf parser
     let pdf_parser = Pdfium::default();
 
-    // safely parse
-    let result = match pdf_parser.load_pdf_from_byte_slice(buffer, None) {
-        Err(_) => String::new(),
-        Ok(pdf_document) => extract_text_simple(&pdf_document),
-    };
-
-    result
+    let pdf_document = pdf_parser
+        .load_pdf_from_byte_slice(buffer, None)
+        .map_err(|e| Error::Parse(e.to_string()))?;
+    Ok(extract_text_simple(&pdf_document))
 }
 
-pub fn extract_file_text(file_path: &str) -> String {
+/// Extract text from a PDF file.
+/// Returns:
+/// - The extracted text, which is empty for a document without text, or a
+///   parse error if the PDF could not be loaded.
+pub fn extract_file_text(file_path: &str) -> Result<String> {
     // init pdf parser
     let pdf_parser = Pdfium::default();
 
-    // safely parse
-    let result = match pdf_parser.load_pdf_from_file(file_path, None) {
-        Err(_) => String::new(),
-        Ok(pdf_document) => extract_text_simple(&pdf_document),
-    };
-
-    result
+    let pdf_document = pdf_parser
+        .load_pdf_from_file(file_path, None)
+        .map_err(|e| Error::Parse(e.to_string()))?;
+    Ok(extract_text_simple(&pdf_document))
 }
 
-pub fn extract_buffer_markdown(buffer: &[u8]) -> String {
+/// Extract markdown from a PDF buffer.
+/// Returns:
+/// - The extracted markdown, which is empty for a document without text, or a
+///   parse error if the PDF could not be loaded.
+pub fn extract_buffer_markdown(buffer: &[u8]) -> Result<String> {
     // init pdf parser
     let pdf_parser = Pdfium::default();
 
-    // safely parse
-    let result = match pdf_parser.load_pdf_from_byte_slice(buffer, None) {
-        Err(_) => String::new(),
-        Ok(pdf_document) => extract_text_positions(&pdf_document),
-    };
+    let pdf_document = pdf_parser
+        .load_pdf_from_byte_slice(buffer, None)
+        .map_err(|e| Error::Parse(e.to_string()))?;
+    Ok(extract_text_positions(&pdf_document))
+}
 
-    result
+/// Extract markdown from a PDF file.
+/// Returns:
+/// - The extracted markdown, which is empty for a document without text, or a
+///   parse error if the PDF could not be loaded.
+pub fn extract_file_markdown(file_path: &str) -> Result<String> {
+    // init pdf parser
+    let pdf_parser = Pdfium::default();
+
+    let pdf_document = pdf_parser
+        .load_pdf_from_file(file_path, None)
+        .map_err(|e| Error::Parse(e.to_string()))?;
+    Ok(extract_text_positions(&pdf_document))
 }
 
-pub fn extract_file_markdown(file_path: &str) -> String {
+/// Extract text or markdown from a PDF buffer together with its document type.
+/// The buffer is loaded once for both, e.g., to check the text layer of OCR'd documents.
+/// Arguments:
+/// - buffer: The PDF buffer.
+/// - markdown: Whether to extract markdown with extract_text_positions instead of plain text.
+/// Returns:
+/// - The extracted text and the document type, or a parse error if the PDF could not be loaded.
+pub fn extract_buffer_with_type(buffer: &[u8], markdown: bool) -> Result<(String, DocumentType)> {
     // init pdf parser
     let pdf_parser = Pdfium::default();
 
-    // safely parse
-    let result = match pdf_parser.load_pdf_from_file(file_path, None) {
-        Err(_) => String::new(),
-        Ok(pdf_document) => extract_text_positions(&pdf_document),
+    let pdf_document = pdf_parser
+        .load_pdf_from_byte_slice(buffer, None)
+        .map_err(|e| Error::Parse(e.to_string()))?;
+    let text = if markdown {
+        extract_text_positions(&pdf_document)
+    } else {
+        extract_text_simple(&pdf_document)
     };
+    Ok((text, detect_document_type(&pdf_document)))
+}
 
-    result
+/// Extract text from several PDF buffers.
+/// pdfium holds a process-wide lock from initialization until it is dropped, and each
+/// extraction keeps it for the whole document, so PDF batches run one document at a time.
+/// The batch variants keep the input order and per-document errors of a single call.
+/// Arguments:
+/// - buffers: The PDF buffers to extract text from.
+/// Returns:
+/// - The extracted text or parse error of each buffer, in input order.
+pub fn extract_buffer_text_batch(buffers: &[Vec<u8>]) -> Vec<Result<String>> {
+    buffers
+        .par_iter()
+        .map(|buffer| extract_buffer_text(buffer))
+        .collect()
+}
+
+/// Extract text from several PDF files; serialized by pdfium, see `extract_buffer_text_batch`.
+/// Arguments:
+/// - file_paths: The paths to the PDF files.
+/// Returns:
+/// - The extracted text or parse error of each file, in input order.
+pub fn extract_file_text_batch(file_paths: &[String]) -> Vec<Result<String>> {
+    file_paths
+        .par_iter()
+        .map(|file_path| extract_file_text(file_path))
+        .collect()
+}
+
+/// Extract markdown from several PDF buffers; serialized by pdfium, see `extract_buffer_text_batch`.
+/// Arguments:
+/// - buffers: The PDF buffers to extract markdown from.
+/// Returns:
+/// - The extracted markdown or parse error of each buffer, in input order.
+pub fn extract_buffer_markdown_batch(buffers: &[Vec<u8>]) -> Vec<Result<String>> {
+    buffers
+        .par_iter()
+        .map(|buffer| extract_buffer_markdown(buffer))
+        .collect()
+}
+
+/// Extract markdown from several PDF files; serialized by pdfium, see `extract_buffer_text_batch`.
+/// Arguments:
+/// - file_paths: The paths to the PDF files.
+/// Returns:
+/// - The extracted markdown or parse error of each file, in input order.
+pub fn extract_file_markdown_batch(file_paths: &[String]) -> Vec<Result<String>> {
+    file_paths
+        .par_iter()
+        .map(|file_path| extract_file_markdown(file_path))
+        .collect()
 }
 
 #[cfg(test)]
@@ -265,16 +349,16 @@ mod tests {
     #[test]
     fn test_bad_file() {
         // get text
-        let text = extract_file_text(get_bad_file_path().to_str().unwrap());
+        let result = extract_file_text(get_bad_file_path().to_str().unwrap());
 
-        // check for empty
-        assert!(text.is_empty());
+        // check for a parse error rather than empty text
+        assert!(matches!(result, Err(Error::Parse(_))));
     }
 
     #[test]
     fn test_extract_text() {
         // get text
-        let text = extract_file_text(get_test_file_path().to_str().unwrap());
+        let text = extract_file_text(get_test_file_path().to_str().unwrap()).unwrap();
 
         // check for "Fair and Competitive Livestock"
         assert!(text.contains("Fair and Competitive Livestock"));
@@ -283,7 +367,7 @@ mod tests {
     #[test]
     fn test_extract_markdown() {
         // get text with position
-        let text = extract_file_markdown(get_test_file_path().to_str().unwrap());
+        let text = extract_file_markdown(get_test_file_path().to_str().unwrap()).unwrap();
 
         // check for "AGENCY: Agricultural Marketing Service,
         assert!(text.contains("**AGENCY :**  Agricultural Marketing Service, USDA."));
@@ -291,22 +375,30 @@ mod tests {
 
     #[test]
     fn test_extract_text_long() {
-        let text = extract_file_text(get_test_long_file_path().to_str().unwrap());
+        let text = extract_file_text(get_test_long_file_path().to_str().unwrap()).unwrap();
 
         assert!(text.contains("[Name of Money Market Mutual Fund]"));
     }
 
     #[test]
     fn test_extract_markdown_long() {
-        let text = extract_file_markdown(get_test_long_file_path().to_str().unwrap());
+        let text = extract_file_markdown(get_test_long_file_path().to_str().unwrap()).unwrap();
 
         assert!(text.contains("### PART 31—LEVERAGE TRANSACTIONS\n"));
     }
 
+    #[test]
+    fn test_extract_buffer_with_type() {
+        let buffer = std::fs::read(get_test_ocr_file_path()).unwrap();
+        let (text, document_type) = extract_buffer_with_type(&buffer, false).unwrap();
+        assert_eq!(document_type, DocumentType::ImagePostOCR);
+        assert_eq!(text, extract_buffer_text(&buffer).unwrap());
+    }
+
     #[test]
     fn test_extract_text_ocr() {
         // get text with position
-        let text = extract_file_text(get_test_ocr_file_path().to_str().unwrap());
+        let text = extract_file_text(get_test_ocr_file_path().to_str().unwrap()).unwrap();
 
         // garbage output with this method
         assert!(!text.contains("AGENCY"));
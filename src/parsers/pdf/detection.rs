@@ -27,6 +27,8 @@
 /// PdfPage::links_mut(), a mutable collection of the links on the PdfPage.
 /// PdfPage::objects(), an immutable collection of all the displayable objects on the PdfPage.
 /// PdfPage::objects_mut(), a mutable collection of all the displayable objects on the PdfPage.
+use crate::algos::quality::noise::{get_noise_report, NoiseConfig};
+use crate::errors::{Error, Result};
 use pdfium_render::prelude::*;
 
 // enum for different page types
@@ -103,22 +105,22 @@ pub fn detect_page_type(page: &PdfPage, document: &PdfDocument) -> PageType {
     if object_counts.image == 1 {
         let page_width = page.page_size().width().value;
         let page_height = page.page_size().height().value;
-        let image = page
+        let image_size = page
             .objects()
             .iter()
-            .filter(|obj| obj.object_type() == PdfPageObjectType::Image)
-            .next()
-            .unwrap();
-        let image_size = image.bounds().unwrap();
-        let image_width = image_size.width().value;
-        let image_height = image_size.height().value;
-
-        // check if the width and height are within 1%
-        let width_diff = (page_width - image_width).abs() / page_width;
-        let height_diff = (page_height - image_height).abs() / page_height;
-
-        if width_diff < 0.01 && height_diff < 0.01 {
-            return PageType::ImagePostOCR;
+            .find(|obj| obj.object_type() == PdfPageObjectType::Image)
+            .and_then(|image| image.bounds().ok());
+        if let Some(image_size) = image_size {
+            let image_width = image_size.width().value;
+            let image_height = image_size.height().value;
+
+            // check if the width and height are within 1%
+            let width_diff = (page_width - image_width).abs() / page_width;
+            let height_diff = (page_height - image_height).abs() / page_height;
+
+            if width_diff < 0.01 && height_diff < 0.01 {
+                return PageType::ImagePostOCR;
+            }
         }
     }
 
@@ -128,7 +130,7 @@ pub fn detect_page_type(page: &PdfPage, document: &PdfDocument) -> PageType {
     for page in document.pages().iter() {
         for font in page.fonts().iter() {
             font_count += 1;
-            if font.is_embedded().unwrap() {
+            if font.is_embedded().unwrap_or(false) {
                 font_embed_count += 1;
             }
         }
@@ -205,19 +207,15 @@ pub fn detect_document_type(pdf_document: &PdfDocument) -> DocumentType {
 /// Args:
 ///   buffer: byte array of the PDF file
 /// Returns:
-///  DocumentType object
-pub fn detect_buffer_type(buffer: &[u8]) -> DocumentType {
+///  DocumentType object, or a parse error if the PDF could not be loaded
+pub fn detect_buffer_type(buffer: &[u8]) -> Result<DocumentType> {
     // init the parser
     let pdf_parser = Pdfium::default();
 
-    // try to load the pdf; if it fails, return malformed; otherwise, return detected type
-    // let pdf_file = pdf_parser.load_pdf_from_byte_slice(buffer, None).unwrap();
-    let detected_type = match pdf_parser.load_pdf_from_byte_slice(buffer, None) {
-        Ok(pdf_file) => detect_document_type(&pdf_file),
-        Err(_) => DocumentType::Malformed,
-    };
-
-    detected_type
+    let pdf_file = pdf_parser
+        .load_pdf_from_byte_slice(buffer, None)
+        .map_err(|e| Error::Parse(e.to_string()))?;
+    Ok(detect_document_type(&pdf_file))
 }
 
 /// Detect the type of a file by parsing all pages, detecting each
@@ -225,17 +223,50 @@ pub fn detect_buffer_type(buffer: &[u8]) -> DocumentType {
 /// Args:
 ///  file_path: path to the PDF file
 /// Returns:
-/// DocumentType object
-pub fn detect_file_type(file_path: &str) -> DocumentType {
+/// DocumentType object, or a parse error if the PDF could not be loaded
+pub fn detect_file_type(file_path: &str) -> Result<DocumentType> {
     // init the parser
     let pdf_parser = Pdfium::default();
 
-    let detected_type = match pdf_parser.load_pdf_from_file(file_path, None) {
-        Ok(pdf_file) => detect_document_type(&pdf_file),
-        Err(_) => DocumentType::Malformed,
-    };
+    let pdf_file = pdf_parser
+        .load_pdf_from_file(file_path, None)
+        .map_err(|e| Error::Parse(e.to_string()))?;
+    Ok(detect_document_type(&pdf_file))
+}
 
-    detected_type
+/// Check whether the text of a document of a known type is an unusable OCR text layer.
+/// Only ImagePostOCR documents are checked; digital and mixed documents keep their text, and
+/// the noise report is only computed for OCR'd documents.
+/// Args:
+///   document_type: type of the PDF, e.g., from extract_buffer_with_type
+///   text: text extracted from the PDF
+///   config: noise thresholds
+/// Returns:
+///  true if the document is ImagePostOCR and its text is unusable
+pub fn is_ocr_document_text_unusable(
+    document_type: DocumentType,
+    text: &str,
+    config: &NoiseConfig,
+) -> bool {
+    document_type == DocumentType::ImagePostOCR && get_noise_report(text, config).is_unusable
+}
+
+/// Check whether the text layer of an OCR'd PDF is too noisy to use.
+/// This loads the PDF to detect its type; when extracting the text as well, use
+/// extract_buffer_with_type and is_ocr_document_text_unusable to load it once.
+/// Args:
+///   buffer: byte array of the PDF file
+///   text: text extracted from the PDF
+///   config: noise thresholds
+/// Returns:
+///  true if the document is ImagePostOCR and its text is unusable, or a parse error if the PDF
+///  could not be loaded
+pub fn is_ocr_text_unusable(buffer: &[u8], text: &str, config: &NoiseConfig) -> Result<bool> {
+    Ok(is_ocr_document_text_unusable(
+        detect_buffer_type(buffer)?,
+        text,
+        config,
+    ))
 }
 
 #[cfg(test)]
@@ -267,7 +298,7 @@ mod tests {
     #[test]
     fn test_detect_digital() {
         // load the file
-        let document_type = detect_file_type(&get_test_file_path());
+        let document_type = detect_file_type(&get_test_file_path()).unwrap();
 
         assert_eq!(document_type, DocumentType::Text);
     }
@@ -275,7 +306,7 @@ mod tests {
     #[test]
     fn test_detect_ocr_pre() {
         // load the file
-        let document_type = detect_file_type(&get_test_ocr_pre_file_path());
+        let document_type = detect_file_type(&get_test_ocr_pre_file_path()).unwrap();
 
         assert_eq!(document_type, DocumentType::ImagePreOCR);
     }
@@ -283,8 +314,52 @@ mod tests {
     #[test]
     fn test_detect_ocr_post() {
         // load the file
-        let document_type = detect_file_type(&get_test_ocr_post_file_path());
+        let document_type = detect_file_type(&get_test_ocr_post_file_path()).unwrap();
 
         assert_eq!(document_type, DocumentType::ImagePostOCR);
     }
+
+    #[test]
+    fn test_is_ocr_document_text_unusable() {
+        let garbage = "¤¤ ^q~u^ick ¦¦ \u{e000}";
+        let config = NoiseConfig::default();
+        assert!(is_ocr_document_text_unusable(
+            DocumentType::ImagePostOCR,
+            garbage,
+            &config
+        ));
+        assert!(!is_ocr_document_text_unusable(
+            DocumentType::Text,
+            garbage,
+            &config
+        ));
+        assert!(!is_ocr_document_text_unusable(
+            DocumentType::ImagePostOCR,
+            "The quick brown fox jumps over the lazy dog.",
+            &config
+        ));
+    }
+
+    #[test]
+    fn test_is_ocr_text_unusable() {
+        // digital documents are never checked, whatever their text
+        let buffer = std::fs::read(get_test_file_path()).unwrap();
+        assert!(!is_ocr_text_unusable(&buffer, "¤¤ ^q~u^ick ¦¦", &NoiseConfig::default()).unwrap());
+
+        let buffer = std::fs::read(get_test_ocr_post_file_path()).unwrap();
+        assert!(
+            is_ocr_text_unusable(&buffer, "¤¤ ^q~u^ick ¦¦ \u{e000}", &NoiseConfig::default())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_detect_bad_file() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("resources");
+        path.push("bad_file.pdf");
+
+        let result = detect_file_type(path.to_str().unwrap());
+        assert!(matches!(result, Err(Error::Parse(_))));
+    }
 }
@@ -1,3 +1,4 @@
+use crate::errors::Result;
 use crate::io::fs::files::read_file_content;
 use crate::parsers::html::conversion::{HtmlToMarkdownParser, ParserConfig};
 use reqwest::blocking::Client as SyncClient;
@@ -17,37 +18,37 @@ impl AsyncTikaClient {
         }
     }
 
-    pub async fn get_recursive_metadata_buffer(&self, buffer: &[u8]) -> Vec<Value> {
+    pub async fn get_recursive_metadata_buffer(&self, buffer: &[u8]) -> Result<Vec<Value>> {
         let response = self
             .client
             .put(&format!("{}/rmeta/ignore", self.server_url))
             .header("Accept", "application/json")
             .body(buffer.to_vec())
             .send()
-            .await
-            .unwrap();
+            .await?
+            .error_for_status()?;
 
-        response.json::<Vec<Value>>().await.unwrap()
+        Ok(response.json::<Vec<Value>>().await?)
     }
 
-    pub async fn get_recursive_metadata_file(&self, path: &str) -> Vec<Value> {
-        let buffer = read_file_content(path).unwrap();
+    pub async fn get_recursive_metadata_file(&self, path: &str) -> Result<Vec<Value>> {
+        let buffer = read_file_content(path)?;
         self.get_recursive_metadata_buffer(&buffer).await
     }
 
-    pub async fn get_recursive_content_html_buffer(&self, buffer: &[u8]) -> Vec<String> {
+    pub async fn get_recursive_content_html_buffer(&self, buffer: &[u8]) -> Result<Vec<String>> {
         let response = self
             .client
             .put(&format!("{}/rmeta/html", self.server_url))
             .header("Accept", "application/json")
             .body(buffer.to_vec())
             .send()
-            .await
-            .unwrap();
+            .await?
+            .error_for_status()?;
 
-        let json_data = response.json::<Vec<Value>>().await.unwrap();
+        let json_data = response.json::<Vec<Value>>().await?;
 
-        json_data
+        Ok(json_data
             .iter()
             .filter_map(|data| {
                 if let Some(content) = data["X-TIKA:content"].as_str() {
@@ -56,11 +57,11 @@ impl AsyncTikaClient {
                     None
                 }
             })
-            .collect()
+            .collect())
     }
 
-    pub async fn get_recursive_content_html_file(&self, path: &str) -> Vec<String> {
-        let buffer = read_file_content(path).unwrap();
+    pub async fn get_recursive_content_html_file(&self, path: &str) -> Result<Vec<String>> {
+        let buffer = read_file_content(path)?;
         self.get_recursive_content_html_buffer(&buffer).await
     }
 
@@ -69,9 +70,10 @@ impl AsyncTikaClient {
         buffer: &[u8],
         output_links: bool,
         output_images: bool,
-    ) -> Vec<String> {
-        self.get_recursive_content_html_buffer(buffer)
-            .await
+    ) -> Result<Vec<String>> {
+        Ok(self
+            .get_recursive_content_html_buffer(buffer)
+            .await?
             .iter()
             .map(|html| {
                 HtmlToMarkdownParser::new(
@@ -80,7 +82,7 @@ impl AsyncTikaClient {
                 )
                 .to_markdown()
             })
-            .collect()
+            .collect())
     }
 
     pub async fn get_recursive_content_markdown_file(
@@ -88,8 +90,8 @@ impl AsyncTikaClient {
         path: &str,
         output_links: bool,
         output_images: bool,
-    ) -> Vec<String> {
-        let buffer = read_file_content(path).unwrap();
+    ) -> Result<Vec<String>> {
+        let buffer = read_file_content(path)?;
         self.get_recursive_content_markdown_buffer(&buffer, output_links, output_images)
             .await
     }
@@ -108,35 +110,35 @@ impl SyncTikaClient {
         }
     }
 
-    pub fn get_recursive_metadata_buffer(&self, buffer: &[u8]) -> Vec<Value> {
+    pub fn get_recursive_metadata_buffer(&self, buffer: &[u8]) -> Result<Vec<Value>> {
         let response = self
             .client
             .put(&format!("{}/rmeta/ignore", self.server_url))
             .header("Accept", "application/json")
             .body(buffer.to_vec())
-            .send()
-            .unwrap();
+            .send()?
+            .error_for_status()?;
 
-        response.json::<Vec<Value>>().unwrap()
+        Ok(response.json::<Vec<Value>>()?)
     }
 
-    pub fn get_recursive_metadata_file(&self, path: &str) -> Vec<Value> {
-        let buffer = read_file_content(path).unwrap();
+    pub fn get_recursive_metadata_file(&self, path: &str) -> Result<Vec<Value>> {
+        let buffer = read_file_content(path)?;
         self.get_recursive_metadata_buffer(&buffer)
     }
 
-    pub fn get_recursive_content_html_buffer(&self, buffer: &[u8]) -> Vec<String> {
+    pub fn get_recursive_content_html_buffer(&self, buffer: &[u8]) -> Result<Vec<String>> {
         let response = self
             .client
             .put(&format!("{}/rmeta/html", self.server_url))
             .header("Accept", "application/json")
             .body(buffer.to_vec())
-            .send()
-            .unwrap();
+            .send()?
+            .error_for_status()?;
 
-        let json_data = response.json::<Vec<Value>>().unwrap();
+        let json_data = response.json::<Vec<Value>>()?;
 
-        json_data
+        Ok(json_data
             .iter()
             .filter_map(|data| {
                 if let Some(content) = data["X-TIKA:content"].as_str() {
@@ -145,11 +147,11 @@ impl SyncTikaClient {
                     None
                 }
             })
-            .collect()
+            .collect())
     }
 
-    pub fn get_recursive_content_html_file(&self, path: &str) -> Vec<String> {
-        let buffer = read_file_content(path).unwrap();
+    pub fn get_recursive_content_html_file(&self, path: &str) -> Result<Vec<String>> {
+        let buffer = read_file_content(path)?;
         self.get_recursive_content_html_buffer(&buffer)
     }
 
@@ -158,8 +160,9 @@ impl SyncTikaClient {
         buffer: &[u8],
         output_links: bool,
         output_images: bool,
-    ) -> Vec<String> {
-        self.get_recursive_content_html_buffer(buffer)
+    ) -> Result<Vec<String>> {
+        Ok(self
+            .get_recursive_content_html_buffer(buffer)?
             .iter()
             .map(|html| {
                 HtmlToMarkdownParser::new(
@@ -168,7 +171,7 @@ impl SyncTikaClient {
                 )
                 .to_markdown()
             })
-            .collect()
+            .collect())
     }
 
     pub fn get_recursive_content_markdown_file(
@@ -176,8 +179,8 @@ impl SyncTikaClient {
         path: &str,
         output_links: bool,
         output_images: bool,
-    ) -> Vec<String> {
-        let buffer = read_file_content(path).unwrap();
+    ) -> Result<Vec<String>> {
+        let buffer = read_file_content(path)?;
         self.get_recursive_content_markdown_buffer(&buffer, output_links, output_images)
     }
 }
@@ -195,7 +198,7 @@ mod tests {
     async fn test_async_get_recursive_metadata_buffer() {
         let buffer = b"<html><body>Hello, world!</body></html>";
         let client = AsyncTikaClient::new(&get_server_url());
-        let metadata = client.get_recursive_metadata_buffer(buffer).await;
+        let metadata = client.get_recursive_metadata_buffer(buffer).await.unwrap();
         assert_eq!(metadata[0]["Content-Encoding"], "ISO-8859-1");
         assert_eq!(metadata[0]["Content-Length"], "39");
     }
@@ -204,7 +207,7 @@ mod tests {
     fn test_sync_get_recursive_metadata_buffer() {
         let buffer = b"<html><body>Hello, world!</body></html>";
         let client = SyncTikaClient::new(&get_server_url());
-        let metadata = client.get_recursive_metadata_buffer(buffer);
+        let metadata = client.get_recursive_metadata_buffer(buffer).unwrap();
         assert_eq!(metadata[0]["Content-Encoding"], "ISO-8859-1");
         assert_eq!(metadata[0]["Content-Length"], "39");
     }
@@ -213,7 +216,7 @@ mod tests {
     async fn test_async_get_recursive_metadata_buffer_pdf() {
         let path = std::env::var("CARGO_MANIFEST_DIR").unwrap() + "/resources/test1.pdf";
         let client = AsyncTikaClient::new(&get_server_url());
-        let metadata = client.get_recursive_metadata_file(&path).await;
+        let metadata = client.get_recursive_metadata_file(&path).await.unwrap();
 
         assert_eq!(metadata[0]["Content-Type"], "application/pdf");
         assert_eq!(
@@ -226,7 +229,7 @@ mod tests {
     fn test_sync_get_recursive_metadata_buffer_pdf() {
         let path = std::env::var("CARGO_MANIFEST_DIR").unwrap() + "/resources/test1.pdf";
         let client = SyncTikaClient::new(&get_server_url());
-        let metadata = client.get_recursive_metadata_file(&path);
+        let metadata = client.get_recursive_metadata_file(&path).unwrap();
 
         assert_eq!(metadata[0]["Content-Type"], "application/pdf");
         assert_eq!(
@@ -239,7 +242,10 @@ mod tests {
     async fn test_async_get_recursive_content_html_buffer() {
         let buffer = b"<html><body>Hello, world!</body></html>";
         let client = AsyncTikaClient::new(&get_server_url());
-        let content = client.get_recursive_content_html_buffer(buffer).await;
+        let content = client
+            .get_recursive_content_html_buffer(buffer)
+            .await
+            .unwrap();
         assert!(content[0].contains("<title></title>\n</head>\n<body>Hello, world!</body>"));
     }
 
@@ -247,7 +253,7 @@ mod tests {
     fn test_sync_get_recursive_content_html_buffer() {
         let buffer = b"<html><body>Hello, world!</body></html>";
         let client = SyncTikaClient::new(&get_server_url());
-        let content = client.get_recursive_content_html_buffer(buffer);
+        let content = client.get_recursive_content_html_buffer(buffer).unwrap();
         assert!(content[0].contains("<title></title>\n</head>\n<body>Hello, world!</body>"));
     }
 
@@ -255,7 +261,7 @@ mod tests {
     async fn test_async_get_recursive_content_html_file() {
         let path = std::env::var("CARGO_MANIFEST_DIR").unwrap() + "/resources/test1.pdf";
         let client = AsyncTikaClient::new(&get_server_url());
-        let content = client.get_recursive_content_html_file(&path).await;
+        let content = client.get_recursive_content_html_file(&path).await.unwrap();
         assert!(content[0].contains("DEPARTMENT OF AGRICULTURE"));
     }
 
@@ -263,7 +269,7 @@ mod tests {
     fn test_sync_get_recursive_content_html_file() {
         let path = std::env::var("CARGO_MANIFEST_DIR").unwrap() + "/resources/test1.pdf";
         let client = SyncTikaClient::new(&get_server_url());
-        let content = client.get_recursive_content_html_file(&path);
+        let content = client.get_recursive_content_html_file(&path).unwrap();
         assert!(content[0].contains("DEPARTMENT OF AGRICULTURE"));
     }
 
@@ -273,7 +279,8 @@ mod tests {
         let client = AsyncTikaClient::new(&get_server_url());
         let content = client
             .get_recursive_content_markdown_buffer(buffer, true, true)
-            .await;
+            .await
+            .unwrap();
         assert_eq!(content[0], "# Test\n\nHello, world!\n");
     }
 
@@ -281,7 +288,9 @@ mod tests {
     fn test_sync_get_recursive_content_markdown_buffer() {
         let buffer = b"<html><body><h1>Test</h1><p>Hello, world!</p></body></html>";
         let client = SyncTikaClient::new(&get_server_url());
-        let content = client.get_recursive_content_markdown_buffer(buffer, true, true);
+        let content = client
+            .get_recursive_content_markdown_buffer(buffer, true, true)
+            .unwrap();
         assert_eq!(content[0], "# Test\n\nHello, world!\n");
     }
 
@@ -291,7 +300,8 @@ mod tests {
         let client = AsyncTikaClient::new(&get_server_url());
         let content = client
             .get_recursive_content_markdown_file(&path, true, true)
-            .await;
+            .await
+            .unwrap();
         assert!(content[0].contains("DEPARTMENT OF AGRICULTURE"));
     }
 
@@ -299,7 +309,9 @@ mod tests {
     fn test_sync_get_recursive_content_markdown_file() {
         let path = std::env::var("CARGO_MANIFEST_DIR").unwrap() + "/resources/file1.html";
         let client = SyncTikaClient::new(&get_server_url());
-        let content = client.get_recursive_content_markdown_file(&path, true, true);
+        let content = client
+            .get_recursive_content_markdown_file(&path, true, true)
+            .unwrap();
         assert!(content[0].contains("# Our blog\n\nWhat we're reading, thinking, and doing.\n"));
     }
 
@@ -307,7 +319,9 @@ mod tests {
     fn test_sync_get_recursive_content_markdown_file_pdf() {
         let path = std::env::var("CARGO_MANIFEST_DIR").unwrap() + "/resources/test1.pdf";
         let client = SyncTikaClient::new(&get_server_url());
-        let content = client.get_recursive_content_markdown_file(&path, true, true);
+        let content = client
+            .get_recursive_content_markdown_file(&path, true, true)
+            .unwrap();
         assert!(content[0].contains("DEPARTMENT OF AGRICULTURE"));
     }
 
@@ -315,7 +329,24 @@ mod tests {
     fn test_sync_get_recursive_content_markdown_file_docx() {
         let path = std::env::var("CARGO_MANIFEST_DIR").unwrap() + "/resources/test1.docx";
         let client = SyncTikaClient::new(&get_server_url());
-        let content = dbg!(client.get_recursive_content_markdown_file(&path, true, true));
+        let content = dbg!(client
+            .get_recursive_content_markdown_file(&path, true, true)
+            .unwrap());
         assert!(content[0].contains("\n\n**Regulatory Impact Analysis**\n\n"));
     }
+
+    #[test]
+    fn test_sync_get_recursive_metadata_file_missing() {
+        let path = std::env::var("CARGO_MANIFEST_DIR").unwrap() + "/resources/missing_file";
+        let client = SyncTikaClient::new(&get_server_url());
+        let result = client.get_recursive_metadata_file(&path);
+        assert!(matches!(result, Err(crate::errors::Error::Io(_))));
+    }
+
+    #[test]
+    fn test_sync_get_recursive_metadata_buffer_bad_server() {
+        let client = SyncTikaClient::new("http://127.0.0.1:1");
+        let result = client.get_recursive_metadata_buffer(b"Hello, world!");
+        assert!(matches!(result, Err(crate::errors::Error::Tika(_))));
+    }
 }
@@ -0,0 +1,499 @@
+/// Declarative document-processing pipelines
+///
+/// A pipeline is an ordered list of stages applied to each document. Stages can rewrite a
+/// document, drop it (filters and dedup), or emit several documents (splitting). Pipelines are
+/// built from a JSON or TOML config and run in parallel over JSONL records or the files of a
+/// directory, keeping per-stage counters and writing failed documents to a side output.
+pub mod stages;
+
+// external imports
+use rayon::prelude::*;
+use serde_json::{json, Map, Value};
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// internal imports
+use crate::errors::{Error, Result};
+use crate::io::fs::directories::get_files;
+use crate::io::fs::files::read_file_content;
+use crate::tasks::sequences::decode_content;
+
+// number of documents processed in parallel before their results are written
+const CHUNK_SIZE: usize = 1024;
+
+/// A document moving through a pipeline
+///
+/// `bytes` holds the raw source (e.g., a PDF or HTML file) until a parse stage fills in `text`;
+/// stages record what they compute in `metadata`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Document {
+    pub id: String,
+    pub bytes: Option<Vec<u8>>,
+    pub text: Option<String>,
+    pub metadata: Map<String, Value>,
+}
+
+impl Document {
+    pub fn new(id: &str) -> Self {
+        Document {
+            id: id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Build a document from a JSONL record with an optional `id`, `text`, base64 `content`
+    /// (compressed or not) and `metadata` object. Records without an id use the fallback id.
+    pub fn from_json(record: &Value, fallback_id: &str) -> Result<Self> {
+        let id = match &record["id"] {
+            Value::String(id) => id.clone(),
+            Value::Null => fallback_id.to_string(),
+            id => id.to_string(),
+        };
+        let bytes = match record["content"].as_str() {
+            Some(content) => Some(decode_content(content, None)?),
+            None => None,
+        };
+        let metadata = match &record["metadata"] {
+            Value::Object(metadata) => metadata.clone(),
+            _ => Map::new(),
+        };
+        Ok(Document {
+            id,
+            bytes,
+            text: record["text"].as_str().map(|text| text.to_string()),
+            metadata,
+        })
+    }
+
+    /// Build a document from a file, using the path as its id.
+    pub fn from_file(path: &str) -> Result<Self> {
+        let mut document = Document::new(path);
+        document.bytes = Some(read_file_content(path)?);
+        document.metadata.insert("path".to_string(), json!(path));
+        Ok(document)
+    }
+
+    /// The output record: id, text and metadata. Raw bytes are not written.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "text": self.text,
+            "metadata": self.metadata,
+        })
+    }
+
+    /// The document text, or an error naming the stage that needed it.
+    pub fn require_text(&self, stage: &str) -> Result<&str> {
+        self.text.as_deref().ok_or_else(|| {
+            Error::Config(format!(
+                "stage {} needs text; add a parse stage before it",
+                stage
+            ))
+        })
+    }
+}
+
+/// A step applied to each document
+///
+/// Returning no documents drops the input; returning several splits it. `process` runs in
+/// parallel; `resolve` then runs on its outputs one at a time in input order, for stages whose
+/// result depends on earlier documents, such as dedup.
+pub trait Stage: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn process(&self, document: Document) -> Result<Vec<Document>>;
+
+    fn resolve(&self, document: Document) -> Result<Vec<Document>> {
+        Ok(vec![document])
+    }
+}
+
+/// A document that failed in a stage, written to the failure side output
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure {
+    pub id: String,
+    pub stage: String,
+    pub error: String,
+}
+
+impl Failure {
+    /// A document that could not be read, reported as a failure of an `input` stage
+    pub fn input(id: &str, error: Error) -> Self {
+        Failure {
+            id: id.to_string(),
+            stage: "input".to_string(),
+            error: error.to_string(),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "id": self.id,
+            "stage": self.stage,
+            "error": self.error,
+        })
+    }
+}
+
+/// Counters for a single stage
+#[derive(Debug, Default)]
+struct StageCounters {
+    input: AtomicUsize,
+    output: AtomicUsize,
+    dropped: AtomicUsize,
+    failed: AtomicUsize,
+}
+
+/// A snapshot of the counters for a single stage
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageStats {
+    pub name: String,
+    pub input: usize,
+    pub output: usize,
+    pub dropped: usize,
+    pub failed: usize,
+}
+
+impl StageStats {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "stage": self.name,
+            "input": self.input,
+            "output": self.output,
+            "dropped": self.dropped,
+            "failed": self.failed,
+        })
+    }
+}
+
+/// Output documents and failures of a chunk, each with the index of its input
+type ChunkResults = (Vec<(usize, Document)>, Vec<(usize, Failure)>);
+
+pub struct Pipeline {
+    stages: Vec<Box<dyn Stage>>,
+    counters: Vec<StageCounters>,
+}
+
+impl Pipeline {
+    pub fn new(stages: Vec<Box<dyn Stage>>) -> Self {
+        let counters = stages.iter().map(|_| StageCounters::default()).collect();
+        Pipeline { stages, counters }
+    }
+
+    /// Build a pipeline from a config with a `stages` array; see `stages::stage_from_config`.
+    pub fn from_config(config: &Value) -> Result<Self> {
+        let stage_configs = config["stages"]
+            .as_array()
+            .ok_or_else(|| Error::Config("missing stages array".to_string()))?;
+        let stages = stage_configs
+            .iter()
+            .map(stages::stage_from_config)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Pipeline::new(stages))
+    }
+
+    pub fn from_json_str(config: &str) -> Result<Self> {
+        let config: Value =
+            serde_json::from_str(config).map_err(|e| Error::Config(e.to_string()))?;
+        Pipeline::from_config(&config)
+    }
+
+    pub fn from_toml_str(config: &str) -> Result<Self> {
+        let config: toml::Value =
+            toml::from_str(config).map_err(|e| Error::Config(e.to_string()))?;
+        let config = serde_json::to_value(config).map_err(|e| Error::Config(e.to_string()))?;
+        Pipeline::from_config(&config)
+    }
+
+    /// Load a pipeline config, reading `.toml` files as TOML and anything else as JSON.
+    pub fn from_file(path: &str) -> Result<Self> {
+        let config = std::fs::read_to_string(path)?;
+        match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some("toml") => Pipeline::from_toml_str(&config),
+            _ => Pipeline::from_json_str(&config),
+        }
+    }
+
+    pub fn stage_names(&self) -> Vec<&str> {
+        self.stages.iter().map(|stage| stage.name()).collect()
+    }
+
+    /// Run one document through every stage.
+    /// Returns the documents that came out of the last stage and any failures along the way.
+    pub fn run_document(&self, document: Document) -> (Vec<Document>, Vec<Failure>) {
+        let (documents, failures) = self.run_chunk(vec![(0, document)]);
+        (
+            documents
+                .into_iter()
+                .map(|(_, document)| document)
+                .collect(),
+            failures.into_iter().map(|(_, failure)| failure).collect(),
+        )
+    }
+
+    /// Run a chunk of documents through every stage, one stage at a time, tagging outputs and
+    /// failures with the index of the input they came from. Outputs stay in input order.
+    fn run_chunk(&self, mut documents: Vec<(usize, Document)>) -> ChunkResults {
+        let mut failures = Vec::new();
+        for (stage, counters) in self.stages.iter().zip(self.counters.iter()) {
+            counters.input.fetch_add(documents.len(), Ordering::Relaxed);
+            let results: Vec<_> = documents
+                .into_par_iter()
+                .map(|(index, document)| {
+                    let id = document.id.clone();
+                    (index, id, stage.process(document))
+                })
+                .collect();
+
+            let mut outputs = Vec::with_capacity(results.len());
+            for (index, id, result) in results {
+                let result = result.and_then(|results| {
+                    results
+                        .into_iter()
+                        .map(|document| stage.resolve(document))
+                        .collect::<Result<Vec<_>>>()
+                });
+                match result.map(|results| results.concat()) {
+                    Ok(results) if results.is_empty() => {
+                        counters.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Ok(results) => {
+                        counters.output.fetch_add(results.len(), Ordering::Relaxed);
+                        outputs.extend(results.into_iter().map(|document| (index, document)));
+                    }
+                    Err(e) => {
+                        counters.failed.fetch_add(1, Ordering::Relaxed);
+                        failures.push((
+                            index,
+                            Failure {
+                                id,
+                                stage: stage.name().to_string(),
+                                error: e.to_string(),
+                            },
+                        ));
+                    }
+                }
+            }
+            documents = outputs;
+        }
+        (documents, failures)
+    }
+
+    /// Run documents through the pipeline in parallel chunks, writing the output documents and
+    /// failures as JSONL in input order. Inputs that could not be read are written as failures
+    /// of an `input` stage. Returns the number of failures written.
+    pub fn run<I, W, F>(&self, documents: I, output: &mut W, failures: &mut F) -> Result<usize>
+    where
+        I: Iterator<Item = std::result::Result<Document, Failure>>,
+        W: Write,
+        F: Write,
+    {
+        let mut documents = documents.peekable();
+        let mut num_failures = 0;
+        while documents.peek().is_some() {
+            let mut inputs = Vec::new();
+            let mut errors = Vec::new();
+            for (index, document) in documents.by_ref().take(CHUNK_SIZE).enumerate() {
+                match document {
+                    Ok(document) => inputs.push((index, document)),
+                    Err(failure) => errors.push((index, failure)),
+                }
+            }
+
+            let (outputs, stage_errors) = self.run_chunk(inputs);
+            errors.extend(stage_errors);
+            errors.sort_by_key(|(index, _)| *index);
+
+            for (_, document) in outputs {
+                write_json_line(output, &document.to_json())?;
+            }
+            num_failures += errors.len();
+            for (_, failure) in errors {
+                write_json_line(failures, &failure.to_json())?;
+            }
+        }
+        output.flush()?;
+        failures.flush()?;
+        Ok(num_failures)
+    }
+
+    pub fn stats(&self) -> Vec<StageStats> {
+        self.stages
+            .iter()
+            .zip(self.counters.iter())
+            .map(|(stage, counters)| StageStats {
+                name: stage.name().to_string(),
+                input: counters.input.load(Ordering::Relaxed),
+                output: counters.output.load(Ordering::Relaxed),
+                dropped: counters.dropped.load(Ordering::Relaxed),
+                failed: counters.failed.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+}
+
+fn write_json_line<W: Write>(writer: &mut W, value: &Value) -> Result<()> {
+    serde_json::to_writer(&mut *writer, value).map_err(|e| Error::Io(e.into()))?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Read documents from JSONL records, using the line number as the id of records without one.
+pub fn read_jsonl_documents<R: BufRead>(
+    reader: R,
+) -> impl Iterator<Item = std::result::Result<Document, Failure>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|(index, line)| {
+            let line_number = (index + 1).to_string();
+            let record: Value = line
+                .map_err(Error::Io)
+                .and_then(|line| {
+                    serde_json::from_str(&line).map_err(|e| Error::Parse(e.to_string()))
+                })
+                .map_err(|e| Failure::input(&line_number, e))?;
+            Document::from_json(&record, &line_number).map_err(|e| Failure::input(&line_number, e))
+        })
+}
+
+/// Read documents from the files under a directory, in sorted path order.
+pub fn read_directory_documents(
+    path: &str,
+) -> impl Iterator<Item = std::result::Result<Document, Failure>> {
+    let mut files = get_files(path);
+    files.sort();
+    files
+        .into_iter()
+        .map(|file| Document::from_file(&file).map_err(|e| Failure::input(&file, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Upper;
+
+    impl Stage for Upper {
+        fn name(&self) -> &str {
+            "upper"
+        }
+
+        fn process(&self, mut document: Document) -> Result<Vec<Document>> {
+            let text = document.require_text("upper")?.to_uppercase();
+            document.text = Some(text);
+            Ok(vec![document])
+        }
+    }
+
+    fn get_document(id: &str, text: Option<&str>) -> Document {
+        let mut document = Document::new(id);
+        document.text = text.map(|text| text.to_string());
+        document
+    }
+
+    #[test]
+    fn test_document_from_json() {
+        let record = json!({
+            "id": 7,
+            "text": "hello",
+            "content": "eJwryUhVSMuvUMgqzS0oVsgvSy1SKAEK5SRWVSqk5KcDALfLC3U=",
+            "metadata": {"source": "test"},
+        });
+        let document = Document::from_json(&record, "1").unwrap();
+        assert_eq!(document.id, "7");
+        assert_eq!(document.text.as_deref(), Some("hello"));
+        assert_eq!(
+            document.bytes.as_deref(),
+            Some(b"the fox jumps over the lazy dog".as_slice())
+        );
+        assert_eq!(document.metadata["source"], "test");
+
+        let document = Document::from_json(&json!({"text": "hi"}), "3").unwrap();
+        assert_eq!(document.id, "3");
+        assert!(document.bytes.is_none());
+    }
+
+    #[test]
+    fn test_run_document_counters() {
+        let pipeline = Pipeline::new(vec![Box::new(Upper)]);
+        let (outputs, failures) = pipeline.run_document(get_document("a", Some("abc")));
+        assert_eq!(outputs[0].text.as_deref(), Some("ABC"));
+        assert!(failures.is_empty());
+
+        let (outputs, failures) = pipeline.run_document(get_document("b", None));
+        assert!(outputs.is_empty());
+        assert_eq!(failures[0].id, "b");
+        assert_eq!(failures[0].stage, "upper");
+
+        assert_eq!(
+            pipeline.stats(),
+            vec![StageStats {
+                name: "upper".to_string(),
+                input: 2,
+                output: 1,
+                dropped: 0,
+                failed: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_run_jsonl() {
+        let pipeline = Pipeline::new(vec![Box::new(Upper)]);
+        let input = "{\"id\": \"a\", \"text\": \"abc\"}\n\nnot json\n{\"id\": \"c\"}\n";
+        let mut output = Vec::new();
+        let mut failures = Vec::new();
+        let num_failures = pipeline
+            .run(
+                read_jsonl_documents(input.as_bytes()),
+                &mut output,
+                &mut failures,
+            )
+            .unwrap();
+        assert_eq!(num_failures, 2);
+
+        let output: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(
+            output,
+            vec![json!({"id": "a", "text": "ABC", "metadata": {}})]
+        );
+
+        let failures: Vec<Value> = String::from_utf8(failures)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0]["id"], "3");
+        assert_eq!(failures[0]["stage"], "input");
+        assert_eq!(failures[1]["id"], "c");
+        assert_eq!(failures[1]["stage"], "upper");
+    }
+
+    #[test]
+    fn test_read_directory_documents() {
+        let path = format!("{}/resources", env!("CARGO_MANIFEST_DIR"));
+        let documents: Vec<Document> = read_directory_documents(&path)
+            .filter_map(|document| document.ok())
+            .collect();
+        assert!(documents.len() > 10);
+        assert!(documents.iter().all(|document| document.bytes.is_some()));
+        assert!(documents
+            .iter()
+            .any(|document| document.id.ends_with("file1.html")));
+    }
+
+    #[test]
+    fn test_from_config_errors() {
+        assert!(Pipeline::from_json_str("{}").is_err());
+        assert!(Pipeline::from_json_str(r#"{"stages": [{"type": "missing"}]}"#).is_err());
+        assert!(Pipeline::from_toml_str("stages = [").is_err());
+    }
+}
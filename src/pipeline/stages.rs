@@ -0,0 +1,815 @@
+/// Built-in pipeline stages and their config parsing
+///
+/// Each entry of a pipeline config's `stages` array is an object with a `type` key naming the
+/// stage and the stage's options, e.g., in TOML:
+///
+/// ```toml
+/// [[stages]]
+/// type = "parse"
+/// format = "auto"
+///
+/// [[stages]]
+/// type = "split"
+/// max_size = 2048
+/// ```
+// external imports
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+// internal imports
+use super::{Document, Stage};
+use crate::algos::hashing::blake3::hash_str;
+use crate::algos::quality::heuristics::{check_quality, QualityConfig, QualitySignals};
+use crate::algos::quality::noise::NoiseConfig;
+use crate::algos::splitting::simple::split_str;
+use crate::algos::unicode::fixes::{fix_text, TextFixConfig};
+use crate::algos::unicode::normalizations::{
+    nfc_str, nfkc_printable_str, nfkc_str, normalize_whitespace, WhitespaceConfig,
+};
+use crate::algos::unicode::scripts::{get_dominant_script, get_script_ratios, script_to_string};
+use crate::errors::{Error, Result};
+use crate::io::encoding::decode_bytes;
+use crate::io::fs::file_info::{get_file_info_from_buffer, FileInfo};
+use crate::parsers::html::conversion::{HtmlToMarkdownParser, HtmlToPlainTextParser, ParserConfig};
+use crate::parsers::pdf::conversion;
+use crate::parsers::pdf::detection::is_ocr_document_text_unusable;
+use crate::parsers::tika::client::SyncTikaClient;
+
+/// Build a stage from its config object.
+pub fn stage_from_config(config: &Value) -> Result<Box<dyn Stage>> {
+    let stage_type = config["type"]
+        .as_str()
+        .ok_or_else(|| Error::Config("stage is missing a type".to_string()))?;
+    match stage_type {
+        "file_info" => Ok(Box::new(FileInfoStage)),
+        "parse" => Ok(Box::new(ParseStage::from_config(config)?)),
+        "normalize" => Ok(Box::new(NormalizeStage::from_config(config)?)),
+        "language" => Ok(Box::new(LanguageStage::from_config(config)?)),
+        "dedup" => Ok(Box::new(DedupStage::new())),
+        "quality" => Ok(Box::new(QualityStage::from_config(config)?)),
+        "split" => Ok(Box::new(SplitStage::from_config(config)?)),
+        _ => Err(Error::Config(format!("unknown stage type: {}", stage_type))),
+    }
+}
+
+fn get_str<'a>(config: &'a Value, key: &str, default: &'a str) -> Result<&'a str> {
+    match &config[key] {
+        Value::Null => Ok(default),
+        Value::String(value) => Ok(value),
+        _ => Err(Error::Config(format!("{} must be a string", key))),
+    }
+}
+
+fn get_usize(config: &Value, key: &str, default: usize) -> Result<usize> {
+    match &config[key] {
+        Value::Null => Ok(default),
+        value => value
+            .as_u64()
+            .map(|value| value as usize)
+            .ok_or_else(|| Error::Config(format!("{} must be a non-negative integer", key))),
+    }
+}
+
+fn get_f64(config: &Value, key: &str, default: f64) -> Result<f64> {
+    match &config[key] {
+        Value::Null => Ok(default),
+        value => value
+            .as_f64()
+            .ok_or_else(|| Error::Config(format!("{} must be a number", key))),
+    }
+}
+
+fn get_bool(config: &Value, key: &str, default: bool) -> Result<bool> {
+    match &config[key] {
+        Value::Null => Ok(default),
+        value => value
+            .as_bool()
+            .ok_or_else(|| Error::Config(format!("{} must be a boolean", key))),
+    }
+}
+
+fn get_f64s(config: &Value, key: &str, default: &[f64]) -> Result<Vec<f64>> {
+    match &config[key] {
+        Value::Null => Ok(default.to_vec()),
+        Value::Array(values) if values.len() == default.len() => values
+            .iter()
+            .map(|value| {
+                value
+                    .as_f64()
+                    .ok_or_else(|| Error::Config(format!("{} must be a list of numbers", key)))
+            })
+            .collect(),
+        _ => Err(Error::Config(format!(
+            "{} must be a list of {} numbers",
+            key,
+            default.len()
+        ))),
+    }
+}
+
+fn get_strings(config: &Value, key: &str) -> Result<Option<Vec<String>>> {
+    match &config[key] {
+        Value::Null => Ok(None),
+        Value::Array(values) => values
+            .iter()
+            .map(|value| {
+                value
+                    .as_str()
+                    .map(|value| value.to_string())
+                    .ok_or_else(|| Error::Config(format!("{} must be a list of strings", key)))
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Some),
+        _ => Err(Error::Config(format!("{} must be a list of strings", key))),
+    }
+}
+
+fn file_info_to_json(file_info: &FileInfo) -> Value {
+    json!({
+        "name": file_info.name,
+        "short_name": file_info.short_name,
+        "media_type": file_info.media_type,
+        "extension": file_info.extension,
+        "kind": file_info.kind,
+        "encoding": file_info.encoding,
+    })
+}
+
+fn require_bytes<'a>(document: &'a Document, stage: &str) -> Result<&'a [u8]> {
+    document.bytes.as_deref().ok_or_else(|| {
+        Error::Config(format!(
+            "stage {} needs raw content; records must have a content field",
+            stage
+        ))
+    })
+}
+
+/// Detects the file format of the raw content and stores it in `metadata.file_info`.
+pub struct FileInfoStage;
+
+impl Stage for FileInfoStage {
+    fn name(&self) -> &str {
+        "file_info"
+    }
+
+    fn process(&self, mut document: Document) -> Result<Vec<Document>> {
+        let file_info = get_file_info_from_buffer(require_bytes(&document, self.name())?);
+        document
+            .metadata
+            .insert("file_info".to_string(), file_info_to_json(&file_info));
+        Ok(vec![document])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseFormat {
+    Auto,
+    Html,
+    Pdf,
+    Text,
+    Tika,
+}
+
+/// Extracts text from the raw content as markdown or plain text.
+///
+/// With `format = "auto"`, the media type from a preceding file_info stage (or detected from the
+/// content) picks the parser; formats other than HTML, PDF and text go to Tika when a `tika_url`
+/// is configured. Documents that already have text are passed through. With
+/// `drop_noisy_ocr = true`, OCR'd PDFs whose text layer is unusable are dropped.
+pub struct ParseStage {
+    pub format: ParseFormat,
+    pub markdown: bool,
+    pub tika: Option<SyncTikaClient>,
+    pub ocr_noise: Option<NoiseConfig>,
+}
+
+impl ParseStage {
+    pub fn from_config(config: &Value) -> Result<Self> {
+        let format = match get_str(config, "format", "auto")? {
+            "auto" => ParseFormat::Auto,
+            "html" => ParseFormat::Html,
+            "pdf" => ParseFormat::Pdf,
+            "text" => ParseFormat::Text,
+            "tika" => ParseFormat::Tika,
+            format => return Err(Error::Config(format!("unknown parse format: {}", format))),
+        };
+        let markdown = match get_str(config, "output", "markdown")? {
+            "markdown" => true,
+            "text" => false,
+            output => return Err(Error::Config(format!("unknown parse output: {}", output))),
+        };
+        let tika = match &config["tika_url"] {
+            Value::String(url) => Some(SyncTikaClient::new(url)),
+            Value::Null if format == ParseFormat::Tika => {
+                return Err(Error::Config("tika format needs a tika_url".to_string()))
+            }
+            _ => None,
+        };
+        let ocr_noise = if get_bool(config, "drop_noisy_ocr", false)? {
+            Some(NoiseConfig::default())
+        } else {
+            None
+        };
+        Ok(ParseStage {
+            format,
+            markdown,
+            tika,
+            ocr_noise,
+        })
+    }
+
+    fn detect_format(&self, document: &Document, bytes: &[u8]) -> ParseFormat {
+        let media_type = match document.metadata.get("file_info") {
+            Some(file_info) => file_info["media_type"].as_str().unwrap_or("").to_string(),
+            None => get_file_info_from_buffer(bytes).media_type,
+        };
+        if media_type.contains("html") {
+            ParseFormat::Html
+        } else if media_type == "application/pdf" {
+            ParseFormat::Pdf
+        } else if media_type.starts_with("text/") || self.tika.is_none() {
+            ParseFormat::Text
+        } else {
+            ParseFormat::Tika
+        }
+    }
+
+    fn parse_html(&self, bytes: &[u8]) -> String {
+        let html = decode_bytes(bytes).text;
+        if self.markdown {
+            HtmlToMarkdownParser::new(ParserConfig::new(None, false, false), &html).to_markdown()
+        } else {
+            HtmlToPlainTextParser::new(ParserConfig::new(None, false, false), &html).to_plain_text()
+        }
+    }
+}
+
+impl Stage for ParseStage {
+    fn name(&self) -> &str {
+        "parse"
+    }
+
+    fn process(&self, mut document: Document) -> Result<Vec<Document>> {
+        if document.text.is_some() {
+            return Ok(vec![document]);
+        }
+        let bytes = require_bytes(&document, self.name())?;
+        let format = match self.format {
+            ParseFormat::Auto => self.detect_format(&document, bytes),
+            format => format,
+        };
+        let text = match format {
+            ParseFormat::Html => self.parse_html(bytes),
+            // the document type is detected from the same load, only when it is needed
+            ParseFormat::Pdf => match &self.ocr_noise {
+                Some(noise_config) => {
+                    let (text, document_type) =
+                        conversion::extract_buffer_with_type(bytes, self.markdown)?;
+                    if is_ocr_document_text_unusable(document_type, &text, noise_config) {
+                        return Ok(Vec::new());
+                    }
+                    text
+                }
+                None if self.markdown => conversion::extract_buffer_markdown(bytes)?,
+                None => conversion::extract_buffer_text(bytes)?,
+            },
+            ParseFormat::Tika => {
+                // checked when the stage is built: tika output needs a client
+                let client = self
+                    .tika
+                    .as_ref()
+                    .ok_or_else(|| Error::Config("tika format needs a tika_url".to_string()))?;
+                if self.markdown {
+                    client.get_recursive_content_markdown_buffer(bytes, false, false)?
+                } else {
+                    client
+                        .get_recursive_content_html_buffer(bytes)?
+                        .iter()
+                        .map(|html| {
+                            HtmlToPlainTextParser::new(ParserConfig::new(None, false, false), html)
+                                .to_plain_text()
+                        })
+                        .collect()
+                }
+                .join("\n\n")
+            }
+            ParseFormat::Text | ParseFormat::Auto => decode_bytes(bytes).text,
+        };
+        document.text = Some(text);
+        Ok(vec![document])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NormalizeForm {
+    Nfc,
+    Nfkc,
+    NfkcPrintable,
+    Whitespace,
+    FixText,
+}
+
+/// Normalizes the document text with one of the unicode normalizations.
+pub struct NormalizeStage {
+    pub form: NormalizeForm,
+}
+
+impl NormalizeStage {
+    pub fn from_config(config: &Value) -> Result<Self> {
+        let form = match get_str(config, "form", "nfkc")? {
+            "nfc" => NormalizeForm::Nfc,
+            "nfkc" => NormalizeForm::Nfkc,
+            "nfkc_printable" => NormalizeForm::NfkcPrintable,
+            "whitespace" => NormalizeForm::Whitespace,
+            "fix_text" => NormalizeForm::FixText,
+            form => return Err(Error::Config(format!("unknown normalize form: {}", form))),
+        };
+        Ok(NormalizeStage { form })
+    }
+}
+
+impl Stage for NormalizeStage {
+    fn name(&self) -> &str {
+        "normalize"
+    }
+
+    fn process(&self, mut document: Document) -> Result<Vec<Document>> {
+        let text = document.require_text(self.name())?;
+        let text = match self.form {
+            NormalizeForm::Nfc => nfc_str(text),
+            NormalizeForm::Nfkc => nfkc_str(text),
+            NormalizeForm::NfkcPrintable => nfkc_printable_str(text),
+            NormalizeForm::Whitespace => {
+                normalize_whitespace(text, &WhitespaceConfig::default()).text
+            }
+            NormalizeForm::FixText => fix_text(text, &TextFixConfig::default()).0,
+        };
+        document.text = Some(text);
+        Ok(vec![document])
+    }
+}
+
+/// Keeps documents whose script-bearing characters are mostly in the given scripts.
+///
+/// The dominant script is stored in `metadata.script`; `keep_empty` controls whether documents
+/// with no script-bearing characters (e.g., only digits and punctuation) are kept.
+pub struct LanguageStage {
+    pub scripts: HashSet<String>,
+    pub min_ratio: f64,
+    pub keep_empty: bool,
+}
+
+impl LanguageStage {
+    pub fn from_config(config: &Value) -> Result<Self> {
+        let scripts = get_strings(config, "scripts")?
+            .ok_or_else(|| Error::Config("language stage needs a scripts list".to_string()))?;
+        Ok(LanguageStage {
+            scripts: scripts.into_iter().collect(),
+            min_ratio: get_f64(config, "min_ratio", 0.5)?,
+            keep_empty: get_bool(config, "keep_empty", false)?,
+        })
+    }
+}
+
+impl Stage for LanguageStage {
+    fn name(&self) -> &str {
+        "language"
+    }
+
+    fn process(&self, mut document: Document) -> Result<Vec<Document>> {
+        let text = document.require_text(self.name())?;
+        let dominant_script = match get_dominant_script(text) {
+            Some(script) => script,
+            None if self.keep_empty => return Ok(vec![document]),
+            None => return Ok(Vec::new()),
+        };
+        let ratio: f64 = get_script_ratios(text)
+            .into_iter()
+            .filter(|(script, _)| self.scripts.contains(script_to_string(*script)))
+            .map(|(_, ratio)| ratio)
+            .sum();
+        if ratio < self.min_ratio {
+            return Ok(Vec::new());
+        }
+        document.metadata.insert(
+            "script".to_string(),
+            json!(script_to_string(dominant_script)),
+        );
+        Ok(vec![document])
+    }
+}
+
+/// Drops documents whose text exactly matches an earlier document, by blake3 hash.
+///
+/// Hashes are computed in parallel and stored in `metadata.blake3`; duplicates are resolved in
+/// input order, so the first occurrence is always the one kept.
+pub struct DedupStage {
+    seen: Mutex<HashSet<String>>,
+}
+
+impl DedupStage {
+    pub fn new() -> Self {
+        DedupStage {
+            seen: Mutex::new(HashSet::new()),
+        }
+    }
+}
+
+impl Default for DedupStage {
+    fn default() -> Self {
+        DedupStage::new()
+    }
+}
+
+impl Stage for DedupStage {
+    fn name(&self) -> &str {
+        "dedup"
+    }
+
+    fn process(&self, mut document: Document) -> Result<Vec<Document>> {
+        let hash = hash_str(document.require_text(self.name())?);
+        document.metadata.insert("blake3".to_string(), json!(hash));
+        Ok(vec![document])
+    }
+
+    fn resolve(&self, document: Document) -> Result<Vec<Document>> {
+        let hash = document.metadata["blake3"].as_str().unwrap_or_default();
+        let is_new = self
+            .seen
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(hash.to_string());
+        Ok(if is_new { vec![document] } else { Vec::new() })
+    }
+}
+
+/// Computes heuristic quality signals and drops documents that fail the thresholds.
+///
+/// Thresholds use the `QualityConfig` field names and default to the Gopher values. The signals
+/// and failed checks are stored in `metadata.quality`; with `drop = false`, failing documents are
+/// kept so the signals can be inspected.
+pub struct QualityStage {
+    pub config: QualityConfig,
+    pub drop: bool,
+}
+
+impl QualityStage {
+    pub fn from_config(config: &Value) -> Result<Self> {
+        let default = QualityConfig::default();
+        Ok(QualityStage {
+            config: QualityConfig {
+                min_words: get_usize(config, "min_words", default.min_words)?,
+                max_words: get_usize(config, "max_words", default.max_words)?,
+                min_mean_word_length: get_f64(
+                    config,
+                    "min_mean_word_length",
+                    default.min_mean_word_length,
+                )?,
+                max_mean_word_length: get_f64(
+                    config,
+                    "max_mean_word_length",
+                    default.max_mean_word_length,
+                )?,
+                max_symbol_to_word_ratio: get_f64(
+                    config,
+                    "max_symbol_to_word_ratio",
+                    default.max_symbol_to_word_ratio,
+                )?,
+                max_ellipsis_line_fraction: get_f64(
+                    config,
+                    "max_ellipsis_line_fraction",
+                    default.max_ellipsis_line_fraction,
+                )?,
+                max_bullet_line_fraction: get_f64(
+                    config,
+                    "max_bullet_line_fraction",
+                    default.max_bullet_line_fraction,
+                )?,
+                max_duplicate_line_fraction: get_f64(
+                    config,
+                    "max_duplicate_line_fraction",
+                    default.max_duplicate_line_fraction,
+                )?,
+                max_duplicate_line_char_fraction: get_f64(
+                    config,
+                    "max_duplicate_line_char_fraction",
+                    default.max_duplicate_line_char_fraction,
+                )?,
+                max_duplicate_paragraph_fraction: get_f64(
+                    config,
+                    "max_duplicate_paragraph_fraction",
+                    default.max_duplicate_paragraph_fraction,
+                )?,
+                max_duplicate_paragraph_char_fraction: get_f64(
+                    config,
+                    "max_duplicate_paragraph_char_fraction",
+                    default.max_duplicate_paragraph_char_fraction,
+                )?,
+                max_top_ngram_char_fractions: get_f64s(
+                    config,
+                    "max_top_ngram_char_fractions",
+                    &default.max_top_ngram_char_fractions,
+                )?,
+                max_duplicate_ngram_char_fractions: get_f64s(
+                    config,
+                    "max_duplicate_ngram_char_fractions",
+                    &default.max_duplicate_ngram_char_fractions,
+                )?,
+                min_stop_words: get_usize(config, "min_stop_words", default.min_stop_words)?,
+                min_alphabetic_ratio: get_f64(
+                    config,
+                    "min_alphabetic_ratio",
+                    default.min_alphabetic_ratio,
+                )?,
+            },
+            drop: get_bool(config, "drop", true)?,
+        })
+    }
+}
+
+fn quality_signals_to_json(signals: &QualitySignals, failures: &[String]) -> Value {
+    json!({
+        "word_count": signals.word_count,
+        "mean_word_length": signals.mean_word_length,
+        "symbol_to_word_ratio": signals.symbol_to_word_ratio,
+        "ellipsis_line_fraction": signals.ellipsis_line_fraction,
+        "bullet_line_fraction": signals.bullet_line_fraction,
+        "duplicate_line_fraction": signals.duplicate_line_fraction,
+        "duplicate_line_char_fraction": signals.duplicate_line_char_fraction,
+        "duplicate_paragraph_fraction": signals.duplicate_paragraph_fraction,
+        "duplicate_paragraph_char_fraction": signals.duplicate_paragraph_char_fraction,
+        "top_ngram_char_fractions": signals.top_ngram_char_fractions,
+        "duplicate_ngram_char_fractions": signals.duplicate_ngram_char_fractions,
+        "stop_word_count": signals.stop_word_count,
+        "alphabetic_ratio": signals.alphabetic_ratio,
+        "failures": failures,
+    })
+}
+
+impl Stage for QualityStage {
+    fn name(&self) -> &str {
+        "quality"
+    }
+
+    fn process(&self, mut document: Document) -> Result<Vec<Document>> {
+        let (signals, failures) = check_quality(document.require_text(self.name())?, &self.config);
+        if self.drop && !failures.is_empty() {
+            return Ok(Vec::new());
+        }
+        document.metadata.insert(
+            "quality".to_string(),
+            quality_signals_to_json(&signals, &failures),
+        );
+        Ok(vec![document])
+    }
+}
+
+/// Splits the document text into chunks with `split_str`.
+///
+/// Each chunk becomes a document with id `{id}:{index}` and `chunk_index` and `parent_id`
+/// metadata; raw content is not copied to the chunks.
+pub struct SplitStage {
+    pub min_size: usize,
+    pub max_size: usize,
+    pub patterns: Vec<String>,
+}
+
+/// Check split sizes for `split_str`: max_size must be positive and at least min_size.
+pub fn check_split_sizes(min_size: usize, max_size: usize) -> Result<()> {
+    if max_size == 0 {
+        return Err(Error::Config("max_size must be positive".to_string()));
+    }
+    if min_size > max_size {
+        return Err(Error::Config(
+            "min_size must not be greater than max_size".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+impl SplitStage {
+    pub fn from_config(config: &Value) -> Result<Self> {
+        let min_size = get_usize(config, "min_size", 64)?;
+        let max_size = get_usize(config, "max_size", 1024)?;
+        check_split_sizes(min_size, max_size)?;
+        let patterns = get_strings(config, "patterns")?.unwrap_or_else(|| {
+            ["\n\n", "\n", ". ", " "]
+                .iter()
+                .map(|pattern| pattern.to_string())
+                .collect()
+        });
+        Ok(SplitStage {
+            min_size,
+            max_size,
+            patterns,
+        })
+    }
+}
+
+impl Stage for SplitStage {
+    fn name(&self) -> &str {
+        "split"
+    }
+
+    fn process(&self, document: Document) -> Result<Vec<Document>> {
+        let text = document.require_text(self.name())?;
+        Ok(
+            split_str(text, self.min_size, self.max_size, self.patterns.clone())
+                .into_iter()
+                .enumerate()
+                .map(|(index, chunk)| {
+                    let mut metadata: Map<String, Value> = document.metadata.clone();
+                    metadata.insert("chunk_index".to_string(), json!(index));
+                    metadata.insert("parent_id".to_string(), json!(document.id));
+                    Document {
+                        id: format!("{}:{}", document.id, index),
+                        bytes: None,
+                        text: Some(chunk),
+                        metadata,
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::{read_jsonl_documents, Pipeline};
+
+    fn get_document(id: &str, text: &str) -> Document {
+        let mut document = Document::new(id);
+        document.text = Some(text.to_string());
+        document
+    }
+
+    fn get_html_document() -> Document {
+        let path = format!("{}/resources/file1.html", env!("CARGO_MANIFEST_DIR"));
+        Document::from_file(&path).unwrap()
+    }
+
+    #[test]
+    fn test_file_info_and_parse_html() {
+        let pipeline = Pipeline::from_json_str(
+            r#"{"stages": [{"type": "file_info"}, {"type": "parse", "output": "text"}]}"#,
+        )
+        .unwrap();
+        let (outputs, failures) = pipeline.run_document(get_html_document());
+        assert!(failures.is_empty());
+        let document = &outputs[0];
+        assert!(document.metadata["file_info"]["media_type"]
+            .as_str()
+            .unwrap()
+            .contains("html"));
+        let text = document.text.as_deref().unwrap();
+        assert!(!text.is_empty());
+        assert!(!text.contains("<html"));
+    }
+
+    #[test]
+    fn test_parse_requires_content() {
+        let stage = ParseStage::from_config(&json!({"type": "parse"})).unwrap();
+        assert!(stage.process(Document::new("a")).is_err());
+        let document = get_document("b", "already parsed");
+        assert_eq!(stage.process(document.clone()).unwrap(), vec![document]);
+
+        assert!(stage.ocr_noise.is_none());
+        let stage = ParseStage::from_config(&json!({"drop_noisy_ocr": true})).unwrap();
+        assert_eq!(stage.ocr_noise, Some(NoiseConfig::default()));
+    }
+
+    #[test]
+    fn test_normalize() {
+        let stage = NormalizeStage::from_config(&json!({"form": "whitespace"})).unwrap();
+        let outputs = stage.process(get_document("a", "  a \t b  ")).unwrap();
+        assert_eq!(outputs[0].text.as_deref(), Some("a b"));
+        assert!(NormalizeStage::from_config(&json!({"form": "nfx"})).is_err());
+    }
+
+    #[test]
+    fn test_language() {
+        let stage = LanguageStage::from_config(&json!({"scripts": ["Latin"]})).unwrap();
+        let outputs = stage
+            .process(get_document("a", "The quick brown fox."))
+            .unwrap();
+        assert_eq!(outputs[0].metadata["script"], "Latin");
+        assert!(stage
+            .process(get_document("b", "Съешь же ещё"))
+            .unwrap()
+            .is_empty());
+        assert!(stage
+            .process(get_document("c", "123 ..."))
+            .unwrap()
+            .is_empty());
+        assert!(LanguageStage::from_config(&json!({})).is_err());
+    }
+
+    #[test]
+    fn test_dedup() {
+        let stage = DedupStage::new();
+        let outputs = stage.process(get_document("a", "same text")).unwrap();
+        assert_eq!(outputs[0].metadata["blake3"], hash_str("same text"));
+        assert_eq!(stage.resolve(outputs[0].clone()).unwrap().len(), 1);
+        assert!(stage
+            .resolve(stage.process(get_document("b", "same text")).unwrap()[0].clone())
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            stage
+                .resolve(stage.process(get_document("c", "other text")).unwrap()[0].clone())
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_dedup_keeps_first() {
+        // every duplicate in a chunk resolves to the first one, whatever the scheduling
+        let pipeline = Pipeline::new(vec![Box::new(DedupStage::new())]);
+        let input: String = (0..200)
+            .map(|i| format!("{{\"id\": \"{}\", \"text\": \"text {}\"}}\n", i, i % 7))
+            .collect();
+        let mut output = Vec::new();
+        pipeline
+            .run(
+                read_jsonl_documents(input.as_bytes()),
+                &mut output,
+                &mut Vec::new(),
+            )
+            .unwrap();
+        let ids: Vec<String> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap()["id"].to_string())
+            .collect();
+        assert_eq!(
+            ids,
+            (0..7).map(|i| format!("\"{}\"", i)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_quality() {
+        let stage =
+            QualityStage::from_config(&json!({"min_words": 3, "min_stop_words": 1})).unwrap();
+        let outputs = stage
+            .process(get_document(
+                "a",
+                "The quick brown fox jumps over the lazy dog.",
+            ))
+            .unwrap();
+        assert_eq!(outputs[0].metadata["quality"]["word_count"], 9);
+        assert_eq!(outputs[0].metadata["quality"]["failures"], json!([]));
+        assert!(stage.process(get_document("b", "fox")).unwrap().is_empty());
+
+        let stage = QualityStage::from_config(&json!({"drop": false})).unwrap();
+        let outputs = stage.process(get_document("c", "fox")).unwrap();
+        assert_eq!(outputs[0].metadata["quality"]["failures"][0], "min_words");
+
+        assert!(
+            QualityStage::from_config(&json!({"max_top_ngram_char_fractions": [0.1]})).is_err()
+        );
+    }
+
+    #[test]
+    fn test_split() {
+        let stage = SplitStage::from_config(&json!({"min_size": 1, "max_size": 10})).unwrap();
+        let outputs = stage
+            .process(get_document("doc", "first part. second part."))
+            .unwrap();
+        assert!(outputs.len() > 1);
+        assert_eq!(outputs[0].id, "doc:0");
+        assert_eq!(outputs[1].metadata["chunk_index"], 1);
+        assert_eq!(outputs[1].metadata["parent_id"], "doc");
+        let text: String = outputs
+            .iter()
+            .map(|document| document.text.clone().unwrap())
+            .collect();
+        assert_eq!(text, "first part. second part.");
+        assert!(SplitStage::from_config(&json!({"min_size": 10, "max_size": 1})).is_err());
+        assert!(SplitStage::from_config(&json!({"min_size": 0, "max_size": 0})).is_err());
+    }
+
+    #[test]
+    fn test_from_toml() {
+        let pipeline = Pipeline::from_toml_str(
+            r#"
+            [[stages]]
+            type = "normalize"
+            form = "nfkc"
+
+            [[stages]]
+            type = "dedup"
+
+            [[stages]]
+            type = "split"
+            max_size = 2048
+            "#,
+        )
+        .unwrap();
+        assert_eq!(pipeline.stage_names(), vec!["normalize", "dedup", "split"]);
+    }
+}
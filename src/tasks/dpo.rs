@@ -0,0 +1,375 @@
+// Direct Preference Optimization (DPO) pair task
+
+// external imports
+use pyo3::prelude::*;
+use serde_json::Value;
+
+// internal imports
+use crate::algos::similarity::strings::normalized_levenshtein_distance;
+use crate::algos::tokenizers::tokenizers::get_tokenizer;
+use crate::io::fs::files::iter_documents;
+
+/// DPO options
+///
+/// Prompts longer than `max_prompt_length` tokens keep their last tokens when the pair does not
+/// fit. Pairs are degenerate if the responses are equal or their normalized edit similarity is at
+/// least `similarity_threshold`.
+#[derive(Debug, Clone)]
+pub struct DpoConfig {
+    pub max_prompt_length: usize,
+    pub similarity_threshold: f64,
+}
+
+impl DpoConfig {
+    pub fn new(max_prompt_length: usize, similarity_threshold: f64) -> Self {
+        DpoConfig {
+            max_prompt_length,
+            similarity_threshold,
+        }
+    }
+}
+
+impl Default for DpoConfig {
+    fn default() -> Self {
+        DpoConfig::new(256, 0.95)
+    }
+}
+
+/// A tokenized preference pair
+///
+/// The chosen and rejected sequences share the same `prompt_length` prompt tokens, and their
+/// labels mask the prompt and padding. `similarity` is one minus the Levenshtein distance of the
+/// responses over the length of the longer one, in characters.
+#[pyclass]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DpoSample {
+    #[pyo3(get)]
+    pub chosen_input_ids: Vec<i32>,
+    #[pyo3(get)]
+    pub chosen_attention_mask: Vec<i32>,
+    #[pyo3(get)]
+    pub chosen_labels: Vec<i32>,
+    #[pyo3(get)]
+    pub rejected_input_ids: Vec<i32>,
+    #[pyo3(get)]
+    pub rejected_attention_mask: Vec<i32>,
+    #[pyo3(get)]
+    pub rejected_labels: Vec<i32>,
+    #[pyo3(get)]
+    pub prompt_length: usize,
+    #[pyo3(get)]
+    pub similarity: f64,
+    #[pyo3(get)]
+    pub is_degenerate: bool,
+}
+
+#[pymethods]
+impl DpoSample {
+    fn __str__(&self) -> String {
+        format!(
+            "DpoSample(prompt_length={}, similarity={:.3}, is_degenerate={})",
+            self.prompt_length, self.similarity, self.is_degenerate
+        )
+    }
+}
+
+/// Get the similarity of two responses and whether the pair is degenerate
+pub fn get_pair_similarity(chosen: &str, rejected: &str, config: &DpoConfig) -> (f64, bool) {
+    if chosen == rejected {
+        return (1.0, true);
+    }
+
+    // strsim's normalized Levenshtein is a similarity, counted in characters rather than bytes
+    let similarity = normalized_levenshtein_distance(chosen, rejected);
+    (similarity, similarity >= config.similarity_threshold)
+}
+
+/// Get the input IDs, attention mask, and labels of a prompt and response
+fn get_padded_sequence(
+    prompt: &[i32],
+    response: &[i32],
+    max_seq_length: usize,
+    pad_token_id: i32,
+    label_mask_id: i32,
+) -> (Vec<i32>, Vec<i32>, Vec<i32>) {
+    let mut input_ids = [prompt, response].concat();
+    let mut labels = vec![label_mask_id; prompt.len()];
+    labels.extend_from_slice(response);
+
+    let mut attention_mask = vec![1; input_ids.len()];
+    attention_mask.resize(max_seq_length, 0);
+    input_ids.resize(max_seq_length, pad_token_id);
+    labels.resize(max_seq_length, label_mask_id);
+
+    (input_ids, attention_mask, labels)
+}
+
+/// Get a DPO sample from a prompt and chosen and rejected responses
+///
+/// The prompt is tokenized once and shared by both sequences. If the prompt and the longer
+/// response do not fit in max_seq_length, the prompt is first cut to its last max_prompt_length
+/// tokens, and then both responses are cut to the remaining length, so both sequences are
+/// truncated the same way.
+///
+/// # Arguments
+/// * `prompt` - The prompt text, e.g., a rendered chat template
+/// * `chosen` - The preferred response
+/// * `rejected` - The dispreferred response
+/// * `tokenizer_name` - The tokenizer name, path or hub identifier
+/// * `max_seq_length` - The length of each padded sequence
+/// * `eos_token_id` - The token appended to each response, if any
+/// * `pad_token_id` - The padding token ID
+/// * `label_mask_id` - The label ID for prompt and padding tokens, e.g., -100
+/// * `config` - The truncation and degenerate pair options
+///
+/// # Returns
+/// The DPO sample
+#[allow(clippy::too_many_arguments)]
+pub fn get_dpo_sample(
+    prompt: &str,
+    chosen: &str,
+    rejected: &str,
+    tokenizer_name: &str,
+    max_seq_length: usize,
+    eos_token_id: Option<i32>,
+    pad_token_id: i32,
+    label_mask_id: i32,
+    config: &DpoConfig,
+) -> tokenizers::Result<DpoSample> {
+    let encodings =
+        get_tokenizer(tokenizer_name)?.encode_batch(vec![prompt, chosen, rejected], false)?;
+    let [mut prompt_ids, mut chosen_ids, mut rejected_ids]: [Vec<i32>; 3] = [0, 1, 2].map(|i| {
+        encodings[i]
+            .get_ids()
+            .iter()
+            .map(|&x| x as i32)
+            .collect::<Vec<i32>>()
+    });
+    chosen_ids.extend(eos_token_id);
+    rejected_ids.extend(eos_token_id);
+
+    // keep the end of the prompt, then cut both responses to the same length
+    let response_length = chosen_ids.len().max(rejected_ids.len());
+    if prompt_ids.len() + response_length > max_seq_length {
+        let prompt_length = prompt_ids
+            .len()
+            .min(config.max_prompt_length)
+            .min(max_seq_length);
+        prompt_ids.drain(..prompt_ids.len() - prompt_length);
+    }
+    let max_response_length = max_seq_length - prompt_ids.len();
+    chosen_ids.truncate(max_response_length);
+    rejected_ids.truncate(max_response_length);
+
+    let (similarity, is_degenerate) = get_pair_similarity(chosen, rejected, config);
+    let (chosen_input_ids, chosen_attention_mask, chosen_labels) = get_padded_sequence(
+        &prompt_ids,
+        &chosen_ids,
+        max_seq_length,
+        pad_token_id,
+        label_mask_id,
+    );
+    let (rejected_input_ids, rejected_attention_mask, rejected_labels) = get_padded_sequence(
+        &prompt_ids,
+        &rejected_ids,
+        max_seq_length,
+        pad_token_id,
+        label_mask_id,
+    );
+
+    Ok(DpoSample {
+        chosen_input_ids,
+        chosen_attention_mask,
+        chosen_labels,
+        rejected_input_ids,
+        rejected_attention_mask,
+        rejected_labels,
+        prompt_length: prompt_ids.len(),
+        similarity,
+        is_degenerate,
+    })
+}
+
+/// Get DPO samples from a JSONL or gzipped JSONL file of preference pairs
+///
+/// Each line is an object with "prompt", "chosen", and "rejected" string fields; blank lines are
+/// skipped.
+///
+/// # Arguments
+/// * `path` - The path to the file
+/// * `tokenizer_name` - The tokenizer name, path or hub identifier
+/// * `max_seq_length` - The length of each padded sequence
+/// * `eos_token_id` - The token appended to each response, if any
+/// * `pad_token_id` - The padding token ID
+/// * `label_mask_id` - The label ID for prompt and padding tokens
+/// * `config` - The truncation and degenerate pair options
+///
+/// # Returns
+/// One sample per line
+pub fn get_dpo_samples_from_file(
+    path: &str,
+    tokenizer_name: &str,
+    max_seq_length: usize,
+    eos_token_id: Option<i32>,
+    pad_token_id: i32,
+    label_mask_id: i32,
+    config: &DpoConfig,
+) -> tokenizers::Result<Vec<DpoSample>> {
+    let mut samples = Vec::new();
+    for line in iter_documents(path, None)? {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let value: Value = serde_json::from_str(&line)?;
+        let get_field = |name: &str| {
+            value
+                .get(name)
+                .and_then(|value| value.as_str())
+                .ok_or_else(|| format!("pair missing field: {}", name))
+        };
+        samples.push(get_dpo_sample(
+            get_field("prompt")?,
+            get_field("chosen")?,
+            get_field("rejected")?,
+            tokenizer_name,
+            max_seq_length,
+            eos_token_id,
+            pad_token_id,
+            label_mask_id,
+            config,
+        )?);
+    }
+    Ok(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EOS_TOKEN_ID: i32 = 3;
+    const PAD_TOKEN_ID: i32 = 0;
+    const LABEL_MASK_ID: i32 = -100;
+
+    fn get_tokenizer_path() -> String {
+        format!(
+            "{}/resources/tokenizer.wordpiece.json",
+            env!("CARGO_MANIFEST_DIR")
+        )
+    }
+
+    #[test]
+    fn test_get_pair_similarity() {
+        let config = DpoConfig::new(256, 0.9);
+        assert_eq!(get_pair_similarity("same", "same", &config), (1.0, true));
+
+        let (similarity, is_degenerate) =
+            get_pair_similarity("the quick brown fox.", "the quick brown fox!", &config);
+        assert!((similarity - 0.95).abs() < 1e-9);
+        assert!(is_degenerate);
+
+        let (_, is_degenerate) = get_pair_similarity("the fox", "a lazy dog", &config);
+        assert!(!is_degenerate);
+
+        // two of four characters differ, whatever their byte length
+        let (similarity, _) = get_pair_similarity("αβγδ", "αβεζ", &config);
+        assert!((similarity - 0.5).abs() < 1e-9);
+        let (similarity, _) = get_pair_similarity("café", "cafe", &config);
+        assert!((similarity - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_get_dpo_sample() {
+        let sample = get_dpo_sample(
+            "the quick fox",
+            "jumps over",
+            "the lazy dog",
+            &get_tokenizer_path(),
+            10,
+            Some(EOS_TOKEN_ID),
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+            &DpoConfig::default(),
+        )
+        .unwrap();
+
+        let m = LABEL_MASK_ID;
+        assert_eq!(sample.prompt_length, 3);
+        assert_eq!(
+            sample.chosen_input_ids,
+            vec![5, 6, 8, 9, 10, 13, 3, 0, 0, 0]
+        );
+        assert_eq!(sample.chosen_labels, vec![m, m, m, 9, 10, 13, 3, m, m, m]);
+        assert_eq!(
+            sample.chosen_attention_mask,
+            vec![1, 1, 1, 1, 1, 1, 1, 0, 0, 0]
+        );
+        assert_eq!(
+            sample.rejected_input_ids,
+            vec![5, 6, 8, 5, 14, 15, 3, 0, 0, 0]
+        );
+        assert_eq!(sample.rejected_labels, vec![m, m, m, 5, 14, 15, 3, m, m, m]);
+        assert!(!sample.is_degenerate);
+    }
+
+    #[test]
+    fn test_get_dpo_sample_truncation() {
+        // the prompt keeps its last 2 tokens, and both responses are cut to 4 tokens
+        let sample = get_dpo_sample(
+            "the quick brown fox",
+            "jumps over the lazy dog",
+            "the dog",
+            &get_tokenizer_path(),
+            6,
+            Some(EOS_TOKEN_ID),
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+            &DpoConfig::new(2, 0.95),
+        )
+        .unwrap();
+
+        assert_eq!(sample.prompt_length, 2);
+        assert_eq!(sample.chosen_input_ids, vec![7, 8, 9, 10, 13, 5]);
+        assert_eq!(sample.rejected_input_ids, vec![7, 8, 5, 15, 3, 0]);
+        assert_eq!(sample.rejected_attention_mask, vec![1, 1, 1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn test_get_dpo_samples_from_file() {
+        let path = std::env::temp_dir().join("alea_preprocess_test_dpo.jsonl");
+        std::fs::write(
+            &path,
+            "{\"prompt\": \"the fox\", \"chosen\": \"jumps\", \"rejected\": \"jumps\"}\n\n",
+        )
+        .unwrap();
+
+        let samples = get_dpo_samples_from_file(
+            path.to_str().unwrap(),
+            &get_tokenizer_path(),
+            8,
+            None,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+            &DpoConfig::default(),
+        )
+        .unwrap();
+
+        std::fs::write(&path, "{\"prompt\": \"the fox\", \"chosen\": \"jumps\"}\n").unwrap();
+        let result = get_dpo_samples_from_file(
+            path.to_str().unwrap(),
+            &get_tokenizer_path(),
+            8,
+            None,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+            &DpoConfig::default(),
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(samples.len(), 1);
+        assert!(samples[0].is_degenerate);
+        assert_eq!(samples[0].chosen_input_ids, samples[0].rejected_input_ids);
+        assert!(result.is_err());
+    }
+}
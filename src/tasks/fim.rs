@@ -0,0 +1,678 @@
+// Fill-in-the-middle (FIM) task
+
+// external imports
+use rand::Rng;
+
+// internal imports
+use crate::algos::segmentation::sentence::get_sentence_boundaries;
+use crate::algos::tokenizers::tokenizers::get_tokenizer;
+use crate::algos::unicode::segmentations::get_line_indices;
+use crate::errors::{Error, Result};
+use crate::tasks::sequences::{extract_content, split_sequence_max};
+
+/// A FIM sample: input_ids, attention_mask, and labels
+pub type FimSample = (Vec<i32>, Vec<i32>, Vec<i32>);
+
+/// FIM sentinel ordering
+///
+/// `Psm` produces `<PRE> prefix <SUF> suffix <MID> middle`, and `Spm` produces
+/// `<PRE> <SUF> suffix <MID> prefix middle`, following Bavarian et al. (2022).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FimMode {
+    Psm,
+    Spm,
+}
+
+/// Where text may be split into prefix, middle, and suffix
+///
+/// `Character` splits at any character, `Line` at line starts from
+/// `unicode::segmentations::get_line_indices`, and `Sentence` at the boundaries from
+/// `segmentation::sentence::get_sentence_boundaries`. Token sequences always split at tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FimBoundary {
+    Character,
+    Line,
+    Sentence,
+}
+
+pub fn fim_boundary_from_string(name: &str) -> Option<FimBoundary> {
+    match name.to_lowercase().as_str() {
+        "character" | "char" => Some(FimBoundary::Character),
+        "line" => Some(FimBoundary::Line),
+        "sentence" => Some(FimBoundary::Sentence),
+        _ => None,
+    }
+}
+
+/// FIM options
+///
+/// Each sample is transformed with probability `fim_rate`, using SPM with probability `spm_rate`
+/// and PSM otherwise; the rest are left as plain causal LM samples.
+#[derive(Debug, Clone)]
+pub struct FimConfig {
+    pub fim_rate: f64,
+    pub spm_rate: f64,
+    pub boundary: FimBoundary,
+}
+
+impl FimConfig {
+    pub fn new(fim_rate: f64, spm_rate: f64, boundary: FimBoundary) -> Self {
+        FimConfig {
+            fim_rate,
+            spm_rate,
+            boundary,
+        }
+    }
+}
+
+impl Default for FimConfig {
+    fn default() -> Self {
+        FimConfig::new(0.5, 0.5, FimBoundary::Character)
+    }
+}
+
+/// Choose whether and how to transform a sample
+fn select_fim_mode<R: Rng>(config: &FimConfig, rng: &mut R) -> Option<FimMode> {
+    if !rng.gen_bool(config.fim_rate.clamp(0.0, 1.0)) {
+        return None;
+    }
+
+    if rng.gen_bool(config.spm_rate.clamp(0.0, 1.0)) {
+        Some(FimMode::Spm)
+    } else {
+        Some(FimMode::Psm)
+    }
+}
+
+/// Choose two sorted split points, with replacement, from the candidates
+fn select_split_points<R: Rng>(candidates: &[usize], rng: &mut R) -> (usize, usize) {
+    let a = candidates[rng.gen_range(0..candidates.len())];
+    let b = candidates[rng.gen_range(0..candidates.len())];
+    (a.min(b), a.max(b))
+}
+
+/// Arrange prefix, middle, and suffix tokens with sentinels
+pub fn arrange_fim_tokens(
+    prefix: &[i32],
+    middle: &[i32],
+    suffix: &[i32],
+    mode: FimMode,
+    prefix_token_id: i32,
+    middle_token_id: i32,
+    suffix_token_id: i32,
+) -> Vec<i32> {
+    let mut tokens = Vec::with_capacity(prefix.len() + middle.len() + suffix.len() + 3);
+    match mode {
+        FimMode::Psm => {
+            tokens.push(prefix_token_id);
+            tokens.extend_from_slice(prefix);
+            tokens.push(suffix_token_id);
+            tokens.extend_from_slice(suffix);
+            tokens.push(middle_token_id);
+            tokens.extend_from_slice(middle);
+        }
+        FimMode::Spm => {
+            tokens.push(prefix_token_id);
+            tokens.push(suffix_token_id);
+            tokens.extend_from_slice(suffix);
+            tokens.push(middle_token_id);
+            tokens.extend_from_slice(prefix);
+            tokens.extend_from_slice(middle);
+        }
+    }
+    tokens
+}
+
+/// Number of positions reserved for the three sentinels and the EOS token
+const RESERVED_TOKENS: usize = 4;
+
+/// Check that max_seq_length leaves room for at least one token after the sentinels and EOS
+pub fn validate_max_seq_length(max_seq_length: usize) -> Result<()> {
+    if max_seq_length <= RESERVED_TOKENS {
+        return Err(Error::Config(format!(
+            "max_seq_length must be greater than {}, got {}",
+            RESERVED_TOKENS, max_seq_length
+        )));
+    }
+    Ok(())
+}
+
+/// Pad a sequence to max_seq_length after appending the EOS token
+/// Labels copy the input IDs, with label_mask_id at padding positions.
+fn get_padded_sample(
+    mut tokens: Vec<i32>,
+    eos_token_id: i32,
+    pad_token_id: i32,
+    label_mask_id: i32,
+    max_seq_length: usize,
+) -> Result<FimSample> {
+    tokens.push(eos_token_id);
+    if tokens.len() > max_seq_length {
+        return Err(Error::Config(format!(
+            "{} tokens exceed max_seq_length {}",
+            tokens.len(),
+            max_seq_length
+        )));
+    }
+
+    let mut attention_mask = vec![1; tokens.len()];
+    let mut labels = tokens.clone();
+    attention_mask.resize(max_seq_length, 0);
+    labels.resize(max_seq_length, label_mask_id);
+    tokens.resize(max_seq_length, pad_token_id);
+
+    Ok((tokens, attention_mask, labels))
+}
+
+/// Get a FIM sample from tokens
+/// Returns the input_ids, attention_mask, and labels
+/// Tokens must fit in max_seq_length with the three sentinels and the EOS token, or an error
+/// is returned.
+#[allow(clippy::too_many_arguments)]
+pub fn get_fim_sample<R: Rng>(
+    tokens: &[i32],
+    prefix_token_id: i32,
+    middle_token_id: i32,
+    suffix_token_id: i32,
+    eos_token_id: i32,
+    pad_token_id: i32,
+    label_mask_id: i32,
+    max_seq_length: usize,
+    config: &FimConfig,
+    rng: &mut R,
+) -> Result<FimSample> {
+    let tokens = match select_fim_mode(config, rng) {
+        Some(mode) => {
+            let candidates: Vec<usize> = (0..=tokens.len()).collect();
+            let (start, end) = select_split_points(&candidates, rng);
+            arrange_fim_tokens(
+                &tokens[..start],
+                &tokens[start..end],
+                &tokens[end..],
+                mode,
+                prefix_token_id,
+                middle_token_id,
+                suffix_token_id,
+            )
+        }
+        None => tokens.to_vec(),
+    };
+
+    get_padded_sample(
+        tokens,
+        eos_token_id,
+        pad_token_id,
+        label_mask_id,
+        max_seq_length,
+    )
+}
+
+/// Get FIM samples from tokens
+/// Tokens are split with split_sequence_max to leave room for the sentinels and the EOS token.
+#[allow(clippy::too_many_arguments)]
+pub fn get_fim_samples_from_tokens<R: Rng>(
+    tokens: &[i32],
+    max_seq_length: usize,
+    prefix_token_id: i32,
+    middle_token_id: i32,
+    suffix_token_id: i32,
+    eos_token_id: i32,
+    pad_token_id: i32,
+    label_mask_id: i32,
+    config: &FimConfig,
+    rng: &mut R,
+) -> Result<Vec<FimSample>> {
+    validate_max_seq_length(max_seq_length)?;
+
+    split_sequence_max(tokens, max_seq_length - RESERVED_TOKENS)
+        .iter()
+        .map(|seq| {
+            get_fim_sample(
+                seq,
+                prefix_token_id,
+                middle_token_id,
+                suffix_token_id,
+                eos_token_id,
+                pad_token_id,
+                label_mask_id,
+                max_seq_length,
+                config,
+                rng,
+            )
+        })
+        .collect()
+}
+
+/// Get the byte offsets where text may be split
+pub fn get_fim_boundaries(text: &str, boundary: FimBoundary) -> Vec<usize> {
+    let mut boundaries: Vec<usize> = match boundary {
+        FimBoundary::Character => text.char_indices().map(|(i, _)| i).collect(),
+        FimBoundary::Line => get_line_indices(text)
+            .iter()
+            .map(|(start, _, _)| *start)
+            .collect(),
+        FimBoundary::Sentence => get_sentence_boundaries(text),
+    };
+    boundaries.push(0);
+    boundaries.push(text.len());
+    boundaries.sort_unstable();
+    boundaries.dedup();
+    boundaries
+}
+
+/// Get FIM samples from text
+///
+/// The text is tokenized once and split into chunks that fit in max_seq_length with the
+/// sentinels and the EOS token. Each transformed chunk is split into prefix, middle, and suffix
+/// text at the configured boundaries, and each part is tokenized on its own. If the parts no
+/// longer fit, the end of the suffix is trimmed, as in StarCoder; if the suffix is too short,
+/// the chunk is left untransformed.
+#[allow(clippy::too_many_arguments)]
+pub fn get_fim_samples_from_text<R: Rng>(
+    text: &str,
+    max_seq_length: usize,
+    tokenizer_name: &str,
+    prefix_token_id: i32,
+    middle_token_id: i32,
+    suffix_token_id: i32,
+    eos_token_id: i32,
+    pad_token_id: i32,
+    label_mask_id: i32,
+    config: &FimConfig,
+    rng: &mut R,
+) -> Result<Vec<FimSample>> {
+    validate_max_seq_length(max_seq_length)?;
+
+    let tokenizer = get_tokenizer(tokenizer_name)?;
+    let encoding = tokenizer.encode(text, false)?;
+    let ids: Vec<i32> = encoding.get_ids().iter().map(|&x| x as i32).collect();
+    let offsets = encoding.get_offsets();
+    let boundaries = get_fim_boundaries(text, config.boundary);
+
+    // chunks cover the text from the start of their first token to the start of the next chunk
+    let max_size = max_seq_length - RESERVED_TOKENS;
+    let chunk_starts: Vec<usize> = (0..ids.len()).step_by(max_size).collect();
+    let get_text_offset = |token_index: usize| {
+        if token_index == 0 {
+            0
+        } else if token_index >= ids.len() {
+            text.len()
+        } else {
+            let mut offset = offsets[token_index].0.min(text.len());
+            while !text.is_char_boundary(offset) {
+                offset -= 1;
+            }
+            offset
+        }
+    };
+
+    let mut samples = Vec::with_capacity(chunk_starts.len());
+    for &chunk_start in chunk_starts.iter() {
+        let chunk_end = (chunk_start + max_size).min(ids.len());
+        let chunk = &ids[chunk_start..chunk_end];
+
+        let tokens = match select_fim_mode(config, rng) {
+            Some(mode) => {
+                let (text_start, text_end) =
+                    (get_text_offset(chunk_start), get_text_offset(chunk_end));
+                let mut candidates: Vec<usize> = boundaries
+                    .iter()
+                    .copied()
+                    .filter(|&b| b > text_start && b < text_end)
+                    .collect();
+                candidates.insert(0, text_start);
+                candidates.push(text_end);
+
+                let (start, end) = select_split_points(&candidates, rng);
+                let parts = tokenizer.encode_batch(
+                    vec![
+                        &text[text_start..start],
+                        &text[start..end],
+                        &text[end..text_end],
+                    ],
+                    false,
+                )?;
+                let [prefix, middle, mut suffix]: [Vec<i32>; 3] = [0, 1, 2].map(|i| {
+                    parts[i]
+                        .get_ids()
+                        .iter()
+                        .map(|&x| x as i32)
+                        .collect::<Vec<i32>>()
+                });
+
+                let excess = (prefix.len() + middle.len() + suffix.len()).saturating_sub(max_size);
+                if excess > suffix.len() {
+                    chunk.to_vec()
+                } else {
+                    suffix.truncate(suffix.len() - excess);
+                    arrange_fim_tokens(
+                        &prefix,
+                        &middle,
+                        &suffix,
+                        mode,
+                        prefix_token_id,
+                        middle_token_id,
+                        suffix_token_id,
+                    )
+                }
+            }
+            None => chunk.to_vec(),
+        };
+
+        samples.push(get_padded_sample(
+            tokens,
+            eos_token_id,
+            pad_token_id,
+            label_mask_id,
+            max_seq_length,
+        )?);
+    }
+
+    Ok(samples)
+}
+
+/// Get FIM samples from content
+#[allow(clippy::too_many_arguments)]
+pub fn get_fim_samples_from_content<R: Rng>(
+    encoded_content: &str,
+    max_seq_length: usize,
+    tokenizer_name: &str,
+    prefix_token_id: i32,
+    middle_token_id: i32,
+    suffix_token_id: i32,
+    eos_token_id: i32,
+    pad_token_id: i32,
+    label_mask_id: i32,
+    config: &FimConfig,
+    rng: &mut R,
+) -> Result<Vec<FimSample>> {
+    // extract text
+    let content = String::from_utf8(extract_content(encoded_content)?)?;
+
+    get_fim_samples_from_text(
+        &content,
+        max_seq_length,
+        tokenizer_name,
+        prefix_token_id,
+        middle_token_id,
+        suffix_token_id,
+        eos_token_id,
+        pad_token_id,
+        label_mask_id,
+        config,
+        rng,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::sampling::get_rng;
+
+    const PAD_TOKEN_ID: i32 = 0;
+    const EOS_TOKEN_ID: i32 = 1;
+    const PREFIX_TOKEN_ID: i32 = 90;
+    const MIDDLE_TOKEN_ID: i32 = 91;
+    const SUFFIX_TOKEN_ID: i32 = 92;
+    const LABEL_MASK_ID: i32 = -100;
+
+    fn get_tokenizer_path() -> String {
+        format!(
+            "{}/resources/tokenizer.wordpiece.json",
+            env!("CARGO_MANIFEST_DIR")
+        )
+    }
+
+    #[test]
+    fn test_fim_boundary_from_string() {
+        assert_eq!(fim_boundary_from_string("Line"), Some(FimBoundary::Line));
+        assert_eq!(
+            fim_boundary_from_string("char"),
+            Some(FimBoundary::Character)
+        );
+        assert_eq!(fim_boundary_from_string("word"), None);
+    }
+
+    #[test]
+    fn test_arrange_fim_tokens() {
+        let psm = arrange_fim_tokens(&[1, 2], &[3], &[4, 5], FimMode::Psm, 90, 91, 92);
+        assert_eq!(psm, vec![90, 1, 2, 92, 4, 5, 91, 3]);
+        let spm = arrange_fim_tokens(&[1, 2], &[3], &[4, 5], FimMode::Spm, 90, 91, 92);
+        assert_eq!(spm, vec![90, 92, 4, 5, 91, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_get_fim_boundaries() {
+        let text = "first line\nsecond line\nthird";
+        assert_eq!(
+            get_fim_boundaries(text, FimBoundary::Line),
+            vec![0, 11, 23, 28]
+        );
+        assert_eq!(
+            get_fim_boundaries("añb", FimBoundary::Character),
+            vec![0, 1, 3, 4]
+        );
+
+        let text = "It is here. Then it left.";
+        assert_eq!(
+            get_fim_boundaries(text, FimBoundary::Sentence),
+            vec![0, 11, 25]
+        );
+    }
+
+    #[test]
+    fn test_get_fim_sample() {
+        let tokens: Vec<i32> = (100..110).collect();
+        let mut rng = get_rng(0);
+
+        // without FIM, the sample is the tokens and EOS
+        let (input_ids, attention_mask, labels) = get_fim_sample(
+            &tokens,
+            PREFIX_TOKEN_ID,
+            MIDDLE_TOKEN_ID,
+            SUFFIX_TOKEN_ID,
+            EOS_TOKEN_ID,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+            16,
+            &FimConfig::new(0.0, 0.0, FimBoundary::Character),
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(
+            input_ids[..11],
+            [tokens.as_slice(), &[EOS_TOKEN_ID]].concat()
+        );
+        assert_eq!(input_ids[11..], [PAD_TOKEN_ID; 5]);
+        assert_eq!(attention_mask.iter().sum::<i32>(), 11);
+        assert_eq!(labels[..11], input_ids[..11]);
+        assert_eq!(labels[11..], [LABEL_MASK_ID; 5]);
+
+        // with PSM, the tokens are rearranged around the sentinels
+        for _ in 0..20 {
+            let (input_ids, attention_mask, _) = get_fim_sample(
+                &tokens,
+                PREFIX_TOKEN_ID,
+                MIDDLE_TOKEN_ID,
+                SUFFIX_TOKEN_ID,
+                EOS_TOKEN_ID,
+                PAD_TOKEN_ID,
+                LABEL_MASK_ID,
+                16,
+                &FimConfig::new(1.0, 0.0, FimBoundary::Character),
+                &mut rng,
+            )
+            .unwrap();
+            assert_eq!(attention_mask.iter().sum::<i32>(), 14);
+            assert_eq!(input_ids[0], PREFIX_TOKEN_ID);
+            assert_eq!(input_ids[13], EOS_TOKEN_ID);
+
+            let suffix_index = input_ids
+                .iter()
+                .position(|&x| x == SUFFIX_TOKEN_ID)
+                .unwrap();
+            let middle_index = input_ids
+                .iter()
+                .position(|&x| x == MIDDLE_TOKEN_ID)
+                .unwrap();
+            let restored = [
+                &input_ids[1..suffix_index],
+                &input_ids[middle_index + 1..13],
+                &input_ids[suffix_index + 1..middle_index],
+            ]
+            .concat();
+            assert_eq!(restored, tokens);
+        }
+    }
+
+    #[test]
+    fn test_get_fim_samples_from_tokens() {
+        let tokens: Vec<i32> = (100..130).collect();
+        let samples = get_fim_samples_from_tokens(
+            &tokens,
+            16,
+            PREFIX_TOKEN_ID,
+            MIDDLE_TOKEN_ID,
+            SUFFIX_TOKEN_ID,
+            EOS_TOKEN_ID,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+            &FimConfig::new(1.0, 0.5, FimBoundary::Character),
+            &mut get_rng(1),
+        )
+        .unwrap();
+
+        // 12 tokens per chunk leave room for the sentinels and EOS
+        assert_eq!(samples.len(), 3);
+        assert_eq!(samples[0].1.iter().sum::<i32>(), 16);
+        assert_eq!(samples[2].1.iter().sum::<i32>(), 10);
+
+        // lengths without room for a token after the sentinels and EOS are rejected
+        for max_seq_length in [0, 3, 4] {
+            let result = get_fim_samples_from_tokens(
+                &tokens,
+                max_seq_length,
+                PREFIX_TOKEN_ID,
+                MIDDLE_TOKEN_ID,
+                SUFFIX_TOKEN_ID,
+                EOS_TOKEN_ID,
+                PAD_TOKEN_ID,
+                LABEL_MASK_ID,
+                &FimConfig::default(),
+                &mut get_rng(1),
+            );
+            assert!(matches!(result, Err(Error::Config(_))));
+        }
+    }
+
+    #[test]
+    fn test_get_fim_sample_too_long() {
+        let tokens: Vec<i32> = (100..110).collect();
+        let result = get_fim_sample(
+            &tokens,
+            PREFIX_TOKEN_ID,
+            MIDDLE_TOKEN_ID,
+            SUFFIX_TOKEN_ID,
+            EOS_TOKEN_ID,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+            8,
+            &FimConfig::new(0.0, 0.0, FimBoundary::Character),
+            &mut get_rng(0),
+        );
+        assert!(matches!(result, Err(Error::Config(_))));
+    }
+
+    #[test]
+    fn test_get_fim_samples_from_text_line() {
+        let text = "the quick fox\nthe lazy dog\nhello world";
+        let tokenizer = get_tokenizer_path();
+        let lines = [vec![5, 6, 8], vec![5, 14, 15], vec![16, 17]];
+
+        for seed in 0..20 {
+            let samples = get_fim_samples_from_text(
+                text,
+                32,
+                &tokenizer,
+                PREFIX_TOKEN_ID,
+                MIDDLE_TOKEN_ID,
+                SUFFIX_TOKEN_ID,
+                EOS_TOKEN_ID,
+                PAD_TOKEN_ID,
+                LABEL_MASK_ID,
+                &FimConfig::new(1.0, 0.0, FimBoundary::Line),
+                &mut get_rng(seed),
+            )
+            .unwrap();
+            assert_eq!(samples.len(), 1);
+
+            // each part holds whole lines
+            let input_ids = &samples[0].0;
+            let suffix_index = input_ids
+                .iter()
+                .position(|&x| x == SUFFIX_TOKEN_ID)
+                .unwrap();
+            let middle_index = input_ids
+                .iter()
+                .position(|&x| x == MIDDLE_TOKEN_ID)
+                .unwrap();
+            let eos_index = input_ids.iter().position(|&x| x == EOS_TOKEN_ID).unwrap();
+            let prefix = &input_ids[1..suffix_index];
+            let suffix = &input_ids[suffix_index + 1..middle_index];
+            let middle = &input_ids[middle_index + 1..eos_index];
+            assert_eq!([prefix, middle, suffix].concat(), lines.concat());
+            for part in [prefix, middle, suffix] {
+                let mut remaining = part;
+                while !remaining.is_empty() {
+                    let line = lines
+                        .iter()
+                        .find(|line| remaining.starts_with(line))
+                        .expect("part does not split at a line");
+                    remaining = &remaining[line.len()..];
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_fim_samples_from_content() {
+        // "the fox jumps over the lazy dog"
+        let content = "eJwryUhVSMuvUMgqzS0oVsgvSy1SKAEK5SRWVSqk5KcDALfLC3U=";
+        let samples = get_fim_samples_from_content(
+            content,
+            8,
+            &get_tokenizer_path(),
+            PREFIX_TOKEN_ID,
+            MIDDLE_TOKEN_ID,
+            SUFFIX_TOKEN_ID,
+            EOS_TOKEN_ID,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+            &FimConfig::new(1.0, 0.5, FimBoundary::Character),
+            &mut get_rng(2),
+        )
+        .unwrap();
+
+        // 8 tokens in chunks of 4, which can grow when words are split
+        assert_eq!(samples.len(), 2);
+        for (input_ids, attention_mask, _) in samples.iter() {
+            assert_eq!(input_ids.len(), 8);
+            assert_eq!(attention_mask.len(), 8);
+        }
+
+        let result = get_fim_samples_from_content(
+            content,
+            4,
+            &get_tokenizer_path(),
+            PREFIX_TOKEN_ID,
+            MIDDLE_TOKEN_ID,
+            SUFFIX_TOKEN_ID,
+            EOS_TOKEN_ID,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+            &FimConfig::default(),
+            &mut get_rng(2),
+        );
+        assert!(matches!(result, Err(Error::Config(_))));
+    }
+}
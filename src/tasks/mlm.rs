@@ -1,96 +1,225 @@
 // Masked Language Modeling task
 
 // external imports
+use rand::seq::SliceRandom;
 use rand::Rng;
 
 // internal imports
-use crate::algos::tokenizers::tokenizers::encode_str;
+use crate::algos::tokenizers::tokenizers::get_tokenizer;
+use crate::io::shards::{SampleShardWriter, ShardDtype};
 use crate::tasks::sequences::{extract_content, split_sequence_max};
 
+/// A masked sample: input_ids, attention_mask, token_type_ids, and labels
+pub type MaskedSample = (Vec<i32>, Vec<i32>, Vec<i32>, Vec<i32>);
+
+/// Masking strategy options
+///
+/// Each selected position is replaced with the mask token with probability `prob_mask_token`,
+/// with a random token from `0..vocab_size` with probability `prob_random_token`, and kept
+/// otherwise; BERT uses 0.8 / 0.1 with the remaining 0.1 kept. Random replacement is disabled
+/// when `vocab_size` is 0.
+///
+/// With `whole_word_masking`, all tokens of a word are selected together, using the word IDs of
+/// each token. Tokens in `excluded_token_ids` are never selected, and at least
+/// `min_masked_tokens` positions are selected if enough tokens are available.
+///
+/// The default masks every selected token and at least one token per sample.
+#[derive(Debug, Clone)]
+pub struct MaskingConfig {
+    pub prob_mask: f64,
+    pub prob_mask_token: f64,
+    pub prob_random_token: f64,
+    pub vocab_size: usize,
+    pub whole_word_masking: bool,
+    pub excluded_token_ids: Vec<i32>,
+    pub min_masked_tokens: usize,
+}
+
+impl MaskingConfig {
+    pub fn new(
+        prob_mask: f64,
+        prob_mask_token: f64,
+        prob_random_token: f64,
+        vocab_size: usize,
+        whole_word_masking: bool,
+        excluded_token_ids: Vec<i32>,
+        min_masked_tokens: usize,
+    ) -> MaskingConfig {
+        MaskingConfig {
+            prob_mask,
+            prob_mask_token,
+            prob_random_token,
+            vocab_size,
+            whole_word_masking,
+            excluded_token_ids,
+            min_masked_tokens,
+        }
+    }
+}
+
+impl Default for MaskingConfig {
+    fn default() -> Self {
+        MaskingConfig::new(0.15, 1.0, 0.0, 0, false, Vec::new(), 1)
+    }
+}
+
+/// Select the positions to mask
+/// Word IDs are only used for whole word masking; negative word IDs mark tokens outside any word.
+pub fn select_masked_positions<R: Rng>(
+    tokens: &[i32],
+    word_ids: Option<&[i64]>,
+    config: &MaskingConfig,
+    rng: &mut R,
+) -> Vec<bool> {
+    // group candidate positions into units that are selected together
+    let mut units: Vec<Vec<usize>> = Vec::new();
+    let mut previous: Option<(usize, i64)> = None;
+    for (i, token) in tokens.iter().enumerate() {
+        if config.excluded_token_ids.contains(token) {
+            previous = None;
+            continue;
+        }
+
+        let word_id = match (config.whole_word_masking, word_ids) {
+            (true, Some(word_ids)) => word_ids.get(i).copied().unwrap_or(-1),
+            _ => -1,
+        };
+        match (previous, units.last_mut()) {
+            (Some((j, previous_word_id)), Some(unit))
+                if word_id >= 0 && j + 1 == i && previous_word_id == word_id =>
+            {
+                unit.push(i)
+            }
+            _ => units.push(vec![i]),
+        }
+        previous = Some((i, word_id));
+    }
+
+    // select units, then top up to the minimum from the unselected units
+    let mut masked = vec![false; tokens.len()];
+    let mut num_masked = 0;
+    let mut unselected = Vec::new();
+    for unit in units.iter() {
+        if rng.gen::<f64>() < config.prob_mask {
+            unit.iter().for_each(|&i| masked[i] = true);
+            num_masked += unit.len();
+        } else {
+            unselected.push(unit);
+        }
+    }
+    unselected.shuffle(rng);
+    while num_masked < config.min_masked_tokens {
+        match unselected.pop() {
+            Some(unit) => {
+                unit.iter().for_each(|&i| masked[i] = true);
+                num_masked += unit.len();
+            }
+            None => break,
+        }
+    }
+
+    masked
+}
+
+/// Get the input token for a masked position
+fn get_masked_token<R: Rng>(
+    token: i32,
+    mask_token_id: i32,
+    config: &MaskingConfig,
+    rng: &mut R,
+) -> i32 {
+    let p = rng.gen::<f64>();
+    if p < config.prob_mask_token {
+        mask_token_id
+    } else if p < config.prob_mask_token + config.prob_random_token && config.vocab_size > 0 {
+        rng.gen_range(0..config.vocab_size) as i32
+    } else {
+        token
+    }
+}
+
 /// Get a masked sample
 /// Returns the input_ids, attention_mask, token_type_ids, and labels
-pub fn get_masked_sample(
+#[allow(clippy::too_many_arguments)]
+pub fn get_masked_sample<R: Rng>(
     tokens: &[i32],
+    word_ids: Option<&[i64]>,
     cls_token_id: i32,
     mask_token_id: i32,
     sep_token_id: i32,
     pad_token_id: i32,
     label_mask_id: i32,
     max_seq_length: usize,
-    prob_mask: f64,
-) -> (Vec<i32>, Vec<i32>, Vec<i32>, Vec<i32>) {
+    config: &MaskingConfig,
+    rng: &mut R,
+) -> MaskedSample {
     // check that tokens fit in max_seq_length
     assert!(
         tokens.len() <= max_seq_length - 2,
         "Tokens exceed max sequence length"
     );
 
-    // get rng
-    let mut rng = rand::thread_rng();
+    let masked = select_masked_positions(tokens, word_ids, config, rng);
 
-    // initialize for fewest touches
-    let mut input_ids = vec![0; max_seq_length];
+    let mut input_ids = vec![pad_token_id; max_seq_length];
     let mut attention_mask = vec![0; max_seq_length];
     let token_type_ids = vec![0; max_seq_length];
     let mut labels = vec![label_mask_id; max_seq_length];
 
-    // iterate over max seq length
-    // note: we only touch labels for the masked tokens
-    // note: we  don't need to touch token_type_ids since we are only using a single segment
-    for i in 0..max_seq_length {
-        if i == 0 {
-            input_ids[i] = cls_token_id;
-            attention_mask[i] = 1;
-        } else if i < tokens.len() + 1 {
-            if rng.gen::<f64>() < prob_mask {
-                input_ids[i] = mask_token_id;
-                labels[i] = tokens[i - 1];
-            } else {
-                input_ids[i] = tokens[i - 1];
-            }
-            attention_mask[i] = 1;
-        } else if i == tokens.len() + 1 {
-            // need sep token here
-            input_ids[i] = sep_token_id;
-            attention_mask[i] = 1;
+    // cls, tokens, and sep are attended to; the rest is padding
+    input_ids[0] = cls_token_id;
+    for (i, &token) in tokens.iter().enumerate() {
+        if masked[i] {
+            input_ids[i + 1] = get_masked_token(token, mask_token_id, config, rng);
+            labels[i + 1] = token;
         } else {
-            // pad tokens
-            input_ids[i] = pad_token_id;
+            input_ids[i + 1] = token;
         }
     }
+    input_ids[tokens.len() + 1] = sep_token_id;
+    attention_mask[..tokens.len() + 2].fill(1);
 
     (input_ids, attention_mask, token_type_ids, labels)
 }
 
 /// Get masked samples from tokens
-pub fn get_masked_samples_from_tokens(
+/// Word IDs, if given, must have one entry per token.
+#[allow(clippy::too_many_arguments)]
+pub fn get_masked_samples_from_tokens<R: Rng>(
     tokens: &[i32],
+    word_ids: Option<&[i64]>,
     max_seq_length: usize,
     cls_token_id: i32,
     mask_token_id: i32,
     sep_token_id: i32,
     pad_token_id: i32,
     label_mask_id: i32,
-    prob_mask: f64,
-) -> Vec<(Vec<i32>, Vec<i32>, Vec<i32>, Vec<i32>)> {
+    config: &MaskingConfig,
+    rng: &mut R,
+) -> Vec<MaskedSample> {
     // we first use split max to get the longest sequence lengths, then provide them to get_masked_sample
     // note that we use convention where each sample should have at least <cls> and <sep> tokens,
     // though we don't require any eos equivalent or padding.
-    let sequences = split_sequence_max(&tokens, max_seq_length - 2);
+    // split_sequence_max yields consecutive chunks of max_size, so word ids split the same way
+    let max_size = max_seq_length - 2;
+    let sequences = split_sequence_max(tokens, max_size);
+    let mut word_id_chunks = word_ids.map(|word_ids| word_ids.chunks(max_size));
 
-    // return the masked samples
     sequences
         .iter()
         .map(|seq| {
+            let seq_word_ids = word_id_chunks.as_mut().and_then(|chunks| chunks.next());
             get_masked_sample(
                 seq,
+                seq_word_ids,
                 cls_token_id,
                 mask_token_id,
                 sep_token_id,
                 pad_token_id,
                 label_mask_id,
                 max_seq_length,
-                prob_mask,
+                config,
+                rng,
             )
         })
         .collect()
@@ -98,7 +227,9 @@ pub fn get_masked_samples_from_tokens(
 
 /// Get masked samples from content
 /// Need to return a vector of tuples containing input_ids, attention_mask, token_type_ids, and labels
-pub fn get_masked_samples_from_content(
+/// Word IDs for whole word masking come from the tokenizer.
+#[allow(clippy::too_many_arguments)]
+pub fn get_masked_samples_from_content<R: Rng>(
     encoded_content: &str,
     max_seq_length: usize,
     tokenizer_name: &str,
@@ -107,29 +238,56 @@ pub fn get_masked_samples_from_content(
     sep_token_id: i32,
     pad_token_id: i32,
     label_mask_id: i32,
-    prob_mask: f64,
-) -> Vec<(Vec<i32>, Vec<i32>, Vec<i32>, Vec<i32>)> {
+    config: &MaskingConfig,
+    rng: &mut R,
+) -> tokenizers::Result<Vec<MaskedSample>> {
     // extract text
-    let content = String::from_utf8(extract_content(encoded_content))
-        .expect("Failed to convert content to string");
+    let content = String::from_utf8(extract_content(encoded_content)?)?;
 
-    // we need to cast u32 tokens to i32
-    let tokens: Vec<i32> = encode_str(&tokenizer_name, &content)
+    // we need to cast u32 tokens to i32 and missing word ids to -1
+    let encoding = get_tokenizer(tokenizer_name)?.encode(content, false)?;
+    let tokens: Vec<i32> = encoding.get_ids().iter().map(|&x| x as i32).collect();
+    let word_ids: Vec<i64> = encoding
+        .get_word_ids()
         .iter()
-        .map(|&x| x as i32)
+        .map(|word_id| word_id.map_or(-1, |w| w as i64))
         .collect();
 
-    // get masked samples from tokens
-    get_masked_samples_from_tokens(
+    Ok(get_masked_samples_from_tokens(
         &tokens,
+        Some(&word_ids),
         max_seq_length,
         cls_token_id,
         mask_token_id,
         sep_token_id,
         pad_token_id,
         label_mask_id,
-        prob_mask,
-    )
+        config,
+        rng,
+    ))
+}
+
+/// Get the shard fields of a masked sample; labels are signed to hold the label mask id
+pub fn get_masked_sample_shard_fields(token_dtype: ShardDtype) -> [(&'static str, ShardDtype); 4] {
+    [
+        ("input_ids", token_dtype),
+        ("attention_mask", ShardDtype::U8),
+        ("token_type_ids", ShardDtype::U8),
+        ("labels", ShardDtype::I32),
+    ]
+}
+
+/// Write the masked samples of one document to a writer created with
+/// `get_masked_sample_shard_fields`
+pub fn write_masked_samples(
+    writer: &mut SampleShardWriter,
+    samples: &[MaskedSample],
+) -> std::io::Result<()> {
+    for (input_ids, attention_mask, token_type_ids, labels) in samples {
+        writer.write_sample(&[input_ids, attention_mask, token_type_ids, labels])?;
+    }
+    writer.end_document();
+    Ok(())
 }
 
 #[cfg(test)]
@@ -147,13 +305,15 @@ mod tests {
         let tokens = vec![33, 34, 35, 36];
         let (input_ids, attention_mask, token_type_ids, labels) = get_masked_sample(
             &tokens,
+            None,
             CLS_TOKEN_ID,
             MASK_TOKEN_ID,
             SEP_TOKEN_ID,
             PAD_TOKEN_ID,
             LABEL_MASK_ID,
             10,
-            0.15,
+            &MaskingConfig::default(),
+            &mut rand::thread_rng(),
         );
 
         // check the length of the output vectors
@@ -186,8 +346,14 @@ mod tests {
             SEP_TOKEN_ID,
             PAD_TOKEN_ID,
             LABEL_MASK_ID,
-            0.0,
+            &MaskingConfig {
+                prob_mask: 0.0,
+                min_masked_tokens: 0,
+                ..Default::default()
+            },
+            &mut rand::thread_rng(),
         )
+        .unwrap()
         // get only first
         .into_iter()
         .next()
@@ -215,8 +381,13 @@ mod tests {
             SEP_TOKEN_ID,
             PAD_TOKEN_ID,
             LABEL_MASK_ID,
-            1.0,
+            &MaskingConfig {
+                prob_mask: 1.0,
+                ..Default::default()
+            },
+            &mut rand::thread_rng(),
         )
+        .unwrap()
         // get only first
         .into_iter()
         .next()
@@ -236,4 +407,188 @@ mod tests {
         assert_eq!(token_type_ids.len(), 10);
         assert_eq!(labels.len(), 10);
     }
+
+    fn get_test_rng(seed: u64) -> crate::tasks::sampling::SamplingRng {
+        crate::tasks::sampling::get_rng(seed)
+    }
+
+    #[test]
+    fn test_get_masked_sample_80_10_10() {
+        let tokens: Vec<i32> = (100..1100).collect();
+        let config = MaskingConfig::new(1.0, 0.8, 0.1, 50, false, Vec::new(), 0);
+        let (input_ids, _, _, labels) = get_masked_sample(
+            &tokens,
+            None,
+            CLS_TOKEN_ID,
+            MASK_TOKEN_ID,
+            SEP_TOKEN_ID,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+            1002,
+            &config,
+            &mut get_test_rng(0),
+        );
+
+        // every token is selected, so every label is set
+        assert!(labels[1..1001].iter().all(|&label| label >= 100));
+        let num_mask = input_ids.iter().filter(|&&id| id == MASK_TOKEN_ID).count();
+        let num_random = input_ids[1..1001].iter().filter(|&&id| id < 50).count() - num_mask;
+        let num_kept = (1..1001).filter(|&i| input_ids[i] == labels[i]).count();
+        assert!((750..850).contains(&num_mask));
+        assert!((60..140).contains(&num_random));
+        assert!((60..140).contains(&num_kept));
+    }
+
+    #[test]
+    fn test_select_masked_positions_whole_word() {
+        let tokens = vec![10, 11, 12, 13, 14, 15];
+        let word_ids = vec![0, 0, 1, 1, 1, 2];
+        let config = MaskingConfig::new(0.5, 1.0, 0.0, 0, true, Vec::new(), 0);
+
+        for seed in 0..20 {
+            let masked =
+                select_masked_positions(&tokens, Some(&word_ids), &config, &mut get_test_rng(seed));
+            assert_eq!(masked[0], masked[1]);
+            assert_eq!(masked[2], masked[3]);
+            assert_eq!(masked[3], masked[4]);
+        }
+    }
+
+    #[test]
+    fn test_select_masked_positions_excluded_and_minimum() {
+        let tokens = vec![CLS_TOKEN_ID, 10, 11, SEP_TOKEN_ID];
+        let config =
+            MaskingConfig::new(1.0, 1.0, 0.0, 0, false, vec![CLS_TOKEN_ID, SEP_TOKEN_ID], 0);
+        let masked = select_masked_positions(&tokens, None, &config, &mut get_test_rng(0));
+        assert_eq!(masked, vec![false, true, true, false]);
+
+        // nothing is selected by chance, so exactly the minimum is selected
+        let config = MaskingConfig::new(0.0, 1.0, 0.0, 0, false, vec![CLS_TOKEN_ID], 1);
+        let masked = select_masked_positions(&tokens, None, &config, &mut get_test_rng(0));
+        assert_eq!(masked.iter().filter(|&&m| m).count(), 1);
+        assert!(!masked[0]);
+
+        // the default masks at least one token, even in short samples
+        for seed in 0..20 {
+            let masked = select_masked_positions(
+                &tokens[1..3],
+                None,
+                &MaskingConfig::default(),
+                &mut get_test_rng(seed),
+            );
+            assert!(masked.contains(&true));
+        }
+
+        // the minimum cannot be met when every token is excluded
+        let config = MaskingConfig::new(0.0, 1.0, 0.0, 0, false, tokens.clone(), 1);
+        let masked = select_masked_positions(&tokens, None, &config, &mut get_test_rng(0));
+        assert!(masked.iter().all(|&m| !m));
+    }
+
+    #[test]
+    fn test_get_masked_samples_from_content() {
+        let tokenizer = format!(
+            "{}/resources/tokenizer.wordpiece.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let encoded_content = "eJwryUhVSMuvUMgqzS0oVsgvSy1SKAEK5SRWVSqk5KcDALfLC3U=";
+        let config = MaskingConfig::new(0.0, 1.0, 0.0, 0, true, Vec::new(), 2);
+
+        // "jump" + "##s" is the only word with two tokens, and its pieces are masked together
+        for seed in 0..10 {
+            let samples = get_masked_samples_from_content(
+                encoded_content,
+                12,
+                &tokenizer,
+                CLS_TOKEN_ID,
+                MASK_TOKEN_ID,
+                SEP_TOKEN_ID,
+                PAD_TOKEN_ID,
+                LABEL_MASK_ID,
+                &config,
+                &mut get_test_rng(seed),
+            )
+            .unwrap();
+            assert_eq!(samples.len(), 1);
+
+            let (input_ids, attention_mask, _, labels) = &samples[0];
+            assert_eq!(attention_mask.iter().sum::<i32>(), 10);
+            let masked: Vec<i32> = labels
+                .iter()
+                .copied()
+                .filter(|&l| l != LABEL_MASK_ID)
+                .collect();
+            assert!(masked.len() >= 2);
+            assert_eq!(masked.contains(&9), masked.contains(&10));
+            assert_eq!(
+                input_ids.iter().filter(|&&id| id == MASK_TOKEN_ID).count(),
+                masked.len()
+            );
+        }
+    }
+
+    #[test]
+    fn test_get_masked_samples_from_tokens_word_ids() {
+        let tokens = vec![10, 11, 12, 13, 14];
+        let word_ids = vec![0, 0, 1, 2, 2];
+        let config = MaskingConfig::new(1.0, 1.0, 0.0, 0, true, Vec::new(), 0);
+        let samples = get_masked_samples_from_tokens(
+            &tokens,
+            Some(&word_ids),
+            5,
+            CLS_TOKEN_ID,
+            MASK_TOKEN_ID,
+            SEP_TOKEN_ID,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+            &config,
+            &mut get_test_rng(0),
+        );
+
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].3, vec![LABEL_MASK_ID, 10, 11, 12, LABEL_MASK_ID]);
+        assert_eq!(
+            samples[1].0,
+            vec![
+                CLS_TOKEN_ID,
+                MASK_TOKEN_ID,
+                MASK_TOKEN_ID,
+                SEP_TOKEN_ID,
+                PAD_TOKEN_ID
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_masked_samples_from_content_seeded() {
+        use crate::tasks::sampling::get_document_rng;
+
+        let tokenizer = format!(
+            "{}/resources/tokenizer.wordpiece.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let content = "eJwryUhVSMuvUMgqzS0oVsgvSy1SKAEK5SRWVSqk5KcDALfLC3U=";
+        let config = MaskingConfig::new(0.5, 0.8, 0.1, 20, false, Vec::new(), 0);
+        let get_samples = |seed: u64| {
+            get_masked_samples_from_content(
+                content,
+                12,
+                &tokenizer,
+                CLS_TOKEN_ID,
+                MASK_TOKEN_ID,
+                SEP_TOKEN_ID,
+                PAD_TOKEN_ID,
+                LABEL_MASK_ID,
+                &config,
+                &mut get_document_rng(Some(seed), content.as_bytes()),
+            )
+            .unwrap()
+        };
+
+        assert_eq!(get_samples(42), get_samples(42));
+        assert_ne!(
+            (0..8).map(get_samples).collect::<Vec<_>>(),
+            (8..16).map(get_samples).collect::<Vec<_>>()
+        );
+    }
 }
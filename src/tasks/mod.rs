@@ -1,3 +1,9 @@
 /// LLM training tasks and related utilities
+pub mod dpo;
+pub mod fim;
 pub mod mlm;
+pub mod packing;
+pub mod sampling;
 pub mod sequences;
+pub mod sft;
+pub mod span_corruption;
@@ -0,0 +1,481 @@
+// Causal language modeling sequence packing
+
+// external imports
+use pyo3::prelude::*;
+use std::collections::BTreeMap;
+
+// internal imports
+use crate::algos::tokenizers::tokenizers::get_tokenizer;
+use crate::io::shards::{SampleShardWriter, ShardDtype};
+use crate::tasks::sequences::{extract_content, split_sequence_max};
+
+/// How documents are assigned to blocks
+///
+/// `Concatenate` streams documents in order and cuts blocks at fixed offsets, so documents can
+/// span blocks and only the last block is padded. `BestFitDecreasing` keeps each document, or
+/// each block-sized chunk of a long document, in a single block, placing the longest first into
+/// the fullest block it fits in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackingStrategy {
+    Concatenate,
+    BestFitDecreasing,
+}
+
+pub fn packing_strategy_from_string(name: &str) -> Option<PackingStrategy> {
+    match name.to_lowercase().as_str() {
+        "concatenate" => Some(PackingStrategy::Concatenate),
+        "best_fit_decreasing" | "bfd" => Some(PackingStrategy::BestFitDecreasing),
+        _ => None,
+    }
+}
+
+/// Packing options
+///
+/// Each document is wrapped as `[bos] tokens [eos]`, with either token omitted if `None`. With
+/// `drop_last`, a final partial block is dropped instead of padded under `Concatenate`.
+#[derive(Debug, Clone)]
+pub struct PackingConfig {
+    pub block_size: usize,
+    pub bos_token_id: Option<i32>,
+    pub eos_token_id: Option<i32>,
+    pub pad_token_id: i32,
+    pub strategy: PackingStrategy,
+    pub drop_last: bool,
+}
+
+impl PackingConfig {
+    pub fn new(
+        block_size: usize,
+        bos_token_id: Option<i32>,
+        eos_token_id: Option<i32>,
+        pad_token_id: i32,
+        strategy: PackingStrategy,
+        drop_last: bool,
+    ) -> Self {
+        PackingConfig {
+            block_size,
+            bos_token_id,
+            eos_token_id,
+            pad_token_id,
+            strategy,
+            drop_last,
+        }
+    }
+}
+
+impl Default for PackingConfig {
+    fn default() -> Self {
+        PackingConfig::new(2048, None, None, 0, PackingStrategy::Concatenate, false)
+    }
+}
+
+/// A fixed-length block of packed documents
+///
+/// `segment_ids` number the document segments in the block from 1, with 0 for padding, so a
+/// position may attend to another only if both have the same non-zero segment ID.
+/// `position_ids` restart at 0 at the start of each segment. `documents` holds one
+/// `(document_index, start, end)` entry per segment, with `start..end` the segment's positions
+/// in the block.
+#[pyclass]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PackedBlock {
+    #[pyo3(get)]
+    pub input_ids: Vec<i32>,
+    #[pyo3(get)]
+    pub attention_mask: Vec<i32>,
+    #[pyo3(get)]
+    pub position_ids: Vec<i32>,
+    #[pyo3(get)]
+    pub segment_ids: Vec<i32>,
+    #[pyo3(get)]
+    pub documents: Vec<(usize, usize, usize)>,
+}
+
+#[pymethods]
+impl PackedBlock {
+    fn __len__(&self) -> usize {
+        self.input_ids.len()
+    }
+
+    fn __str__(&self) -> String {
+        format!(
+            "PackedBlock(num_tokens={}, num_documents={}, num_padding={})",
+            self.input_ids.len(),
+            self.documents.len(),
+            self.get_num_padding()
+        )
+    }
+}
+
+impl PackedBlock {
+    fn with_capacity(block_size: usize) -> Self {
+        PackedBlock {
+            input_ids: Vec::with_capacity(block_size),
+            attention_mask: Vec::with_capacity(block_size),
+            position_ids: Vec::with_capacity(block_size),
+            segment_ids: Vec::with_capacity(block_size),
+            documents: Vec::new(),
+        }
+    }
+
+    /// Get the number of padding positions
+    pub fn get_num_padding(&self) -> usize {
+        self.attention_mask.iter().filter(|&&x| x == 0).count()
+    }
+
+    /// Append a segment of a document
+    fn push_segment(&mut self, document_index: usize, tokens: &[i32]) {
+        let start = self.input_ids.len();
+        let segment_id = self.documents.len() as i32 + 1;
+        self.input_ids.extend_from_slice(tokens);
+        self.attention_mask
+            .extend(std::iter::repeat_n(1, tokens.len()));
+        self.position_ids.extend(0..tokens.len() as i32);
+        self.segment_ids
+            .extend(std::iter::repeat_n(segment_id, tokens.len()));
+        self.documents
+            .push((document_index, start, start + tokens.len()));
+    }
+
+    /// Pad the block to the block size
+    fn pad(&mut self, block_size: usize, pad_token_id: i32) {
+        self.input_ids.resize(block_size, pad_token_id);
+        self.attention_mask.resize(block_size, 0);
+        self.position_ids.resize(block_size, 0);
+        self.segment_ids.resize(block_size, 0);
+    }
+}
+
+/// Wrap a document with the BOS and EOS tokens
+fn wrap_document(tokens: &[i32], config: &PackingConfig) -> Vec<i32> {
+    let mut wrapped = Vec::with_capacity(tokens.len() + 2);
+    wrapped.extend(config.bos_token_id);
+    wrapped.extend_from_slice(tokens);
+    wrapped.extend(config.eos_token_id);
+    wrapped
+}
+
+/// Pack documents in order, cutting blocks at fixed offsets
+fn pack_concatenate(documents: &[Vec<i32>], config: &PackingConfig) -> Vec<PackedBlock> {
+    let mut blocks = Vec::new();
+    let mut block = PackedBlock::with_capacity(config.block_size);
+
+    for (document_index, tokens) in documents.iter().enumerate() {
+        let wrapped = wrap_document(tokens, config);
+        let mut remaining = wrapped.as_slice();
+        while !remaining.is_empty() {
+            let available = config.block_size - block.input_ids.len();
+            let (segment, rest) = remaining.split_at(available.min(remaining.len()));
+            block.push_segment(document_index, segment);
+            remaining = rest;
+
+            if block.input_ids.len() == config.block_size {
+                blocks.push(std::mem::replace(
+                    &mut block,
+                    PackedBlock::with_capacity(config.block_size),
+                ));
+            }
+        }
+    }
+
+    if !block.input_ids.is_empty() && !config.drop_last {
+        block.pad(config.block_size, config.pad_token_id);
+        blocks.push(block);
+    }
+
+    blocks
+}
+
+/// Pack documents with best-fit-decreasing bin packing
+fn pack_best_fit_decreasing(documents: &[Vec<i32>], config: &PackingConfig) -> Vec<PackedBlock> {
+    // long documents are split into block-sized chunks, which are packed like documents
+    let mut items: Vec<(usize, Vec<i32>)> = documents
+        .iter()
+        .enumerate()
+        .flat_map(|(document_index, tokens)| {
+            split_sequence_max(&wrap_document(tokens, config), config.block_size)
+                .into_iter()
+                .filter(|chunk| !chunk.is_empty())
+                .map(move |chunk| (document_index, chunk))
+        })
+        .collect();
+
+    // the sort is stable, so ties keep document order and packing is deterministic
+    items.sort_by_key(|item| std::cmp::Reverse(item.1.len()));
+
+    // map from remaining capacity to the blocks with that capacity, in creation order
+    let mut blocks: Vec<PackedBlock> = Vec::new();
+    let mut capacities: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (document_index, chunk) in items.iter() {
+        let best_fit = capacities
+            .range_mut(chunk.len()..)
+            .next()
+            .map(|(&capacity, indices)| (capacity, indices.remove(0)));
+
+        let block_index = match best_fit {
+            Some((capacity, block_index)) => {
+                if capacities[&capacity].is_empty() {
+                    capacities.remove(&capacity);
+                }
+                block_index
+            }
+            None => {
+                blocks.push(PackedBlock::with_capacity(config.block_size));
+                blocks.len() - 1
+            }
+        };
+
+        let block = &mut blocks[block_index];
+        block.push_segment(*document_index, chunk);
+        let capacity = config.block_size - block.input_ids.len();
+        if capacity > 0 {
+            capacities.entry(capacity).or_default().push(block_index);
+        }
+    }
+
+    for block in blocks.iter_mut() {
+        block.pad(config.block_size, config.pad_token_id);
+    }
+
+    blocks
+}
+
+/// Pack token sequences into fixed-length blocks for causal language modeling
+///
+/// # Arguments
+/// * `documents` - The token IDs of each document
+/// * `config` - The packing options
+///
+/// # Returns
+/// The packed blocks, each `block_size` long
+pub fn pack_sequences(documents: &[Vec<i32>], config: &PackingConfig) -> Vec<PackedBlock> {
+    assert!(config.block_size > 0, "Block size must be positive");
+
+    match config.strategy {
+        PackingStrategy::Concatenate => pack_concatenate(documents, config),
+        PackingStrategy::BestFitDecreasing => pack_best_fit_decreasing(documents, config),
+    }
+}
+
+/// Pack encoded contents into fixed-length blocks for causal language modeling
+///
+/// # Arguments
+/// * `encoded_contents` - The encoded contents, one per document
+/// * `tokenizer_name` - The tokenizer name, path or hub identifier
+/// * `config` - The packing options
+///
+/// # Returns
+/// The packed blocks, each `block_size` long
+pub fn pack_sequences_from_content(
+    encoded_contents: &[String],
+    tokenizer_name: &str,
+    config: &PackingConfig,
+) -> tokenizers::Result<Vec<PackedBlock>> {
+    let contents = encoded_contents
+        .iter()
+        .map(|encoded_content| Ok(String::from_utf8(extract_content(encoded_content)?)?))
+        .collect::<tokenizers::Result<Vec<String>>>()?;
+
+    // we need to cast u32 tokens to i32
+    let documents: Vec<Vec<i32>> = get_tokenizer(tokenizer_name)?
+        .encode_batch(contents, false)?
+        .iter()
+        .map(|encoding| encoding.get_ids().iter().map(|&x| x as i32).collect())
+        .collect();
+
+    Ok(pack_sequences(&documents, config))
+}
+
+/// Get the shard fields of a packed block
+pub fn get_packed_block_shard_fields(token_dtype: ShardDtype) -> [(&'static str, ShardDtype); 4] {
+    [
+        ("input_ids", token_dtype),
+        ("attention_mask", ShardDtype::U8),
+        ("position_ids", ShardDtype::U32),
+        ("segment_ids", ShardDtype::U32),
+    ]
+}
+
+/// Write packed blocks to a writer created with `get_packed_block_shard_fields`
+pub fn write_packed_blocks(
+    writer: &mut SampleShardWriter,
+    blocks: &[PackedBlock],
+) -> std::io::Result<()> {
+    for block in blocks {
+        writer.write_sample(&[
+            &block.input_ids,
+            &block.attention_mask,
+            &block.position_ids,
+            &block.segment_ids,
+        ])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOS_TOKEN_ID: i32 = 1;
+    const EOS_TOKEN_ID: i32 = 2;
+    const PAD_TOKEN_ID: i32 = 0;
+
+    fn get_config(block_size: usize, strategy: PackingStrategy) -> PackingConfig {
+        PackingConfig::new(
+            block_size,
+            Some(BOS_TOKEN_ID),
+            Some(EOS_TOKEN_ID),
+            PAD_TOKEN_ID,
+            strategy,
+            false,
+        )
+    }
+
+    #[test]
+    fn test_packing_strategy_from_string() {
+        assert_eq!(
+            packing_strategy_from_string("BFD"),
+            Some(PackingStrategy::BestFitDecreasing)
+        );
+        assert_eq!(
+            packing_strategy_from_string("concatenate"),
+            Some(PackingStrategy::Concatenate)
+        );
+        assert_eq!(packing_strategy_from_string("first_fit"), None);
+    }
+
+    #[test]
+    fn test_pack_concatenate() {
+        let documents = vec![vec![10, 11, 12], vec![20, 21], vec![30]];
+        let blocks = pack_sequences(&documents, &get_config(4, PackingStrategy::Concatenate));
+
+        // 5 + 4 + 3 = 12 tokens in 3 full blocks
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].input_ids, vec![1, 10, 11, 12]);
+        assert_eq!(blocks[1].input_ids, vec![2, 1, 20, 21]);
+        assert_eq!(blocks[2].input_ids, vec![2, 1, 30, 2]);
+
+        // the first document continues into the second block
+        assert_eq!(blocks[1].segment_ids, vec![1, 2, 2, 2]);
+        assert_eq!(blocks[1].position_ids, vec![0, 0, 1, 2]);
+        assert_eq!(blocks[1].documents, vec![(0, 0, 1), (1, 1, 4)]);
+        assert!(blocks.iter().all(|block| block.get_num_padding() == 0));
+    }
+
+    #[test]
+    fn test_pack_concatenate_padding() {
+        let documents = vec![vec![10, 11, 12], vec![20]];
+        let mut config = get_config(4, PackingStrategy::Concatenate);
+        let blocks = pack_sequences(&documents, &config);
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1].input_ids, vec![2, 1, 20, 2]);
+
+        config.eos_token_id = None;
+        let blocks = pack_sequences(&documents, &config);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1].input_ids, vec![1, 20, 0, 0]);
+        assert_eq!(blocks[1].attention_mask, vec![1, 1, 0, 0]);
+        assert_eq!(blocks[1].segment_ids, vec![1, 1, 0, 0]);
+        assert_eq!(blocks[1].get_num_padding(), 2);
+
+        config.drop_last = true;
+        assert_eq!(pack_sequences(&documents, &config).len(), 1);
+    }
+
+    #[test]
+    fn test_pack_best_fit_decreasing() {
+        // wrapped lengths 6, 3, 5, 4, 2 into blocks of 8
+        let documents = vec![
+            vec![10, 11, 12, 13],
+            vec![20],
+            vec![30, 31, 32],
+            vec![40, 41],
+            vec![],
+        ];
+        let blocks = pack_sequences(
+            &documents,
+            &get_config(8, PackingStrategy::BestFitDecreasing),
+        );
+
+        // 6 + 2, 5 + 3, 4
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].documents, vec![(0, 0, 6), (4, 6, 8)]);
+        assert_eq!(blocks[1].documents, vec![(2, 0, 5), (1, 5, 8)]);
+        assert_eq!(blocks[2].documents, vec![(3, 0, 4)]);
+        assert_eq!(blocks[0].input_ids, vec![1, 10, 11, 12, 13, 2, 1, 2]);
+        assert_eq!(blocks[0].segment_ids, vec![1, 1, 1, 1, 1, 1, 2, 2]);
+        assert_eq!(blocks[0].position_ids, vec![0, 1, 2, 3, 4, 5, 0, 1]);
+        assert_eq!(blocks[2].get_num_padding(), 4);
+        assert!(blocks.iter().all(|block| block.input_ids.len() == 8));
+    }
+
+    #[test]
+    fn test_pack_best_fit_decreasing_long_document() {
+        let documents = vec![(100..110).collect::<Vec<i32>>(), vec![20]];
+        let blocks = pack_sequences(
+            &documents,
+            &get_config(8, PackingStrategy::BestFitDecreasing),
+        );
+
+        // 12 wrapped tokens split into 8 + 4, and the 3 token document fills the second block
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].documents, vec![(0, 0, 8)]);
+        assert_eq!(blocks[1].documents, vec![(0, 0, 4), (1, 4, 7)]);
+    }
+
+    #[test]
+    fn test_pack_sequences_from_content() {
+        let tokenizer = format!(
+            "{}/resources/tokenizer.wordpiece.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        // "the fox jumps over the lazy dog"
+        let content = "eJwryUhVSMuvUMgqzS0oVsgvSy1SKAEK5SRWVSqk5KcDALfLC3U=".to_string();
+        let blocks = pack_sequences_from_content(
+            &[content.clone(), content],
+            &tokenizer,
+            &PackingConfig::new(
+                16,
+                Some(2),
+                Some(3),
+                0,
+                PackingStrategy::BestFitDecreasing,
+                false,
+            ),
+        )
+        .unwrap();
+
+        // 10 wrapped tokens each, so one document per block
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].input_ids[..3], [2, 5, 8]);
+        assert_eq!(blocks[0].input_ids[9], 3);
+    }
+
+    #[test]
+    fn test_write_packed_blocks() {
+        use crate::io::shards::{ShardFormat, ShardReader};
+
+        let documents = vec![vec![10, 11, 12], vec![20, 21]];
+        let blocks = pack_sequences(&documents, &get_config(4, PackingStrategy::Concatenate));
+
+        let prefix = std::env::temp_dir()
+            .join("alea_preprocess_test_packing_shards")
+            .to_string_lossy()
+            .to_string();
+        let mut writer = SampleShardWriter::create(
+            &prefix,
+            &get_packed_block_shard_fields(ShardDtype::U16),
+            ShardFormat::Bin,
+        )
+        .unwrap();
+        write_packed_blocks(&mut writer, &blocks).unwrap();
+        assert_eq!(writer.finish().unwrap(), blocks.len());
+
+        let input_ids = ShardReader::open(&format!("{}.input_ids", prefix)).unwrap();
+        assert_eq!(input_ids.len(), blocks.len());
+        assert_eq!(input_ids.get(0), Some(blocks[0].input_ids.clone()));
+        let segment_ids = ShardReader::open(&format!("{}.segment_ids", prefix)).unwrap();
+        assert_eq!(segment_ids.get(1), Some(blocks[1].segment_ids.clone()));
+    }
+}
@@ -0,0 +1,79 @@
+// Deterministic random number generation for sampling tasks
+
+// external imports
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+// internal imports
+use crate::algos::hashing::blake3::hash_bytes_to_u64;
+
+/// The random number generator used by sampling tasks.
+///
+/// ChaCha8 produces the same stream for the same seed on every platform and is stable across
+/// releases of `rand_chacha`, unlike `rand::rngs::StdRng`.
+pub type SamplingRng = ChaCha8Rng;
+
+/// Get a random number generator from a seed
+pub fn get_rng(seed: u64) -> SamplingRng {
+    SamplingRng::seed_from_u64(seed)
+}
+
+/// Get the seed for a document from a global seed and the Blake3 hash of its content
+pub fn get_document_seed(seed: u64, content: &[u8]) -> u64 {
+    hash_bytes_to_u64(content, seed)
+}
+
+/// Get a random number generator for a document
+///
+/// With a seed, the generator is seeded from the seed and the content, so identical inputs give
+/// identical samples on every run and machine while different documents still get different
+/// streams. Without a seed, the generator is seeded from system entropy.
+pub fn get_document_rng(seed: Option<u64>, content: &[u8]) -> SamplingRng {
+    match seed {
+        Some(seed) => get_rng(get_document_seed(seed, content)),
+        None => SamplingRng::from_entropy(),
+    }
+}
+
+/// Get a random number generator for a document given as token IDs
+///
+/// Tokens are hashed as little-endian bytes.
+pub fn get_token_document_rng(seed: Option<u64>, tokens: &[i32]) -> SamplingRng {
+    let content: Vec<u8> = tokens
+        .iter()
+        .flat_map(|token| token.to_le_bytes())
+        .collect();
+    get_document_rng(seed, &content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    fn sample(rng: &mut SamplingRng) -> Vec<u32> {
+        (0..8).map(|_| rng.gen()).collect()
+    }
+
+    #[test]
+    fn test_get_document_rng() {
+        let a = sample(&mut get_document_rng(Some(42), b"the fox"));
+        assert_eq!(a, sample(&mut get_document_rng(Some(42), b"the fox")));
+        assert_ne!(a, sample(&mut get_document_rng(Some(43), b"the fox")));
+        assert_ne!(a, sample(&mut get_document_rng(Some(42), b"the dog")));
+    }
+
+    #[test]
+    fn test_get_document_seed_is_stable() {
+        // pinned so that changes to hashing or seeding are caught
+        assert_eq!(get_document_seed(0, b"Hello, world!"), 5513748078711345449);
+        assert_eq!(sample(&mut get_rng(0))[0], 2811902828);
+    }
+
+    #[test]
+    fn test_get_token_document_rng() {
+        let a = sample(&mut get_token_document_rng(Some(7), &[1, 2, 3]));
+        assert_eq!(a, sample(&mut get_token_document_rng(Some(7), &[1, 2, 3])));
+        assert_ne!(a, sample(&mut get_token_document_rng(Some(7), &[1, 2, 4])));
+    }
+}
@@ -1,20 +1,180 @@
 // Utility methods for sequences
 
+// external imports
 use base64::prelude::BASE64_STANDARD;
 use base64::Engine;
-use flate2::read::ZlibDecoder;
-use std::io::Read;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use flate2::Compression;
+use std::fmt;
+use std::io::{Read, Write};
 
-pub fn extract_content(encoded_content: &str) -> Vec<u8> {
-    // decode the content
-    let decoded_content = BASE64_STANDARD.decode(encoded_content.as_bytes()).unwrap();
+/// Compression applied to content before base64 encoding
+///
+/// KL3M records store content as base64 over zlib; `Gzip`, `Zstd`, and `Raw` (no compression)
+/// cover other sources and can be told apart by their leading bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentCodec {
+    Raw,
+    Zlib,
+    Gzip,
+    Zstd,
+}
+
+pub fn content_codec_from_string(name: &str) -> Option<ContentCodec> {
+    match name.to_lowercase().as_str() {
+        "raw" | "none" => Some(ContentCodec::Raw),
+        "zlib" => Some(ContentCodec::Zlib),
+        "gzip" | "gz" => Some(ContentCodec::Gzip),
+        "zstd" | "zst" => Some(ContentCodec::Zstd),
+        _ => None,
+    }
+}
+
+impl fmt::Display for ContentCodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ContentCodec::Raw => "raw",
+            ContentCodec::Zlib => "zlib",
+            ContentCodec::Gzip => "gzip",
+            ContentCodec::Zstd => "zstd",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Errors from decoding or encoding content
+#[derive(Debug)]
+pub enum ContentError {
+    Base64(base64::DecodeError),
+    Decompress(ContentCodec, std::io::Error),
+    Compress(ContentCodec, std::io::Error),
+}
+
+impl fmt::Display for ContentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContentError::Base64(e) => write!(f, "invalid base64 content: {}", e),
+            ContentError::Decompress(codec, e) => {
+                write!(f, "failed to decompress {} content: {}", codec, e)
+            }
+            ContentError::Compress(codec, e) => {
+                write!(f, "failed to compress {} content: {}", codec, e)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ContentError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ContentError::Base64(e) => Some(e),
+            ContentError::Decompress(_, e) | ContentError::Compress(_, e) => Some(e),
+        }
+    }
+}
+
+impl From<base64::DecodeError> for ContentError {
+    fn from(e: base64::DecodeError) -> Self {
+        ContentError::Base64(e)
+    }
+}
+
+/// Detect the codec of decoded (post-base64) bytes from their magic number.
+///
+/// gzip starts with `1f 8b` and zstd frames with `28 b5 2f fd`. zlib has no magic number, so a
+/// deflate CMF byte whose header passes the FCHECK test and sets no preset dictionary is taken
+/// as zlib. Anything else is treated as raw. About 1 in 300 raw texts also pass the zlib test
+/// (e.g., ones starting with "HK" or "x^"), so `decode_content` keeps UTF-8 text that does not
+/// inflate as raw.
+pub fn detect_codec(bytes: &[u8]) -> ContentCodec {
+    match bytes {
+        [0x1f, 0x8b, ..] => ContentCodec::Gzip,
+        [0x28, 0xb5, 0x2f, 0xfd, ..] => ContentCodec::Zstd,
+        [cmf, flg, ..]
+            if cmf & 0x0f == 8
+                && cmf >> 4 <= 7
+                && flg & 0x20 == 0
+                && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0 =>
+        {
+            ContentCodec::Zlib
+        }
+        _ => ContentCodec::Raw,
+    }
+}
+
+/// Decompress bytes with the given codec.
+pub fn decompress_bytes(bytes: &[u8], codec: ContentCodec) -> Result<Vec<u8>, ContentError> {
+    // compressed text typically expands 3-4x, so reserve up front to avoid regrowth
+    let mut output_buffer = Vec::with_capacity(bytes.len() * 4);
+    let result = match codec {
+        ContentCodec::Raw => return Ok(bytes.to_vec()),
+        ContentCodec::Zlib => ZlibDecoder::new(bytes).read_to_end(&mut output_buffer),
+        ContentCodec::Gzip => GzDecoder::new(bytes).read_to_end(&mut output_buffer),
+        ContentCodec::Zstd => zstd::stream::read::Decoder::new(bytes)
+            .and_then(|mut d| d.read_to_end(&mut output_buffer)),
+    };
+    result.map_err(|e| ContentError::Decompress(codec, e))?;
+
+    Ok(output_buffer)
+}
+
+/// Compress bytes with the given codec at its default level.
+pub fn compress_bytes(bytes: &[u8], codec: ContentCodec) -> Result<Vec<u8>, ContentError> {
+    let result = match codec {
+        ContentCodec::Raw => return Ok(bytes.to_vec()),
+        ContentCodec::Zlib => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(bytes).and_then(|_| encoder.finish())
+        }
+        ContentCodec::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(bytes).and_then(|_| encoder.finish())
+        }
+        ContentCodec::Zstd => zstd::stream::encode_all(bytes, 0),
+    };
+
+    result.map_err(|e| ContentError::Compress(codec, e))
+}
 
-    // decompress the content
-    let mut zlib_decoder = ZlibDecoder::new(&decoded_content[..]);
-    let mut output_buffer = Vec::new();
-    zlib_decoder.read_to_end(&mut output_buffer).unwrap();
+/// Decode base64 content and decompress it.
+///
+/// # Arguments
+/// * `encoded_content` - The base64-encoded content
+/// * `codec` - The compression codec, or `None` to detect it from the decoded bytes; detected
+///   zlib content that fails to inflate is returned as is if it is valid UTF-8 text
+///
+/// # Returns
+/// The decoded content bytes
+pub fn decode_content(
+    encoded_content: &str,
+    codec: Option<ContentCodec>,
+) -> Result<Vec<u8>, ContentError> {
+    let decoded_content = BASE64_STANDARD.decode(encoded_content.as_bytes())?;
 
-    output_buffer
+    match codec.unwrap_or_else(|| detect_codec(&decoded_content)) {
+        ContentCodec::Raw => Ok(decoded_content),
+        ContentCodec::Zlib if codec.is_none() => {
+            match decompress_bytes(&decoded_content, ContentCodec::Zlib) {
+                Err(_) if std::str::from_utf8(&decoded_content).is_ok() => Ok(decoded_content),
+                result => result,
+            }
+        }
+        codec => decompress_bytes(&decoded_content, codec),
+    }
+}
+
+/// Compress content and base64 encode it; the inverse of `decode_content`.
+pub fn encode_content(content: &[u8], codec: ContentCodec) -> Result<String, ContentError> {
+    match codec {
+        ContentCodec::Raw => Ok(BASE64_STANDARD.encode(content)),
+        _ => Ok(BASE64_STANDARD.encode(compress_bytes(content, codec)?)),
+    }
+}
+
+/// Decode base64 content, detecting its compression codec.
+pub fn extract_content(encoded_content: &str) -> Result<Vec<u8>, ContentError> {
+    decode_content(encoded_content, None)
 }
 
 pub fn split_sequence_max(sequence: &[i32], max_size: usize) -> Vec<Vec<i32>> {
@@ -83,7 +243,77 @@ mod tests {
     #[test]
     fn test_extract_content() {
         let encoded_content = "eJwLycgsVgCiRIWS1OISACRzBPY=";
-        let content = extract_content(encoded_content);
+        let content = extract_content(encoded_content).unwrap();
         assert_eq!(content, b"This is a test");
     }
+
+    #[test]
+    fn test_content_codec_from_string() {
+        assert_eq!(content_codec_from_string("ZSTD"), Some(ContentCodec::Zstd));
+        assert_eq!(content_codec_from_string("none"), Some(ContentCodec::Raw));
+        assert_eq!(content_codec_from_string("lz4"), None);
+    }
+
+    #[test]
+    fn test_encode_content_round_trip() {
+        let content = b"the fox jumps over the lazy dog";
+        for codec in [
+            ContentCodec::Raw,
+            ContentCodec::Zlib,
+            ContentCodec::Gzip,
+            ContentCodec::Zstd,
+        ] {
+            let encoded_content = encode_content(content, codec).unwrap();
+            let decoded_bytes = BASE64_STANDARD.decode(&encoded_content).unwrap();
+            assert_eq!(detect_codec(&decoded_bytes), codec);
+            assert_eq!(extract_content(&encoded_content).unwrap(), content);
+            assert_eq!(
+                decode_content(&encoded_content, Some(codec)).unwrap(),
+                content
+            );
+        }
+    }
+
+    #[test]
+    fn test_encode_content_zlib_matches_kl3m() {
+        let encoded_content = encode_content(b"This is a test", ContentCodec::Zlib).unwrap();
+        assert_eq!(encoded_content, "eJwLycgsVgCiRIWS1OISACRzBPY=");
+    }
+
+    #[test]
+    fn test_detect_codec_raw_text() {
+        assert_eq!(detect_codec(b"This is a test"), ContentCodec::Raw);
+        assert_eq!(detect_codec(b"x"), ContentCodec::Raw);
+        assert_eq!(detect_codec(b""), ContentCodec::Raw);
+    }
+
+    #[test]
+    fn test_extract_content_errors() {
+        assert!(matches!(
+            extract_content("not base64!"),
+            Err(ContentError::Base64(_))
+        ));
+
+        // valid zlib header followed by a truncated stream
+        let encoded_content = BASE64_STANDARD.encode([0x78, 0x9c, 0x0b, 0xc9]);
+        assert!(matches!(
+            decode_content(&encoded_content, Some(ContentCodec::Zlib)),
+            Err(ContentError::Decompress(ContentCodec::Zlib, _))
+        ));
+        assert!(matches!(
+            extract_content(&encoded_content),
+            Err(ContentError::Decompress(ContentCodec::Zlib, _))
+        ));
+    }
+
+    #[test]
+    fn test_extract_content_raw_zlib_header() {
+        // "HK" passes the zlib header check but is plain text
+        assert_eq!(detect_codec(b"HK Holdings Ltd."), ContentCodec::Zlib);
+        let encoded_content = BASE64_STANDARD.encode(b"HK Holdings Ltd.");
+        assert_eq!(
+            extract_content(&encoded_content).unwrap(),
+            b"HK Holdings Ltd."
+        );
+    }
 }
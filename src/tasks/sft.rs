@@ -0,0 +1,480 @@
+// Supervised fine-tuning (SFT) chat task
+
+// external imports
+use serde_json::Value;
+
+// internal imports
+use crate::algos::tokenizers::tokenizers::get_tokenizer;
+use crate::io::fs::files::iter_documents;
+
+/// An SFT sample: input_ids, attention_mask, and labels
+pub type SftSample = (Vec<i32>, Vec<i32>, Vec<i32>);
+
+/// The role whose tokens are trained on
+const ASSISTANT_ROLE: &str = "assistant";
+
+/// The role that is kept when older turns are truncated
+const SYSTEM_ROLE: &str = "system";
+
+/// A conversation turn
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatMessage {
+    pub role: String,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn new(role: &str, content: &str) -> Self {
+        ChatMessage {
+            role: role.to_string(),
+            content: content.to_string(),
+        }
+    }
+}
+
+/// A chat template
+///
+/// Each turn is rendered as `header content footer`, where the header and footer come from a
+/// turn template split at `{{ content }}`, and `{{ role }}` is replaced with the turn's role.
+/// `prefix` is rendered once before the first turn, e.g., a BOS token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatTemplate {
+    pub prefix: String,
+    pub header: String,
+    pub footer: String,
+}
+
+impl ChatTemplate {
+    /// Create a template from a prefix and a turn template
+    /// Returns None if the turn template has no `{{ content }}` placeholder.
+    pub fn new(prefix: &str, turn_template: &str) -> Option<Self> {
+        let turn_template = turn_template
+            .replace("{{content}}", "{{ content }}")
+            .replace("{{role}}", "{{ role }}");
+        let (header, footer) = turn_template.split_once("{{ content }}")?;
+        Some(ChatTemplate {
+            prefix: prefix.to_string(),
+            header: header.to_string(),
+            footer: footer.to_string(),
+        })
+    }
+
+    /// ChatML, as used by OpenAI and Qwen models
+    pub fn chatml() -> Self {
+        ChatTemplate::new("", "<|im_start|>{{ role }}\n{{ content }}<|im_end|>\n").unwrap()
+    }
+
+    /// Llama 3
+    pub fn llama() -> Self {
+        ChatTemplate::new(
+            "<|begin_of_text|>",
+            "<|start_header_id|>{{ role }}<|end_header_id|>\n\n{{ content }}<|eot_id|>",
+        )
+        .unwrap()
+    }
+}
+
+/// Get a chat template by name ("chatml" or "llama"), or from a custom turn template
+pub fn chat_template_from_string(name: &str) -> Option<ChatTemplate> {
+    match name.to_lowercase().as_str() {
+        "chatml" => Some(ChatTemplate::chatml()),
+        "llama" | "llama3" => Some(ChatTemplate::llama()),
+        _ => ChatTemplate::new("", name),
+    }
+}
+
+/// A rendered conversation
+///
+/// `message_spans` holds the byte range of each turn in `text`, and `label_spans` the byte ranges
+/// to train on: the content and footer of each assistant turn, without trailing whitespace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedConversation {
+    pub text: String,
+    pub message_spans: Vec<(usize, usize)>,
+    pub label_spans: Vec<(usize, usize)>,
+}
+
+/// Render a conversation with a chat template
+pub fn render_conversation(
+    messages: &[ChatMessage],
+    template: &ChatTemplate,
+) -> RenderedConversation {
+    let mut text = template.prefix.clone();
+    let mut message_spans = Vec::with_capacity(messages.len());
+    let mut label_spans = Vec::new();
+
+    for message in messages {
+        let start = text.len();
+        text.push_str(&template.header.replace("{{ role }}", &message.role));
+        let content_start = text.len();
+        text.push_str(&message.content);
+        text.push_str(&template.footer.replace("{{ role }}", &message.role));
+        message_spans.push((start, text.len()));
+
+        if message.role == ASSISTANT_ROLE {
+            let label_end = content_start + text[content_start..].trim_end().len();
+            label_spans.push((content_start, label_end));
+        }
+    }
+
+    RenderedConversation {
+        text,
+        message_spans,
+        label_spans,
+    }
+}
+
+/// Parse the messages of a conversation from a JSON object
+/// The messages are a list of objects with "role" and "content" fields.
+pub fn parse_conversation(json: &str, field: &str) -> tokenizers::Result<Vec<ChatMessage>> {
+    let value: Value = serde_json::from_str(json)?;
+    let messages = value
+        .get(field)
+        .and_then(|messages| messages.as_array())
+        .ok_or_else(|| format!("missing message list: {}", field))?;
+
+    messages
+        .iter()
+        .map(|message| {
+            let get_field = |name: &str| {
+                message
+                    .get(name)
+                    .and_then(|value| value.as_str())
+                    .ok_or_else(|| format!("message missing field: {}", name))
+            };
+            Ok(ChatMessage::new(get_field("role")?, get_field("content")?))
+        })
+        .collect()
+}
+
+/// Choose the first message to keep so that the conversation fits
+///
+/// System messages are always kept. Older turns are dropped so that the kept turns start at a
+/// user message, or at the last candidate if none fits.
+fn select_first_message(
+    messages: &[ChatMessage],
+    message_lengths: &[usize],
+    fixed_length: usize,
+    max_seq_length: usize,
+) -> usize {
+    let first_turn = messages
+        .iter()
+        .position(|message| message.role != SYSTEM_ROLE)
+        .unwrap_or(messages.len());
+    let candidates: Vec<usize> = (first_turn..messages.len())
+        .filter(|&i| i == first_turn || messages[i].role != ASSISTANT_ROLE)
+        .collect();
+
+    let get_length = |first: usize| -> usize {
+        fixed_length
+            + (0..messages.len())
+                .filter(|&i| i >= first || messages[i].role == SYSTEM_ROLE)
+                .map(|i| message_lengths[i])
+                .sum::<usize>()
+    };
+
+    candidates
+        .iter()
+        .copied()
+        .find(|&first| get_length(first) <= max_seq_length)
+        .or(candidates.last().copied())
+        .unwrap_or(first_turn)
+}
+
+/// Get an SFT sample from a conversation
+///
+/// The conversation is rendered and tokenized once, and labels are set only for tokens that
+/// start inside an assistant turn's content or footer. If the conversation does not fit in
+/// max_seq_length, the oldest non-system turns are dropped, starting the kept turns at a user
+/// message; if the most recent exchange alone does not fit, its end is truncated.
+///
+/// # Arguments
+/// * `messages` - The conversation turns
+/// * `tokenizer_name` - The tokenizer name, path or hub identifier
+/// * `template` - The chat template
+/// * `max_seq_length` - The length of the padded sample
+/// * `pad_token_id` - The padding token ID
+/// * `label_mask_id` - The label ID for tokens that are not trained on, e.g., -100
+///
+/// # Returns
+/// The input_ids, attention_mask, and labels
+pub fn get_sft_sample(
+    messages: &[ChatMessage],
+    tokenizer_name: &str,
+    template: &ChatTemplate,
+    max_seq_length: usize,
+    pad_token_id: i32,
+    label_mask_id: i32,
+) -> tokenizers::Result<SftSample> {
+    let rendered = render_conversation(messages, template);
+    let encoding = get_tokenizer(tokenizer_name)?.encode(rendered.text.as_str(), false)?;
+
+    // assign each token to the turn its first byte belongs to, or None for the prefix
+    let token_messages: Vec<Option<usize>> = encoding
+        .get_offsets()
+        .iter()
+        .map(|&(start, _)| {
+            rendered
+                .message_spans
+                .iter()
+                .position(|&(span_start, span_end)| start >= span_start && start < span_end)
+        })
+        .collect();
+    let token_labeled: Vec<bool> = encoding
+        .get_offsets()
+        .iter()
+        .map(|&(start, _)| {
+            rendered
+                .label_spans
+                .iter()
+                .any(|&(span_start, span_end)| start >= span_start && start < span_end)
+        })
+        .collect();
+
+    let mut message_lengths = vec![0; messages.len()];
+    let mut fixed_length = 0;
+    for message_index in token_messages.iter() {
+        match message_index {
+            Some(i) => message_lengths[*i] += 1,
+            None => fixed_length += 1,
+        }
+    }
+    let first = select_first_message(messages, &message_lengths, fixed_length, max_seq_length);
+
+    let mut input_ids = Vec::with_capacity(max_seq_length);
+    let mut labels = Vec::with_capacity(max_seq_length);
+    for (i, &id) in encoding.get_ids().iter().enumerate() {
+        let keep = match token_messages[i] {
+            Some(message_index) => {
+                message_index >= first || messages[message_index].role == SYSTEM_ROLE
+            }
+            None => true,
+        };
+        if keep && input_ids.len() < max_seq_length {
+            input_ids.push(id as i32);
+            labels.push(if token_labeled[i] {
+                id as i32
+            } else {
+                label_mask_id
+            });
+        }
+    }
+
+    let mut attention_mask = vec![1; input_ids.len()];
+    attention_mask.resize(max_seq_length, 0);
+    input_ids.resize(max_seq_length, pad_token_id);
+    labels.resize(max_seq_length, label_mask_id);
+
+    Ok((input_ids, attention_mask, labels))
+}
+
+/// Get SFT samples from a JSONL or gzipped JSONL file of conversations
+///
+/// # Arguments
+/// * `path` - The path to the file, with one conversation object per line
+/// * `field` - The field holding the list of messages, e.g., "messages"
+/// * `tokenizer_name` - The tokenizer name, path or hub identifier
+/// * `template` - The chat template
+/// * `max_seq_length` - The length of the padded samples
+/// * `pad_token_id` - The padding token ID
+/// * `label_mask_id` - The label ID for tokens that are not trained on
+///
+/// # Returns
+/// One sample per conversation
+pub fn get_sft_samples_from_file(
+    path: &str,
+    field: &str,
+    tokenizer_name: &str,
+    template: &ChatTemplate,
+    max_seq_length: usize,
+    pad_token_id: i32,
+    label_mask_id: i32,
+) -> tokenizers::Result<Vec<SftSample>> {
+    let mut samples = Vec::new();
+    for line in iter_documents(path, None)? {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let messages = parse_conversation(&line, field)?;
+        samples.push(get_sft_sample(
+            &messages,
+            tokenizer_name,
+            template,
+            max_seq_length,
+            pad_token_id,
+            label_mask_id,
+        )?);
+    }
+    Ok(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAD_TOKEN_ID: i32 = 0;
+    const LABEL_MASK_ID: i32 = -100;
+
+    fn get_tokenizer_path() -> String {
+        format!(
+            "{}/resources/tokenizer.wordpiece.json",
+            env!("CARGO_MANIFEST_DIR")
+        )
+    }
+
+    /// Roles are unknown words, so each turn is [UNK] content "."
+    fn get_template() -> ChatTemplate {
+        chat_template_from_string("{{ role }} {{ content }} .").unwrap()
+    }
+
+    fn get_conversation() -> Vec<ChatMessage> {
+        vec![
+            ChatMessage::new("system", "hello"),
+            ChatMessage::new("user", "the fox"),
+            ChatMessage::new("assistant", "the dog"),
+            ChatMessage::new("user", "the lazy fox"),
+            ChatMessage::new("assistant", "jumps over"),
+        ]
+    }
+
+    #[test]
+    fn test_chat_template_from_string() {
+        assert_eq!(
+            chat_template_from_string("ChatML"),
+            Some(ChatTemplate::chatml())
+        );
+        assert_eq!(
+            chat_template_from_string("llama"),
+            Some(ChatTemplate::llama())
+        );
+        assert_eq!(
+            chat_template_from_string("[{{role}}] {{content}}\n"),
+            ChatTemplate::new("", "[{{ role }}] {{ content }}\n")
+        );
+        assert_eq!(chat_template_from_string("no placeholder"), None);
+    }
+
+    #[test]
+    fn test_render_conversation_chatml() {
+        let messages = vec![
+            ChatMessage::new("user", "Hi"),
+            ChatMessage::new("assistant", "Hello!"),
+        ];
+        let rendered = render_conversation(&messages, &ChatTemplate::chatml());
+        assert_eq!(
+            rendered.text,
+            "<|im_start|>user\nHi<|im_end|>\n<|im_start|>assistant\nHello!<|im_end|>\n"
+        );
+        assert_eq!(rendered.message_spans, vec![(0, 30), (30, 69)]);
+        let (start, end) = rendered.label_spans[0];
+        assert_eq!(&rendered.text[start..end], "Hello!<|im_end|>");
+    }
+
+    #[test]
+    fn test_render_conversation_llama() {
+        let messages = vec![ChatMessage::new("user", "Hi")];
+        let rendered = render_conversation(&messages, &ChatTemplate::llama());
+        assert_eq!(
+            rendered.text,
+            "<|begin_of_text|><|start_header_id|>user<|end_header_id|>\n\nHi<|eot_id|>"
+        );
+        assert!(rendered.label_spans.is_empty());
+    }
+
+    #[test]
+    fn test_parse_conversation() {
+        let json = r#"{"messages": [{"role": "user", "content": "Hi"}, {"role": "assistant", "content": "Hello"}]}"#;
+        let messages = parse_conversation(json, "messages").unwrap();
+        assert_eq!(messages[1], ChatMessage::new("assistant", "Hello"));
+
+        assert!(parse_conversation(json, "conversation").is_err());
+        assert!(parse_conversation(r#"{"messages": [{"role": "user"}]}"#, "messages").is_err());
+    }
+
+    #[test]
+    fn test_get_sft_sample() {
+        let (input_ids, attention_mask, labels) = get_sft_sample(
+            &get_conversation(),
+            &get_tokenizer_path(),
+            &get_template(),
+            24,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+        )
+        .unwrap();
+
+        let expected = vec![
+            1, 16, 18, 1, 5, 8, 18, 1, 5, 15, 18, 1, 5, 14, 8, 18, 1, 9, 10, 13, 18,
+        ];
+        assert_eq!(input_ids[..21], expected);
+        assert_eq!(input_ids[21..], [PAD_TOKEN_ID; 3]);
+        assert_eq!(attention_mask.iter().sum::<i32>(), 21);
+
+        // only the assistant content and the footer are trained on
+        let m = LABEL_MASK_ID;
+        assert_eq!(
+            labels[..21],
+            [m, m, m, m, m, m, m, m, 5, 15, 18, m, m, m, m, m, m, 9, 10, 13, 18]
+        );
+        assert_eq!(labels[21..], [m; 3]);
+    }
+
+    #[test]
+    fn test_get_sft_sample_truncation() {
+        // the first exchange is dropped, keeping the system message
+        let (input_ids, _, labels) = get_sft_sample(
+            &get_conversation(),
+            &get_tokenizer_path(),
+            &get_template(),
+            14,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+        )
+        .unwrap();
+        assert_eq!(
+            input_ids,
+            vec![1, 16, 18, 1, 5, 14, 8, 18, 1, 9, 10, 13, 18, 0]
+        );
+        assert_eq!(labels[9..13], [9, 10, 13, 18]);
+
+        // the last exchange alone does not fit, so its end is truncated
+        let (input_ids, _, labels) = get_sft_sample(
+            &get_conversation(),
+            &get_tokenizer_path(),
+            &get_template(),
+            10,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+        )
+        .unwrap();
+        assert_eq!(input_ids, vec![1, 16, 18, 1, 5, 14, 8, 18, 1, 9]);
+        assert_eq!(labels[9], 9);
+    }
+
+    #[test]
+    fn test_get_sft_samples_from_file() {
+        let path = std::env::temp_dir().join("alea_preprocess_test_sft.jsonl");
+        std::fs::write(
+            &path,
+            "{\"messages\": [{\"role\": \"user\", \"content\": \"the fox\"}, {\"role\": \"assistant\", \"content\": \"the dog\"}]}\n\n{\"messages\": []}\n",
+        )
+        .unwrap();
+
+        let samples = get_sft_samples_from_file(
+            path.to_str().unwrap(),
+            "messages",
+            &get_tokenizer_path(),
+            &get_template(),
+            8,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+        )
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].0, vec![1, 5, 8, 18, 1, 5, 15, 18]);
+        assert!(samples[1].1.iter().all(|&x| x == 0));
+    }
+}
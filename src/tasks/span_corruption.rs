@@ -0,0 +1,504 @@
+// T5-style span corruption task
+
+// external imports
+use rand::seq::index::sample;
+use rand::Rng;
+
+// internal imports
+use crate::algos::tokenizers::tokenizers::get_tokenizer;
+use crate::errors::{Error, Result};
+use crate::tasks::sequences::{extract_content, split_sequence_max};
+
+/// A span corruption sample: input_ids, attention_mask, and labels
+pub type SpanCorruptionSample = (Vec<i32>, Vec<i32>, Vec<i32>);
+
+/// Span corruption options
+///
+/// About `noise_density` of the tokens are replaced, in spans whose mean length is
+/// `mean_span_length`; T5 uses 0.15 and 3.0. The noise density must be strictly between 0 and 1
+/// and the mean span length positive.
+#[derive(Debug, Clone)]
+pub struct SpanCorruptionConfig {
+    pub noise_density: f64,
+    pub mean_span_length: f64,
+}
+
+impl SpanCorruptionConfig {
+    pub fn new(noise_density: f64, mean_span_length: f64) -> Result<Self> {
+        let config = SpanCorruptionConfig {
+            noise_density,
+            mean_span_length,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Check that the noise density is in (0, 1) and the mean span length is positive
+    pub fn validate(&self) -> Result<()> {
+        if !(self.noise_density > 0.0 && self.noise_density < 1.0) {
+            return Err(Error::Config(format!(
+                "noise_density must be between 0 and 1, got {}",
+                self.noise_density
+            )));
+        }
+        if self.mean_span_length.is_nan() || self.mean_span_length <= 0.0 {
+            return Err(Error::Config(format!(
+                "mean_span_length must be positive, got {}",
+                self.mean_span_length
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for SpanCorruptionConfig {
+    fn default() -> Self {
+        SpanCorruptionConfig {
+            noise_density: 0.15,
+            mean_span_length: 3.0,
+        }
+    }
+}
+
+/// Get the number of noise tokens and noise spans for a sequence length
+/// Sequences of at least two tokens keep at least one token and corrupt at least one span, and
+/// there are never more spans than noise or non-noise tokens.
+pub fn get_noise_counts(length: usize, config: &SpanCorruptionConfig) -> (usize, usize) {
+    if length < 2 {
+        return (0, 0);
+    }
+
+    let num_noise_tokens =
+        ((length as f64 * config.noise_density).round() as usize).clamp(1, length - 1);
+    let num_noise_spans = ((num_noise_tokens as f64 / config.mean_span_length).round() as usize)
+        .clamp(1, num_noise_tokens.min(length - num_noise_tokens));
+
+    (num_noise_tokens, num_noise_spans)
+}
+
+/// Get the number of raw tokens per sample and the target length for an input length
+/// Returns the largest number of tokens whose corrupted input, including the EOS token, fits in
+/// max_seq_length, and the length of the matching targets, also including the EOS token. Fails if
+/// the config is invalid or max_seq_length cannot hold a token and the EOS token.
+pub fn get_span_corruption_lengths(
+    max_seq_length: usize,
+    config: &SpanCorruptionConfig,
+) -> Result<(usize, usize)> {
+    config.validate()?;
+    if max_seq_length < 2 {
+        return Err(Error::Config(format!(
+            "max_seq_length must be at least 2, got {}",
+            max_seq_length
+        )));
+    }
+
+    let get_lengths = |length: usize| {
+        let (num_noise_tokens, num_noise_spans) = get_noise_counts(length, config);
+        (
+            length - num_noise_tokens + num_noise_spans + 1,
+            num_noise_tokens + num_noise_spans + 1,
+        )
+    };
+
+    // both lengths are non-decreasing in the number of tokens, so shorter chunks also fit;
+    // at least (1 - noise_density) of the tokens are kept, which bounds the search
+    let max_tokens_length = (max_seq_length as f64 / (1.0 - config.noise_density)).ceil() as usize;
+    let mut tokens_length = 1;
+    while tokens_length < max_tokens_length && get_lengths(tokens_length + 1).0 <= max_seq_length {
+        tokens_length += 1;
+    }
+
+    Ok((tokens_length, get_lengths(tokens_length).1))
+}
+
+/// Randomly split a number of items into a number of non-empty segments
+fn get_segment_lengths<R: Rng>(num_items: usize, num_segments: usize, rng: &mut R) -> Vec<usize> {
+    // choose distinct cut points between items
+    let mut cuts = sample(rng, num_items - 1, num_segments - 1).into_vec();
+    cuts.sort_unstable();
+
+    let mut lengths = Vec::with_capacity(num_segments);
+    let mut start = 0;
+    for cut in cuts {
+        lengths.push(cut + 1 - start);
+        start = cut + 1;
+    }
+    lengths.push(num_items - start);
+    lengths
+}
+
+/// Select the noise positions of a sequence
+/// Noise and non-noise spans alternate, starting with a non-noise span, as in T5.
+pub fn select_noise_spans<R: Rng>(
+    length: usize,
+    config: &SpanCorruptionConfig,
+    rng: &mut R,
+) -> Vec<bool> {
+    let (num_noise_tokens, num_noise_spans) = get_noise_counts(length, config);
+    if num_noise_spans == 0 {
+        return vec![false; length];
+    }
+
+    let noise_lengths = get_segment_lengths(num_noise_tokens, num_noise_spans, rng);
+    let nonnoise_lengths = get_segment_lengths(length - num_noise_tokens, num_noise_spans, rng);
+
+    let mut noise = Vec::with_capacity(length);
+    for (&nonnoise_length, &noise_length) in nonnoise_lengths.iter().zip(noise_lengths.iter()) {
+        noise.extend(std::iter::repeat_n(false, nonnoise_length));
+        noise.extend(std::iter::repeat_n(true, noise_length));
+    }
+    noise
+}
+
+/// Get a span corruption sample
+/// Returns the input_ids, attention_mask, and labels
+/// Each noise span is replaced by the next sentinel in the inputs, and the targets list each
+/// sentinel followed by the tokens it replaced. Both end with the EOS token; inputs are padded
+/// to max_seq_length and labels to target_length. Fails if there are fewer sentinel tokens than
+/// noise spans, or if the sample does not fit.
+#[allow(clippy::too_many_arguments)]
+pub fn get_span_corruption_sample<R: Rng>(
+    tokens: &[i32],
+    sentinel_token_ids: &[i32],
+    eos_token_id: i32,
+    pad_token_id: i32,
+    label_mask_id: i32,
+    max_seq_length: usize,
+    target_length: usize,
+    config: &SpanCorruptionConfig,
+    rng: &mut R,
+) -> Result<SpanCorruptionSample> {
+    let noise = select_noise_spans(tokens.len(), config, rng);
+
+    let mut inputs = Vec::with_capacity(max_seq_length);
+    let mut targets = Vec::with_capacity(target_length);
+    let mut sentinels = sentinel_token_ids.iter();
+    for (i, &token) in tokens.iter().enumerate() {
+        if !noise[i] {
+            inputs.push(token);
+            continue;
+        }
+
+        // a new span starts at the first token or after a non-noise token
+        if i == 0 || !noise[i - 1] {
+            let sentinel = *sentinels.next().ok_or_else(|| {
+                Error::Config(format!(
+                    "Not enough sentinel tokens for noise spans: got {}",
+                    sentinel_token_ids.len()
+                ))
+            })?;
+            inputs.push(sentinel);
+            targets.push(sentinel);
+        }
+        targets.push(token);
+    }
+    inputs.push(eos_token_id);
+    targets.push(eos_token_id);
+
+    // check that the sample fits
+    if inputs.len() > max_seq_length || targets.len() > target_length {
+        return Err(Error::Config(format!(
+            "Sample exceeds max sequence length: {} inputs for {}, {} targets for {}",
+            inputs.len(),
+            max_seq_length,
+            targets.len(),
+            target_length
+        )));
+    }
+
+    let mut attention_mask = vec![1; inputs.len()];
+    attention_mask.resize(max_seq_length, 0);
+    inputs.resize(max_seq_length, pad_token_id);
+    targets.resize(target_length, label_mask_id);
+
+    Ok((inputs, attention_mask, targets))
+}
+
+/// Get span corruption samples from tokens
+/// Tokens are split with split_sequence_max into chunks that fit in max_seq_length after
+/// corruption; see get_span_corruption_lengths for the chunk and target lengths. Fails if there
+/// are fewer sentinel tokens than the number of noise spans in a full chunk.
+#[allow(clippy::too_many_arguments)]
+pub fn get_span_corruption_samples_from_tokens<R: Rng>(
+    tokens: &[i32],
+    max_seq_length: usize,
+    sentinel_token_ids: &[i32],
+    eos_token_id: i32,
+    pad_token_id: i32,
+    label_mask_id: i32,
+    config: &SpanCorruptionConfig,
+    rng: &mut R,
+) -> Result<Vec<SpanCorruptionSample>> {
+    let (tokens_length, target_length) = get_span_corruption_lengths(max_seq_length, config)?;
+    let (_, max_noise_spans) = get_noise_counts(tokens_length, config);
+    if sentinel_token_ids.len() < max_noise_spans {
+        return Err(Error::Config(format!(
+            "Not enough sentinel tokens for noise spans: need {}, got {}",
+            max_noise_spans,
+            sentinel_token_ids.len()
+        )));
+    }
+
+    split_sequence_max(tokens, tokens_length)
+        .iter()
+        .map(|seq| {
+            get_span_corruption_sample(
+                seq,
+                sentinel_token_ids,
+                eos_token_id,
+                pad_token_id,
+                label_mask_id,
+                max_seq_length,
+                target_length,
+                config,
+                rng,
+            )
+        })
+        .collect()
+}
+
+/// Get span corruption samples from content
+#[allow(clippy::too_many_arguments)]
+pub fn get_span_corruption_samples_from_content<R: Rng>(
+    encoded_content: &str,
+    max_seq_length: usize,
+    tokenizer_name: &str,
+    sentinel_token_ids: &[i32],
+    eos_token_id: i32,
+    pad_token_id: i32,
+    label_mask_id: i32,
+    config: &SpanCorruptionConfig,
+    rng: &mut R,
+) -> Result<Vec<SpanCorruptionSample>> {
+    // extract text
+    let content = String::from_utf8(extract_content(encoded_content)?)?;
+
+    // we need to cast u32 tokens to i32
+    let encoding = get_tokenizer(tokenizer_name)?.encode(content, false)?;
+    let tokens: Vec<i32> = encoding.get_ids().iter().map(|&x| x as i32).collect();
+
+    get_span_corruption_samples_from_tokens(
+        &tokens,
+        max_seq_length,
+        sentinel_token_ids,
+        eos_token_id,
+        pad_token_id,
+        label_mask_id,
+        config,
+        rng,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::sampling::get_rng;
+
+    const PAD_TOKEN_ID: i32 = 0;
+    const EOS_TOKEN_ID: i32 = 1;
+    const LABEL_MASK_ID: i32 = -100;
+
+    fn get_sentinel_token_ids() -> Vec<i32> {
+        // T5 style, counting down from the end of the vocabulary
+        (900..1000).rev().collect()
+    }
+
+    #[test]
+    fn test_get_noise_counts() {
+        let config = SpanCorruptionConfig::default();
+        assert_eq!(get_noise_counts(0, &config), (0, 0));
+        assert_eq!(get_noise_counts(1, &config), (0, 0));
+        assert_eq!(get_noise_counts(2, &config), (1, 1));
+        assert_eq!(get_noise_counts(100, &config), (15, 5));
+        assert_eq!(
+            get_noise_counts(4, &SpanCorruptionConfig::new(0.9, 1.0).unwrap()),
+            (3, 1)
+        );
+    }
+
+    #[test]
+    fn test_get_span_corruption_lengths() {
+        // matches the T5 / HF defaults of 568 raw tokens and 114 targets for 512 inputs
+        let config = SpanCorruptionConfig::default();
+        assert_eq!(
+            get_span_corruption_lengths(512, &config).unwrap(),
+            (568, 114)
+        );
+        assert_eq!(get_span_corruption_lengths(2, &config).unwrap(), (1, 1));
+        assert!(get_span_corruption_lengths(1, &config).is_err());
+    }
+
+    #[test]
+    fn test_span_corruption_config_validation() {
+        assert!(SpanCorruptionConfig::new(1.0, 3.0).is_err());
+        assert!(SpanCorruptionConfig::new(0.0, 3.0).is_err());
+        assert!(SpanCorruptionConfig::new(f64::NAN, 3.0).is_err());
+        assert!(SpanCorruptionConfig::new(0.15, 0.0).is_err());
+
+        // configs built directly are checked before searching for lengths
+        let config = SpanCorruptionConfig {
+            noise_density: 1.0,
+            mean_span_length: 3.0,
+        };
+        assert!(matches!(
+            get_span_corruption_lengths(512, &config),
+            Err(Error::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_select_noise_spans() {
+        let config = SpanCorruptionConfig::default();
+        let mut rng = get_rng(0);
+        for _ in 0..20 {
+            let noise = select_noise_spans(100, &config, &mut rng);
+            assert_eq!(noise.len(), 100);
+            assert_eq!(noise.iter().filter(|&&x| x).count(), 15);
+            assert!(!noise[0]);
+            let num_spans = (0..100)
+                .filter(|&i| noise[i] && (i == 0 || !noise[i - 1]))
+                .count();
+            assert_eq!(num_spans, 5);
+        }
+    }
+
+    #[test]
+    fn test_get_span_corruption_sample() {
+        let tokens: Vec<i32> = (100..120).collect();
+        let config = SpanCorruptionConfig::new(0.25, 2.5).unwrap();
+        let sentinels = get_sentinel_token_ids();
+        let (input_ids, attention_mask, labels) = get_span_corruption_sample(
+            &tokens,
+            &sentinels,
+            EOS_TOKEN_ID,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+            24,
+            16,
+            &config,
+            &mut get_rng(1),
+        )
+        .unwrap();
+
+        assert_eq!(input_ids.len(), 24);
+        assert_eq!(attention_mask.len(), 24);
+        assert_eq!(labels.len(), 16);
+
+        // 5 noise tokens in 2 spans: 15 kept, 2 sentinels, and eos
+        let input_length = attention_mask.iter().sum::<i32>() as usize;
+        assert_eq!(input_length, 18);
+        assert_eq!(input_ids[input_length - 1], EOS_TOKEN_ID);
+        assert!(input_ids[input_length..].iter().all(|&x| x == PAD_TOKEN_ID));
+        assert!(input_ids.contains(&999) && input_ids.contains(&998));
+
+        // 2 sentinels, 5 noise tokens, and eos
+        assert_eq!(labels[0], 999);
+        assert_eq!(labels[7], EOS_TOKEN_ID);
+        assert!(labels[8..].iter().all(|&x| x == LABEL_MASK_ID));
+
+        // filling each sentinel with its span restores the tokens
+        let mut restored = Vec::new();
+        for &token in &input_ids[..input_length - 1] {
+            if sentinels.contains(&token) {
+                let start = labels.iter().position(|&x| x == token).unwrap() + 1;
+                restored.extend(
+                    labels[start..]
+                        .iter()
+                        .take_while(|&&x| !sentinels.contains(&x) && x != EOS_TOKEN_ID),
+                );
+            } else {
+                restored.push(token);
+            }
+        }
+        assert_eq!(restored, tokens);
+    }
+
+    #[test]
+    fn test_get_span_corruption_samples_from_tokens() {
+        let tokens: Vec<i32> = (100..1100).collect();
+        let config = SpanCorruptionConfig::default();
+        let (tokens_length, target_length) = get_span_corruption_lengths(128, &config).unwrap();
+        let samples = get_span_corruption_samples_from_tokens(
+            &tokens,
+            128,
+            &get_sentinel_token_ids(),
+            EOS_TOKEN_ID,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+            &config,
+            &mut get_rng(2),
+        )
+        .unwrap();
+
+        assert_eq!(samples.len(), tokens.len().div_ceil(tokens_length));
+        for (input_ids, attention_mask, labels) in samples.iter() {
+            assert_eq!(input_ids.len(), 128);
+            assert_eq!(attention_mask.len(), 128);
+            assert_eq!(labels.len(), target_length);
+        }
+
+        // full chunks fill the input exactly
+        assert!(samples[0].1.iter().all(|&x| x == 1));
+    }
+
+    #[test]
+    fn test_get_span_corruption_samples_from_tokens_sentinels() {
+        let tokens: Vec<i32> = (100..1100).collect();
+        let result = get_span_corruption_samples_from_tokens(
+            &tokens,
+            128,
+            &[999],
+            EOS_TOKEN_ID,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+            &SpanCorruptionConfig::default(),
+            &mut get_rng(3),
+        );
+        assert!(matches!(result, Err(Error::Config(_))));
+
+        // samples built directly also report missing sentinels and overlong samples
+        let tokens: Vec<i32> = (100..120).collect();
+        let config = SpanCorruptionConfig::new(0.25, 2.5).unwrap();
+        for (sentinels, max_seq_length) in [(vec![999], 24), (get_sentinel_token_ids(), 8)] {
+            let result = get_span_corruption_sample(
+                &tokens,
+                &sentinels,
+                EOS_TOKEN_ID,
+                PAD_TOKEN_ID,
+                LABEL_MASK_ID,
+                max_seq_length,
+                16,
+                &config,
+                &mut get_rng(1),
+            );
+            assert!(matches!(result, Err(Error::Config(_))));
+        }
+    }
+
+    #[test]
+    fn test_get_span_corruption_samples_from_content() {
+        let tokenizer = format!(
+            "{}/resources/tokenizer.wordpiece.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        // "the fox jumps over the lazy dog"
+        let content = "eJwryUhVSMuvUMgqzS0oVsgvSy1SKAEK5SRWVSqk5KcDALfLC3U=";
+        let samples = get_span_corruption_samples_from_content(
+            content,
+            16,
+            &tokenizer,
+            &get_sentinel_token_ids(),
+            EOS_TOKEN_ID,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+            &SpanCorruptionConfig::new(0.3, 2.0).unwrap(),
+            &mut get_rng(4),
+        )
+        .unwrap();
+
+        assert_eq!(samples.len(), 1);
+        let (input_ids, _, labels) = &samples[0];
+        assert_eq!(input_ids[0], 5);
+        assert_eq!(labels[0], 999);
+    }
+}
@@ -0,0 +1,60 @@
+from pathlib import Path
+
+import alea_preprocess
+
+FILE_PATH = Path(__file__)
+PROJECT_PATH = FILE_PATH.parent.parent.parent
+RESOURCE_PATH = PROJECT_PATH / "resources/"
+
+
+def test_detect_encoding_utf8():
+    name, confidence, source = alea_preprocess.io.encoding.detect_encoding(
+        "café".encode("utf-8")
+    )
+    assert name == "UTF-8"
+    assert confidence == 1.0
+    assert source == "utf8"
+
+
+def test_detect_encoding_meta():
+    buffer = '<meta charset="iso-8859-1"><p>café</p>'.encode("latin-1")
+    name, _, source = alea_preprocess.io.encoding.detect_encoding(buffer)
+    assert name == "windows-1252"
+    assert source == "html_meta"
+
+
+def test_decode_bytes_shift_jis():
+    text = "これは日本語のテキストです。文字コードの判定をテストします。"
+    decoded, name, confidence, had_errors = alea_preprocess.io.encoding.decode_bytes(
+        text.encode("shift_jis")
+    )
+    assert decoded == text
+    assert name == "Shift_JIS"
+    assert confidence > 0.5
+    assert not had_errors
+
+
+def test_decode_with_encoding():
+    assert (
+        alea_preprocess.io.encoding.decode_with_encoding(b"caf\xe9", "cp1252") == "café"
+    )
+
+
+def test_decode_file():
+    text, name, _, _ = alea_preprocess.io.encoding.decode_file(
+        str(RESOURCE_PATH / "hello-world.txt")
+    )
+    assert text == "Hello, world!\n"
+    assert name == "UTF-8"
+
+
+def test_file_info_encoding():
+    file_info = alea_preprocess.io.fs.file_info.get_file_info_from_file(
+        str(RESOURCE_PATH / "file1.html")
+    )
+    assert file_info.encoding == "UTF-8"
+
+    file_info = alea_preprocess.io.fs.file_info.get_file_info_from_file(
+        str(RESOURCE_PATH / "test1.pdf")
+    )
+    assert file_info.encoding == ""
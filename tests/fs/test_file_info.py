@@ -1,5 +1,7 @@
 from pathlib import Path
 
+import pytest
+
 import alea_preprocess
 
 FILE_PATH = Path(__file__)
@@ -58,3 +60,10 @@ def test_random_file_info_from_buffer():
     assert file_info.media_type == "application/octet-stream"
     assert file_info.extension == "bin"
     assert file_info.kind == "Other"
+
+
+def test_get_missing_file_info_from_file():
+    with pytest.raises(OSError):
+        alea_preprocess.io.fs.file_info.get_file_info_from_file(
+            str(RESOURCE_PATH / "missing_file")
+        )
@@ -30,3 +30,18 @@ def test_blake3_hash_bytes():
 def test_blake3_hash_str_exception():
     with pytest.raises(TypeError):
         alea_preprocess.algos.hashing.blake3.hash_str(INPUT_STR.encode())
+
+
+def test_blake3_hash_file_batch():
+    paths = ["resources/file1.html", "resources/file2.html"]
+    hashes = alea_preprocess.algos.hashing.blake3.hash_file_batch(paths)
+    assert hashes == [
+        alea_preprocess.algos.hashing.blake3.hash_file(path) for path in paths
+    ]
+
+
+def test_blake3_hash_file_batch_missing():
+    with pytest.raises(OSError):
+        alea_preprocess.algos.hashing.blake3.hash_file_batch(
+            ["resources/file1.html", "resources/missing.html"]
+        )
@@ -24,3 +24,19 @@ def test_compare_html(window_size, precision):
 
     # arbitrary based on testing; some value that is expected to be true based on architecture-independent testing
     assert mean > 0.075
+
+
+def test_hash_file_batch():
+    paths = ["resources/file1.html", "resources/file2.html"]
+    hashes = alea_preprocess.algos.hashing.ctph.hash_file_batch(paths, 32, 16, 32)
+    assert hashes == [
+        alea_preprocess.algos.hashing.ctph.hash_file(path, 32, 16, 32)
+        for path in paths
+    ]
+
+
+def test_hash_file_batch_missing():
+    with pytest.raises(OSError):
+        alea_preprocess.algos.hashing.ctph.hash_file_batch(
+            ["resources/file1.html", "resources/missing.html"], 32, 16, 32
+        )
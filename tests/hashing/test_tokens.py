@@ -6,6 +6,7 @@ Test token rolling hash and token CTPH.
 from pathlib import Path
 
 # packages
+import pytest
 
 # extension module
 import alea_preprocess
@@ -52,3 +53,12 @@ def test_compare_html():
     similarity = alea_preprocess.algos.hashing.token_ctph.compare(hash1, hash2)
 
     assert similarity > 0.1
+
+
+def test_hash_token_windows_numpy():
+    np = pytest.importorskip("numpy")
+    hashes = alea_preprocess.algos.hashing.token_rolling.hash_token_windows_numpy(
+        [1, 2, 3, 4, 5], 3
+    )
+    assert hashes.dtype == np.uint64
+    assert hashes.shape == (5,)
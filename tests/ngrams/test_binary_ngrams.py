@@ -1,4 +1,5 @@
 # imports
+import pytest
 
 # extension module
 import alea_preprocess
@@ -78,3 +79,12 @@ def test_hindi():
     text = "नमस्ते".encode()
     result = alea_preprocess.algos.ngrams.binary.extract(text, 2)
     assert result.get(b"\xa4\xb8") == 1
+
+
+def test_transform_numpy():
+    np = pytest.importorskip("numpy")
+    text = b"Hello, world"
+    result = alea_preprocess.algos.ngrams.binary.transform_numpy(text, 2)
+    assert result.dtype == np.uint8
+    assert result.shape == (11, 2)
+    assert result.tolist() == alea_preprocess.algos.ngrams.binary.transform(text, 2)
@@ -88,3 +88,35 @@ def test_example_dol_text():
         output_file.write(output)
 
     assert "Microenterprise organizations include capital" in output
+
+
+def test_extract_buffer_markdown_batch():
+    buffers = [EXAMPLE_1, open("resources/file1.html").read()]
+    outputs = alea_preprocess.parsers.html.conversion.extract_buffer_markdown_batch(
+        buffers, output_links=True, output_images=True
+    )
+    assert outputs == [
+        alea_preprocess.parsers.html.conversion.extract_buffer_markdown(
+            buffer, output_links=True, output_images=True
+        )
+        for buffer in buffers
+    ]
+
+
+def test_extract_buffer_markdown_threads():
+    from concurrent.futures import ThreadPoolExecutor
+
+    buffer = open("resources/file1.html").read()
+    expected = alea_preprocess.parsers.html.conversion.extract_buffer_markdown(
+        buffer, True, True
+    )
+    with ThreadPoolExecutor(max_workers=4) as executor:
+        outputs = list(
+            executor.map(
+                lambda b: alea_preprocess.parsers.html.conversion.extract_buffer_markdown(
+                    b, True, True
+                ),
+                [buffer] * 8,
+            )
+        )
+    assert outputs == [expected] * 8
@@ -1,11 +1,11 @@
+import pytest
+
 import alea_preprocess
 
 
 def test_pdf_file_bad():
-    result = alea_preprocess.parsers.pdf.detection.detect_file_type(
-        "resources/bad_file.pdf"
-    )
-    assert result == alea_preprocess.parsers.pdf.detection.PyDocumentType.Unknown
+    with pytest.raises(alea_preprocess.errors.ParseError):
+        alea_preprocess.parsers.pdf.detection.detect_file_type("resources/bad_file.pdf")
 
 
 def test_pdf_file_1():
@@ -48,3 +48,21 @@ def test_pdf_extract_markdown():
         "resources/test1.pdf"
     )
     assert "**AGENCY :**  Agricultural Marketing Service, USDA." in text
+
+
+def test_pdf_extract_bad():
+    with pytest.raises(alea_preprocess.errors.ParseError):
+        alea_preprocess.parsers.pdf.conversion.extract_file_text("resources/bad_file.pdf")
+
+    # parse errors are still ValueErrors
+    with pytest.raises(ValueError):
+        alea_preprocess.parsers.pdf.conversion.extract_buffer_markdown(b"not a pdf")
+
+
+def test_pdf_extract_file_text_batch():
+    paths = ["resources/test1.pdf", "resources/bad_file.pdf"]
+    results = alea_preprocess.parsers.pdf.conversion.extract_file_text_batch(paths)
+    assert results == [
+        alea_preprocess.parsers.pdf.conversion.extract_file_text("resources/test1.pdf"),
+        None,
+    ]
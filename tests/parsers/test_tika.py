@@ -1,6 +1,8 @@
 import alea_preprocess
 from pathlib import Path
 
+import pytest
+
 TEST_SERVER_URL = "http://tika-alb-1540561742.us-east-2.elb.amazonaws.com"
 
 FILE_PATH = Path(__file__)
@@ -78,3 +80,10 @@ def test_recursive_content_markdown_file_pdf():
         str(RESOURCE_PATH / "MVDefectsandRecall.pdf"), TEST_SERVER_URL, True, True
     )
     assert "(NHTSA)" in results[0]
+
+
+def test_tika_error():
+    with pytest.raises(alea_preprocess.errors.TikaError):
+        alea_preprocess.parsers.tika.client.get_recursive_metadata_buffer(
+            EXAMPLE_1, "http://127.0.0.1:1"
+        )
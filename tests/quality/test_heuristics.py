@@ -0,0 +1,65 @@
+"""
+Test heuristic quality signals
+"""
+
+# imports
+
+# packages
+import pytest
+
+# extension module
+import alea_preprocess
+
+PROSE = "\n\n".join(
+    [
+        "The court held that the statute applies to all of the parties with standing.",
+        "Each party shall have the right to appeal the decision within thirty days.",
+        "The agreement was executed by the buyer and the seller on the closing date.",
+        "Notice must be given to the other party in writing before any termination.",
+        "Nothing in this section limits the remedies available to the injured party.",
+    ]
+)
+
+
+def test_get_quality_signals():
+    signals = alea_preprocess.algos.quality.heuristics.get_quality_signals(PROSE)
+    assert signals.word_count == 66
+    assert 3.0 < signals.mean_word_length < 10.0
+    assert signals.duplicate_paragraph_fraction == 0.0
+    assert signals.alphabetic_ratio == pytest.approx(66 / 71)
+    assert len(signals.top_ngram_char_fractions) == 3
+    assert len(signals.duplicate_ngram_char_fractions) == 6
+
+
+def test_get_quality_signals_lines():
+    signals = alea_preprocess.algos.quality.heuristics.get_quality_signals(
+        "• one\n• two\nthree...\n\n• one\n"
+    )
+    assert signals.bullet_line_fraction == 0.75
+    assert signals.ellipsis_line_fraction == 0.25
+    assert signals.duplicate_line_fraction == 0.25
+
+
+def test_get_quality_signals_batch():
+    results = alea_preprocess.algos.quality.heuristics.get_quality_signals_batch([PROSE, ""])
+    assert [signals.word_count for signals in results] == [66, 0]
+
+
+def test_check_quality():
+    signals, failures = alea_preprocess.algos.quality.heuristics.check_quality(PROSE)
+    assert signals.word_count == 66
+    assert failures == []
+
+    _, failures = alea_preprocess.algos.quality.heuristics.check_quality(PROSE + "\n\n" + PROSE)
+    assert "duplicate_paragraph_fraction" in failures
+    assert "duplicate_5gram_char_fraction" in failures
+
+    _, failures = alea_preprocess.algos.quality.heuristics.check_quality(PROSE, min_words=100)
+    assert failures == ["min_words"]
+
+
+def test_check_quality_bad_thresholds():
+    with pytest.raises(ValueError):
+        alea_preprocess.algos.quality.heuristics.check_quality(
+            PROSE, max_top_ngram_char_fractions=[0.2]
+        )
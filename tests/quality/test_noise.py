@@ -0,0 +1,51 @@
+"""
+Test unicode category noise signals
+"""
+
+# imports
+
+# packages
+
+# extension module
+import alea_preprocess
+
+CLEAN = "The quick brown fox jumps over the lazy dog.\nIt was the best of times, it was the worst of times.\n"
+GARBAGE = "¤¤ tHe ^q~u^ick ¦¦ ■ fo¬x \ue000\ue001 j$u%m^p¨s\n"
+
+
+def test_get_noise_signals():
+    signals = alea_preprocess.algos.quality.noise.get_noise_signals(CLEAN)
+    assert signals.unusual_transition_ratio == 0.0
+    assert signals.other_symbol_ratio == 0.0
+    assert signals.letter_ratio > 0.9
+
+    signals = alea_preprocess.algos.quality.noise.get_noise_signals(GARBAGE)
+    assert signals.unusual_transition_ratio > 0.1
+    assert signals.private_use_ratio > 0.01
+
+
+def test_get_line_noise_signals():
+    lines = alea_preprocess.algos.quality.noise.get_line_noise_signals(CLEAN + GARBAGE)
+    assert len(lines) == 3
+    assert lines[0].unusual_transition_ratio == 0.0
+    assert lines[2].unusual_transition_ratio > 0.1
+
+
+def test_get_noise_report():
+    report = alea_preprocess.algos.quality.noise.get_noise_report(CLEAN)
+    assert report.line_count == 2
+    assert report.noisy_line_count == 0
+    assert not report.is_unusable
+
+    report = alea_preprocess.algos.quality.noise.get_noise_report(GARBAGE)
+    assert report.noisy_line_count == 1
+    assert report.is_unusable
+
+    report = alea_preprocess.algos.quality.noise.get_noise_report(
+        GARBAGE,
+        max_unusual_transition_ratio=1.0,
+        max_other_symbol_ratio=1.0,
+        max_private_use_ratio=1.0,
+        min_letter_ratio=0.0,
+    )
+    assert not report.is_unusable
@@ -0,0 +1,75 @@
+"""
+Test token shard writing and reading
+"""
+
+# imports
+import pytest
+
+# extension module
+import alea_preprocess
+
+TOKENIZER_ID = "alea-institute/kl3m-003-64k"
+
+
+def test_write_read_shard(tmp_path):
+    prefix = str(tmp_path / "shard")
+    num_sequences, num_tokens = alea_preprocess.io.shards.write_shard(
+        [[5, 6, 7], [65535], [8, 9]], prefix
+    )
+    assert (num_sequences, num_tokens) == (3, 6)
+    assert (tmp_path / "shard.bin").stat().st_size == 12
+
+    reader = alea_preprocess.io.shards.ShardReader(prefix)
+    assert len(reader) == 3
+    assert reader.dtype == "uint16"
+    assert reader.sizes == [3, 1, 2]
+    assert reader.document_indices == [0, 1, 2, 3]
+    assert reader[0] == [5, 6, 7]
+    assert reader[-1] == [8, 9]
+    with pytest.raises(IndexError):
+        reader[3]
+
+
+def test_write_shard_npy(tmp_path):
+    np = pytest.importorskip("numpy")
+    prefix = str(tmp_path / "shard")
+    alea_preprocess.io.shards.write_shard(
+        [[1, 2], [70000]], prefix, dtype="uint32", format="npy"
+    )
+    array = np.load(prefix + ".npy", mmap_mode="r")
+    assert array.dtype == np.uint32
+    assert array.tolist() == [1, 2, 70000]
+    assert alea_preprocess.io.shards.ShardReader(prefix)[1] == [70000]
+
+
+def test_write_shard_invalid(tmp_path):
+    prefix = str(tmp_path / "shard")
+    with pytest.raises(ValueError):
+        alea_preprocess.io.shards.write_shard([[70000]], prefix)
+    with pytest.raises(ValueError):
+        alea_preprocess.io.shards.write_shard([[1]], prefix, dtype="float32")
+
+
+def test_write_masked_samples_from_content(tmp_path):
+    prefix = str(tmp_path / "mlm")
+    num_samples = alea_preprocess.tasks.mlm.write_masked_samples_from_content(
+        ["eJwLycgsVgCiRIWS1OISACRzBPY="] * 2,
+        prefix,
+        max_seq_length=8,
+        tokenizer_name=TOKENIZER_ID,
+        cls_token_id=5,
+        mask_token_id=6,
+        sep_token_id=4,
+        pad_token_id=2,
+        label_mask_id=-100,
+        prob_mask=0.0,
+        min_masked_tokens=0,
+    )
+    assert num_samples == 2
+
+    input_ids = alea_preprocess.io.shards.ShardReader(prefix + ".input_ids")
+    labels = alea_preprocess.io.shards.ShardReader(prefix + ".labels")
+    assert input_ids[0] == [5, 2556, 400, 270, 2329, 4, 2, 2]
+    assert input_ids.document_indices == [0, 1, 2]
+    assert labels.dtype == "int32"
+    assert labels[0] == [-100] * 8
@@ -0,0 +1,46 @@
+"""
+Test DPO preference pairs
+"""
+
+# imports
+import json
+from pathlib import Path
+
+# extension module
+import alea_preprocess
+
+EOS_TOKEN_ID = 3
+LABEL_MASK_ID = -100
+
+
+def get_tokenizer_path() -> str:
+    return str(Path(__file__).parent.parent.parent / "resources" / "tokenizer.wordpiece.json")
+
+
+def test_get_dpo_sample():
+    sample = alea_preprocess.tasks.dpo.get_dpo_sample(
+        "the quick fox",
+        "jumps over",
+        "the lazy dog",
+        get_tokenizer_path(),
+        10,
+        eos_token_id=EOS_TOKEN_ID,
+    )
+    assert sample.prompt_length == 3
+    assert sample.chosen_input_ids == [5, 6, 8, 9, 10, 13, 3, 0, 0, 0]
+    assert sample.rejected_labels[:3] == [LABEL_MASK_ID] * 3
+    assert sample.rejected_labels[3:7] == [5, 14, 15, 3]
+    assert not sample.is_degenerate
+
+
+def test_get_dpo_samples_from_file(tmp_path):
+    path = tmp_path / "pairs.jsonl"
+    path.write_text(
+        json.dumps({"prompt": "the fox", "chosen": "jumps", "rejected": "jumps"}) + "\n"
+    )
+    samples = alea_preprocess.tasks.dpo.get_dpo_samples_from_file(
+        str(path), get_tokenizer_path(), 8
+    )
+    assert len(samples) == 1
+    assert samples[0].is_degenerate
+    assert samples[0].similarity == 1.0
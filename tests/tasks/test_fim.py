@@ -0,0 +1,95 @@
+"""
+Test fill-in-the-middle samples
+"""
+
+# imports
+from pathlib import Path
+
+import pytest
+
+# extension module
+import alea_preprocess
+
+PAD_TOKEN_ID = 0
+EOS_TOKEN_ID = 1
+PREFIX_TOKEN_ID = 90
+MIDDLE_TOKEN_ID = 91
+SUFFIX_TOKEN_ID = 92
+LABEL_MASK_ID = -100
+
+
+def get_tokenizer_path() -> str:
+    return str(Path(__file__).parent.parent.parent / "resources" / "tokenizer.wordpiece.json")
+
+
+def test_fim_tokens():
+    tokens = list(range(100, 130))
+    samples = alea_preprocess.tasks.fim.get_fim_samples_from_tokens(
+        tokens,
+        16,
+        PREFIX_TOKEN_ID,
+        MIDDLE_TOKEN_ID,
+        SUFFIX_TOKEN_ID,
+        EOS_TOKEN_ID,
+        PAD_TOKEN_ID,
+        LABEL_MASK_ID,
+        fim_rate=1.0,
+        seed=42,
+    )
+    assert len(samples) == 3
+    for input_ids, attention_mask, labels in samples:
+        assert len(input_ids) == len(attention_mask) == len(labels) == 16
+        assert input_ids[0] == PREFIX_TOKEN_ID
+        assert MIDDLE_TOKEN_ID in input_ids and SUFFIX_TOKEN_ID in input_ids
+
+
+def test_fim_text_line():
+    text = "the quick fox\nthe lazy dog\nhello world"
+    samples = alea_preprocess.tasks.fim.get_fim_samples_from_text(
+        text,
+        32,
+        get_tokenizer_path(),
+        PREFIX_TOKEN_ID,
+        MIDDLE_TOKEN_ID,
+        SUFFIX_TOKEN_ID,
+        EOS_TOKEN_ID,
+        PAD_TOKEN_ID,
+        LABEL_MASK_ID,
+        fim_rate=1.0,
+        boundary="line",
+        seed=0,
+    )
+    assert len(samples) == 1
+    input_ids, attention_mask, labels = samples[0]
+    assert sum(attention_mask) == 8 + 3 + 1
+    assert labels[sum(attention_mask)] == LABEL_MASK_ID
+
+
+def test_fim_invalid_boundary():
+    with pytest.raises(ValueError):
+        alea_preprocess.tasks.fim.get_fim_samples_from_text(
+            "text",
+            32,
+            get_tokenizer_path(),
+            PREFIX_TOKEN_ID,
+            MIDDLE_TOKEN_ID,
+            SUFFIX_TOKEN_ID,
+            EOS_TOKEN_ID,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+            boundary="word",
+        )
+
+
+def test_fim_max_seq_length_too_short():
+    with pytest.raises(ValueError):
+        alea_preprocess.tasks.fim.get_fim_samples_from_tokens(
+            list(range(100, 130)),
+            4,
+            PREFIX_TOKEN_ID,
+            MIDDLE_TOKEN_ID,
+            SUFFIX_TOKEN_ID,
+            EOS_TOKEN_ID,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+        )
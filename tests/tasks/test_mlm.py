@@ -0,0 +1,140 @@
+"""
+Test MLM masking options
+"""
+
+# imports
+from pathlib import Path
+
+import pytest
+
+# extension module
+import alea_preprocess
+
+PAD_TOKEN_ID = 0
+CLS_TOKEN_ID = 2
+SEP_TOKEN_ID = 3
+MASK_TOKEN_ID = 4
+LABEL_MASK_ID = -100
+
+
+def get_tokenizer_path() -> str:
+    return str(Path(__file__).parent.parent.parent / "resources" / "tokenizer.wordpiece.json")
+
+
+def test_mlm_whole_word_masking():
+    tokens = [5, 8, 9, 10, 13]
+    word_ids = [0, 1, 2, 2, 3]
+    samples = alea_preprocess.tasks.mlm.get_masked_samples_from_tokens(
+        tokens,
+        16,
+        CLS_TOKEN_ID,
+        MASK_TOKEN_ID,
+        SEP_TOKEN_ID,
+        PAD_TOKEN_ID,
+        LABEL_MASK_ID,
+        0.5,
+        whole_word_masking=True,
+        word_ids=word_ids,
+        min_masked_tokens=1,
+    )
+    assert len(samples) == 1
+    input_ids, _, _, labels = samples[0]
+    masked = [i for i, label in enumerate(labels) if label != LABEL_MASK_ID]
+    assert len(masked) >= 1
+    # "jump" and "##s" are at positions 3 and 4 and are masked together
+    assert (3 in masked) == (4 in masked)
+    for i in masked:
+        assert input_ids[i] == MASK_TOKEN_ID
+
+
+def test_mlm_excluded_tokens():
+    tokens = [5, 8, 5, 8, 5, 8]
+    input_ids, _, _, labels = alea_preprocess.tasks.mlm.get_masked_sample(
+        tokens,
+        CLS_TOKEN_ID,
+        MASK_TOKEN_ID,
+        SEP_TOKEN_ID,
+        PAD_TOKEN_ID,
+        LABEL_MASK_ID,
+        16,
+        1.0,
+        excluded_token_ids=[5],
+    )
+    for i, token in enumerate(tokens, start=1):
+        if token == 5:
+            assert labels[i] == LABEL_MASK_ID
+            assert input_ids[i] == 5
+        else:
+            assert labels[i] == 8
+
+
+def test_mlm_content_random_tokens():
+    # "the fox jumps over the lazy dog"
+    content = "eJwryUhVSMuvUMgqzS0oVsgvSy1SKAEK5SRWVSqk5KcDALfLC3U="
+    samples = alea_preprocess.tasks.mlm.get_masked_samples_from_content(
+        content,
+        12,
+        get_tokenizer_path(),
+        CLS_TOKEN_ID,
+        MASK_TOKEN_ID,
+        SEP_TOKEN_ID,
+        PAD_TOKEN_ID,
+        LABEL_MASK_ID,
+        1.0,
+        prob_mask_token=0.0,
+        prob_random_token=1.0,
+        vocab_size=20,
+    )
+    assert len(samples) == 1
+    input_ids, _, _, labels = samples[0]
+    for input_id, label in zip(input_ids, labels):
+        if label != LABEL_MASK_ID:
+            assert 0 <= input_id < 20
+
+
+def test_mlm_seed():
+    tokens = list(range(100, 200))
+
+    def get_sample(seed):
+        return alea_preprocess.tasks.mlm.get_masked_sample(
+            tokens,
+            CLS_TOKEN_ID,
+            MASK_TOKEN_ID,
+            SEP_TOKEN_ID,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+            128,
+            0.15,
+            prob_mask_token=0.8,
+            prob_random_token=0.1,
+            vocab_size=20,
+            seed=seed,
+        )
+
+    assert get_sample(42) == get_sample(42)
+    assert [get_sample(seed) for seed in range(4)] != [
+        get_sample(seed) for seed in range(4, 8)
+    ]
+
+
+def test_get_masked_samples_from_tokens_numpy():
+    np = pytest.importorskip("numpy")
+    tokens = list(range(10, 40))
+    kwargs = dict(
+        max_seq_length=8,
+        cls_token_id=CLS_TOKEN_ID,
+        mask_token_id=MASK_TOKEN_ID,
+        sep_token_id=SEP_TOKEN_ID,
+        pad_token_id=PAD_TOKEN_ID,
+        label_mask_id=LABEL_MASK_ID,
+        prob_mask=0.15,
+        seed=42,
+    )
+    samples = alea_preprocess.tasks.mlm.get_masked_samples_from_tokens(tokens, **kwargs)
+    arrays = alea_preprocess.tasks.mlm.get_masked_samples_from_tokens_numpy(
+        tokens, **kwargs
+    )
+    for field, array in enumerate(arrays):
+        assert array.dtype == np.int32
+        assert array.shape == (len(samples), 8)
+        assert array.tolist() == [list(sample[field]) for sample in samples]
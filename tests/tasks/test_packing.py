@@ -0,0 +1,47 @@
+"""
+Test causal LM sequence packing
+"""
+
+# imports
+import pytest
+
+# extension module
+import alea_preprocess
+
+BOS_TOKEN_ID = 1
+EOS_TOKEN_ID = 2
+PAD_TOKEN_ID = 0
+
+
+def test_pack_sequences_concatenate():
+    blocks = alea_preprocess.tasks.packing.pack_sequences(
+        [[10, 11, 12], [20, 21], [30]],
+        4,
+        bos_token_id=BOS_TOKEN_ID,
+        eos_token_id=EOS_TOKEN_ID,
+    )
+    assert len(blocks) == 3
+    assert blocks[1].input_ids == [2, 1, 20, 21]
+    assert blocks[1].segment_ids == [1, 2, 2, 2]
+    assert blocks[1].position_ids == [0, 0, 1, 2]
+    assert blocks[1].documents == [(0, 0, 1), (1, 1, 4)]
+
+
+def test_pack_sequences_best_fit_decreasing():
+    blocks = alea_preprocess.tasks.packing.pack_sequences(
+        [[10, 11, 12, 13], [20], [30, 31, 32], [40, 41], []],
+        8,
+        bos_token_id=BOS_TOKEN_ID,
+        eos_token_id=EOS_TOKEN_ID,
+        pad_token_id=PAD_TOKEN_ID,
+        strategy="best_fit_decreasing",
+    )
+    assert len(blocks) == 3
+    assert all(len(block) == 8 for block in blocks)
+    assert blocks[2].attention_mask == [1, 1, 1, 1, 0, 0, 0, 0]
+    assert blocks[2].segment_ids == [1, 1, 1, 1, 0, 0, 0, 0]
+
+
+def test_pack_sequences_invalid_strategy():
+    with pytest.raises(ValueError):
+        alea_preprocess.tasks.packing.pack_sequences([[1, 2]], 4, strategy="first_fit")
@@ -3,8 +3,11 @@ Test sequenec methods
 """
 
 # imports
+import base64
 from pathlib import Path
 
+import pytest
+
 # extension module
 import alea_preprocess
 
@@ -76,3 +79,36 @@ def test_mlm_encoded_content():
         [0, 0, 0, 0, 0, 0, 0, 0],
         [-100, -100, -100, -100, -100, -100, -100, -100],
     )
+
+
+def test_extract_content_zlib():
+    content = alea_preprocess.tasks.sequences.extract_content(
+        "eJwLycgsVgCiRIWS1OISACRzBPY="
+    )
+    assert content == b"This is a test"
+
+
+@pytest.mark.parametrize("codec", ["raw", "zlib", "gzip", "zstd"])
+def test_encode_content_round_trip(codec):
+    content = Path("resources/10usc101.txt").read_bytes()
+    encoded_content = alea_preprocess.tasks.sequences.encode_content(content, codec)
+    assert alea_preprocess.tasks.sequences.detect_codec(encoded_content) == codec
+    assert alea_preprocess.tasks.sequences.extract_content(encoded_content) == content
+
+
+def test_extract_content_raw_zlib_header():
+    encoded_content = base64.b64encode(b"HK Holdings Ltd.").decode()
+    assert alea_preprocess.tasks.sequences.extract_content(encoded_content) == b"HK Holdings Ltd."
+
+
+def test_extract_content_invalid():
+    with pytest.raises(ValueError):
+        alea_preprocess.tasks.sequences.extract_content("not base64!")
+
+    with pytest.raises(ValueError):
+        alea_preprocess.tasks.sequences.encode_content(b"test", "lz4")
+
+
+def test_extract_content_decode_error():
+    with pytest.raises(alea_preprocess.errors.DecodeError):
+        alea_preprocess.tasks.sequences.extract_content("not base64!")
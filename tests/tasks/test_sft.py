@@ -0,0 +1,59 @@
+"""
+Test SFT chat templates and labels
+"""
+
+# imports
+import json
+from pathlib import Path
+
+import pytest
+
+# extension module
+import alea_preprocess
+
+LABEL_MASK_ID = -100
+
+# roles are unknown words, so each turn is [UNK] content "."
+TEMPLATE = "{{ role }} {{ content }} ."
+
+MESSAGES = [
+    {"role": "system", "content": "hello"},
+    {"role": "user", "content": "the fox"},
+    {"role": "assistant", "content": "the dog"},
+]
+
+
+def get_tokenizer_path() -> str:
+    return str(Path(__file__).parent.parent.parent / "resources" / "tokenizer.wordpiece.json")
+
+
+def test_render_conversation():
+    text = alea_preprocess.tasks.sft.render_conversation(MESSAGES[1:])
+    assert (
+        text
+        == "<|im_start|>user\nthe fox<|im_end|>\n<|im_start|>assistant\nthe dog<|im_end|>\n"
+    )
+
+
+def test_get_sft_sample():
+    input_ids, attention_mask, labels = alea_preprocess.tasks.sft.get_sft_sample(
+        MESSAGES, get_tokenizer_path(), 16, template=TEMPLATE
+    )
+    assert input_ids[:11] == [1, 16, 18, 1, 5, 8, 18, 1, 5, 15, 18]
+    assert sum(attention_mask) == 11
+    assert labels[8:11] == [5, 15, 18]
+    assert all(label == LABEL_MASK_ID for label in labels[:8] + labels[11:])
+
+
+def test_get_sft_samples_from_file(tmp_path):
+    path = tmp_path / "conversations.jsonl"
+    path.write_text(json.dumps({"messages": MESSAGES}) + "\n")
+    samples = alea_preprocess.tasks.sft.get_sft_samples_from_file(
+        str(path), get_tokenizer_path(), 16, template=TEMPLATE
+    )
+    assert len(samples) == 1
+
+
+def test_invalid_template():
+    with pytest.raises(ValueError):
+        alea_preprocess.tasks.sft.render_conversation(MESSAGES, template="no placeholder")
@@ -0,0 +1,105 @@
+"""
+Test T5-style span corruption
+"""
+
+# imports
+from pathlib import Path
+
+import pytest
+
+# extension module
+import alea_preprocess
+
+PAD_TOKEN_ID = 0
+EOS_TOKEN_ID = 1
+LABEL_MASK_ID = -100
+SENTINEL_TOKEN_IDS = list(range(999, 899, -1))
+
+
+def get_tokenizer_path() -> str:
+    return str(Path(__file__).parent.parent.parent / "resources" / "tokenizer.wordpiece.json")
+
+
+def test_get_span_corruption_lengths():
+    assert alea_preprocess.tasks.span_corruption.get_span_corruption_lengths(512) == (
+        568,
+        114,
+    )
+
+
+def test_get_span_corruption_lengths_invalid():
+    with pytest.raises(alea_preprocess.errors.AleaPreprocessError):
+        alea_preprocess.tasks.span_corruption.get_span_corruption_lengths(512, noise_density=1.0)
+    with pytest.raises(alea_preprocess.errors.AleaPreprocessError):
+        alea_preprocess.tasks.span_corruption.get_span_corruption_lengths(512, mean_span_length=0.0)
+    with pytest.raises(alea_preprocess.errors.AleaPreprocessError):
+        alea_preprocess.tasks.span_corruption.get_span_corruption_lengths(1)
+
+
+def test_span_corruption_tokens():
+    tokens = list(range(100, 1100))
+    tokens_length, target_length = (
+        alea_preprocess.tasks.span_corruption.get_span_corruption_lengths(128)
+    )
+    samples = alea_preprocess.tasks.span_corruption.get_span_corruption_samples_from_tokens(
+        tokens,
+        128,
+        SENTINEL_TOKEN_IDS,
+        EOS_TOKEN_ID,
+        PAD_TOKEN_ID,
+        LABEL_MASK_ID,
+        seed=42,
+    )
+    assert len(samples) == -(-len(tokens) // tokens_length)
+    for input_ids, attention_mask, labels in samples:
+        assert len(input_ids) == 128
+        assert len(attention_mask) == 128
+        assert len(labels) == target_length
+        assert labels[0] == SENTINEL_TOKEN_IDS[0]
+        assert EOS_TOKEN_ID in input_ids
+
+    # identical inputs and seeds give identical samples
+    assert samples == alea_preprocess.tasks.span_corruption.get_span_corruption_samples_from_tokens(
+        tokens,
+        128,
+        SENTINEL_TOKEN_IDS,
+        EOS_TOKEN_ID,
+        PAD_TOKEN_ID,
+        LABEL_MASK_ID,
+        seed=42,
+    )
+
+
+def test_span_corruption_content():
+    # "the fox jumps over the lazy dog"
+    content = "eJwryUhVSMuvUMgqzS0oVsgvSy1SKAEK5SRWVSqk5KcDALfLC3U="
+    samples = alea_preprocess.tasks.span_corruption.get_span_corruption_samples_from_content(
+        content,
+        16,
+        get_tokenizer_path(),
+        SENTINEL_TOKEN_IDS,
+        EOS_TOKEN_ID,
+        PAD_TOKEN_ID,
+        LABEL_MASK_ID,
+        noise_density=0.3,
+        mean_span_length=2.0,
+        seed=0,
+    )
+    assert len(samples) == 1
+    input_ids, attention_mask, labels = samples[0]
+    assert input_ids[0] == 5
+    assert labels[0] == SENTINEL_TOKEN_IDS[0]
+
+
+def test_span_corruption_not_enough_sentinels():
+    tokens = list(range(100, 1100))
+    with pytest.raises(alea_preprocess.errors.AleaPreprocessError):
+        alea_preprocess.tasks.span_corruption.get_span_corruption_samples_from_tokens(
+            tokens,
+            128,
+            SENTINEL_TOKEN_IDS[:1],
+            EOS_TOKEN_ID,
+            PAD_TOKEN_ID,
+            LABEL_MASK_ID,
+            seed=0,
+        )
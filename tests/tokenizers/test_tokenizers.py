@@ -3,6 +3,7 @@ import json
 from pathlib import Path
 
 # packages
+import pytest
 
 # extension module
 import alea_preprocess
@@ -42,3 +43,152 @@ def test_decode_str_list():
     encoded = alea_preprocess.algos.tokenizers.encode_str_list("gpt2", rows)
     result = alea_preprocess.algos.tokenizers.decode_str_list("gpt2", encoded)
     assert all([rows[i] == result[i] for i in range(len(encoded))])
+
+
+def get_tokenizer_path() -> Path:
+    return Path("resources/tokenizer.wordpiece.json")
+
+
+def test_load_tokenizer_from_file():
+    alea_preprocess.algos.tokenizers.load_tokenizer_from_file(
+        "test-wordpiece", str(get_tokenizer_path())
+    )
+    assert alea_preprocess.algos.tokenizers.is_tokenizer_loaded("test-wordpiece")
+    assert alea_preprocess.algos.tokenizers.encode_str(
+        "test-wordpiece", "The quick brown fox jumps"
+    ) == [5, 6, 7, 8, 9, 10]
+
+
+def test_load_tokenizer_from_str():
+    alea_preprocess.algos.tokenizers.load_tokenizer_from_str(
+        "test-wordpiece-json", get_tokenizer_path().read_text()
+    )
+    encoded = alea_preprocess.algos.tokenizers.encode_str("test-wordpiece-json", "hello world")
+    assert alea_preprocess.algos.tokenizers.decode_str("test-wordpiece-json", encoded) == "hello world"
+
+
+def test_encode_str_by_path():
+    result = alea_preprocess.algos.tokenizers.encode_str(str(get_tokenizer_path()), "lazy dog")
+    assert result == [14, 15]
+
+
+def test_load_tokenizer_errors():
+    with pytest.raises(ValueError):
+        alea_preprocess.algos.tokenizers.load_tokenizer_from_file("missing", "/does/not/exist.json")
+
+    with pytest.raises(ValueError):
+        alea_preprocess.algos.tokenizers.load_tokenizer_from_str("invalid", "{not json")
+
+
+def test_tokenizer_error_type():
+    with pytest.raises(alea_preprocess.errors.TokenizerError):
+        alea_preprocess.algos.tokenizers.load_tokenizer_from_str("invalid", "{not json")
+
+
+def test_encode_with_options():
+    encoding = alea_preprocess.algos.tokenizers.encode(
+        str(get_tokenizer_path()), "Héllo, xyzzy", add_special_tokens=True, pad_to_length=8
+    )
+    assert encoding.ids == [2, 16, 19, 1, 3, 0, 0, 0]
+    assert encoding.attention_mask == [1, 1, 1, 1, 1, 0, 0, 0]
+    assert encoding.tokens[0] == "[CLS]"
+    assert encoding.offsets[3] == (7, 12)
+    assert len(encoding) == 8
+
+
+def test_encode_truncation():
+    text = "the quick brown fox jumps over the lazy dog"
+    encoding = alea_preprocess.algos.tokenizers.encode(
+        str(get_tokenizer_path()), text, max_length=4, stride=1
+    )
+    assert encoding.ids == [5, 6, 7, 8]
+    assert len(encoding.overflowing) == 2
+    assert encoding.overflowing[0].ids[0] == 8
+
+
+def test_encode_list_padding():
+    encodings = alea_preprocess.algos.tokenizers.encode_list(
+        str(get_tokenizer_path()), ["hello", "the quick brown fox jumps"], pad_to_multiple_of=4
+    )
+    assert [len(encoding) for encoding in encodings] == [8, 8]
+    assert sum(encodings[0].attention_mask) == 1
+
+
+def test_count_tokens():
+    assert alea_preprocess.algos.tokenizers.count_tokens(str(get_tokenizer_path()), "hello world") == 2
+    assert alea_preprocess.algos.tokenizers.count_tokens_list(
+        str(get_tokenizer_path()), ["hello world", ""]
+    ) == [2, 0]
+
+
+def test_get_token_stats():
+    stats = alea_preprocess.algos.tokenizers.get_token_stats(
+        str(get_tokenizer_path()), ["the quick brown fox", "hello xyzzy world", ""]
+    )
+    assert stats.num_documents == 3
+    assert stats.total_tokens == 7
+    assert stats.unk_tokens == 1
+    assert stats.length_histogram == {0: 1, 2: 1, 4: 1}
+
+
+def test_get_token_stats_from_file():
+    stats = alea_preprocess.algos.tokenizers.get_token_stats_from_file(
+        str(get_tokenizer_path()), "resources/usc.100.jsonl.gz", field="text"
+    )
+    assert stats.num_documents == 100
+    assert stats.bytes_per_token > 0
+
+
+def test_train_tokenizer(tmp_path):
+    output_path = str(tmp_path / "tokenizer.json")
+    alea_preprocess.algos.tokenizers.train_tokenizer(
+        ["resources/usc.100.jsonl.gz"],
+        output_path,
+        vocab_size=1000,
+        special_tokens=["<|endoftext|>"],
+        field="text",
+    )
+
+    text = "Definitions of the United States Code"
+    encoded = alea_preprocess.algos.tokenizers.encode_str(output_path, text)
+    assert alea_preprocess.algos.tokenizers.decode_str(output_path, encoded) == text
+
+
+def test_train_tokenizer_invalid_options(tmp_path):
+    with pytest.raises(ValueError):
+        alea_preprocess.algos.tokenizers.train_tokenizer(
+            ["resources/usc.100.jsonl"], str(tmp_path / "tokenizer.json"), model_type="wordpiece"
+        )
+
+
+def test_compare_tokenizers():
+    tokenizer = str(get_tokenizer_path())
+    comparisons = alea_preprocess.algos.tokenizers.compare_tokenizers(
+        [tokenizer], ["The fox jumps", ""], domain_terms=["plaintiff", "jumps"]
+    )
+    comparison = comparisons[tokenizer]
+    assert comparison.total_words == 3
+    assert comparison.document_tokens == [4, 0]
+    assert comparison.split_domain_terms == ["jumps"]
+
+
+def test_get_vocab_overlap():
+    tokenizer = str(get_tokenizer_path())
+    shared, jaccard, _, _ = alea_preprocess.algos.tokenizers.get_vocab_overlap(tokenizer, tokenizer)
+    assert shared > 0
+    assert jaccard == 1.0
+
+
+def test_encode_str_list_numpy():
+    np = pytest.importorskip("numpy")
+    rows = list(get_jsonl_text())[:10]
+    expected = alea_preprocess.algos.tokenizers.encode_str_list("gpt2", rows)
+    ids, lengths = alea_preprocess.algos.tokenizers.encode_str_list_numpy(
+        "gpt2", rows, pad_id=50256
+    )
+    assert ids.dtype == np.uint32
+    assert ids.shape == (len(rows), max(len(x) for x in expected))
+    assert lengths.tolist() == [len(x) for x in expected]
+    for row, length, tokens in zip(ids, lengths, expected):
+        assert row[:length].tolist() == tokens
+        assert (row[length:] == 50256).all()
@@ -0,0 +1,35 @@
+# imports
+
+# packages
+
+# extension module
+import alea_preprocess
+
+
+def test_fix_mojibake():
+    assert alea_preprocess.algos.unicode.fixes.fix_mojibake("cafÃ©") == "café"
+    assert alea_preprocess.algos.unicode.fixes.fix_mojibake("donâ€™t") == "don’t"
+    assert alea_preprocess.algos.unicode.fixes.fix_mojibake("café") == "café"
+
+
+def test_has_mojibake():
+    assert alea_preprocess.algos.unicode.fixes.has_mojibake("cafÃ©")
+    assert not alea_preprocess.algos.unicode.fixes.has_mojibake("café")
+
+
+def test_fix_html_entities():
+    assert alea_preprocess.algos.unicode.fixes.fix_html_entities("AT&amp;amp;T") == "AT&T"
+
+
+def test_fix_text_default():
+    text, fixes = alea_preprocess.algos.unicode.fixes.fix_text("donâ€™t &amp;amp; ﬁx")
+    assert text == "don’t & ﬁx"
+    assert fixes == ["mojibake", "html_entities"]
+
+
+def test_fix_text_all():
+    text, fixes = alea_preprocess.algos.unicode.fixes.fix_text(
+        "donâ€™t ﬁx", uncurl_quotes=True, fix_ligatures=True
+    )
+    assert text == "don't fix"
+    assert fixes == ["mojibake", "curly_quotes", "ligatures"]
@@ -20,3 +20,20 @@ def test_nfkc():
 
 def test_nfkd():
     assert alea_preprocess.algos.unicode.normalizations.nfkd_str("café") == "cafe\u0301"
+
+
+def test_normalize_whitespace():
+    buffer = "  The quick\t\tbrown ‎fox\r\n"
+    text, offsets = alea_preprocess.algos.unicode.normalizations.normalize_whitespace(buffer)
+    assert text == "The quick brown fox"
+    assert len(offsets) == len(text) + 1
+
+    start = text.index("fox")
+    assert buffer[offsets[start] : offsets[start + 3]] == "fox"
+
+
+def test_normalize_whitespace_options():
+    text, _ = alea_preprocess.algos.unicode.normalizations.normalize_whitespace(
+        " a  b ", collapse_whitespace=False, trim=False
+    )
+    assert text == " a  b "
@@ -0,0 +1,44 @@
+# imports
+
+# packages
+
+# extension module
+import alea_preprocess
+
+
+def test_char_to_script():
+    assert alea_preprocess.algos.unicode.scripts.char_to_script("a") == "Latin"
+    assert alea_preprocess.algos.unicode.scripts.char_to_script("Ж") == "Cyrillic"
+    assert alea_preprocess.algos.unicode.scripts.char_to_script("木") == "Han"
+    assert alea_preprocess.algos.unicode.scripts.char_to_script("1") == "Common"
+
+
+def test_char_to_script_group():
+    assert alea_preprocess.algos.unicode.scripts.char_to_script_group("a") == "Latin"
+    assert alea_preprocess.algos.unicode.scripts.char_to_script_group("の") == "CJK"
+    assert alea_preprocess.algos.unicode.scripts.char_to_script_group(" ") == "Common"
+
+
+def test_to_script_vector():
+    input_data = "a1Ж"
+    expected = ["Latin", "Common", "Cyrillic"]
+    result = alea_preprocess.algos.unicode.scripts.to_script_vector(input_data)
+    assert result == expected
+
+
+def test_get_script_ratios():
+    result = alea_preprocess.algos.unicode.scripts.get_script_ratios("abc, 123 Ж")
+    assert result == {"Latin": 0.75, "Cyrillic": 0.25}
+
+
+def test_get_dominant_script():
+    assert (
+        alea_preprocess.algos.unicode.scripts.get_dominant_script("Hello, мир")
+        == "Latin"
+    )
+    assert alea_preprocess.algos.unicode.scripts.get_dominant_script("123") is None
+
+
+def test_get_script_runs():
+    result = alea_preprocess.algos.unicode.scripts.get_script_runs("Hello мир")
+    assert result == [(0, 6, "Latin", "Hello "), (6, 12, "Cyrillic", "мир")]